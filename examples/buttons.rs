@@ -1,73 +1,55 @@
 #![no_std]
 #![no_main]
 
+//! Shows which buttons are held down and their last gesture: click, double
+//! click, long press, repeat or both together. The buttons are read on
+//! their edges from the GPIO interrupt instead of polled.
+
 // Based on: https://github.com/espressif/esp-mdf/tree/master/examples/development_kit/buddy
 
-use embedded_graphics::{
-    mono_font::{ascii::FONT_6X10, MonoTextStyleBuilder},
-    pixelcolor::BinaryColor,
-    prelude::*,
-    text::{Baseline, Text},
-};
 use esp_backtrace as _;
-use hal::{
-    delay::Delay,
-    gpio::{Input, Io, Level, Output, Pin, Pull},
-    i2c,
-    prelude::*,
+use esp_buddy::{
+    input::Interrupts,
+    screens::{Buttons, Scene},
+    Buddy,
 };
-use ssd1306::{prelude::*, I2CDisplayInterface, Ssd1306};
+use hal::prelude::*;
+
+/// Time between two looks at the edges and deadlines.
+const POLL_MS: u32 = 5;
 
 #[entry]
 fn main() -> ! {
     let peripherals = hal::init(hal::Config::default());
 
-    let mut delay = Delay::new();
-
-    let io = Io::new(peripherals.GPIO, peripherals.IO_MUX);
-
-    let sda = io.pins.gpio18;
-    let scl = io.pins.gpio23;
-
-    let i2c = i2c::I2c::new(peripherals.I2C0, sda, scl, 100u32.kHz());
-
-    let interface = I2CDisplayInterface::new(i2c);
-    let mut display = Ssd1306::new(interface, DisplaySize128x32, DisplayRotation::Rotate0)
-        .into_buffered_graphics_mode();
-    display.init().unwrap();
-
-    let text_style = MonoTextStyleBuilder::new()
-        .font(&FONT_6X10)
-        .text_color(BinaryColor::On)
-        .build();
-
-    let button_left_pin = io.pins.gpio0.degrade();
-    let button_left_pin = Input::new(button_left_pin, Pull::Up);
-    let button_right_pin = io.pins.gpio4.degrade();
-    let button_right_pin = Input::new(button_right_pin, Pull::Up);
-
-    // let button_right_pin = io.pins.gpio4.into_pull_down_input();
-
+    let mut buddy = Buddy::new(
+        peripherals.I2C0,
+        peripherals.GPIO,
+        peripherals.IO_MUX,
+        peripherals.RNG,
+    );
+    let display = &mut buddy.display;
+    let buttons = Interrupts::new(buddy.buttons);
+
+    let mut scene = Buttons::new();
+    let mut redraw = true;
     loop {
-        display.clear();
-        Text::with_baseline("Buttons example", Point::zero(), text_style, Baseline::Top)
-            .draw(&mut display)
-            .unwrap();
-
-        if button_left_pin.is_low() {
-            Text::with_baseline("Left", Point::new(0, 16), text_style, Baseline::Top)
-                .draw(&mut display)
-                .unwrap();
+        // Between edges only the debounce, long press and click timers matter
+        let now_ms = hal::time::now().duration_since_epoch().to_millis();
+        let due = scene
+            .input()
+            .next_deadline_ms()
+            .is_some_and(|deadline_ms| now_ms >= deadline_ms);
+        if buttons.take_edge() || due {
+            redraw |= scene.update_at(buttons.state(), now_ms);
         }
 
-        if button_right_pin.is_low() {
-            Text::with_baseline("Right", Point::new(60, 16), text_style, Baseline::Top)
-                .draw(&mut display)
-                .unwrap();
+        if redraw {
+            redraw = false;
+            display.clear();
+            scene.draw(display).unwrap();
+            display.flush().unwrap();
         }
-
-        display.flush().unwrap();
-        delay.delay_millis(30u32);
-        // delay.delay_millis(25u32);
+        buddy.delay.delay_millis(POLL_MS);
     }
 }
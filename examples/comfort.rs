@@ -0,0 +1,53 @@
+#![no_std]
+#![no_main]
+
+//! Office climate monitor: how comfortable the room is, with the dew point,
+//! heat index and absolute humidity from the HTS221.
+
+use esp_backtrace as _;
+use esp_buddy::{
+    psychrometrics::Climate, screens::comfort, sensors::Hts221Reader, settings, Buddy,
+};
+use esp_println::println;
+use hal::prelude::*;
+
+const READINGS_INTERVAL_MS: u32 = 5000;
+
+#[entry]
+fn main() -> ! {
+    let peripherals = hal::init(hal::Config::default());
+
+    let mut buddy = Buddy::new(
+        peripherals.I2C0,
+        peripherals.GPIO,
+        peripherals.IO_MUX,
+        peripherals.RNG,
+    );
+    let display = &mut buddy.display;
+    let mut i2c = buddy.i2c.acquire_i2c();
+
+    let calibration = settings::open()
+        .and_then(|mut store| store.hts221_calibration())
+        .unwrap_or_default();
+    let mut hts221 = Hts221Reader::new().with_calibration(calibration);
+    hts221.start(&mut i2c).ok();
+    // The first conversion takes up to a second at 1 Hz
+    buddy.delay.delay_millis(1000u32);
+
+    loop {
+        display.clear();
+        match hts221.read(&mut i2c) {
+            Ok(reading) => {
+                let climate = Climate::from(reading);
+                println!("{} {:?}", reading, climate);
+                comfort::draw(display, &climate).unwrap();
+            }
+            Err(err) => {
+                println!("HTS221 {:?}", err);
+                comfort::draw_error(display, err.message()).unwrap();
+            }
+        }
+        display.flush().unwrap();
+        buddy.delay.delay_millis(READINGS_INTERVAL_MS);
+    }
+}
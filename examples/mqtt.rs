@@ -0,0 +1,373 @@
+#![no_std]
+#![no_main]
+
+//! Publishes the readings and button presses to an MQTT broker and shows up
+//! in Home Assistant. The broker is set at build time:
+//!
+//! ```text
+//! MQTT_BROKER=192.168.1.10 MQTT_USER=buddy MQTT_PASSWORD=secret cargo run --release --example mqtt
+//! ```
+
+use core::{
+    fmt::Write,
+    net::{Ipv4Addr, SocketAddrV4},
+};
+use embedded_graphics::{
+    prelude::*,
+    text::{Alignment, Baseline, Text, TextStyle, TextStyleBuilder},
+};
+
+use esp_backtrace as _;
+use esp_buddy::{
+    http::api,
+    led::{self, effects::Output, Rgb},
+    mqtt::{self, discovery, Client, Command, Event, QoS, Topics},
+    portal, screens,
+    sensors::{registry::Field, Hts221Reader, Registry, Sensor},
+    settings, style,
+    wifi::{EspWifi, State, WifiManager},
+    Buddy,
+};
+use esp_println::println;
+use esp_wifi::wifi::{utils::create_network_interface, WifiStaDevice};
+use esp_wifi::wifi_interface::{IoError, WifiStack};
+use esp_wifi::{current_millis, EspWifiInitFor};
+use hal::{clock::CpuClock, efuse::Efuse, prelude::*, rmt::Rmt, timer::timg::TimerGroup};
+use smoltcp::{iface::SocketStorage, wire::IpAddress};
+
+/// `ip` or `ip:port` of the broker.
+const BROKER: &str = match option_env!("MQTT_BROKER") {
+    Some(broker) => broker,
+    None => "192.168.1.10",
+};
+
+const TOP_RIGHT: TextStyle = TextStyleBuilder::new()
+    .alignment(Alignment::Right)
+    .baseline(Baseline::Top)
+    .build();
+
+const READINGS_INTERVAL_MS: u64 = 5000;
+const RECONNECT_INTERVAL_MS: u64 = 5000;
+
+#[entry]
+fn main() -> ! {
+    let peripherals = hal::init({
+        let mut config = hal::Config::default();
+        config.cpu_clock = CpuClock::max();
+        config
+    });
+
+    let mut buddy = Buddy::new(
+        peripherals.I2C0,
+        peripherals.GPIO,
+        peripherals.IO_MUX,
+        peripherals.RNG,
+    );
+    let display = &mut buddy.display;
+    let rmt = Rmt::new(peripherals.RMT, led::CLOCK_MHZ.MHz()).unwrap();
+    let mut ws2812 = led::Ws2812::new(rmt.channel0, buddy.led).unwrap();
+    let mut i2c = buddy.i2c.acquire_i2c();
+    let mut hts221 = Hts221Reader::new();
+    let mut registry = Registry::scan(&mut i2c);
+
+    let broker = BROKER
+        .parse::<SocketAddrV4>()
+        .or_else(|_| {
+            BROKER
+                .parse::<Ipv4Addr>()
+                .map(|ip| SocketAddrV4::new(ip, mqtt::PORT))
+        })
+        .expect("MQTT_BROKER is not an IPv4 address");
+
+    // The MAC address keeps topics and entities apart on a shared broker
+    let mut device_id = heapless::String::<12>::new();
+    for byte in Efuse::get_mac_address() {
+        write!(device_id, "{:02x}", byte).unwrap();
+    }
+    let mut client_id = heapless::String::<22>::new();
+    write!(client_id, "esp-buddy-{}", device_id).unwrap();
+    let topics = Topics::new(&device_id);
+    let availability = topics.availability();
+    let device = discovery::Device::default();
+
+    let timer = TimerGroup::new(peripherals.TIMG1).timer0;
+    let init = esp_wifi::init(
+        EspWifiInitFor::Wifi,
+        timer,
+        buddy.rng,
+        peripherals.RADIO_CLK,
+    )
+    .unwrap();
+
+    let mut store = settings::open().unwrap();
+    let stored = store.networks().unwrap();
+    hts221.set_calibration(store.hts221_calibration().unwrap_or_default());
+    // The light is capped at the LED brightness setting
+    let config = store.config().unwrap_or_default();
+    let led_output = Output::new()
+        .with_brightness(config.led_brightness)
+        .with_gamma(false);
+    let networks = settings::known_networks(&stored, settings::BUILT_IN_NETWORK);
+    if networks.is_empty() || buddy.buttons.left_pressed() {
+        portal::run(&init, peripherals.WIFI, display, &mut store);
+    }
+
+    let mut socket_set_entries: [SocketStorage; 3] = Default::default();
+    let (iface, device_interface, controller, sockets) = create_network_interface(
+        &init,
+        peripherals.WIFI,
+        WifiStaDevice,
+        &mut socket_set_entries,
+    )
+    .unwrap();
+    let wifi_stack = WifiStack::new(iface, device_interface, sockets, current_millis);
+
+    let mut wifi = WifiManager::new(EspWifi::new(controller, &wifi_stack), &networks);
+    wifi.start();
+
+    let mut socket_rx_buffer = [0u8; 1024];
+    let mut socket_tx_buffer = [0u8; 2048];
+    let mut socket = wifi_stack.get_socket(&mut socket_rx_buffer, &mut socket_tx_buffer);
+
+    let mut mqtt_rx = [0u8; 256];
+    let mut mqtt_tx = [0u8; 1200];
+    let mut client =
+        Client::new(&client_id, &mut mqtt_rx, &mut mqtt_tx).with_will(&availability, mqtt::OFFLINE);
+    if let (Some(user), Some(password)) = (option_env!("MQTT_USER"), option_env!("MQTT_PASSWORD")) {
+        client = client.with_credentials(user, password);
+    }
+
+    let mut state = State::Idle;
+    let mut connected = false;
+    let mut next_connect_ms = 0;
+    let mut next_readings_ms = 0;
+    let mut buttons = buddy.buttons.state();
+    let mut message = heapless::String::<{ api::MAX_TEXT_LEN }>::new();
+    // Set over MQTT
+    let mut led_on = false;
+    let mut led = Rgb::new(255, 255, 255);
+    ws2812.set(Rgb::default()).ok();
+
+    loop {
+        let now_ms = current_millis();
+        let mut redraw = false;
+        if let Some(new_state) = wifi.poll(now_ms) {
+            state = new_state;
+            println!("WiFi {:?}", state);
+            redraw = true;
+        }
+
+        if !matches!(state, State::Connected { .. }) {
+            connected = false;
+        } else if !connected {
+            if now_ms >= next_connect_ms {
+                next_connect_ms = now_ms + RECONNECT_INTERVAL_MS;
+                socket.close();
+                let address = IpAddress::from(*broker.ip());
+                connected = socket.open(address, broker.port()).is_ok()
+                    && client.connect(&mut socket, now_ms).is_ok();
+            }
+        } else {
+            let mut online = false;
+            // Owned, the message text is copied out of the client
+            let mut command: Option<Command<'static>> = None;
+            match client.poll(&mut socket, now_ms) {
+                Ok(Some(Event::Connected { .. })) => online = true,
+                Ok(Some(Event::Message { topic, payload })) => {
+                    command = match topics.command(topic, payload) {
+                        Some(Command::ShowText(text)) => {
+                            message.clear();
+                            message.push_str(text).ok();
+                            Some(Command::ShowText(""))
+                        }
+                        Some(Command::Led(on)) => Some(Command::Led(on)),
+                        Some(Command::LedColor(color)) => Some(Command::LedColor(color)),
+                        None => None,
+                    }
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    println!("MQTT {:?}", err);
+                    connected = false;
+                    redraw = true;
+                }
+            }
+
+            let result = (|| -> Result<(), mqtt::Error<IoError>> {
+                // Waiting for CONNACK, or failed above
+                if !connected || !client.is_connected() {
+                    return Ok(());
+                }
+                if online {
+                    println!("MQTT connected to {}", broker);
+                    for component in discovery::Component::ALL {
+                        let mut config = heapless::String::<{ discovery::MAX_CONFIG_LEN }>::new();
+                        component.config(&topics, &device, &mut config).unwrap();
+                        let topic = component.config_topic(&topics);
+                        client.publish(
+                            &mut socket,
+                            &topic,
+                            config.as_bytes(),
+                            QoS::AtMostOnce,
+                            true,
+                        )?;
+                    }
+                    for field in registry.fields().filter(is_breakout) {
+                        let mut config = heapless::String::<{ discovery::MAX_CONFIG_LEN }>::new();
+                        discovery::field_config(&topics, &device, &field, &mut config).unwrap();
+                        let topic = discovery::field_config_topic(&topics, &field);
+                        client.publish(
+                            &mut socket,
+                            &topic,
+                            config.as_bytes(),
+                            QoS::AtMostOnce,
+                            true,
+                        )?;
+                    }
+                    client.subscribe(&mut socket, &topics.commands(), QoS::AtLeastOnce)?;
+                    client.publish(
+                        &mut socket,
+                        &availability,
+                        mqtt::ONLINE.as_bytes(),
+                        QoS::AtMostOnce,
+                        true,
+                    )?;
+                    next_readings_ms = now_ms;
+                }
+
+                match command {
+                    Some(Command::ShowText(_)) => {
+                        let topic = topics.state(Topics::DISPLAY);
+                        client.publish(
+                            &mut socket,
+                            &topic,
+                            message.as_bytes(),
+                            QoS::AtMostOnce,
+                            true,
+                        )?;
+                    }
+                    Some(Command::Led(on)) => led_on = on,
+                    Some(Command::LedColor(color)) => led = color,
+                    None => {}
+                }
+                if matches!(command, Some(Command::Led(_) | Command::LedColor(_))) {
+                    let color = if led_on { led } else { Rgb::default() };
+                    ws2812.set(led_output.correct(color)).ok();
+                }
+                if online || matches!(command, Some(Command::Led(_) | Command::LedColor(_))) {
+                    let on = if led_on { mqtt::ON } else { mqtt::OFF };
+                    let topic = topics.state(Topics::LED);
+                    client.publish(&mut socket, &topic, on.as_bytes(), QoS::AtMostOnce, true)?;
+                    let topic = topics.state(Topics::LED_RGB);
+                    let rgb = mqtt::format_rgb(led);
+                    client.publish(&mut socket, &topic, rgb.as_bytes(), QoS::AtMostOnce, true)?;
+                }
+
+                // Presses, as device triggers
+                let pressed = buddy.buttons.state();
+                for (name, was, is) in [
+                    ("left", buttons.left, pressed.left),
+                    ("right", buttons.right, pressed.right),
+                ] {
+                    if is && !was {
+                        let topic = topics.state(Topics::BUTTON);
+                        client.publish(
+                            &mut socket,
+                            &topic,
+                            name.as_bytes(),
+                            QoS::AtMostOnce,
+                            false,
+                        )?;
+                    }
+                }
+                buttons = pressed;
+
+                if now_ms >= next_readings_ms {
+                    next_readings_ms = now_ms + READINGS_INTERVAL_MS;
+                    match hts221.read(&mut i2c) {
+                        Ok(reading) => {
+                            for (name, tenths) in [
+                                (Topics::TEMPERATURE, reading.temperature_dc),
+                                (Topics::HUMIDITY, reading.humidity_dpct),
+                            ] {
+                                let topic = topics.state(name);
+                                let value = mqtt::format_tenths(tenths);
+                                client.publish(
+                                    &mut socket,
+                                    &topic,
+                                    value.as_bytes(),
+                                    QoS::AtMostOnce,
+                                    false,
+                                )?;
+                            }
+                        }
+                        Err(err) => println!("HTS221 {:?}", err),
+                    }
+                    registry.poll(&mut i2c);
+                    for field in registry.fields().filter(is_breakout) {
+                        let Some(tenths) = field.value else {
+                            continue;
+                        };
+                        let topic = topics.field(&field.key);
+                        let value = mqtt::format_tenths(tenths);
+                        client.publish(
+                            &mut socket,
+                            &topic,
+                            value.as_bytes(),
+                            QoS::AtMostOnce,
+                            false,
+                        )?;
+                    }
+                    if let Some(rssi) = wifi.rssi() {
+                        let mut value = heapless::String::<4>::new();
+                        write!(value, "{}", rssi).unwrap();
+                        let topic = topics.state(Topics::RSSI);
+                        client.publish(
+                            &mut socket,
+                            &topic,
+                            value.as_bytes(),
+                            QoS::AtMostOnce,
+                            false,
+                        )?;
+                    }
+                }
+                Ok(())
+            })();
+            if let Err(err) = result {
+                println!("MQTT {:?}", err);
+                connected = false;
+                redraw = true;
+            }
+            redraw |= online || matches!(command, Some(Command::ShowText(_)));
+        }
+
+        if redraw {
+            display.clear();
+            // Text sent over MQTT takes the place of the connection state
+            if message.is_empty() {
+                screens::wifi::draw_state(display, &state).unwrap();
+            } else {
+                Text::with_baseline(&message, Point::zero(), style::TEXT, Baseline::Top)
+                    .draw(display)
+                    .unwrap();
+            }
+            // Broker connection in the top right corner once on the network
+            if matches!(state, State::Connected { .. }) {
+                let status = if connected && client.is_connected() {
+                    "MQTT ok"
+                } else {
+                    "MQTT off"
+                };
+                Text::with_text_style(status, Point::new(127, 0), style::SMALL, TOP_RIGHT)
+                    .draw(display)
+                    .unwrap();
+            }
+            display.flush().unwrap();
+        }
+    }
+}
+
+/// Values of the breakouts, the HTS221 of the board has its own entities.
+fn is_breakout(field: &Field) -> bool {
+    field.sensor != Hts221Reader::NAME
+}
@@ -1,130 +1,99 @@
-// Warning! This examples is not working.
-// Blocked by: https://github.com/georgik/esp32-buddy-rs/issues/1
-//             https://github.com/esp-rs/esp-hal/issues/855
-//             https://github.com/bjoernQ/esp-hal/pull/1/files
-// Based on https://github.com/esp-rs/esp-hal/blob/main/esp32-hal/examples/hello_rgb.rs
-// ESP-Buddy HW: // Based on: https://github.com/espressif/esp-mdf/tree/master/examples/development_kit/buddy
-
-//! RGB LED Demo
-//!
-//! This example drives an 12-element RGB ring that is connected to GPIO33
-//!
-//! The LEDs in the ring are transitioning though the HSV color spectrum for
-//! - Saturation: 255
-//! - Hue: 0 - 255
-//! - Value: 255
-//!
-//! For the 12-element RGB ring to work, building the release version is going
-//! to be required.
-
 #![no_std]
 #![no_main]
 
+//! Shows the LED effects. The right button selects the next effect, the left
+//! one the next brightness. The LED follows the time, the display is drawn
+//! only when something changes.
+
+// Based on https://github.com/esp-rs/esp-hal/blob/main/esp32-hal/examples/hello_rgb.rs
+// ESP-Buddy HW: https://github.com/espressif/esp-mdf/tree/master/examples/development_kit/buddy
+
+use core::fmt::Write;
+
 use embedded_graphics::{
-    mono_font::{ascii::FONT_6X10, MonoTextStyleBuilder},
-    pixelcolor::BinaryColor,
     prelude::*,
     text::{Baseline, Text},
 };
-use ssd1306::{prelude::*, I2CDisplayInterface, Ssd1306};
-
-use hal::{
-    clock::ClockControl, i2c, peripherals::Peripherals, prelude::*, timer::TimerGroup, Delay,
-    PulseControl, Rtc, IO,
-};
-
-use esp_hal_smartled::smartLedAdapter;
-
-#[allow(unused_imports)]
 use esp_backtrace as _;
-use heapless::String;
-use smart_leds::{
-    brightness, gamma,
-    hsv::{hsv2rgb, Hsv},
-    SmartLedsWrite,
+use esp_buddy::{
+    led::{
+        self,
+        effects::{Blink, Breathe, Comet, Effect, Output, Rainbow, Solid, Status, BLINK_DOUBLE},
+        Rgb,
+    },
+    screens::Presses,
+    style, Buddy,
 };
+use hal::{prelude::*, rmt::Rmt};
+
+const FRAME_MS: u32 = 20;
+/// Brightness caps the left button cycles through, out of 255.
+const BRIGHTNESS: [u8; 3] = [10, 32, 96];
+const ORANGE: Rgb = Rgb::new(255, 96, 0);
+
+const EFFECTS: [(&str, &dyn Effect); 7] = [
+    ("Rainbow", &Rainbow::new(5000)),
+    (
+        "Breathing rainbow",
+        &Breathe::new(Rainbow::new(20_000), 3000),
+    ),
+    ("Comet", &Comet::new(Solid(ORANGE), 1000)),
+    ("Double blink", &Blink::new(Solid(ORANGE), BLINK_DOUBLE)),
+    ("Connecting", &Status::Connecting),
+    ("Connected", &Status::Connected),
+    ("Error", &Status::Error),
+];
 
 #[entry]
 fn main() -> ! {
-    let peripherals = Peripherals::take();
-    let mut system = peripherals.SYSTEM.split();
-    let clocks = ClockControl::boot_defaults(system.clock_control).freeze();
-
-    let io = IO::new(peripherals.GPIO, peripherals.IO_MUX);
-
-    // Configure RMT peripheral globally
-    let pulse = PulseControl::new(
-        peripherals.RMT,
-        &mut system.peripheral_clock_control,
-        ClockSource::APB,
-        0,
-        0,
-        0,
-    )
-    .unwrap();
-
-    // We use one of the RMT channels to instantiate a `SmartLedsAdapter` which can
-    // be used directly with all `smart_led` implementations
-    // -> We need to use the macro `smartLedAdapter!` with the number of addressed
-    // LEDs here to initialize the internal LED pulse buffer to the correct
-    // size!
-    let mut led = <smartLedAdapter!(1)>::new(pulse.channel1, io.pins.gpio25);
-
-    // Initialize the Delay peripheral, and use it to toggle the LED state in a
-    // loop.
-    let mut delay = Delay::new(&clocks);
-
-    let sda = io.pins.gpio18;
-    let scl = io.pins.gpio23;
-
-    let i2c = i2c::I2C::new(peripherals.I2C0, sda, scl, 100u32.kHz(), &clocks).unwrap();
-
-    let interface = I2CDisplayInterface::new(i2c);
-    let mut display = Ssd1306::new(interface, DisplaySize128x32, DisplayRotation::Rotate0)
-        .into_buffered_graphics_mode();
-    display.init().unwrap();
-
-    let mut color = Hsv {
-        hue: 0,
-        sat: 255,
-        val: 255,
-    };
-    let mut data;
-
-    let text_style = MonoTextStyleBuilder::new()
-        .font(&FONT_6X10)
-        .text_color(BinaryColor::On)
-        .build();
+    let peripherals = hal::init(hal::Config::default());
+
+    let mut buddy = Buddy::new(
+        peripherals.I2C0,
+        peripherals.GPIO,
+        peripherals.IO_MUX,
+        peripherals.RNG,
+    );
+    let display = &mut buddy.display;
+    let rmt = Rmt::new(peripherals.RMT, led::CLOCK_MHZ.MHz()).unwrap();
+    let mut ws2812 = led::Ws2812::new(rmt.channel0, buddy.led).unwrap();
+
+    let mut presses = Presses::new();
+    let mut effect = 0;
+    let mut brightness = 0;
+    let mut redraw = true;
+    let mut leds = [Rgb::default(); 1];
 
     loop {
-        // Iterate over the rainbow!
-        for hue in 0..=255 {
-            display.clear();
-            Text::with_baseline("Rainbow example", Point::zero(), text_style, Baseline::Top)
-                .draw(&mut display)
-                .unwrap();
-
-            color.hue = hue;
-            // Convert from the HSV color space (where we can easily transition from one
-            // color to the other) to the RGB color space that we can then send to the LED
-            let rgb_color = hsv2rgb(color);
+        let pressed = presses.update(buddy.buttons.state());
+        if pressed.right {
+            effect = (effect + 1) % EFFECTS.len();
+            redraw = true;
+        }
+        if pressed.left {
+            brightness = (brightness + 1) % BRIGHTNESS.len();
+            redraw = true;
+        }
 
-            // Assign new color to all 12 LEDs
-            data = [rgb_color; 12];
+        let now_ms = hal::time::now().duration_since_epoch().to_millis();
+        let (name, shown) = EFFECTS[effect];
+        let output = Output::new().with_brightness(BRIGHTNESS[brightness]);
+        output.render(shown, now_ms, &mut leds);
+        ws2812.write_colors(leds).unwrap();
 
-            // When sending to the LED, we do a gamma correction first (see smart_leds
-            // documentation for details) and then limit the brightness to 10 out of 255 so
-            // that the output it's not too bright.
-            led.write(brightness(gamma(data.iter().cloned()), 10))
+        if redraw {
+            redraw = false;
+            display.clear();
+            Text::with_baseline(name, Point::zero(), style::TEXT, Baseline::Top)
+                .draw(display)
                 .unwrap();
-
-            let hue_string: String<32> = String::from(hue);
-            Text::with_baseline(&hue_string, Point::new(0, 16), text_style, Baseline::Top)
-                .draw(&mut display)
+            let mut text: heapless::String<24> = heapless::String::new();
+            write!(text, "brightness {}", output.brightness()).unwrap();
+            Text::with_baseline(&text, Point::new(0, 16), style::TEXT, Baseline::Top)
+                .draw(display)
                 .unwrap();
-
             display.flush().unwrap();
-            delay.delay_ms(50u32);
         }
+        buddy.delay.delay_millis(FRAME_MS);
     }
 }
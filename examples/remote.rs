@@ -0,0 +1,101 @@
+#![no_std]
+#![no_main]
+
+use esp_backtrace as _;
+use esp_buddy::{
+    portal,
+    remote::{self, protocol, Panel, StreamReader},
+    screens, settings,
+    wifi::{EspWifi, State, WifiManager},
+    Buddy,
+};
+use esp_println::println;
+use esp_wifi::wifi::{utils::create_network_interface, WifiStaDevice};
+use esp_wifi::wifi_interface::WifiStack;
+use esp_wifi::{current_millis, EspWifiInitFor};
+use hal::{clock::CpuClock, prelude::*, timer::timg::TimerGroup};
+use smoltcp::iface::SocketStorage;
+use smoltcp::socket::udp::PacketMetadata;
+
+#[entry]
+fn main() -> ! {
+    let peripherals = hal::init({
+        let mut config = hal::Config::default();
+        config.cpu_clock = CpuClock::max();
+        config
+    });
+
+    let mut buddy = Buddy::new(
+        peripherals.I2C0,
+        peripherals.GPIO,
+        peripherals.IO_MUX,
+        peripherals.RNG,
+    );
+    let display = &mut buddy.display;
+
+    let timer = TimerGroup::new(peripherals.TIMG1).timer0;
+    let init = esp_wifi::init(
+        EspWifiInitFor::Wifi,
+        timer,
+        buddy.rng,
+        peripherals.RADIO_CLK,
+    )
+    .unwrap();
+
+    let mut store = settings::open().unwrap();
+    let stored = store.networks().unwrap();
+    let networks = settings::known_networks(&stored, settings::BUILT_IN_NETWORK);
+    if networks.is_empty() || buddy.buttons.left_pressed() {
+        portal::run(&init, peripherals.WIFI, display, &mut store);
+    }
+
+    // DHCP, the UDP and the TCP socket
+    let mut socket_set_entries: [SocketStorage; 3] = Default::default();
+    let (iface, device, controller, sockets) = create_network_interface(
+        &init,
+        peripherals.WIFI,
+        WifiStaDevice,
+        &mut socket_set_entries,
+    )
+    .unwrap();
+    let wifi_stack = WifiStack::new(iface, device, sockets, current_millis);
+
+    let mut wifi = WifiManager::new(EspWifi::new(controller, &wifi_stack), &networks);
+    wifi.start();
+
+    let mut rx_meta = [PacketMetadata::EMPTY; 4];
+    let mut rx_buffer = [0u8; 4 * protocol::MAX_PACKET_LEN];
+    let mut tx_meta = [PacketMetadata::EMPTY; 1];
+    let mut tx_buffer = [0u8; 64];
+    let mut udp_socket =
+        wifi_stack.get_udp_socket(&mut rx_meta, &mut rx_buffer, &mut tx_meta, &mut tx_buffer);
+    udp_socket.bind(protocol::PORT).unwrap();
+
+    let mut tcp_rx_buffer = [0u8; 1536];
+    let mut tcp_tx_buffer = [0u8; 64];
+    let mut tcp_socket = wifi_stack.get_socket(&mut tcp_rx_buffer, &mut tcp_tx_buffer);
+
+    let mut panel = Panel::new();
+    let mut reader = StreamReader::new();
+
+    loop {
+        // The address to send to is shown until the first packet arrives
+        if let Some(state) = wifi.poll(current_millis()) {
+            println!("WiFi {:?}", state);
+            display.clear();
+            screens::wifi::draw_state(display, &state).unwrap();
+            display.flush().unwrap();
+        }
+        if !matches!(wifi.state(), State::Connected { .. }) {
+            continue;
+        }
+
+        let received = remote::receive(&mut udp_socket, &mut panel);
+        let streamed = remote::serve(&mut tcp_socket, protocol::PORT, &mut reader, &mut panel);
+        if received || streamed {
+            display.clear();
+            panel.draw(display).unwrap();
+            display.flush().unwrap();
+        }
+    }
+}
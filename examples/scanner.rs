@@ -0,0 +1,42 @@
+#![no_std]
+#![no_main]
+
+//! I2C bus diagnostics: scans the bus on GPIO18/GPIO23, lists what answers
+//! with the usual chip at each address and the share of failed transfers,
+//! and dumps and writes registers. See `esp_buddy::scanner` for the buttons.
+
+use esp_backtrace as _;
+use esp_buddy::{scanner::Diagnostics, screens, Buddy};
+use esp_println::println;
+use hal::prelude::*;
+
+#[entry]
+fn main() -> ! {
+    let peripherals = hal::init(hal::Config::default());
+
+    let mut buddy = Buddy::new(
+        peripherals.I2C0,
+        peripherals.GPIO,
+        peripherals.IO_MUX,
+        peripherals.RNG,
+    );
+    let display = &mut buddy.display;
+    // The display answers too, at 0x3c
+    let mut i2c = buddy.i2c.acquire_i2c();
+
+    let mut diagnostics = Diagnostics::new();
+    diagnostics.scan(&mut i2c);
+    for device in diagnostics.devices() {
+        println!("{:02x} {}", device.address, device.name.unwrap_or("?"));
+    }
+
+    loop {
+        let now_ms = hal::time::now().duration_since_epoch().to_millis();
+        if diagnostics.update(&mut i2c, buddy.buttons.state(), now_ms) {
+            display.clear();
+            screens::scanner::draw(display, &diagnostics).unwrap();
+            display.flush().unwrap();
+        }
+        buddy.delay.delay_millis(20u32);
+    }
+}
@@ -0,0 +1,67 @@
+#![no_std]
+#![no_main]
+
+//! Shows every known sensor on the I2C bus, one page each. The right button
+//! goes to the next sensor, the left one back.
+
+use esp_backtrace as _;
+use esp_buddy::{
+    screens::sensors::Pages,
+    sensors::{Device, Registry},
+    settings, Buddy,
+};
+use esp_println::println;
+use hal::prelude::*;
+
+const READINGS_INTERVAL_MS: u64 = 5000;
+
+#[entry]
+fn main() -> ! {
+    let peripherals = hal::init(hal::Config::default());
+
+    let mut buddy = Buddy::new(
+        peripherals.I2C0,
+        peripherals.GPIO,
+        peripherals.IO_MUX,
+        peripherals.RNG,
+    );
+    let display = &mut buddy.display;
+    let mut i2c = buddy.i2c.acquire_i2c();
+
+    // Sensors plugged in later show up after a restart
+    let mut registry = Registry::scan(&mut i2c);
+    let calibration = settings::open()
+        .and_then(|mut store| store.hts221_calibration())
+        .unwrap_or_default();
+    for entry in registry.entries_mut() {
+        if let Device::Hts221(reader) = &mut entry.device {
+            reader.set_calibration(calibration);
+        }
+    }
+    for entry in registry.entries() {
+        println!("Found {}", entry.title());
+    }
+
+    let mut pages = Pages::new();
+    let mut next_readings_ms = 0;
+    loop {
+        let now_ms = hal::time::now().duration_since_epoch().to_millis();
+        let mut redraw = pages.update(buddy.buttons.state(), registry.len());
+
+        if now_ms >= next_readings_ms {
+            next_readings_ms = now_ms + READINGS_INTERVAL_MS;
+            registry.poll(&mut i2c);
+            for field in registry.fields() {
+                println!("{} {:?}", field.key, field.value);
+            }
+            redraw = true;
+        }
+
+        if redraw {
+            display.clear();
+            pages.draw(display, &registry).unwrap();
+            display.flush().unwrap();
+        }
+        buddy.delay.delay_millis(20u32);
+    }
+}
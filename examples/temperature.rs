@@ -3,86 +3,92 @@
 
 // Based on: https://github.com/espressif/esp-mdf/tree/master/examples/development_kit/buddy
 
-use core::fmt::Write;
-use embedded_graphics::{
-    mono_font::{ascii::FONT_6X10, MonoTextStyleBuilder},
-    pixelcolor::BinaryColor,
-    prelude::*,
-    text::{Baseline, Text},
-};
 use esp_backtrace as _;
+use esp_buddy::{
+    history::{History, DAY_MINUTES, MINUTE_MS},
+    screens::history::Graphs,
+    sensors::Hts221Reader,
+    settings, Buddy,
+};
 use esp_println::println;
-use hal::{clock::ClockControl, i2c, peripherals::Peripherals, prelude::*, Delay, IO};
-use shared_bus::BusManagerSimple;
-use ssd1306::{prelude::*, I2CDisplayInterface, Ssd1306};
+use hal::prelude::*;
+
+// 24 h at 1-minute resolution, kept out of the stack
+static mut TEMPERATURE: History<DAY_MINUTES> = History::new(MINUTE_MS);
+static mut HUMIDITY: History<DAY_MINUTES> = History::new(MINUTE_MS);
 
 #[entry]
 fn main() -> ! {
-    let peripherals = Peripherals::take();
-    let system = peripherals.SYSTEM.split();
-    let clocks = ClockControl::boot_defaults(system.clock_control).freeze();
-
-    let mut delay = Delay::new(&clocks);
-
-    let io = IO::new(peripherals.GPIO, peripherals.IO_MUX);
-
-    let sda = io.pins.gpio18;
-    let scl = io.pins.gpio23;
-
-    let i2c = i2c::I2C::new(peripherals.I2C0, sda, scl, 100u32.kHz(), &clocks);
-
-    // We need to access two peripherals on I2C
-    // based on example: https://github.com/ferrous-systems/espressif-trainings/blob/main/advanced/i2c-sensor-reading/examples/part_2.rs
-    let bus = shared_bus::BusManagerSimple::new(i2c);
-
-    let proxy_1 = bus.acquire_i2c();
-    let mut proxy_2 = bus.acquire_i2c();
-
-    let interface = I2CDisplayInterface::new(proxy_1);
-    let mut display = Ssd1306::new(interface, DisplaySize128x32, DisplayRotation::Rotate0)
-        .into_buffered_graphics_mode();
-    display.init().unwrap();
-
-    let text_style = MonoTextStyleBuilder::new()
-        .font(&FONT_6X10)
-        .text_color(BinaryColor::On)
-        .build();
-
-    let mut hts221 = hts221::Builder::new().build(&mut proxy_2).unwrap();
-
-    display.flush().unwrap();
-
-    loop {
-        display.clear();
-        Text::with_baseline(
-            "Temperature/Humidity",
-            Point::zero(),
-            text_style,
-            Baseline::Top,
+    let peripherals = hal::init(hal::Config::default());
+
+    let mut buddy = Buddy::new(
+        peripherals.I2C0,
+        peripherals.GPIO,
+        peripherals.IO_MUX,
+        peripherals.RNG,
+    );
+    let display = &mut buddy.display;
+
+    // The display already holds one proxy of the shared bus, the sensor gets another one
+    let mut i2c = buddy.i2c.acquire_i2c();
+
+    // Corrections, units and the sample interval are kept in the settings
+    let (calibration, config) = match settings::open() {
+        Ok(mut store) => (
+            store.hts221_calibration().unwrap_or_default(),
+            store.config().unwrap_or_default(),
+        ),
+        Err(_) => Default::default(),
+    };
+    let mut hts221 = Hts221Reader::new().with_calibration(calibration);
+
+    // Only borrowed here, main never returns
+    let (temperature, humidity) = unsafe {
+        (
+            &mut *core::ptr::addr_of_mut!(TEMPERATURE),
+            &mut *core::ptr::addr_of_mut!(HUMIDITY),
         )
-        .draw(&mut display)
-        .unwrap();
-
-        // Acquire measurement and perform correction - https://crates.io/crates/hts221
-        let rh = hts221.humidity_x2(&mut proxy_2).unwrap() / 2;
-        let deg_c = hts221.temperature_x8(&mut proxy_2).unwrap() / 8;
+    };
+    let mut graphs = Graphs::new().with_units(config.units);
+    let mut next_readings_ms = 0;
+    let mut next_minute_ms = 0;
 
-        let mut rh_string: heapless::String<32> = heapless::String::new();
-        let mut deg_string: heapless::String<32> = heapless::String::new();
-
-        write!(rh_string, "{}%", rh).unwrap();
-        write!(deg_string, "{} C", deg_c).unwrap();
-
-        println!("{} {}", rh_string, deg_string);
-
-        Text::with_baseline(&deg_string, Point::new(0, 16), text_style, Baseline::Top)
-            .draw(&mut display)
-            .unwrap();
-        Text::with_baseline(&rh_string, Point::new(72, 16), text_style, Baseline::Top)
-            .draw(&mut display)
-            .unwrap();
-
-        display.flush().unwrap();
-        delay.delay_ms(5000u32);
+    loop {
+        let now_ms = hal::time::now().duration_since_epoch().to_millis();
+        let mut redraw = graphs.update(buddy.buttons.state());
+
+        if now_ms >= next_readings_ms {
+            next_readings_ms = now_ms + config.sample_interval_ms;
+            // A failed reading leaves a gap in the graph and shows why
+            match hts221.read(&mut i2c) {
+                Ok(reading) => {
+                    println!("{}", reading);
+                    let temperature_dc = config.units.from_dc(reading.temperature_dc);
+                    temperature.record(now_ms, temperature_dc as i16);
+                    humidity.record(now_ms, reading.humidity_dpct as i16);
+                    graphs.set_error(None);
+                }
+                Err(err) => {
+                    println!("HTS221 {:?}", err);
+                    graphs.set_error(Some(err.message()));
+                }
+            }
+            redraw = true;
+        }
+
+        // Scroll the graph every minute, also while the sensor does not answer
+        if now_ms >= next_minute_ms {
+            next_minute_ms = (now_ms / MINUTE_MS + 1) * MINUTE_MS;
+            temperature.advance(now_ms);
+            humidity.advance(now_ms);
+            redraw = true;
+        }
+
+        if redraw {
+            display.clear();
+            graphs.draw(display, temperature, humidity).unwrap();
+            display.flush().unwrap();
+        }
+        buddy.delay.delay_millis(20u32);
     }
 }
@@ -0,0 +1,24 @@
+// ESP-Buddy HW: // Based on: https://github.com/espressif/esp-mdf/tree/master/examples/development_kit/buddy
+
+#![no_std]
+#![no_main]
+
+use esp_buddy::{screens::Timers, Buddy};
+
+use hal::prelude::*;
+
+use esp_backtrace as _;
+
+#[entry]
+fn main() -> ! {
+    let peripherals = hal::init(hal::Config::default());
+
+    let mut buddy = Buddy::new(
+        peripherals.I2C0,
+        peripherals.GPIO,
+        peripherals.IO_MUX,
+        peripherals.RNG,
+    );
+
+    buddy.run(&mut Timers::new())
+}
@@ -1,176 +1,199 @@
 #![no_std]
 #![no_main]
 
-use embedded_svc::ipv4::Interface;
-use embedded_svc::wifi::{AccessPointInfo, ClientConfiguration, Configuration, Wifi};
-
-use ssd1306::{prelude::*, I2CDisplayInterface, Ssd1306};
-
+use core::mem::MaybeUninit;
 use embedded_graphics::{
-    mono_font::{ascii::FONT_6X10, MonoTextStyleBuilder},
-    pixelcolor::BinaryColor,
     prelude::*,
     text::{Baseline, Text},
 };
 
 use esp_backtrace as _;
+use esp_buddy::{
+    http::{
+        self,
+        api::{self, Command, DeviceStatus},
+    },
+    led::{self, effects::Output, Rgb},
+    portal, screens,
+    sensors::Hts221Reader,
+    settings, style,
+    wifi::{EspWifi, State, WifiManager},
+    Buddy,
+};
 use esp_println::logger::init_logger;
 use esp_println::println;
 use esp_wifi::wifi::{utils::create_network_interface, WifiStaDevice};
-use esp_wifi::wifi::{WifiError, WifiMode};
 use esp_wifi::wifi_interface::WifiStack;
-use esp_wifi::{current_millis, initialize, EspWifiInitFor};
-use hal::clock::{ClockControl, CpuClock};
-use hal::Rng;
-use hal::{i2c, prelude::*, Delay, IO};
-use hal::{peripherals::Peripherals, prelude::*, timer::TimerGroup};
+use esp_wifi::{current_millis, EspWifiInitFor};
+use hal::{clock::CpuClock, prelude::*, rmt::Rmt, timer::timg::TimerGroup};
 use smoltcp::iface::SocketStorage;
 
-const SSID: &str = env!("SSID");
-const PASSWORD: &str = env!("PASSWORD");
+const HTTP_PORT: u16 = 80;
 
-#[entry]
-fn main() -> ! {
-    init_logger(log::LevelFilter::Info);
-
-    let peripherals = Peripherals::take();
-    let system = peripherals.SYSTEM.split();
+#[global_allocator]
+static ALLOCATOR: esp_alloc::EspHeap = esp_alloc::EspHeap::empty();
 
-    let clocks = ClockControl::max(system.clock_control).freeze();
-    let mut delay = Delay::new(&clocks);
+fn init_heap() {
+    const HEAP_SIZE: usize = 5 * 1024;
+    static mut HEAP: MaybeUninit<[u8; HEAP_SIZE]> = MaybeUninit::uninit();
 
-    let timer = TimerGroup::new(peripherals.TIMG1, &clocks).timer0;
-
-    let io = IO::new(peripherals.GPIO, peripherals.IO_MUX);
-    let sda = io.pins.gpio18;
-    let scl = io.pins.gpio23;
+    unsafe {
+        ALLOCATOR.init(HEAP.as_mut_ptr() as *mut u8, HEAP_SIZE - 1024);
+    }
+}
 
-    let i2c = i2c::I2C::new(peripherals.I2C0, sda, scl, 100u32.kHz(), &clocks);
+#[entry]
+fn main() -> ! {
+    init_logger(log::LevelFilter::Info);
+    init_heap();
 
-    let interface = I2CDisplayInterface::new(i2c);
-    let mut display = Ssd1306::new(interface, DisplaySize128x32, DisplayRotation::Rotate0)
-        .into_buffered_graphics_mode();
-    display.init().unwrap();
+    let peripherals = hal::init({
+        let mut config = hal::Config::default();
+        config.cpu_clock = CpuClock::max();
+        config
+    });
 
-    let text_style = MonoTextStyleBuilder::new()
-        .font(&FONT_6X10)
-        .text_color(BinaryColor::On)
-        .build();
+    let mut buddy = Buddy::new(
+        peripherals.I2C0,
+        peripherals.GPIO,
+        peripherals.IO_MUX,
+        peripherals.RNG,
+    );
+    let display = &mut buddy.display;
+    let rmt = Rmt::new(peripherals.RMT, led::CLOCK_MHZ.MHz()).unwrap();
+    let mut ws2812 = led::Ws2812::new(rmt.channel0, buddy.led).unwrap();
+    // Readings for the status endpoint, when the sensor answers
+    let mut i2c = buddy.i2c.acquire_i2c();
+    let mut hts221 = Hts221Reader::new();
 
     display.clear();
-    Text::with_baseline("WiFi example", Point::zero(), text_style, Baseline::Top)
-        .draw(&mut display)
+    Text::with_baseline("WiFi example", Point::zero(), style::TEXT, Baseline::Top)
+        .draw(display)
         .unwrap();
 
     display.flush().unwrap();
 
-    let rng = Rng::new(peripherals.RNG);
-    let radio_clock_control = system.radio_clock_control;
-    let init = initialize(
+    let timer = TimerGroup::new(peripherals.TIMG1).timer0;
+    let init = esp_wifi::init(
         EspWifiInitFor::Wifi,
         timer,
-        rng,
-        radio_clock_control,
-        &clocks,
+        buddy.rng,
+        peripherals.RADIO_CLK,
     )
     .unwrap();
 
-    let wifi = peripherals.WIFI;
+    // Networks saved in flash first, then the one from `SSID` at build time.
+    // Without any, or with the left button held, ask for one in the portal.
+    let mut store = settings::open().unwrap();
+    let stored = store.networks().unwrap();
+    hts221.set_calibration(store.hts221_calibration().unwrap_or_default());
+    // Colors are dimmed to the LED brightness setting
+    let config = store.config().unwrap_or_default();
+    let led_output = Output::new()
+        .with_brightness(config.led_brightness)
+        .with_gamma(false);
+    let networks = settings::known_networks(&stored, settings::BUILT_IN_NETWORK);
+    if networks.is_empty() || buddy.buttons.left_pressed() {
+        portal::run(&init, peripherals.WIFI, display, &mut store);
+    }
+
     let mut socket_set_entries: [SocketStorage; 3] = Default::default();
-    let (iface, device, mut controller, sockets) =
-        create_network_interface(&init, wifi, WifiStaDevice, &mut socket_set_entries).unwrap();
+    let (iface, device, controller, sockets) = create_network_interface(
+        &init,
+        peripherals.WIFI,
+        WifiStaDevice,
+        &mut socket_set_entries,
+    )
+    .unwrap();
 
     let wifi_stack = WifiStack::new(iface, device, sockets, current_millis);
 
-    let client_config = Configuration::Client(ClientConfiguration {
-        ssid: SSID.try_into().unwrap(),
-        password: PASSWORD.try_into().unwrap(),
-        ..Default::default()
-    });
-    let res = controller.set_configuration(&client_config);
-    println!("wifi_set_configuration returned {:?}", res);
-
-    controller.start().unwrap();
-    println!("is wifi started: {:?}", controller.is_started());
-
-    println!("Start Wifi Scan");
-    let res: Result<(heapless::Vec<AccessPointInfo, 10>, usize), WifiError> = controller.scan_n();
-    if let Ok((res, _count)) = res {
-        for ap in res {
-            println!("{:?}", ap);
-        }
-    }
+    let mut wifi = WifiManager::new(EspWifi::new(controller, &wifi_stack), &networks);
+    wifi.start();
 
-    println!("{:?}", controller.get_capabilities());
-    println!("wifi_connect {:?}", controller.connect());
+    let mut http_rx_buffer = [0u8; 1024];
+    let mut http_tx_buffer = [0u8; 1024];
+    let mut http_socket = wifi_stack.get_socket(&mut http_rx_buffer, &mut http_tx_buffer);
+    let mut http_connection = http::Connection::default();
+    let mut request = [0u8; 512];
+    let mut response = [0u8; 1024];
 
-    // wait to get connected
-    println!("Wait to get connected");
-    display.clear();
-    Text::with_baseline(
-        "WiFi example\nWait to get connected",
-        Point::zero(),
-        text_style,
-        Baseline::Top,
-    )
-    .draw(&mut display)
-    .unwrap();
-    display.flush().unwrap();
+    let mut state = State::Idle;
+    let mut message = heapless::String::<{ api::MAX_TEXT_LEN }>::new();
+    // Set over HTTP
+    let mut led = Rgb::default();
+    ws2812.set(led).ok();
 
     loop {
-        let res = controller.is_connected();
-        match res {
-            Ok(connected) => {
-                if connected {
-                    break;
-                }
+        let changed = wifi.poll(current_millis());
+        if let Some(new_state) = changed {
+            state = new_state;
+            println!("WiFi {:?}", state);
+            if let Some(err) = wifi.last_error() {
+                println!("Last error {:?}", err);
             }
-            Err(err) => {
-                println!("{:?}", err);
-                loop {}
+            for access_point in wifi.scan_results() {
+                println!("{:?}", access_point);
             }
         }
-    }
-    println!("{:?}", controller.is_connected());
 
-    // wait for getting an ip address
-    println!("Wait to get an ip address");
-    loop {
-        wifi_stack.work();
-
-        if wifi_stack.is_iface_up() {
-            println!("got ip {:?}", wifi_stack.get_ip_info());
-            use core::fmt::Write as FmtWrite;
-            let mut ip_addr: heapless::String<256> = heapless::String::new();
-            let bytes = wifi_stack.get_ip_info().unwrap().ip.octets();
-            write!(
-                ip_addr,
-                "{}.{}.{}.{}",
-                bytes[0], bytes[1], bytes[2], bytes[3]
-            )
-            .unwrap();
+        let mut redraw = changed.is_some();
+        if let State::Connected { ip } = state {
+            let served = http::serve(
+                &mut http_socket,
+                HTTP_PORT,
+                &mut http_connection,
+                &mut request,
+                &mut response,
+                |request, buf| {
+                    let request = match request {
+                        Ok(request) => request,
+                        Err(err) => return (http::error_response(buf, err), false),
+                    };
+                    let routed = api::route(&request);
+                    let mut status = DeviceStatus::default();
+                    match routed {
+                        Ok(Command::Status) => {
+                            let reading = hts221.read(&mut i2c).ok();
+                            status = DeviceStatus {
+                                ip: Some(ip),
+                                rssi: wifi.rssi(),
+                                uptime_ms: current_millis(),
+                                free_heap: Some(ALLOCATOR.free()),
+                                temperature_dc: reading.map(|reading| reading.temperature_dc),
+                                humidity_dpct: reading.map(|reading| reading.humidity_dpct),
+                                buttons: buddy.buttons.state(),
+                                led,
+                            };
+                        }
+                        Ok(Command::ShowText(text)) => {
+                            message.clear();
+                            message.push_str(text).ok();
+                        }
+                        Ok(Command::SetLed(color)) => {
+                            led = color;
+                            ws2812.set(led_output.correct(led)).ok();
+                        }
+                        Err(_) => {}
+                    }
+                    let len = api::respond(routed, &status, buf);
+                    (len, matches!(routed, Ok(Command::ShowText(_))))
+                },
+            );
+            redraw |= served == Some(true);
+        }
+
+        if redraw {
             display.clear();
-            Text::with_baseline(
-                "WiFi example\nConnected.\nIP:",
-                Point::zero(),
-                text_style,
-                Baseline::Top,
-            )
-            .draw(&mut display)
-            .unwrap();
-            Text::new(&ip_addr, Point::new(21, 28), text_style)
-                .draw(&mut display)
-                .unwrap();
+            // Text sent over HTTP takes the place of the connection state
+            if message.is_empty() {
+                screens::wifi::draw_state(display, &state).unwrap();
+            } else {
+                Text::with_baseline(&message, Point::zero(), style::TEXT, Baseline::Top)
+                    .draw(display)
+                    .unwrap();
+            }
             display.flush().unwrap();
-            break;
         }
     }
-
-    println!("Start busy loop on main");
-
-    let mut rx_buffer = [0u8; 1536];
-    let mut tx_buffer = [0u8; 1536];
-    let _socket = wifi_stack.get_socket(&mut rx_buffer, &mut tx_buffer);
-
-    loop {}
 }
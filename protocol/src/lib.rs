@@ -0,0 +1,207 @@
+//! Wire format of the ESP-Buddy remote display.
+//!
+//! A packet is a 4 byte header, the magic `EB`, the protocol [`VERSION`] and
+//! the [`Kind`] of the payload, followed by one of:
+//!
+//! - a raw [`Frame`]: 128x32 pixels row by row, 16 bytes per row, most
+//!   significant bit first, a set bit is a lit pixel
+//! - the same frame compressed with [`rle`]
+//! - UTF-8 text of at most [`MAX_TEXT_LEN`] bytes, `\n` starts a new line
+//!
+//! Over UDP every datagram carries one packet. Over TCP every packet is
+//! preceded by its length as big endian `u16`, see [`split_stream`].
+//!
+//! ```
+//! use esp_buddy_protocol::{Packet, MAX_PACKET_LEN};
+//!
+//! let mut buf = [0u8; MAX_PACKET_LEN];
+//! let len = Packet::Text("Build #42\npassed").encode(&mut buf).unwrap();
+//! assert_eq!(Packet::decode(&buf[..len]), Ok(Packet::Text("Build #42\npassed")));
+//! ```
+
+#![no_std]
+
+pub mod rle;
+
+/// UDP and TCP port of the firmware.
+pub const PORT: u16 = 4210;
+
+pub const WIDTH: usize = 128;
+pub const HEIGHT: usize = 32;
+pub const FRAME_BYTES: usize = WIDTH * HEIGHT / 8;
+
+pub const MAGIC: [u8; 2] = *b"EB";
+pub const VERSION: u8 = 1;
+pub const HEADER_LEN: usize = 4;
+
+/// Longest text, 4 lines of 32 characters.
+pub const MAX_TEXT_LEN: usize = 128;
+
+/// Longest packet, a frame that does not compress.
+pub const MAX_PACKET_LEN: usize = HEADER_LEN + rle::MAX_COMPRESSED_LEN;
+
+/// Length prefix of the packets on a TCP stream.
+pub const LENGTH_PREFIX_LEN: usize = 2;
+
+/// Pixels of the display.
+pub type Frame = [u8; FRAME_BYTES];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Kind {
+    Raw = 1,
+    Rle = 2,
+    Text = 3,
+}
+
+impl TryFrom<u8> for Kind {
+    type Error = Error;
+
+    fn try_from(kind: u8) -> Result<Self, Error> {
+        match kind {
+            1 => Ok(Kind::Raw),
+            2 => Ok(Kind::Rle),
+            3 => Ok(Kind::Text),
+            kind => Err(Error::UnknownKind(kind)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    BufferTooSmall,
+    /// Shorter than the header.
+    Truncated,
+    BadMagic,
+    UnsupportedVersion(u8),
+    UnknownKind(u8),
+    /// A raw frame of another size than [`FRAME_BYTES`], or a stream packet
+    /// longer than [`MAX_PACKET_LEN`].
+    InvalidLength,
+    /// Compressed data that does not decompress to exactly one frame.
+    InvalidRle,
+    InvalidText,
+    TextTooLong,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Packet<'a> {
+    Raw(&'a Frame),
+    /// Frame compressed with [`rle::compress`].
+    Rle(&'a [u8]),
+    Text(&'a str),
+}
+
+impl<'a> Packet<'a> {
+    /// Parses a packet. The compressed data of [`Packet::Rle`] is only
+    /// checked by [`Packet::frame`].
+    pub fn decode(bytes: &'a [u8]) -> Result<Self, Error> {
+        let header = bytes.get(..HEADER_LEN).ok_or(Error::Truncated)?;
+        if header[..2] != MAGIC {
+            return Err(Error::BadMagic);
+        }
+        if header[2] != VERSION {
+            return Err(Error::UnsupportedVersion(header[2]));
+        }
+        let payload = &bytes[HEADER_LEN..];
+        match Kind::try_from(header[3])? {
+            Kind::Raw => payload
+                .try_into()
+                .map(Packet::Raw)
+                .map_err(|_| Error::InvalidLength),
+            Kind::Rle => Ok(Packet::Rle(payload)),
+            Kind::Text => {
+                let text = core::str::from_utf8(payload).map_err(|_| Error::InvalidText)?;
+                if text.len() > MAX_TEXT_LEN {
+                    return Err(Error::TextTooLong);
+                }
+                Ok(Packet::Text(text))
+            }
+        }
+    }
+
+    pub fn kind(&self) -> Kind {
+        match self {
+            Packet::Raw(_) => Kind::Raw,
+            Packet::Rle(_) => Kind::Rle,
+            Packet::Text(_) => Kind::Text,
+        }
+    }
+
+    fn payload(&self) -> &'a [u8] {
+        match *self {
+            Packet::Raw(frame) => frame,
+            Packet::Rle(data) => data,
+            Packet::Text(text) => text.as_bytes(),
+        }
+    }
+
+    /// Length of the encoded packet.
+    pub fn encoded_len(&self) -> usize {
+        HEADER_LEN + self.payload().len()
+    }
+
+    /// Writes the packet to `buf` and returns its length.
+    pub fn encode(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        if let Packet::Text(text) = self {
+            if text.len() > MAX_TEXT_LEN {
+                return Err(Error::TextTooLong);
+            }
+        }
+        let len = self.encoded_len();
+        let buf = buf.get_mut(..len).ok_or(Error::BufferTooSmall)?;
+        buf[..2].copy_from_slice(&MAGIC);
+        buf[2] = VERSION;
+        buf[3] = self.kind() as u8;
+        buf[HEADER_LEN..].copy_from_slice(self.payload());
+        Ok(len)
+    }
+
+    /// Writes the pixels of a raw or compressed frame to `frame`, returns
+    /// `false` for text.
+    pub fn frame(&self, frame: &mut Frame) -> Result<bool, Error> {
+        match self {
+            Packet::Raw(raw) => frame.copy_from_slice(*raw),
+            Packet::Rle(data) => rle::decompress(data, frame)?,
+            Packet::Text(_) => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+/// Writes `frame` as the shorter of a compressed and a raw packet and
+/// returns its length.
+pub fn encode_frame(frame: &Frame, buf: &mut [u8]) -> Result<usize, Error> {
+    let mut compressed = [0u8; rle::MAX_COMPRESSED_LEN];
+    let len = rle::compress(frame, &mut compressed)?;
+    if len < FRAME_BYTES {
+        Packet::Rle(&compressed[..len]).encode(buf)
+    } else {
+        Packet::Raw(frame).encode(buf)
+    }
+}
+
+/// Writes `packet` with its length prefix for a TCP stream.
+pub fn encode_stream(packet: &Packet, buf: &mut [u8]) -> Result<usize, Error> {
+    if buf.len() < LENGTH_PREFIX_LEN {
+        return Err(Error::BufferTooSmall);
+    }
+    let len = packet.encode(&mut buf[LENGTH_PREFIX_LEN..])?;
+    buf[..LENGTH_PREFIX_LEN].copy_from_slice(&(len as u16).to_be_bytes());
+    Ok(LENGTH_PREFIX_LEN + len)
+}
+
+/// Splits the first packet off the bytes received on a TCP stream so far.
+/// Returns the packet and the number of bytes it took including its prefix,
+/// `Ok(None)` until it is complete.
+pub fn split_stream(data: &[u8]) -> Result<Option<(&[u8], usize)>, Error> {
+    let Some(prefix) = data.get(..LENGTH_PREFIX_LEN) else {
+        return Ok(None);
+    };
+    let len = u16::from_be_bytes([prefix[0], prefix[1]]) as usize;
+    if !(HEADER_LEN..=MAX_PACKET_LEN).contains(&len) {
+        return Err(Error::InvalidLength);
+    }
+    let end = LENGTH_PREFIX_LEN + len;
+    Ok(data.get(LENGTH_PREFIX_LEN..end).map(|packet| (packet, end)))
+}
@@ -0,0 +1,104 @@
+//! PackBits run-length encoding, as used by TIFF and MacPaint.
+//!
+//! A header byte `n` is followed by `n + 1` literal bytes for `0..=127`, or
+//! by one byte repeated `1 - n` times for `-127..=-1` as `i8`. `-128` is not
+//! used.
+
+use crate::{Error, FRAME_BYTES};
+
+/// Longest encoding of a frame, one header byte per 128 literal bytes.
+pub const MAX_COMPRESSED_LEN: usize = FRAME_BYTES + FRAME_BYTES.div_ceil(MAX_RUN);
+
+const MAX_RUN: usize = 128;
+
+/// Compresses `data` into `out` and returns the compressed length.
+pub fn compress(data: &[u8], out: &mut [u8]) -> Result<usize, Error> {
+    let mut out = Output { buf: out, len: 0 };
+    let mut literal_start = 0;
+    let mut position = 0;
+    while position < data.len() {
+        let byte = data[position];
+        let run = data[position..]
+            .iter()
+            .take(MAX_RUN)
+            .take_while(|&&other| other == byte)
+            .count();
+        // Runs of two only pay off between two other runs, keep them literal
+        if run >= 3 {
+            out.literal(&data[literal_start..position])?;
+            out.push(&[(1 - run as i16) as u8, byte])?;
+            position += run;
+            literal_start = position;
+        } else {
+            position += 1;
+            if position - literal_start == MAX_RUN {
+                out.literal(&data[literal_start..position])?;
+                literal_start = position;
+            }
+        }
+    }
+    out.literal(&data[literal_start..])?;
+    Ok(out.len)
+}
+
+/// Decompresses `data`, which has to fill `out` exactly.
+pub fn decompress(data: &[u8], out: &mut [u8]) -> Result<(), Error> {
+    let mut position = 0;
+    let mut written = 0;
+    while let Some(&header) = data.get(position) {
+        position += 1;
+        let len = match header as i8 {
+            -128 => return Err(Error::InvalidRle),
+            n @ 0.. => {
+                let len = n as usize + 1;
+                let literal = data
+                    .get(position..position + len)
+                    .ok_or(Error::InvalidRle)?;
+                out.get_mut(written..written + len)
+                    .ok_or(Error::InvalidRle)?
+                    .copy_from_slice(literal);
+                position += len;
+                len
+            }
+            n => {
+                let len = (1 - n as isize) as usize;
+                let byte = *data.get(position).ok_or(Error::InvalidRle)?;
+                out.get_mut(written..written + len)
+                    .ok_or(Error::InvalidRle)?
+                    .fill(byte);
+                position += 1;
+                len
+            }
+        };
+        written += len;
+    }
+    if written != out.len() {
+        return Err(Error::InvalidRle);
+    }
+    Ok(())
+}
+
+struct Output<'b> {
+    buf: &'b mut [u8],
+    len: usize,
+}
+
+impl Output<'_> {
+    fn push(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        self.buf
+            .get_mut(self.len..self.len + bytes.len())
+            .ok_or(Error::BufferTooSmall)?
+            .copy_from_slice(bytes);
+        self.len += bytes.len();
+        Ok(())
+    }
+
+    /// Literal of at most [`MAX_RUN`] bytes, nothing when empty.
+    fn literal(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        self.push(&[bytes.len() as u8 - 1])?;
+        self.push(bytes)
+    }
+}
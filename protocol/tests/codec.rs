@@ -0,0 +1,219 @@
+use esp_buddy_protocol::{
+    encode_frame, encode_stream, rle, split_stream, Error, Frame, Kind, Packet, FRAME_BYTES,
+    HEADER_LEN, LENGTH_PREFIX_LEN, MAX_PACKET_LEN, MAX_TEXT_LEN,
+};
+
+/// Frame with a bit of everything: blank rows, stripes, a gradient of noise.
+fn test_frame() -> Frame {
+    let mut frame = [0u8; FRAME_BYTES];
+    let mut state = 0x2545_f491u32;
+    for (index, byte) in frame.iter_mut().enumerate() {
+        *byte = match index / 16 {
+            0..=7 => 0,
+            8..=15 => 0xaa,
+            16..=23 => index as u8,
+            _ => {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            }
+        };
+    }
+    frame
+}
+
+fn round_trip_rle(data: &[u8]) -> Vec<u8> {
+    let mut compressed = vec![0u8; data.len() + data.len().div_ceil(128)];
+    let len = rle::compress(data, &mut compressed).unwrap();
+    let mut decompressed = vec![0u8; data.len()];
+    rle::decompress(&compressed[..len], &mut decompressed).unwrap();
+    assert_eq!(decompressed, data);
+    compressed.truncate(len);
+    compressed
+}
+
+#[test]
+fn rle_encoding() {
+    assert_eq!(round_trip_rle(&[]), []);
+    assert_eq!(round_trip_rle(&[7]), [0, 7]);
+    assert_eq!(round_trip_rle(&[5, 5, 5]), [0xfe, 5]);
+    assert_eq!(round_trip_rle(&[1, 2, 2, 3]), [3, 1, 2, 2, 3]);
+    assert_eq!(
+        round_trip_rle(&[1, 2, 0, 0, 0, 0, 3]),
+        [1, 1, 2, 0xfd, 0, 0, 3]
+    );
+    // Runs longer than 128 are split
+    assert_eq!(round_trip_rle(&[9; 300]), [0x81, 9, 0x81, 9, 0xd5, 9]);
+    // Literals too
+    let literal: Vec<u8> = (0..=255).collect();
+    let compressed = round_trip_rle(&literal);
+    assert_eq!(compressed.len(), 258);
+    assert_eq!(compressed[0], 127);
+    assert_eq!(compressed[129], 127);
+}
+
+#[test]
+fn rle_round_trips() {
+    for frame in [[0u8; FRAME_BYTES], [0xff; FRAME_BYTES], test_frame()] {
+        let compressed = round_trip_rle(&frame);
+        assert!(compressed.len() <= rle::MAX_COMPRESSED_LEN);
+    }
+    assert_eq!(round_trip_rle(&[0u8; FRAME_BYTES]).len(), 8);
+
+    // Worst case, no two neighbours alike
+    let frame: Vec<u8> = (0..FRAME_BYTES).map(|index| index as u8).collect();
+    assert_eq!(round_trip_rle(&frame).len(), rle::MAX_COMPRESSED_LEN);
+}
+
+#[test]
+fn rle_rejects_invalid_data() {
+    let mut out = [0u8; 4];
+    assert_eq!(rle::decompress(&[0xfd, 1], &mut out), Ok(()));
+    assert_eq!(out, [1; 4]);
+    // Too short, too long, truncated, reserved header
+    assert_eq!(
+        rle::decompress(&[0xfe, 1], &mut out),
+        Err(Error::InvalidRle)
+    );
+    assert_eq!(
+        rle::decompress(&[0xfc, 1], &mut out),
+        Err(Error::InvalidRle)
+    );
+    assert_eq!(
+        rle::decompress(&[3, 1, 2], &mut out),
+        Err(Error::InvalidRle)
+    );
+    assert_eq!(rle::decompress(&[0xfd], &mut out), Err(Error::InvalidRle));
+    assert_eq!(
+        rle::decompress(&[0x80, 0xfd, 1], &mut out),
+        Err(Error::InvalidRle)
+    );
+
+    assert_eq!(
+        rle::compress(&[1, 2, 3], &mut [0u8; 3]),
+        Err(Error::BufferTooSmall)
+    );
+}
+
+#[test]
+fn packets_round_trip() {
+    let frame = test_frame();
+    let mut compressed = [0u8; rle::MAX_COMPRESSED_LEN];
+    let compressed_len = rle::compress(&frame, &mut compressed).unwrap();
+    let text = "Build #42\npassed ✓";
+
+    for packet in [
+        Packet::Raw(&frame),
+        Packet::Rle(&compressed[..compressed_len]),
+        Packet::Text(text),
+        Packet::Text(""),
+    ] {
+        let mut buf = [0u8; MAX_PACKET_LEN];
+        let len = packet.encode(&mut buf).unwrap();
+        assert_eq!(len, packet.encoded_len());
+        assert_eq!(&buf[..4], [b'E', b'B', 1, packet.kind() as u8]);
+        assert_eq!(Packet::decode(&buf[..len]), Ok(packet));
+    }
+}
+
+#[test]
+fn frames_decode_to_pixels() {
+    let frame = test_frame();
+    let mut buf = [0u8; MAX_PACKET_LEN];
+
+    let len = encode_frame(&frame, &mut buf).unwrap();
+    let packet = Packet::decode(&buf[..len]).unwrap();
+    assert_eq!(packet.kind(), Kind::Rle);
+    let mut decoded = [0u8; FRAME_BYTES];
+    assert_eq!(packet.frame(&mut decoded), Ok(true));
+    assert_eq!(decoded, frame);
+
+    // Noise does not compress, so it goes out raw
+    let noise: Frame = core::array::from_fn(|index| (index * 7 + index / 3) as u8);
+    let len = encode_frame(&noise, &mut buf).unwrap();
+    assert_eq!(len, HEADER_LEN + FRAME_BYTES);
+    let packet = Packet::decode(&buf[..len]).unwrap();
+    assert_eq!(packet, Packet::Raw(&noise));
+    assert_eq!(packet.frame(&mut decoded), Ok(true));
+    assert_eq!(decoded, noise);
+
+    assert_eq!(Packet::Text("Hi").frame(&mut decoded), Ok(false));
+    assert_eq!(
+        Packet::Rle(&[0x81, 0]).frame(&mut decoded),
+        Err(Error::InvalidRle)
+    );
+}
+
+#[test]
+fn invalid_packets() {
+    assert_eq!(Packet::decode(b"EB\x01"), Err(Error::Truncated));
+    assert_eq!(Packet::decode(b"XX\x01\x03"), Err(Error::BadMagic));
+    assert_eq!(
+        Packet::decode(b"EB\x02\x03"),
+        Err(Error::UnsupportedVersion(2))
+    );
+    assert_eq!(Packet::decode(b"EB\x01\x09"), Err(Error::UnknownKind(9)));
+    assert_eq!(
+        Packet::decode(b"EB\x01\x01\x00\x00"),
+        Err(Error::InvalidLength)
+    );
+    assert_eq!(Packet::decode(b"EB\x01\x03\xff"), Err(Error::InvalidText));
+
+    let long = "x".repeat(MAX_TEXT_LEN + 1);
+    let mut buf = [0u8; MAX_PACKET_LEN];
+    assert_eq!(
+        Packet::Text(&long).encode(&mut buf),
+        Err(Error::TextTooLong)
+    );
+    let mut packet = b"EB\x01\x03".to_vec();
+    packet.extend_from_slice(long.as_bytes());
+    assert_eq!(Packet::decode(&packet), Err(Error::TextTooLong));
+
+    assert_eq!(
+        Packet::Text("Hello").encode(&mut buf[..8]),
+        Err(Error::BufferTooSmall)
+    );
+}
+
+#[test]
+fn stream_framing() {
+    let frame = test_frame();
+    let mut stream = Vec::new();
+    let mut buf = [0u8; LENGTH_PREFIX_LEN + MAX_PACKET_LEN];
+    for packet in [
+        Packet::Text("one"),
+        Packet::Raw(&frame),
+        Packet::Text("two"),
+    ] {
+        let len = encode_stream(&packet, &mut buf).unwrap();
+        assert_eq!(
+            u16::from_be_bytes([buf[0], buf[1]]) as usize,
+            packet.encoded_len()
+        );
+        stream.extend_from_slice(&buf[..len]);
+    }
+
+    let mut decoded = Vec::new();
+    let mut rest = &stream[..];
+    while let Some((packet, used)) = split_stream(rest).unwrap() {
+        decoded.push(Packet::decode(packet).unwrap());
+        rest = &rest[used..];
+    }
+    assert!(rest.is_empty());
+    assert_eq!(
+        decoded,
+        [
+            Packet::Text("one"),
+            Packet::Raw(&frame),
+            Packet::Text("two")
+        ]
+    );
+
+    // Incomplete prefix or packet
+    assert_eq!(split_stream(&stream[..1]), Ok(None));
+    assert_eq!(split_stream(&stream[..8]), Ok(None));
+
+    assert_eq!(split_stream(&[0, 3, 0, 0, 0]), Err(Error::InvalidLength));
+    assert_eq!(split_stream(&[0xff, 0xff]), Err(Error::InvalidLength));
+}
@@ -0,0 +1,12 @@
+#!/usr/bin/env bash
+
+# Sends frames and text to the remote display example, e.g.:
+#   scripts/remote.sh 192.168.1.42 text "Build #42" "passed"
+#   scripts/remote.sh 192.168.1.42 --tcp image status.pbm
+
+set -e
+
+HOST_TARGET=$(rustc +stable -vV | sed -n 's/^host: //p')
+
+cargo +stable run --release --quiet --no-default-features --features remote \
+    --target "${HOST_TARGET}" --bin remote -- "$@"
@@ -0,0 +1,18 @@
+#!/usr/bin/env bash
+
+# Runs the desktop simulator on the host, e.g.:
+#   scripts/run-simulator.sh car
+#   scripts/run-simulator.sh snow --png target/frames --frames 100
+
+set -e
+
+HOST_TARGET=$(rustc +stable -vV | sed -n 's/^host: //p')
+
+FEATURES="simulator-window"
+if [[ " $* " == *" --png "* ]]; then
+    # PNG frames do not need SDL2, so this also works in headless CI
+    FEATURES="simulator"
+fi
+
+cargo +stable run --release --no-default-features --features "${FEATURES}" \
+    --target "${HOST_TARGET}" --bin simulator -- "$@"
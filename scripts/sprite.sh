@@ -0,0 +1,12 @@
+#!/usr/bin/env bash
+
+# Converts PNG images to sprite bitmaps, e.g.:
+#   scripts/sprite.sh car.png -o src/screens/car.pbm
+#   scripts/sprite.sh icon.png --format ascii
+
+set -e
+
+HOST_TARGET=$(rustc +stable -vV | sed -n 's/^host: //p')
+
+cargo +stable run --release --quiet --no-default-features --features sprite-tool \
+    --target "${HOST_TARGET}" --bin sprite -- "$@"
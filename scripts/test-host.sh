@@ -0,0 +1,12 @@
+#!/usr/bin/env bash
+
+# Runs the host tests of the library and the protocol crate, e.g.:
+#   scripts/test-host.sh
+#   BLESS=1 scripts/test-host.sh screens   # update golden images
+
+set -e
+
+HOST_TARGET=$(rustc +stable -vV | sed -n 's/^host: //p')
+
+cargo +stable test --no-default-features --target "${HOST_TARGET}" "$@"
+cargo +stable test --manifest-path protocol/Cargo.toml --target "${HOST_TARGET}" "$@"
@@ -0,0 +1,190 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    Stopped { remaining_ms: u64 },
+    Running { ends_at_ms: u64 },
+    Finished,
+}
+
+/// Countdown timer that can be paused and resumed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Countdown {
+    duration_ms: u64,
+    state: State,
+}
+
+impl Countdown {
+    /// Creates a stopped countdown.
+    pub fn new(duration_ms: u64) -> Self {
+        Self {
+            duration_ms,
+            state: State::Stopped {
+                remaining_ms: duration_ms,
+            },
+        }
+    }
+
+    pub fn duration_ms(&self) -> u64 {
+        self.duration_ms
+    }
+
+    /// Starts or resumes the countdown, a finished one starts over.
+    pub fn start(&mut self, now_ms: u64) {
+        let remaining_ms = match self.state {
+            State::Stopped { remaining_ms } => remaining_ms,
+            State::Running { .. } => return,
+            State::Finished => self.duration_ms,
+        };
+        self.state = State::Running {
+            ends_at_ms: now_ms + remaining_ms,
+        };
+    }
+
+    pub fn pause(&mut self, now_ms: u64) {
+        if let State::Running { .. } = self.state {
+            self.state = State::Stopped {
+                remaining_ms: self.remaining_ms(now_ms),
+            };
+        }
+    }
+
+    /// Pauses a running countdown, starts it otherwise.
+    pub fn toggle(&mut self, now_ms: u64) {
+        if self.is_running() {
+            self.pause(now_ms);
+        } else {
+            self.start(now_ms);
+        }
+    }
+
+    /// Stops the countdown and rewinds it to the full duration.
+    pub fn reset(&mut self) {
+        *self = Self::new(self.duration_ms);
+    }
+
+    pub fn is_running(&self) -> bool {
+        matches!(self.state, State::Running { .. })
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.state == State::Finished
+    }
+
+    pub fn remaining_ms(&self, now_ms: u64) -> u64 {
+        match self.state {
+            State::Stopped { remaining_ms } => remaining_ms,
+            State::Running { ends_at_ms } => ends_at_ms.saturating_sub(now_ms),
+            State::Finished => 0,
+        }
+    }
+
+    /// Returns `true` once, when the countdown reaches zero.
+    pub fn update(&mut self, now_ms: u64) -> bool {
+        match self.state {
+            State::Running { ends_at_ms } if now_ms >= ends_at_ms => {
+                self.state = State::Finished;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl Phase {
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::Work => "Work",
+            Phase::ShortBreak => "Break",
+            Phase::LongBreak => "Long break",
+        }
+    }
+}
+
+/// Pomodoro timer: work sessions separated by short breaks and a long break
+/// after every fourth session. The next phase starts right away.
+pub struct Pomodoro {
+    phase: Phase,
+    /// Work sessions finished so far.
+    sessions: u32,
+    countdown: Countdown,
+    work_ms: u64,
+    short_break_ms: u64,
+    long_break_ms: u64,
+}
+
+impl Default for Pomodoro {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pomodoro {
+    pub const SESSIONS_PER_LONG_BREAK: u32 = 4;
+
+    /// 25 minutes of work, 5 minute breaks and a 15 minute long break.
+    pub fn new() -> Self {
+        Self::with_durations_min(25, 5, 15)
+    }
+
+    pub fn with_durations_min(work: u64, short_break: u64, long_break: u64) -> Self {
+        let work_ms = work * 60_000;
+        Self {
+            phase: Phase::Work,
+            sessions: 0,
+            countdown: Countdown::new(work_ms),
+            work_ms,
+            short_break_ms: short_break * 60_000,
+            long_break_ms: long_break * 60_000,
+        }
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    pub fn sessions(&self) -> u32 {
+        self.sessions
+    }
+
+    pub fn countdown(&self) -> &Countdown {
+        &self.countdown
+    }
+
+    pub fn countdown_mut(&mut self) -> &mut Countdown {
+        &mut self.countdown
+    }
+
+    /// Returns the new phase when the current one ended.
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)]
+    pub fn update(&mut self, now_ms: u64) -> Option<Phase> {
+        if !self.countdown.update(now_ms) {
+            return None;
+        }
+
+        self.phase = match self.phase {
+            Phase::Work => {
+                self.sessions += 1;
+                if self.sessions % Self::SESSIONS_PER_LONG_BREAK == 0 {
+                    Phase::LongBreak
+                } else {
+                    Phase::ShortBreak
+                }
+            }
+            Phase::ShortBreak | Phase::LongBreak => Phase::Work,
+        };
+        let duration_ms = match self.phase {
+            Phase::Work => self.work_ms,
+            Phase::ShortBreak => self.short_break_ms,
+            Phase::LongBreak => self.long_break_ms,
+        };
+        self.countdown = Countdown::new(duration_ms);
+        self.countdown.start(now_ms);
+        Some(self.phase)
+    }
+}
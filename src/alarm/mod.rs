@@ -0,0 +1,229 @@
+//! Alarms, countdown timers and a stopwatch.
+//!
+//! Everything here is a pure state machine. The caller passes the monotonic
+//! time in milliseconds and, for alarms, the local date and time, maps the
+//! buttons to method calls and shows [`Scheduler::flash`] by inverting the
+//! display.
+
+mod countdown;
+mod stopwatch;
+
+pub use countdown::{Countdown, Phase, Pomodoro};
+pub use stopwatch::Stopwatch;
+
+use crate::time::{DateTime, Weekday, SECONDS_PER_MINUTE};
+
+/// Half period of the flashing while an alarm rings.
+pub const FLASH_MS: u64 = 500;
+
+/// Days an alarm repeats on, none for a one-shot alarm.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Weekdays(u8);
+
+impl Weekdays {
+    pub const NONE: Self = Self(0);
+    pub const WORKDAYS: Self = Self(0b011_1110);
+    pub const WEEKEND: Self = Self(0b100_0001);
+    pub const EVERY_DAY: Self = Self(0b111_1111);
+
+    pub const fn with(self, day: Weekday) -> Self {
+        Self(self.0 | 1 << day as u8)
+    }
+
+    pub fn contains(self, day: Weekday) -> bool {
+        self.0 & 1 << day as u8 != 0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+/// Alarm at a local wall-clock time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Alarm {
+    pub hour: u8,
+    pub minute: u8,
+    /// Repeats on these days, rings once and disables itself when empty.
+    pub days: Weekdays,
+    pub enabled: bool,
+}
+
+impl Alarm {
+    pub const fn once(hour: u8, minute: u8) -> Self {
+        Self::weekly(hour, minute, Weekdays::NONE)
+    }
+
+    pub const fn weekly(hour: u8, minute: u8, days: Weekdays) -> Self {
+        Self {
+            hour,
+            minute,
+            days,
+            enabled: true,
+        }
+    }
+
+    fn is_due(&self, local: &DateTime) -> bool {
+        self.enabled
+            && self.hour == local.hour
+            && self.minute == local.minute
+            && (self.days.is_empty() || self.days.contains(local.weekday))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum State {
+    Idle,
+    Ringing { alarm: usize, since_ms: u64 },
+    Snoozed { alarm: usize, until_ms: u64 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// The alarm at this index started ringing, also after a snooze.
+    Ring(usize),
+    /// Nobody reacted and the alarm stopped by itself.
+    Timeout(usize),
+}
+
+/// Up to `N` alarms, at most one of them ringing or snoozed.
+pub struct Scheduler<const N: usize> {
+    alarms: heapless::Vec<Alarm, N>,
+    state: State,
+    snooze_ms: u64,
+    ring_ms: u64,
+    /// Local minute of the last update, an alarm rings once per matching minute.
+    last_minute: Option<i64>,
+}
+
+impl<const N: usize> Default for Scheduler<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Scheduler<N> {
+    pub const DEFAULT_SNOOZE_S: u64 = 9 * 60;
+    pub const DEFAULT_RING_S: u64 = 2 * 60;
+
+    pub fn new() -> Self {
+        Self {
+            alarms: heapless::Vec::new(),
+            state: State::Idle,
+            snooze_ms: Self::DEFAULT_SNOOZE_S * 1000,
+            ring_ms: Self::DEFAULT_RING_S * 1000,
+            last_minute: None,
+        }
+    }
+
+    pub fn with_snooze_s(mut self, snooze_s: u64) -> Self {
+        self.snooze_ms = snooze_s * 1000;
+        self
+    }
+
+    /// Time after which a ringing alarm stops by itself.
+    pub fn with_ring_s(mut self, ring_s: u64) -> Self {
+        self.ring_ms = ring_s * 1000;
+        self
+    }
+
+    /// Adds an alarm and returns its index, or gives it back when full.
+    pub fn add(&mut self, alarm: Alarm) -> Result<usize, Alarm> {
+        self.alarms.push(alarm)?;
+        Ok(self.alarms.len() - 1)
+    }
+
+    /// Removes an alarm, shifting the later ones down. Silences a ringing
+    /// or snoozed alarm.
+    pub fn remove(&mut self, index: usize) -> Option<Alarm> {
+        if index >= self.alarms.len() {
+            return None;
+        }
+        self.state = State::Idle;
+        Some(self.alarms.remove(index))
+    }
+
+    pub fn alarms(&self) -> &[Alarm] {
+        &self.alarms
+    }
+
+    pub fn alarm_mut(&mut self, index: usize) -> Option<&mut Alarm> {
+        self.alarms.get_mut(index)
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    pub fn is_ringing(&self) -> bool {
+        matches!(self.state, State::Ringing { .. })
+    }
+
+    /// Advances the state machine, call it at least once per second.
+    ///
+    /// An alarm is due during its whole minute, a minute skipped by a
+    /// daylight saving time change does not ring.
+    pub fn update(&mut self, now_ms: u64, local: &DateTime) -> Option<Event> {
+        let minute = local.to_unix().div_euclid(SECONDS_PER_MINUTE);
+        let new_minute = self.last_minute != Some(minute);
+        self.last_minute = Some(minute);
+
+        match self.state {
+            State::Ringing { alarm, since_ms } if now_ms - since_ms >= self.ring_ms => {
+                self.state = State::Idle;
+                return Some(Event::Timeout(alarm));
+            }
+            State::Snoozed { alarm, until_ms } if now_ms >= until_ms => {
+                self.state = State::Ringing {
+                    alarm,
+                    since_ms: now_ms,
+                };
+                return Some(Event::Ring(alarm));
+            }
+            _ => {}
+        }
+
+        if !new_minute {
+            return None;
+        }
+        let index = self.alarms.iter().position(|alarm| alarm.is_due(local))?;
+        let alarm = &mut self.alarms[index];
+        if alarm.days.is_empty() {
+            alarm.enabled = false;
+        }
+        // A newly due alarm replaces one that is ringing or snoozed
+        self.state = State::Ringing {
+            alarm: index,
+            since_ms: now_ms,
+        };
+        Some(Event::Ring(index))
+    }
+
+    /// Silences a ringing alarm for the snooze time, returns whether it rang.
+    pub fn snooze(&mut self, now_ms: u64) -> bool {
+        let State::Ringing { alarm, .. } = self.state else {
+            return false;
+        };
+        self.state = State::Snoozed {
+            alarm,
+            until_ms: now_ms + self.snooze_ms,
+        };
+        true
+    }
+
+    /// Stops a ringing or snoozed alarm, returns whether there was one.
+    pub fn dismiss(&mut self) -> bool {
+        let active = self.state != State::Idle;
+        self.state = State::Idle;
+        active
+    }
+
+    /// Whether the display should be inverted and the LED lit right now.
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)]
+    pub fn flash(&self, now_ms: u64) -> bool {
+        match self.state {
+            State::Ringing { since_ms, .. } => (now_ms - since_ms) / FLASH_MS % 2 == 0,
+            _ => false,
+        }
+    }
+}
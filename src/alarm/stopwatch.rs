@@ -0,0 +1,57 @@
+/// Stopwatch with lap times.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stopwatch {
+    /// Time accumulated before the last start.
+    elapsed_ms: u64,
+    started_at_ms: Option<u64>,
+    /// Elapsed time at the last lap.
+    lap_ms: u64,
+}
+
+impl Stopwatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn start(&mut self, now_ms: u64) {
+        if self.started_at_ms.is_none() {
+            self.started_at_ms = Some(now_ms);
+        }
+    }
+
+    pub fn stop(&mut self, now_ms: u64) {
+        self.elapsed_ms = self.elapsed_ms(now_ms);
+        self.started_at_ms = None;
+    }
+
+    pub fn toggle(&mut self, now_ms: u64) {
+        if self.is_running() {
+            self.stop(now_ms);
+        } else {
+            self.start(now_ms);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.started_at_ms.is_some()
+    }
+
+    pub fn elapsed_ms(&self, now_ms: u64) -> u64 {
+        let running = self
+            .started_at_ms
+            .map_or(0, |started_at| now_ms.saturating_sub(started_at));
+        self.elapsed_ms + running
+    }
+
+    /// Returns the time since the previous lap, or since the start.
+    pub fn lap(&mut self, now_ms: u64) -> u64 {
+        let elapsed_ms = self.elapsed_ms(now_ms);
+        let lap = elapsed_ms - self.lap_ms;
+        self.lap_ms = elapsed_ms;
+        lap
+    }
+}
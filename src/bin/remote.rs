@@ -0,0 +1,168 @@
+//! Sends frames and text to the remote display example.
+//!
+//! ```text
+//! remote <host>[:port] [--tcp] text <line>...
+//! remote <host>[:port] [--tcp] image <file.pbm> [--invert]
+//! remote <host>[:port] [--tcp] clear
+//! ```
+//!
+//! Text lines come from the arguments, or from stdin for `-`. Images are
+//! 128x32 PBM files, black pixels are lit; other images can be converted
+//! with e.g. `convert in.png -resize 128x32! -monochrome out.pbm`. Packets go
+//! out as UDP datagrams, or over a TCP connection with `--tcp`.
+
+use std::{
+    io::{Read, Write},
+    net::{Shutdown, TcpStream, UdpSocket},
+    process::ExitCode,
+};
+
+use esp_buddy_protocol::{
+    encode_frame, Frame, Packet, FRAME_BYTES, HEIGHT, MAX_PACKET_LEN, MAX_TEXT_LEN, PORT, WIDTH,
+};
+
+const USAGE: &str = "usage: remote <host>[:port] [--tcp] \
+                     <text <line>...|image <file.pbm> [--invert]|clear>";
+
+fn main() -> ExitCode {
+    match run(std::env::args().skip(1).collect()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(mut args: Vec<String>) -> Result<(), String> {
+    let tcp = match args.iter().position(|arg| arg == "--tcp") {
+        Some(index) => {
+            args.remove(index);
+            true
+        }
+        None => false,
+    };
+    let mut args = args.into_iter();
+    let (Some(host), Some(command)) = (args.next(), args.next()) else {
+        return Err(USAGE.into());
+    };
+    let address = if host.contains(':') {
+        host
+    } else {
+        format!("{host}:{PORT}")
+    };
+
+    let mut packet = [0u8; MAX_PACKET_LEN];
+    let len = match command.as_str() {
+        "text" => {
+            let lines: Vec<String> = args.collect();
+            let text = match lines.as_slice() {
+                [dash] if dash == "-" => {
+                    let mut text = String::new();
+                    std::io::stdin()
+                        .read_to_string(&mut text)
+                        .map_err(|err| format!("stdin: {err}"))?;
+                    text.trim_end().to_string()
+                }
+                _ => lines.join("\n"),
+            };
+            if text.len() > MAX_TEXT_LEN {
+                return Err(format!("text longer than {MAX_TEXT_LEN} bytes"));
+            }
+            Packet::Text(&text).encode(&mut packet)
+        }
+        "image" => {
+            let (Some(path), invert) = (args.next(), args.next()) else {
+                return Err(USAGE.into());
+            };
+            let invert = match invert.as_deref() {
+                Some("--invert") => true,
+                None => false,
+                Some(_) => return Err(USAGE.into()),
+            };
+            let bytes = std::fs::read(&path).map_err(|err| format!("{path}: {err}"))?;
+            let mut frame = decode_pbm(&bytes).map_err(|err| format!("{path}: {err}"))?;
+            if invert {
+                frame.iter_mut().for_each(|byte| *byte = !*byte);
+            }
+            encode_frame(&frame, &mut packet)
+        }
+        "clear" => encode_frame(&[0; FRAME_BYTES], &mut packet),
+        _ => return Err(USAGE.into()),
+    }
+    .map_err(|err| format!("encoding failed: {err:?}"))?;
+
+    send(&address, &packet[..len], tcp).map_err(|err| format!("{address}: {err}"))
+}
+
+fn send(address: &str, packet: &[u8], tcp: bool) -> std::io::Result<()> {
+    if tcp {
+        let mut stream = TcpStream::connect(address)?;
+        // Length prefix of the stream framing, see `split_stream`
+        stream.write_all(&(packet.len() as u16).to_be_bytes())?;
+        stream.write_all(packet)?;
+        stream.shutdown(Shutdown::Write)
+    } else {
+        let socket = UdpSocket::bind("0.0.0.0:0")?;
+        socket.send_to(packet, address)?;
+        Ok(())
+    }
+}
+
+/// Reads a plain (`P1`) or binary (`P4`) PBM image of the display size.
+fn decode_pbm(bytes: &[u8]) -> Result<Frame, String> {
+    // Magic, width and height separated by whitespace or comments
+    let mut fields = Vec::new();
+    let mut position = 0;
+    while fields.len() < 3 {
+        match bytes.get(position) {
+            Some(b'#') => {
+                while bytes.get(position).is_some_and(|&byte| byte != b'\n') {
+                    position += 1;
+                }
+            }
+            Some(byte) if byte.is_ascii_whitespace() => position += 1,
+            Some(_) => {
+                let start = position;
+                while bytes
+                    .get(position)
+                    .is_some_and(|byte| !byte.is_ascii_whitespace())
+                {
+                    position += 1;
+                }
+                fields.push(String::from_utf8_lossy(&bytes[start..position]).into_owned());
+            }
+            None => return Err("truncated PBM header".into()),
+        }
+    }
+    let plain = match fields[0].as_str() {
+        "P1" => true,
+        "P4" => false,
+        magic => return Err(format!("not a PBM image: {magic}")),
+    };
+    if fields[1..] != [WIDTH.to_string(), HEIGHT.to_string()] {
+        return Err(format!(
+            "image is {}x{}, expected {WIDTH}x{HEIGHT}",
+            fields[1], fields[2]
+        ));
+    }
+
+    if !plain {
+        return bytes
+            .get(position + 1..)
+            .and_then(|data| Frame::try_from(data).ok())
+            .ok_or_else(|| "pixel data does not match the size".into());
+    }
+    let mut frame = [0; FRAME_BYTES];
+    let mut pixels = bytes[position..]
+        .iter()
+        .filter(|byte| !byte.is_ascii_whitespace());
+    for index in 0..WIDTH * HEIGHT {
+        match pixels.next() {
+            Some(b'1') => frame[index / 8] |= 0x80 >> (index % 8),
+            Some(b'0') => {}
+            _ => return Err("pixel data does not match the size".into()),
+        }
+    }
+    Ok(frame)
+}
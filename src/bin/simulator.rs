@@ -0,0 +1,208 @@
+//! Desktop simulator of the ESP-Buddy display.
+//!
+//! Renders the screens of the examples in a window (feature `simulator-window`)
+//! or to PNG frames (feature `simulator`), without any hardware:
+//!
+//! ```text
+//! simulator <animation|buttons|car|clock|snow|timer> [--png <dir>] [--frames <n>]
+//! ```
+//!
+//! In the window the left/right arrow keys (or `A`/`D`) act as the left and
+//! right buttons of the board. The clock shows the POSIX `TZ` zone if set,
+//! e.g. `TZ=CET-1CEST,M3.5.0,M10.5.0/3`, UTC otherwise.
+
+use std::{
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::SystemTime,
+};
+
+use embedded_graphics::{pixelcolor::BinaryColor, prelude::*};
+use embedded_graphics_simulator::{BinaryColorTheme, OutputSettingsBuilder, SimulatorDisplay};
+use esp_buddy::{
+    screens::{Animation, ButtonState, Buttons, Car, Clock, Scene, Snow, Timers, DISPLAY_SIZE},
+    time::TimeZone,
+};
+use rand_core::{impls, RngCore};
+
+const USAGE: &str =
+    "usage: simulator <animation|buttons|car|clock|snow|timer> [--png <dir>] [--frames <n>]";
+
+struct Options {
+    /// Write PNG frames into this directory instead of opening a window.
+    png: Option<PathBuf>,
+    /// Number of frames to render, unlimited in a window by default.
+    frames: Option<u32>,
+}
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let Some(scene) = args.next() else {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    };
+
+    let mut options = Options {
+        png: None,
+        frames: None,
+    };
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--png", Some(dir)) => options.png = Some(dir.into()),
+            ("--frames", Some(n)) => match n.parse() {
+                Ok(n) => options.frames = Some(n),
+                Err(_) => {
+                    eprintln!("invalid frame count: {n}");
+                    return ExitCode::FAILURE;
+                }
+            },
+            _ => {
+                eprintln!("{USAGE}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let result = match scene.as_str() {
+        "animation" => run(Animation::new(), &options),
+        "buttons" => run(Buttons::new(), &options),
+        "car" => run(Car::new(), &options),
+        "clock" => match time_zone() {
+            Ok(time_zone) => run(
+                Clock::new(unix_time() as i64).with_time_zone(time_zone),
+                &options,
+            ),
+            Err(err) => Err(err),
+        },
+        "snow" => run(Snow::new(XorShift(unix_time() as u32 | 1)), &options),
+        "timer" => run(Timers::new(), &options),
+        _ => {
+            eprintln!("unknown scene: {scene}\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run<S: Scene>(scene: S, options: &Options) -> Result<(), String> {
+    match &options.png {
+        Some(dir) => render_png(scene, dir, options.frames.unwrap_or(1)),
+        None => show_window(scene, options.frames),
+    }
+}
+
+fn render_png<S: Scene>(mut scene: S, dir: &Path, frames: u32) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|err| format!("{}: {err}", dir.display()))?;
+
+    let output_settings = OutputSettingsBuilder::new()
+        .theme(BinaryColorTheme::OledBlue)
+        .scale(4)
+        .build();
+    let mut display = SimulatorDisplay::<BinaryColor>::new(DISPLAY_SIZE);
+
+    for frame in 0..frames {
+        display.clear(BinaryColor::Off).unwrap();
+        scene.draw(&mut display).unwrap();
+
+        let path = dir.join(format!("frame-{frame:04}.png"));
+        display
+            .to_rgb_output_image(&output_settings)
+            .save_png(&path)
+            .map_err(|err| format!("{}: {err}", path.display()))?;
+
+        scene.update(ButtonState::default());
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "simulator-window")]
+fn show_window<S: Scene>(mut scene: S, frames: Option<u32>) -> Result<(), String> {
+    use std::time::Duration;
+
+    use embedded_graphics_simulator::{sdl2::Keycode, SimulatorEvent, Window};
+
+    let output_settings = OutputSettingsBuilder::new()
+        .theme(BinaryColorTheme::OledBlue)
+        .scale(4)
+        .build();
+    let mut window = Window::new("ESP-Buddy", &output_settings);
+    let mut display = SimulatorDisplay::<BinaryColor>::new(DISPLAY_SIZE);
+    let mut buttons = ButtonState::default();
+
+    for _ in 0..frames.unwrap_or(u32::MAX) {
+        display.clear(BinaryColor::Off).unwrap();
+        scene.draw(&mut display).unwrap();
+        window.update(&display);
+
+        for event in window.events() {
+            let (keycode, pressed) = match event {
+                SimulatorEvent::Quit => return Ok(()),
+                SimulatorEvent::KeyDown { keycode, .. } => (keycode, true),
+                SimulatorEvent::KeyUp { keycode, .. } => (keycode, false),
+                _ => continue,
+            };
+            match keycode {
+                Keycode::Left | Keycode::A => buttons.left = pressed,
+                Keycode::Right | Keycode::D => buttons.right = pressed,
+                Keycode::Escape | Keycode::Q => return Ok(()),
+                _ => {}
+            }
+        }
+
+        std::thread::sleep(Duration::from_millis(S::FRAME_MS.into()));
+        scene.update(buttons);
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "simulator-window"))]
+fn show_window<S: Scene>(_scene: S, _frames: Option<u32>) -> Result<(), String> {
+    Err("built without the `simulator-window` feature, use --png <dir>".into())
+}
+
+fn time_zone() -> Result<TimeZone<'static>, String> {
+    let Ok(tz) = std::env::var("TZ") else {
+        return Ok(TimeZone::UTC);
+    };
+    TimeZone::parse(tz.leak()).map_err(|err| format!("invalid TZ: {err:?}"))
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+/// Small PRNG standing in for the hardware RNG of the ESP32.
+struct XorShift(u32);
+
+impl RngCore for XorShift {
+    fn next_u32(&mut self) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_u32(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
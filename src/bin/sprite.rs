@@ -0,0 +1,155 @@
+//! Converts PNG images to sprite bitmaps for `esp_buddy::gfx::sprite`.
+//!
+//! ```text
+//! sprite <in.png> [--format pbm|xbm|ascii] [--threshold <0-255>] [--invert] [-o <out>]
+//! ```
+//!
+//! Opaque pixels darker than the threshold, 128 by default, are lit, like
+//! black pixels in PBM images; `--invert` lights the brighter ones instead.
+//! The output is a binary PBM image for `Bitmap::from_pbm` by default, XBM
+//! source for `xbm_bitmap!` or ASCII art for `ascii_bitmap!`. It goes to
+//! stdout without `-o`. XBM identifiers are named after the input file.
+
+use std::{fs::File, io::Write, path::Path, process::ExitCode};
+
+use esp_buddy::gfx::sprite::Bitmap;
+
+const USAGE: &str = "usage: sprite <in.png> [--format pbm|xbm|ascii] \
+                     [--threshold <0-255>] [--invert] [-o <out>]";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Pbm,
+    Xbm,
+    Ascii,
+}
+
+fn main() -> ExitCode {
+    match run(std::env::args().skip(1).collect()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Vec<String>) -> Result<(), String> {
+    let mut input = None;
+    let mut output = None;
+    let mut format = Format::Pbm;
+    let mut threshold = 128;
+    let mut invert = false;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                format = match args.next().as_deref() {
+                    Some("pbm") => Format::Pbm,
+                    Some("xbm") => Format::Xbm,
+                    Some("ascii") => Format::Ascii,
+                    _ => return Err(USAGE.into()),
+                }
+            }
+            "--threshold" => {
+                threshold = args
+                    .next()
+                    .and_then(|value| value.parse::<u8>().ok())
+                    .ok_or(USAGE)?
+            }
+            "--invert" => invert = true,
+            "-o" => output = Some(args.next().ok_or(USAGE)?),
+            _ if input.is_none() && !arg.starts_with('-') => input = Some(arg),
+            _ => return Err(USAGE.into()),
+        }
+    }
+    let input = input.ok_or(USAGE)?;
+
+    let (width, height, data) = read_png(&input, threshold, invert)?;
+    let bitmap = Bitmap::new(width, height, &data);
+    let bytes = match format {
+        Format::Pbm => {
+            let mut bytes = format!("P4\n{width} {height}\n").into_bytes();
+            bytes.extend_from_slice(bitmap.data());
+            bytes
+        }
+        Format::Xbm => {
+            let name = identifier(&input);
+            let mut source = String::new();
+            bitmap.write_xbm(&mut source, &name).unwrap();
+            source.into_bytes()
+        }
+        Format::Ascii => {
+            let mut art = String::new();
+            bitmap.write_ascii(&mut art).unwrap();
+            art.into_bytes()
+        }
+    };
+
+    match output {
+        Some(path) => std::fs::write(&path, bytes).map_err(|err| format!("{path}: {err}")),
+        None => std::io::stdout()
+            .write_all(&bytes)
+            .map_err(|err| err.to_string()),
+    }
+}
+
+/// Size and packed rows of the image, see [`Bitmap`].
+fn read_png(path: &str, threshold: u8, invert: bool) -> Result<(u32, u32, Vec<u8>), String> {
+    let file = File::open(path).map_err(|err| format!("{path}: {err}"))?;
+    let mut decoder = png::Decoder::new(file);
+    // Palettes, transparency and bit depths become 8-bit channels
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder
+        .read_info()
+        .map_err(|err| format!("{path}: {err}"))?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut buf)
+        .map_err(|err| format!("{path}: {err}"))?;
+
+    let channels = info.color_type.samples();
+    let row_bytes = Bitmap::row_bytes(info.width);
+    let mut data = vec![0; Bitmap::byte_len(info.width, info.height)];
+    for (y, row) in buf
+        .chunks(info.line_size)
+        .take(info.height as usize)
+        .enumerate()
+    {
+        for (x, pixel) in row.chunks(channels).take(info.width as usize).enumerate() {
+            let (luma, alpha) = match *pixel {
+                [gray] => (gray, 255),
+                [gray, alpha] => (gray, alpha),
+                [r, g, b] => (luma(r, g, b), 255),
+                [r, g, b, alpha] => (luma(r, g, b), alpha),
+                _ => return Err(format!("{path}: unsupported color type")),
+            };
+            let dark = luma < threshold;
+            if alpha >= 128 && dark != invert {
+                data[y * row_bytes + x / 8] |= 0x80 >> (x % 8);
+            }
+        }
+    }
+    Ok((info.width, info.height, data))
+}
+
+/// Perceived brightness of a color.
+fn luma(r: u8, g: u8, b: u8) -> u8 {
+    ((299 * u32::from(r) + 587 * u32::from(g) + 114 * u32::from(b)) / 1000) as u8
+}
+
+/// C identifier from the file name, e.g. `car` for `assets/car.png`.
+fn identifier(path: &str) -> String {
+    let stem = Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name: String = stem
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    match name.chars().next() {
+        Some(first) if !first.is_ascii_digit() => name,
+        _ => format!("_{name}"),
+    }
+}
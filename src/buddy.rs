@@ -0,0 +1,140 @@
+use hal::{
+    delay::Delay,
+    gpio::{GpioPin, Input, Io, Pin, Pull},
+    i2c::I2c,
+    peripherals::{GPIO, I2C0, IO_MUX, RNG},
+    prelude::*,
+    rng::Rng,
+    Blocking,
+};
+use shared_bus::{BusManagerXtensa, I2cProxy, XtensaMutex};
+use ssd1306::{mode::BufferedGraphicsMode, prelude::*, I2CDisplayInterface, Ssd1306};
+
+use crate::pins::{self, BoardPins};
+use crate::screens::{ButtonState, Scene};
+use crate::settings::{self, schema::Rotation};
+
+/// I2C0 peripheral wired to the display and the sensors.
+pub type I2cBus = I2c<'static, I2C0, Blocking>;
+
+/// Handle to the shared I2C bus, one per device on the bus.
+pub type SharedI2c = I2cProxy<'static, XtensaMutex<I2cBus>>;
+
+/// The 128x32 SSD1306 OLED in buffered graphics mode.
+pub type Display =
+    Ssd1306<I2CInterface<SharedI2c>, DisplaySize128x32, BufferedGraphicsMode<DisplaySize128x32>>;
+
+/// The two user buttons. Both are active low with internal pull-ups.
+pub struct Buttons {
+    pub left: Input<'static>,
+    pub right: Input<'static>,
+}
+
+impl Buttons {
+    pub fn left_pressed(&self) -> bool {
+        self.left.is_low()
+    }
+
+    pub fn right_pressed(&self) -> bool {
+        self.right.is_low()
+    }
+
+    pub fn state(&self) -> ButtonState {
+        ButtonState {
+            left: self.left_pressed(),
+            right: self.right_pressed(),
+        }
+    }
+}
+
+/// Everything on the ESP-Buddy board, ready to use.
+pub struct Buddy {
+    pub display: Display,
+    pub buttons: Buttons,
+    /// Data line of the WS2812 LED, left unconfigured for [`crate::led::Ws2812`].
+    pub led: GpioPin<{ pins::LED }>,
+    /// Shared I2C bus, acquire a proxy for every additional sensor.
+    pub i2c: &'static BusManagerXtensa<I2cBus>,
+    pub rng: Rng,
+    pub delay: Delay,
+}
+
+impl Buddy {
+    /// Brings up the board from the peripherals returned by `hal::init`.
+    ///
+    /// The display is initialized, cleared and set up as stored in the
+    /// settings. The remaining peripherals (`WIFI`, `TIMG1`, `RADIO_CLK`,
+    /// ...) stay available to the caller.
+    pub fn new(i2c0: I2C0, gpio: GPIO, io_mux: IO_MUX, rng: RNG) -> Self {
+        let mut io = Io::new(gpio, io_mux);
+        // Handles the buttons once moved to `input::Interrupts`
+        io.set_interrupt_handler(crate::input::interrupt_handler);
+        let pins = BoardPins::new(io.pins);
+
+        let i2c = new_i2c_bus(i2c0, pins.sda, pins.scl);
+        let mut display = new_display(i2c.acquire_i2c());
+        let config = settings::open()
+            .and_then(|mut store| store.config())
+            .unwrap_or_default();
+        configure_display(&mut display, config.rotation, config.contrast_pct);
+
+        let buttons = Buttons {
+            left: Input::new(pins.button_left.degrade(), Pull::Up),
+            right: Input::new(pins.button_right.degrade(), Pull::Up),
+        };
+
+        Self {
+            display,
+            buttons,
+            led: pins.led,
+            i2c,
+            rng: Rng::new(rng),
+            delay: Delay::new(),
+        }
+    }
+
+    /// Shows the scene on the display forever.
+    pub fn run<S: Scene>(&mut self, scene: &mut S) -> ! {
+        loop {
+            self.display.clear();
+            scene.draw(&mut self.display).unwrap();
+            self.display.flush().unwrap();
+            self.delay.delay_millis(S::FRAME_MS);
+            scene.update(self.buttons.state());
+        }
+    }
+}
+
+/// Creates the shared I2C bus on the board's SDA/SCL pins.
+///
+/// Can be called only once, [`Buddy::new`] calls it for you.
+pub fn new_i2c_bus(
+    i2c0: I2C0,
+    sda: GpioPin<{ pins::I2C_SDA }>,
+    scl: GpioPin<{ pins::I2C_SCL }>,
+) -> &'static BusManagerXtensa<I2cBus> {
+    let i2c = I2c::new(i2c0, sda, scl, pins::I2C_FREQUENCY_KHZ.kHz());
+    shared_bus::new_xtensa!(I2cBus = i2c).unwrap()
+}
+
+/// Creates and initializes the display on the given bus proxy.
+pub fn new_display(i2c: SharedI2c) -> Display {
+    let interface = I2CDisplayInterface::new_custom_address(i2c, pins::DISPLAY_ADDRESS);
+    let mut display = Ssd1306::new(interface, DisplaySize128x32, DisplayRotation::Rotate0)
+        .into_buffered_graphics_mode();
+    display.init().unwrap();
+    display
+}
+
+/// Applies the rotation and contrast settings to the display.
+pub fn configure_display(display: &mut Display, rotation: Rotation, contrast_pct: u8) {
+    let rotation = match rotation {
+        Rotation::Normal => DisplayRotation::Rotate0,
+        Rotation::Flipped => DisplayRotation::Rotate180,
+    };
+    display.set_rotation(rotation).unwrap();
+    let contrast = u16::from(contrast_pct.min(100)) * 255 / 100;
+    display
+        .set_brightness(Brightness::custom(2, contrast as u8))
+        .unwrap();
+}
@@ -0,0 +1,102 @@
+//! In-memory copy of the 128x32 display.
+//!
+//! Pixels are stored row by row, 16 bytes per row, most significant bit
+//! first, a set bit is [`BinaryColor::On`]. This is also the layout of the
+//! binary PBM (`P4`) image format.
+
+use embedded_graphics::{pixelcolor::BinaryColor, prelude::*};
+
+use crate::screens::DISPLAY_SIZE;
+
+const WIDTH: usize = DISPLAY_SIZE.width as usize;
+const HEIGHT: usize = DISPLAY_SIZE.height as usize;
+const ROW_BYTES: usize = WIDTH / 8;
+
+/// 1-bit framebuffer implementing [`DrawTarget`].
+///
+/// Two framebuffers are equal when they hold the same pixels.
+#[derive(Clone, Debug)]
+pub struct Framebuffer {
+    data: [u8; Self::BYTES],
+    clipped: u32,
+}
+
+impl Framebuffer {
+    /// Size of the pixel data in bytes.
+    pub const BYTES: usize = ROW_BYTES * HEIGHT;
+
+    pub const fn new() -> Self {
+        Self {
+            data: [0; Self::BYTES],
+            clipped: 0,
+        }
+    }
+
+    pub const fn from_bytes(data: [u8; Self::BYTES]) -> Self {
+        Self { data, clipped: 0 }
+    }
+
+    pub fn as_bytes(&self) -> &[u8; Self::BYTES] {
+        &self.data
+    }
+
+    /// Returns the color at `point`, `None` outside of the display.
+    pub fn pixel(&self, point: Point) -> Option<BinaryColor> {
+        let (index, mask) = Self::locate(point)?;
+        Some(BinaryColor::from(self.data[index] & mask != 0))
+    }
+
+    pub fn set_pixel(&mut self, point: Point, color: BinaryColor) {
+        match Self::locate(point) {
+            Some((index, mask)) if color.is_on() => self.data[index] |= mask,
+            Some((index, mask)) => self.data[index] &= !mask,
+            None => self.clipped += 1,
+        }
+    }
+
+    /// Number of pixels drawn outside of the display.
+    pub fn clipped(&self) -> u32 {
+        self.clipped
+    }
+
+    fn locate(point: Point) -> Option<(usize, u8)> {
+        let x = usize::try_from(point.x).ok().filter(|&x| x < WIDTH)?;
+        let y = usize::try_from(point.y).ok().filter(|&y| y < HEIGHT)?;
+        Some((y * ROW_BYTES + x / 8, 0x80 >> (x % 8)))
+    }
+}
+
+impl Default for Framebuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PartialEq for Framebuffer {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data
+    }
+}
+
+impl Eq for Framebuffer {}
+
+impl OriginDimensions for Framebuffer {
+    fn size(&self) -> Size {
+        DISPLAY_SIZE
+    }
+}
+
+impl DrawTarget for Framebuffer {
+    type Color = BinaryColor;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            self.set_pixel(point, color);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,3 @@
+//! Drawing on top of embedded-graphics.
+
+pub mod sprite;
@@ -0,0 +1,642 @@
+//! 1-bit sprites and scenes of them.
+//!
+//! A [`Bitmap`] is packed like a binary PBM (`P4`) image: rows of
+//! [`Bitmap::row_bytes`] bytes, most significant bit first, a set bit is lit.
+//! Bitmaps are made at compile time from
+//!
+//! - PBM images: `Bitmap::from_pbm(include_bytes!("car.pbm"))`
+//! - XBM images: `xbm_bitmap!(include_str!("car.xbm"))`
+//! - ASCII art: see [`ascii_bitmap!`](crate::ascii_bitmap)
+//!
+//! `scripts/sprite.sh` converts PNG images to any of them.
+//!
+//! A [`Sprite`] places a bitmap on the display and draws its lit pixels,
+//! clipped to the target. A [`Stage`] holds several sprites, draws them
+//! from the lowest layer up and wraps them around its edges if asked to.
+//!
+//! ```ignore
+//! const TRUCK: Bitmap = Bitmap::from_pbm(include_bytes!("car.pbm"));
+//!
+//! let mut stage: Stage<2> = Stage::new().with_wrap(Wrap::HORIZONTAL);
+//! let truck = stage.add(Sprite::new(TRUCK).with_position(Point::new(0, 1))).unwrap();
+//! stage.sprite_mut(truck).unwrap().move_by(Point::new(1, 0));
+//! stage.draw(display)?;
+//! ```
+
+use core::fmt;
+
+use embedded_graphics::{pixelcolor::BinaryColor, prelude::*, primitives::Rectangle};
+
+use crate::screens::DISPLAY_SIZE;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// Not a binary PBM (`P4`) image.
+    NotPbm,
+    InvalidHeader,
+    /// Less pixel data than the size in the header needs.
+    TooShort,
+}
+
+/// 1-bit image borrowing its pixel data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bitmap<'a> {
+    width: u32,
+    height: u32,
+    data: &'a [u8],
+}
+
+impl<'a> Bitmap<'a> {
+    /// `data` holds `height` rows of [`Bitmap::row_bytes`], bytes after them
+    /// are ignored.
+    ///
+    /// Panics when `data` is too short.
+    pub const fn new(width: u32, height: u32, data: &'a [u8]) -> Self {
+        assert!(
+            data.len() >= Self::byte_len(width, height),
+            "bitmap data too short"
+        );
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+
+    /// Bytes of a row, padded to whole bytes.
+    pub const fn row_bytes(width: u32) -> usize {
+        (width as usize).div_ceil(8)
+    }
+
+    /// Bytes of the pixel data of a bitmap.
+    pub const fn byte_len(width: u32, height: u32) -> usize {
+        Self::row_bytes(width) * height as usize
+    }
+
+    /// Like [`Bitmap::parse_pbm`], panics on invalid images, which fails the
+    /// build in a `const`.
+    pub const fn from_pbm(bytes: &'a [u8]) -> Self {
+        match Self::parse_pbm(bytes) {
+            Ok(bitmap) => bitmap,
+            Err(ParseError::NotPbm) => panic!("not a binary PBM image"),
+            Err(ParseError::InvalidHeader) => panic!("invalid PBM header"),
+            Err(ParseError::TooShort) => panic!("PBM image too short"),
+        }
+    }
+
+    /// Reads a binary PBM (`P4`) image, black pixels are lit. The pixel data
+    /// is borrowed from `bytes`.
+    pub const fn parse_pbm(bytes: &'a [u8]) -> Result<Self, ParseError> {
+        if bytes.len() < 2 || bytes[0] != b'P' || bytes[1] != b'4' {
+            return Err(ParseError::NotPbm);
+        }
+        let Some((width, at)) = pbm_number(bytes, 2) else {
+            return Err(ParseError::InvalidHeader);
+        };
+        let Some((height, at)) = pbm_number(bytes, at) else {
+            return Err(ParseError::InvalidHeader);
+        };
+        // A single whitespace character ends the header
+        if at >= bytes.len() || !bytes[at].is_ascii_whitespace() {
+            return Err(ParseError::InvalidHeader);
+        }
+        let (_, data) = bytes.split_at(at + 1);
+        if data.len() < Self::byte_len(width, height) {
+            return Err(ParseError::TooShort);
+        }
+        Ok(Self {
+            width,
+            height,
+            data,
+        })
+    }
+
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub const fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Pixel data of the rows, without the bytes after them.
+    pub fn data(&self) -> &'a [u8] {
+        &self.data[..Self::byte_len(self.width, self.height)]
+    }
+
+    /// Whether the pixel at `x`, `y` is lit, `false` outside of the bitmap.
+    pub fn pixel(&self, x: u32, y: u32) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+        let index = y as usize * Self::row_bytes(self.width) + x as usize / 8;
+        self.data[index] & (0x80 >> (x % 8)) != 0
+    }
+
+    /// Writes the bitmap as XBM C source, `name` starts the identifiers.
+    pub fn write_xbm<W: fmt::Write>(&self, out: &mut W, name: &str) -> fmt::Result {
+        writeln!(out, "#define {name}_width {}", self.width)?;
+        writeln!(out, "#define {name}_height {}", self.height)?;
+        write!(out, "static unsigned char {name}_bits[] = {{")?;
+        for (index, byte) in self.data().iter().enumerate() {
+            let separator = if index == 0 { "" } else { "," };
+            let indent = if index % 12 == 0 { "\n  " } else { "" };
+            // XBM starts with the least significant bit
+            write!(out, "{separator}{indent} 0x{:02x}", byte.reverse_bits())?;
+        }
+        writeln!(out, " }};")
+    }
+
+    /// Writes the bitmap as ASCII art, `#` for lit and `.` for dark pixels.
+    pub fn write_ascii<W: fmt::Write>(&self, out: &mut W) -> fmt::Result {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                out.write_char(if self.pixel(x, y) { '#' } else { '.' })?;
+            }
+            out.write_char('\n')?;
+        }
+        Ok(())
+    }
+}
+
+impl OriginDimensions for Bitmap<'_> {
+    fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+}
+
+/// Skips whitespace and `#` comments, then reads a decimal number. Returns
+/// the number and the index after it.
+const fn pbm_number(bytes: &[u8], mut at: usize) -> Option<(u32, usize)> {
+    while at < bytes.len() {
+        if bytes[at] == b'#' {
+            while at < bytes.len() && bytes[at] != b'\n' {
+                at += 1;
+            }
+        } else if bytes[at].is_ascii_whitespace() {
+            at += 1;
+        } else {
+            break;
+        }
+    }
+    let start = at;
+    let mut value: u32 = 0;
+    while at < bytes.len() && bytes[at].is_ascii_digit() {
+        let Some(shifted) = value.checked_mul(10) else {
+            return None;
+        };
+        value = shifted + (bytes[at] - b'0') as u32;
+        at += 1;
+    }
+    if at == start {
+        return None;
+    }
+    Some((value, at))
+}
+
+/// Bitmap from ASCII art at compile time, `#` is lit and `.` is dark.
+///
+/// Leading whitespace and blank lines are skipped, so the art can be
+/// indented in the source. Rows shorter than the longest one are dark at
+/// the end.
+///
+/// ```ignore
+/// const ARROW: Bitmap = ascii_bitmap!(
+///     "
+///     ..#..
+///     .###.
+///     #####
+///     "
+/// );
+/// ```
+#[macro_export]
+macro_rules! ascii_bitmap {
+    ($art:expr) => {{
+        const ART: &str = $art;
+        const WIDTH: u32 = $crate::gfx::sprite::ascii_size(ART).width;
+        const HEIGHT: u32 = $crate::gfx::sprite::ascii_size(ART).height;
+        const BITS: [u8; $crate::gfx::sprite::Bitmap::byte_len(WIDTH, HEIGHT)] =
+            $crate::gfx::sprite::ascii_bits(ART);
+        $crate::gfx::sprite::Bitmap::new(WIDTH, HEIGHT, &BITS)
+    }};
+}
+
+/// Bitmap from XBM C source at compile time, usually from `include_str!`.
+#[macro_export]
+macro_rules! xbm_bitmap {
+    ($source:expr) => {{
+        const SOURCE: &str = $source;
+        const WIDTH: u32 = $crate::gfx::sprite::xbm_size(SOURCE).width;
+        const HEIGHT: u32 = $crate::gfx::sprite::xbm_size(SOURCE).height;
+        const BITS: [u8; $crate::gfx::sprite::Bitmap::byte_len(WIDTH, HEIGHT)] =
+            $crate::gfx::sprite::xbm_bits(SOURCE);
+        $crate::gfx::sprite::Bitmap::new(WIDTH, HEIGHT, &BITS)
+    }};
+}
+
+/// Size of ASCII art, see [`ascii_bitmap!`](crate::ascii_bitmap).
+pub const fn ascii_size(art: &str) -> Size {
+    let bytes = art.as_bytes();
+    let (mut width, mut height) = (0, 0);
+    let mut at = 0;
+    while at < bytes.len() {
+        let (start, end, next) = ascii_row(bytes, at);
+        if end > start {
+            if end - start > width {
+                width = end - start;
+            }
+            height += 1;
+        }
+        at = next;
+    }
+    Size::new(width as u32, height)
+}
+
+/// Pixel data of ASCII art, see [`ascii_bitmap!`](crate::ascii_bitmap).
+///
+/// Panics when `N` is not the length of the data.
+pub const fn ascii_bits<const N: usize>(art: &str) -> [u8; N] {
+    let size = ascii_size(art);
+    assert!(
+        N == Bitmap::byte_len(size.width, size.height),
+        "wrong length of the bitmap data"
+    );
+    let row_bytes = Bitmap::row_bytes(size.width);
+    let bytes = art.as_bytes();
+    let mut bits = [0; N];
+    let (mut at, mut y) = (0, 0);
+    while at < bytes.len() {
+        let (start, end, next) = ascii_row(bytes, at);
+        if end > start {
+            let mut x = 0;
+            while start + x < end {
+                if bytes[start + x] == b'#' {
+                    bits[y * row_bytes + x / 8] |= 0x80 >> (x % 8);
+                }
+                x += 1;
+            }
+            y += 1;
+        }
+        at = next;
+    }
+    bits
+}
+
+/// The row of ASCII art in the line starting at `at`, without the
+/// whitespace around it. Returns its start and end, and the start of the
+/// next line.
+const fn ascii_row(bytes: &[u8], at: usize) -> (usize, usize, usize) {
+    let mut next = at;
+    while next < bytes.len() && bytes[next] != b'\n' {
+        next += 1;
+    }
+    let (mut start, mut end) = (at, next);
+    while start < end && bytes[start].is_ascii_whitespace() {
+        start += 1;
+    }
+    while end > start && bytes[end - 1].is_ascii_whitespace() {
+        end -= 1;
+    }
+    (start, end, next + 1)
+}
+
+/// Size from the `_width` and `_height` defines of XBM source.
+///
+/// Panics when they are missing.
+pub const fn xbm_size(source: &str) -> Size {
+    let bytes = source.as_bytes();
+    Size::new(
+        xbm_define(bytes, b"_width "),
+        xbm_define(bytes, b"_height "),
+    )
+}
+
+/// Pixel data of XBM source, see [`xbm_bitmap!`](crate::xbm_bitmap).
+///
+/// Panics when `N` is not the length of the data or the source has fewer
+/// bytes.
+pub const fn xbm_bits<const N: usize>(source: &str) -> [u8; N] {
+    let size = xbm_size(source);
+    assert!(
+        N == Bitmap::byte_len(size.width, size.height),
+        "wrong length of the bitmap data"
+    );
+    let bytes = source.as_bytes();
+    let Some(mut at) = find(bytes, b"{", 0) else {
+        panic!("XBM data not found");
+    };
+    let mut bits = [0; N];
+    let mut index = 0;
+    while index < N {
+        let Some(start) = find(bytes, b"0x", at) else {
+            panic!("XBM data too short");
+        };
+        let (mut value, mut end) = (0u32, start + 2);
+        while end < bytes.len() && bytes[end].is_ascii_hexdigit() {
+            let digit = match bytes[end] {
+                b'0'..=b'9' => bytes[end] - b'0',
+                digit => (digit | 0x20) - b'a' + 10,
+            };
+            value = value * 16 + digit as u32;
+            end += 1;
+        }
+        assert!(value <= 0xff, "XBM value out of range");
+        bits[index] = (value as u8).reverse_bits();
+        index += 1;
+        at = end;
+    }
+    bits
+}
+
+/// Value of the `#define` whose name ends with `suffix`.
+const fn xbm_define(bytes: &[u8], suffix: &[u8]) -> u32 {
+    let Some(at) = find(bytes, suffix, 0) else {
+        panic!("XBM size not found");
+    };
+    match pbm_number(bytes, at + suffix.len()) {
+        Some((value, _)) => value,
+        None => panic!("invalid XBM size"),
+    }
+}
+
+/// Index of the first `needle` in `haystack` from `from`.
+const fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    let mut at = from;
+    while at + needle.len() <= haystack.len() {
+        let mut matched = 0;
+        while matched < needle.len() && haystack[at + matched] == needle[matched] {
+            matched += 1;
+        }
+        if matched == needle.len() {
+            return Some(at);
+        }
+        at += 1;
+    }
+    None
+}
+
+/// A bitmap placed on the display.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sprite<'a> {
+    bitmap: Bitmap<'a>,
+    position: Point,
+    layer: i8,
+    opaque: bool,
+    visible: bool,
+}
+
+impl<'a> Sprite<'a> {
+    /// Visible at the origin on layer 0, drawing only the lit pixels.
+    pub const fn new(bitmap: Bitmap<'a>) -> Self {
+        Self {
+            bitmap,
+            position: Point::zero(),
+            layer: 0,
+            opaque: false,
+            visible: true,
+        }
+    }
+
+    /// Top left corner of the bitmap.
+    pub const fn with_position(mut self, position: Point) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Higher layers are drawn over lower ones in a [`Stage`].
+    pub const fn with_layer(mut self, layer: i8) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    /// Also draws the dark pixels, hiding what is below.
+    pub const fn with_opaque(mut self, opaque: bool) -> Self {
+        self.opaque = opaque;
+        self
+    }
+
+    pub fn bitmap(&self) -> &Bitmap<'a> {
+        &self.bitmap
+    }
+
+    pub fn set_bitmap(&mut self, bitmap: Bitmap<'a>) {
+        self.bitmap = bitmap;
+    }
+
+    pub fn position(&self) -> Point {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: Point) {
+        self.position = position;
+    }
+
+    pub fn move_by(&mut self, offset: Point) {
+        self.position += offset;
+    }
+
+    pub fn layer(&self) -> i8 {
+        self.layer
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    /// Area covered by the bitmap.
+    pub fn bounding_box(&self) -> Rectangle {
+        Rectangle::new(self.position, self.bitmap.size())
+    }
+
+    /// Draws the pixels at `origin` that are inside `clip`.
+    fn draw_at<D>(&self, target: &mut D, origin: Point, clip: &Rectangle) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let area = Rectangle::new(origin, self.bitmap.size()).intersection(clip);
+        let (bitmap, opaque) = (&self.bitmap, self.opaque);
+        let pixels = area.points().filter_map(|point| {
+            let offset = point - origin;
+            let lit = bitmap.pixel(offset.x as u32, offset.y as u32);
+            (lit || opaque).then_some(Pixel(point, BinaryColor::from(lit)))
+        });
+        target.draw_iter(pixels)
+    }
+}
+
+impl Drawable for Sprite<'_> {
+    type Color = BinaryColor;
+    type Output = ();
+
+    /// Draws the sprite if visible, clipped to the target.
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        if !self.visible {
+            return Ok(());
+        }
+        let clip = target.bounding_box();
+        self.draw_at(target, self.position, &clip)
+    }
+}
+
+/// Edges of a [`Stage`] that sprites wrap around: a sprite leaving on one
+/// side shows up on the other.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Wrap {
+    pub horizontal: bool,
+    pub vertical: bool,
+}
+
+impl Wrap {
+    pub const NONE: Wrap = Wrap {
+        horizontal: false,
+        vertical: false,
+    };
+    pub const HORIZONTAL: Wrap = Wrap {
+        horizontal: true,
+        vertical: false,
+    };
+    pub const VERTICAL: Wrap = Wrap {
+        horizontal: false,
+        vertical: true,
+    };
+    pub const BOTH: Wrap = Wrap {
+        horizontal: true,
+        vertical: true,
+    };
+}
+
+/// Scene of up to `N` sprites, drawn from the lowest layer up and clipped
+/// to its area. Sprites on the same layer are drawn in the order added.
+#[derive(Clone, Debug)]
+pub struct Stage<'a, const N: usize> {
+    area: Rectangle,
+    wrap: Wrap,
+    sprites: heapless::Vec<Sprite<'a>, N>,
+}
+
+impl<const N: usize> Default for Stage<'_, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, const N: usize> Stage<'a, N> {
+    /// Covers the display, without wrapping.
+    pub const fn new() -> Self {
+        Self {
+            area: Rectangle::new(Point::zero(), DISPLAY_SIZE),
+            wrap: Wrap::NONE,
+            sprites: heapless::Vec::new(),
+        }
+    }
+
+    /// Part of the target the sprites are drawn into. Positions stay
+    /// relative to the target.
+    pub const fn with_area(mut self, area: Rectangle) -> Self {
+        self.area = area;
+        self
+    }
+
+    pub const fn with_wrap(mut self, wrap: Wrap) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn area(&self) -> Rectangle {
+        self.area
+    }
+
+    pub fn wrap(&self) -> Wrap {
+        self.wrap
+    }
+
+    /// Adds a sprite and returns its index, or the sprite when the stage is
+    /// full.
+    pub fn add(&mut self, sprite: Sprite<'a>) -> Result<usize, Sprite<'a>> {
+        self.sprites.push(sprite)?;
+        Ok(self.sprites.len() - 1)
+    }
+
+    pub fn sprites(&self) -> &[Sprite<'a>] {
+        &self.sprites
+    }
+
+    pub fn sprite(&self, index: usize) -> Option<&Sprite<'a>> {
+        self.sprites.get(index)
+    }
+
+    pub fn sprite_mut(&mut self, index: usize) -> Option<&mut Sprite<'a>> {
+        self.sprites.get_mut(index)
+    }
+
+    pub fn clear(&mut self) {
+        self.sprites.clear();
+    }
+}
+
+impl<const N: usize> Drawable for Stage<'_, N> {
+    type Color = BinaryColor;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let clip = self.area.intersection(&target.bounding_box());
+        let mut order: heapless::Vec<usize, N> = (0..self.sprites.len()).collect();
+        order.sort_unstable_by_key(|&index| (self.sprites[index].layer, index));
+
+        let (top_left, size) = (self.area.top_left, self.area.size);
+        for sprite in order.iter().map(|&index| &self.sprites[index]) {
+            if !sprite.visible {
+                continue;
+            }
+            let bitmap = sprite.bitmap.size();
+            let position = sprite.position;
+            for y in copies(
+                position.y,
+                bitmap.height,
+                top_left.y,
+                size.height,
+                self.wrap.vertical,
+            ) {
+                for x in copies(
+                    position.x,
+                    bitmap.width,
+                    top_left.x,
+                    size.width,
+                    self.wrap.horizontal,
+                ) {
+                    sprite.draw_at(target, Point::new(x, y), &clip)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Starts of the copies of a sprite at `position` that are in view, along
+/// one axis of an area from `start`. Without `wrap` only `position`.
+fn copies(position: i32, len: u32, start: i32, size: u32, wrap: bool) -> impl Iterator<Item = i32> {
+    let size = size as i32;
+    let (first, count) = if wrap && size > 0 {
+        // Back from the copy inside the area to the first one reaching into it
+        let mut first = start + (position - start).rem_euclid(size);
+        while first - size + len as i32 > start {
+            first -= size;
+        }
+        let count = ((start + size - first) as usize).div_ceil(size as usize);
+        (first, count)
+    } else {
+        (position, 1)
+    };
+    (0..count).map(move |index| first + index as i32 * size)
+}
@@ -0,0 +1,210 @@
+//! Fixed-capacity history of sensor readings.
+//!
+//! Readings are collected into buckets of a fixed interval, each keeping the
+//! minimum, maximum and average of its readings. The buckets live in a ring
+//! of `N`, so 24 h at 1-minute resolution is a `History<1440>`, about 12 KiB.
+//! Intervals without readings are kept as gaps.
+//!
+//! ```
+//! use esp_buddy::history::{History, MINUTE_MS};
+//!
+//! let mut history = History::<60>::new(MINUTE_MS);
+//! history.record(0, 215);
+//! history.record(30_000, 217);
+//! history.record(60_000, 220);
+//! assert_eq!(history.len(), 2);
+//! assert_eq!(history.latest().unwrap().avg, 220);
+//! ```
+
+use crate::sensors::div_round;
+
+pub const MINUTE_MS: u64 = 60_000;
+
+/// Buckets of one day at 1-minute resolution.
+pub const DAY_MINUTES: usize = 24 * 60;
+
+/// Readings of one interval, or of several merged ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bucket {
+    pub min: i16,
+    pub max: i16,
+    pub avg: i16,
+}
+
+impl Bucket {
+    pub const fn single(value: i16) -> Self {
+        Self {
+            min: value,
+            max: value,
+            avg: value,
+        }
+    }
+
+    /// Merges buckets, each weighted the same. `None` when there are none.
+    pub fn merge(buckets: impl IntoIterator<Item = Bucket>) -> Option<Self> {
+        let mut buckets = buckets.into_iter();
+        let mut merged = buckets.next()?;
+        let (mut sum, mut count) = (i32::from(merged.avg), 1);
+        for bucket in buckets {
+            merged.min = merged.min.min(bucket.min);
+            merged.max = merged.max.max(bucket.max);
+            sum += i32::from(bucket.avg);
+            count += 1;
+        }
+        merged.avg = div_round(sum, count) as i16;
+        Some(merged)
+    }
+}
+
+/// Readings collected so far for the newest interval.
+#[derive(Clone, Copy, Debug)]
+struct Readings {
+    min: i16,
+    max: i16,
+    sum: i32,
+    count: i32,
+}
+
+impl Readings {
+    fn bucket(&self) -> Bucket {
+        Bucket {
+            min: self.min,
+            max: self.max,
+            avg: div_round(self.sum, self.count) as i16,
+        }
+    }
+}
+
+pub struct History<const N: usize> {
+    buckets: [Option<Bucket>; N],
+    /// Index of the oldest closed bucket.
+    start: usize,
+    /// Closed buckets, at most `N - 1` next to the open one.
+    closed: usize,
+    /// Interval of the open bucket, `None` before the first reading.
+    slot: Option<u64>,
+    open: Option<Readings>,
+    interval_ms: u64,
+}
+
+impl<const N: usize> History<N> {
+    /// Creates an empty history of buckets `interval_ms` long.
+    pub const fn new(interval_ms: u64) -> Self {
+        assert!(N > 0 && interval_ms > 0);
+        Self {
+            buckets: [None; N],
+            start: 0,
+            closed: 0,
+            slot: None,
+            open: None,
+            interval_ms,
+        }
+    }
+
+    pub fn interval_ms(&self) -> u64 {
+        self.interval_ms
+    }
+
+    /// Buckets including gaps and the open one, at most `N`.
+    pub fn len(&self) -> usize {
+        self.closed + self.slot.is_some() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slot.is_none()
+    }
+
+    /// Adds a reading at the monotonic time `now_ms`, e.g. in tenths of a
+    /// degree. Readings from before the open bucket go into it.
+    pub fn record(&mut self, now_ms: u64, value: i16) {
+        self.advance(now_ms);
+        if self.slot.is_none() {
+            self.slot = Some(now_ms / self.interval_ms);
+        }
+        self.open = Some(match self.open {
+            Some(open) => Readings {
+                min: open.min.min(value),
+                max: open.max.max(value),
+                sum: open.sum + i32::from(value),
+                count: open.count + 1,
+            },
+            None => Readings {
+                min: value,
+                max: value,
+                sum: value.into(),
+                count: 1,
+            },
+        });
+    }
+
+    /// Closes the open bucket once `now_ms` is past its interval, adding a
+    /// gap for every interval without readings. Called by
+    /// [`History::record`]; call it before drawing, so the history ends at
+    /// `now_ms` even while the sensor does not answer.
+    pub fn advance(&mut self, now_ms: u64) {
+        let slot = now_ms / self.interval_ms;
+        let Some(open_slot) = self.slot.filter(|&open_slot| slot > open_slot) else {
+            return;
+        };
+        let closed = self.open.take().map(|open| open.bucket());
+        self.push(closed);
+        let gaps = (slot - open_slot - 1).min(N as u64);
+        for _ in 0..gaps {
+            self.push(None);
+        }
+        self.slot = Some(slot);
+    }
+
+    fn push(&mut self, bucket: Option<Bucket>) {
+        if N == 1 {
+            return;
+        }
+        self.buckets[(self.start + self.closed) % N] = bucket;
+        if self.closed == N - 1 {
+            self.start = (self.start + 1) % N;
+        } else {
+            self.closed += 1;
+        }
+    }
+
+    /// Bucket `index` from the oldest, `None` for gaps.
+    pub fn get(&self, index: usize) -> Option<Bucket> {
+        if index < self.closed {
+            self.buckets[(self.start + index) % N]
+        } else if index == self.closed {
+            self.open.map(|open| open.bucket())
+        } else {
+            None
+        }
+    }
+
+    /// Buckets from the oldest to the open one, `None` for gaps.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = Option<Bucket>> + '_ {
+        (0..self.len()).map(|index| self.get(index))
+    }
+
+    /// Newest bucket with readings.
+    pub fn latest(&self) -> Option<Bucket> {
+        self.iter().rev().flatten().next()
+    }
+
+    /// Merges the newest `span` buckets into `out.len()` columns, oldest
+    /// first. Columns before the first bucket and of gaps only are `None`.
+    /// With fewer buckets than columns, every bucket spans several columns.
+    pub fn downsample(&self, span: usize, out: &mut [Option<Bucket>]) {
+        let columns = out.len();
+        // Position of the first bucket of the span in the history, negative
+        // while the history is shorter than the span
+        let first = self.len() as isize - span as isize;
+        for (column, out) in out.iter_mut().enumerate() {
+            let start = column * span / columns;
+            let end = ((column + 1) * span / columns).max(start + 1);
+            *out = Bucket::merge((start..end).filter_map(|offset| {
+                let index = first + offset as isize;
+                usize::try_from(index)
+                    .ok()
+                    .and_then(|index| self.get(index))
+            }));
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//! HTTP API of the device.
+//!
+//! - `GET /status`: [`DeviceStatus`] as JSON
+//! - `POST /display`: shows the UTF-8 body on the display
+//! - `POST /led`: sets the LED to the color in the body, `#rrggbb`
+//!
+//! ```text
+//! curl http://192.168.1.42/status
+//! curl -d 'Hello' http://192.168.1.42/display
+//! curl -d '#ff8000' http://192.168.1.42/led
+//! ```
+
+use core::{
+    fmt::{self, Display, Write},
+    net::Ipv4Addr,
+};
+
+use super::{write_response, Method, Request, Status};
+use crate::{led::Rgb, screens::ButtonState, sensors::Tenths};
+
+/// Longest text accepted by `POST /display`, in bytes.
+pub const MAX_TEXT_LEN: usize = 64;
+
+const JSON: &str = "application/json";
+
+/// What a request asks the device to do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command<'a> {
+    Status,
+    ShowText(&'a str),
+    SetLed(Rgb),
+}
+
+/// Why a request was turned down.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rejection {
+    NotFound,
+    /// The path exists, with the methods in `allow`.
+    MethodNotAllowed {
+        allow: &'static str,
+    },
+    BadRequest(&'static str),
+    PayloadTooLarge,
+}
+
+impl Rejection {
+    pub fn status(self) -> Status {
+        match self {
+            Rejection::NotFound => Status::NotFound,
+            Rejection::MethodNotAllowed { .. } => Status::MethodNotAllowed,
+            Rejection::BadRequest(_) => Status::BadRequest,
+            Rejection::PayloadTooLarge => Status::PayloadTooLarge,
+        }
+    }
+
+    pub fn message(self) -> &'static str {
+        match self {
+            Rejection::BadRequest(message) => message,
+            rejection => rejection.status().reason(),
+        }
+    }
+}
+
+/// Maps a request to a [`Command`].
+pub fn route<'a>(request: &Request<'a>) -> Result<Command<'a>, Rejection> {
+    let allow = match request.path {
+        "/status" => "GET",
+        "/display" | "/led" => "POST",
+        _ => return Err(Rejection::NotFound),
+    };
+    let method = match request.method {
+        Method::Get => "GET",
+        Method::Post => "POST",
+        _ => "",
+    };
+    if method != allow {
+        return Err(Rejection::MethodNotAllowed { allow });
+    }
+
+    match request.path {
+        "/display" => {
+            let text = core::str::from_utf8(request.body)
+                .map_err(|_| Rejection::BadRequest("Text is not UTF-8"))?;
+            if text.len() > MAX_TEXT_LEN {
+                return Err(Rejection::PayloadTooLarge);
+            }
+            Ok(Command::ShowText(text))
+        }
+        "/led" => core::str::from_utf8(request.body)
+            .ok()
+            .and_then(|color| Rgb::parse(color.trim()))
+            .map(Command::SetLed)
+            .ok_or(Rejection::BadRequest("Expected a color like #ff8000")),
+        _ => Ok(Command::Status),
+    }
+}
+
+/// Everything `GET /status` reports, `None` for what is not known.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DeviceStatus {
+    pub ip: Option<Ipv4Addr>,
+    /// Signal strength of the access point in dBm.
+    pub rssi: Option<i8>,
+    pub uptime_ms: u64,
+    /// Free heap in bytes.
+    pub free_heap: Option<usize>,
+    /// Tenths of a degree Celsius.
+    pub temperature_dc: Option<i32>,
+    /// Tenths of a percent of relative humidity.
+    pub humidity_dpct: Option<i32>,
+    pub buttons: ButtonState,
+    pub led: Rgb,
+}
+
+impl DeviceStatus {
+    pub fn write_json(&self, out: &mut impl Write) -> fmt::Result {
+        write!(
+            out,
+            "{{\"ip\":{},\"rssi\":{},\"uptime_ms\":{},\"free_heap\":{},\
+             \"temperature_c\":{},\"humidity_pct\":{},\
+             \"buttons\":{{\"left\":{},\"right\":{}}},\"led\":\"{}\"}}",
+            Json(self.ip.map(Quoted)),
+            Json(self.rssi),
+            self.uptime_ms,
+            Json(self.free_heap),
+            Json(self.temperature_dc.map(Tenths)),
+            Json(self.humidity_dpct.map(Tenths)),
+            self.buttons.left,
+            self.buttons.right,
+            self.led,
+        )
+    }
+}
+
+/// Writes the response to a routed request. [`Command::Status`] is answered
+/// with `status`, the other commands are acknowledged with no content and
+/// carried out by the caller.
+pub fn respond(routed: Result<Command, Rejection>, status: &DeviceStatus, buf: &mut [u8]) -> usize {
+    match routed {
+        Ok(Command::Status) => {
+            write_response(buf, Status::Ok, &[], JSON, |out| status.write_json(out))
+        }
+        Ok(Command::ShowText(_) | Command::SetLed(_)) => {
+            write_response(buf, Status::NoContent, &[], JSON, |_| Ok(()))
+        }
+        Err(rejection) => {
+            let allow = match rejection {
+                Rejection::MethodNotAllowed { allow } => Some(("Allow", allow)),
+                _ => None,
+            };
+            write_response(buf, rejection.status(), allow.as_slice(), JSON, |out| {
+                write!(out, "{{\"error\":\"{}\"}}", rejection.message())
+            })
+        }
+    }
+}
+
+/// JSON value or `null`.
+struct Json<T>(Option<T>);
+
+impl<T: Display> Display for Json<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.0 {
+            Some(value) => value.fmt(f),
+            None => f.write_str("null"),
+        }
+    }
+}
+
+struct Quoted<T>(T);
+
+impl<T: Display> Display for Quoted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\"{}\"", self.0)
+    }
+}
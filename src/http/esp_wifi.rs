@@ -0,0 +1,79 @@
+use embedded_io::{Read, ReadReady, Write};
+use esp_wifi::{current_millis, wifi::WifiDeviceMode, wifi_interface::Socket};
+
+use super::{parse, Error, Request};
+
+/// Time a client has to send the whole request before it is dropped.
+pub const REQUEST_TIMEOUT_MS: u64 = 2000;
+
+/// The request received so far on the socket, kept between calls to
+/// [`serve`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Connection {
+    len: usize,
+    deadline_ms: Option<u64>,
+}
+
+/// Serves one request on `socket` if a client is connected, without
+/// blocking.
+///
+/// Listens on `port` while the socket is closed. Once a client connected,
+/// every call reads what arrived into `request_buf`. The complete request is
+/// handed to `handle` together with `response_buf`; the response of the
+/// returned length is sent and the connection closed. A client that does not
+/// send its request within [`REQUEST_TIMEOUT_MS`] is disconnected. Returns
+/// what `handle` returned, `None` while there is no complete request.
+pub fn serve<MODE: WifiDeviceMode, T>(
+    socket: &mut Socket<'_, '_, MODE>,
+    port: u16,
+    connection: &mut Connection,
+    request_buf: &mut [u8],
+    response_buf: &mut [u8],
+    handle: impl FnOnce(Result<Request, Error>, &mut [u8]) -> (usize, T),
+) -> Option<T> {
+    socket.work();
+    if !socket.is_open() {
+        *connection = Connection::default();
+        socket.listen(port).ok();
+    }
+    if !socket.is_connected() {
+        return None;
+    }
+
+    let now_ms = current_millis();
+    let deadline_ms = *connection
+        .deadline_ms
+        .get_or_insert(now_ms + REQUEST_TIMEOUT_MS);
+    if now_ms >= deadline_ms {
+        socket.close();
+        *connection = Connection::default();
+        return None;
+    }
+    if !socket.read_ready().unwrap_or(false) {
+        return None;
+    }
+
+    let len = connection.len;
+    let request = match socket.read(&mut request_buf[len..]) {
+        Ok(0) | Err(_) => None,
+        Ok(read) => {
+            connection.len += read;
+            match parse(&request_buf[..connection.len]) {
+                Ok(None) if connection.len < request_buf.len() => return None,
+                Ok(None) => Some(Err(Error::TooLarge)),
+                Ok(Some(request)) => Some(Ok(request)),
+                Err(err) => Some(Err(err)),
+            }
+        }
+    };
+
+    let handled = request.map(|request| {
+        let (response_len, handled) = handle(request, response_buf);
+        socket.write_all(&response_buf[..response_len]).ok();
+        socket.flush().ok();
+        handled
+    });
+    socket.close();
+    *connection = Connection::default();
+    handled
+}
@@ -0,0 +1,312 @@
+//! Minimal HTTP/1.1 server side.
+//!
+//! Requests are parsed from the bytes received so far, responses are written
+//! to a byte buffer, one request per connection. The device API on top of it
+//! lives in [`api`], the socket handling in [`serve`].
+//!
+//! ```ignore
+//! let mut connection = http::Connection::default();
+//! loop {
+//!     http::serve(&mut socket, 80, &mut connection, &mut request, &mut response, |request, buf| {
+//!         let len = match request {
+//!             Ok(request) => api::respond(api::route(&request), &status, buf),
+//!             Err(err) => http::error_response(buf, err),
+//!         };
+//!         (len, ())
+//!     });
+//! }
+//! ```
+
+use core::fmt::{self, Write};
+
+pub mod api;
+#[cfg(feature = "esp32")]
+mod esp_wifi;
+
+#[cfg(feature = "esp32")]
+pub use self::esp_wifi::{serve, Connection, REQUEST_TIMEOUT_MS};
+
+/// Headers kept per request, further ones are ignored.
+pub const MAX_HEADERS: usize = 16;
+
+/// Space for the status line and headers in front of the body.
+const HEADER_SPACE: usize = 192;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Head,
+    Post,
+    Put,
+    Delete,
+    Options,
+    Other,
+}
+
+impl Method {
+    fn parse(method: &str) -> Self {
+        match method {
+            "GET" => Self::Get,
+            "HEAD" => Self::Head,
+            "POST" => Self::Post,
+            "PUT" => Self::Put,
+            "DELETE" => Self::Delete,
+            "OPTIONS" => Self::Options,
+            _ => Self::Other,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Not an HTTP request line, or headers that are not ASCII.
+    Malformed,
+    UnsupportedVersion,
+    InvalidContentLength,
+    /// `Transfer-Encoding` other than `identity`, i.e. chunked bodies.
+    UnsupportedTransferEncoding,
+    /// The request does not fit into the receive buffer.
+    TooLarge,
+}
+
+impl Error {
+    pub fn status(self) -> Status {
+        match self {
+            Error::Malformed | Error::InvalidContentLength => Status::BadRequest,
+            Error::UnsupportedVersion => Status::VersionNotSupported,
+            Error::UnsupportedTransferEncoding => Status::NotImplemented,
+            Error::TooLarge => Status::PayloadTooLarge,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    NoContent,
+    Found,
+    BadRequest,
+    NotFound,
+    MethodNotAllowed,
+    PayloadTooLarge,
+    InternalServerError,
+    NotImplemented,
+    VersionNotSupported,
+}
+
+impl Status {
+    pub fn code(self) -> u16 {
+        match self {
+            Status::Ok => 200,
+            Status::NoContent => 204,
+            Status::Found => 302,
+            Status::BadRequest => 400,
+            Status::NotFound => 404,
+            Status::MethodNotAllowed => 405,
+            Status::PayloadTooLarge => 413,
+            Status::InternalServerError => 500,
+            Status::NotImplemented => 501,
+            Status::VersionNotSupported => 505,
+        }
+    }
+
+    pub fn reason(self) -> &'static str {
+        match self {
+            Status::Ok => "OK",
+            Status::NoContent => "No Content",
+            Status::Found => "Found",
+            Status::BadRequest => "Bad Request",
+            Status::NotFound => "Not Found",
+            Status::MethodNotAllowed => "Method Not Allowed",
+            Status::PayloadTooLarge => "Payload Too Large",
+            Status::InternalServerError => "Internal Server Error",
+            Status::NotImplemented => "Not Implemented",
+            Status::VersionNotSupported => "HTTP Version Not Supported",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Request<'a> {
+    pub method: Method,
+    /// Path of the target, without the query.
+    pub path: &'a str,
+    pub query: Option<&'a str>,
+    pub headers: heapless::Vec<(&'a str, &'a str), MAX_HEADERS>,
+    pub body: &'a [u8],
+}
+
+impl<'a> Request<'a> {
+    /// Value of the first header called `name`, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&'a str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|&(_, value)| value)
+    }
+}
+
+/// Parses the bytes received so far, `Ok(None)` until the headers and the
+/// body announced by `Content-Length` are complete.
+pub fn parse(data: &[u8]) -> Result<Option<Request<'_>>, Error> {
+    let Some(header_len) = data.windows(4).position(|window| window == b"\r\n\r\n") else {
+        return Ok(None);
+    };
+    let head = core::str::from_utf8(&data[..header_len]).map_err(|_| Error::Malformed)?;
+    let mut lines = head.split("\r\n");
+
+    let mut request_line = lines.next().unwrap_or_default().split(' ');
+    let (Some(method), Some(target), Some(version), None) = (
+        request_line.next(),
+        request_line.next(),
+        request_line.next(),
+        request_line.next(),
+    ) else {
+        return Err(Error::Malformed);
+    };
+    if method.is_empty() || !target.starts_with('/') || !version.starts_with("HTTP/") {
+        return Err(Error::Malformed);
+    }
+    if !version.starts_with("HTTP/1.") {
+        return Err(Error::UnsupportedVersion);
+    }
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (target, None),
+    };
+
+    let mut headers = heapless::Vec::new();
+    for line in lines {
+        let (name, value) = line.split_once(':').ok_or(Error::Malformed)?;
+        if name.is_empty() || name.ends_with(' ') {
+            return Err(Error::Malformed);
+        }
+        // Keep the first ones, the API does not need many
+        headers.push((name, value.trim())).ok();
+    }
+
+    let mut request = Request {
+        method: Method::parse(method),
+        path,
+        query,
+        headers,
+        body: &[],
+    };
+    if request
+        .header("transfer-encoding")
+        .is_some_and(|encoding| !encoding.eq_ignore_ascii_case("identity"))
+    {
+        return Err(Error::UnsupportedTransferEncoding);
+    }
+    let content_length = match request.header("content-length") {
+        Some(length) => length.parse().map_err(|_| Error::InvalidContentLength)?,
+        None => 0,
+    };
+
+    let body_start = header_len + 4;
+    let body_end = body_start
+        .checked_add(content_length)
+        .ok_or(Error::InvalidContentLength)?;
+    let Some(body) = data.get(body_start..body_end) else {
+        return Ok(None);
+    };
+    request.body = body;
+    Ok(Some(request))
+}
+
+/// Writes into a byte buffer, failing once it is full.
+pub struct Writer<'b> {
+    buf: &'b mut [u8],
+    len: usize,
+}
+
+impl<'b> Writer<'b> {
+    pub fn new(buf: &'b mut [u8]) -> Self {
+        Self { buf, len: 0 }
+    }
+
+    /// Bytes written so far.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Write for Writer<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let target = self
+            .buf
+            .get_mut(self.len..self.len + s.len())
+            .ok_or(fmt::Error)?;
+        target.copy_from_slice(s.as_bytes());
+        self.len += s.len();
+        Ok(())
+    }
+}
+
+/// Writes a response with the `body` rendered by the closure to `buf` and
+/// returns its length. The connection is always closed after it.
+///
+/// A body that does not fit turns the response into a bodyless
+/// `500 Internal Server Error`.
+pub fn write_response(
+    buf: &mut [u8],
+    status: Status,
+    headers: &[(&str, &str)],
+    content_type: &str,
+    body: impl FnOnce(&mut Writer) -> fmt::Result,
+) -> usize {
+    let (status, body_len) = match buf.get_mut(HEADER_SPACE..) {
+        Some(body_buf) => {
+            let mut out = Writer::new(body_buf);
+            match body(&mut out) {
+                Ok(()) => (status, out.len),
+                Err(_) => (Status::InternalServerError, 0),
+            }
+        }
+        None => (Status::InternalServerError, 0),
+    };
+
+    let mut head_buf = [0u8; HEADER_SPACE];
+    let mut head = Writer::new(&mut head_buf);
+    let written = write_head(&mut head, status, headers, content_type, body_len);
+    let head_len = head.len;
+    if written.is_err() || head_len + body_len > buf.len() {
+        return 0;
+    }
+
+    if body_len > 0 {
+        buf.copy_within(HEADER_SPACE..HEADER_SPACE + body_len, head_len);
+    }
+    buf[..head_len].copy_from_slice(&head_buf[..head_len]);
+    head_len + body_len
+}
+
+fn write_head(
+    out: &mut Writer,
+    status: Status,
+    headers: &[(&str, &str)],
+    content_type: &str,
+    body_len: usize,
+) -> fmt::Result {
+    write!(out, "HTTP/1.1 {} {}\r\n", status.code(), status.reason())?;
+    for (name, value) in headers {
+        write!(out, "{}: {}\r\n", name, value)?;
+    }
+    if body_len > 0 {
+        write!(out, "Content-Type: {}\r\n", content_type)?;
+    }
+    write!(
+        out,
+        "Content-Length: {}\r\nConnection: close\r\n\r\n",
+        body_len
+    )
+}
+
+/// Bodyless response to a request that could not be parsed.
+pub fn error_response(buf: &mut [u8], err: Error) -> usize {
+    write_response(buf, err.status(), &[], "", |_| Ok(()))
+}
@@ -0,0 +1,70 @@
+use core::{
+    cell::RefCell,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use critical_section::Mutex;
+use hal::{gpio::Event, macros::handler};
+
+use crate::{screens::ButtonState, Buttons};
+
+/// Buttons owned by the GPIO interrupt once [`Interrupts::new`] ran.
+static BUTTONS: Mutex<RefCell<Option<Buttons>>> = Mutex::new(RefCell::new(None));
+/// Set by the interrupt on every edge.
+static EDGE: AtomicBool = AtomicBool::new(false);
+
+/// The buttons read on their edges instead of polled.
+///
+/// ```ignore
+/// let buttons = Interrupts::new(buddy.buttons);
+/// loop {
+///     let due = input.next_deadline_ms().is_some_and(|deadline| now_ms >= deadline);
+///     if buttons.take_edge() || due {
+///         input.update(buttons.state(), now_ms);
+///     }
+/// }
+/// ```
+pub struct Interrupts(());
+
+impl Interrupts {
+    /// Moves the buttons to the interrupt handler [`crate::Buddy::new`]
+    /// installed. Only one instance can exist.
+    pub fn new(mut buttons: Buttons) -> Self {
+        critical_section::with(|cs| {
+            buttons.left.listen(Event::AnyEdge);
+            buttons.right.listen(Event::AnyEdge);
+            assert!(BUTTONS.borrow_ref_mut(cs).replace(buttons).is_none());
+        });
+        Self(())
+    }
+
+    /// Whether a button changed since the last call.
+    pub fn take_edge(&self) -> bool {
+        EDGE.swap(false, Ordering::Relaxed)
+    }
+
+    pub fn state(&self) -> ButtonState {
+        critical_section::with(|cs| {
+            BUTTONS
+                .borrow_ref(cs)
+                .as_ref()
+                .map(Buttons::state)
+                .unwrap_or_default()
+        })
+    }
+}
+
+/// GPIO interrupt handler of the board, clears the edges of the buttons.
+#[handler]
+pub(crate) fn interrupt_handler() {
+    critical_section::with(|cs| {
+        if let Some(buttons) = BUTTONS.borrow_ref_mut(cs).as_mut() {
+            for input in [&mut buttons.left, &mut buttons.right] {
+                if input.is_interrupt_set() {
+                    input.clear_interrupt();
+                    EDGE.store(true, Ordering::Relaxed);
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,324 @@
+//! Debounced buttons with clicks, double clicks, long presses and chords.
+//!
+//! [`Input::update`] takes the levels of GPIO0 and GPIO4 with the time and
+//! queues an [`Event`] for everything that happened since:
+//!
+//! - [`Event::Pressed`] and [`Event::Released`] once a level held for
+//!   [`Timing::debounce_ms`], timed from its first change
+//! - [`Event::Click`] after a short press, once no second press followed
+//!   within [`Timing::double_click_ms`], or [`Event::DoubleClick`]
+//! - [`Event::LongPress`] after [`Timing::long_press_ms`] held, then
+//!   [`Event::Repeat`] every [`Timing::repeat_ms`]
+//! - [`Event::Chord`] when both buttons are down, after which neither clicks
+//!   nor repeats until it is released
+//!
+//! Everything follows from the levels and times passed in, so the timing is
+//! tested on the host with level traces. The levels come from polling, or
+//! with the `esp32` feature from the GPIO interrupt, see [`Interrupts`]:
+//! update on an edge or at [`Input::next_deadline_ms`].
+//!
+//! ```ignore
+//! let mut input = Input::new();
+//! loop {
+//!     input.update(buddy.buttons.state(), now_ms);
+//!     while let Some(event) = input.pop() {
+//!         match event {
+//!             Event::Click(Button::Left) => menu.next(),
+//!             Event::LongPress(Button::Right) => menu.back(),
+//!             _ => {}
+//!         }
+//!     }
+//! }
+//! ```
+
+#[cfg(feature = "esp32")]
+mod esp_hal;
+
+#[cfg(feature = "esp32")]
+pub(crate) use self::esp_hal::interrupt_handler;
+#[cfg(feature = "esp32")]
+pub use self::esp_hal::Interrupts;
+
+use core::fmt;
+
+use crate::screens::ButtonState;
+
+/// How long a level has to hold to count.
+pub const DEBOUNCE_MS: u64 = 20;
+/// Longest time from releasing a click to the second press of a double click.
+pub const DOUBLE_CLICK_MS: u64 = 300;
+/// How long a button is held down before it is a long press.
+pub const LONG_PRESS_MS: u64 = 800;
+/// How often a long press repeats.
+pub const REPEAT_MS: u64 = 200;
+
+/// Most events queued, newer ones are dropped until [`Input::pop`] makes
+/// room.
+pub const QUEUE_LEN: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Timing {
+    pub debounce_ms: u64,
+    /// With 0 a click is sent on release, without double clicks.
+    pub double_click_ms: u64,
+    pub long_press_ms: u64,
+    pub repeat_ms: u64,
+}
+
+impl Default for Timing {
+    fn default() -> Self {
+        Self {
+            debounce_ms: DEBOUNCE_MS,
+            double_click_ms: DOUBLE_CLICK_MS,
+            long_press_ms: LONG_PRESS_MS,
+            repeat_ms: REPEAT_MS,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Button {
+    /// GPIO0.
+    Left,
+    /// GPIO4.
+    Right,
+}
+
+impl Button {
+    pub fn name(self) -> &'static str {
+        match self {
+            Button::Left => "left",
+            Button::Right => "right",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    Pressed(Button),
+    Released(Button),
+    Click(Button),
+    DoubleClick(Button),
+    LongPress(Button),
+    Repeat(Button),
+    /// Both buttons down.
+    Chord,
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (gesture, button) = match self {
+            Event::Pressed(button) => ("pressed", button),
+            Event::Released(button) => ("released", button),
+            Event::Click(button) => ("click", button),
+            Event::DoubleClick(button) => ("double click", button),
+            Event::LongPress(button) => ("long press", button),
+            Event::Repeat(button) => ("repeat", button),
+            Event::Chord => return f.write_str("chord"),
+        };
+        write!(f, "{} {}", gesture, button.name())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Clicks {
+    #[default]
+    None,
+    /// Released after a click, waiting for a second press until the deadline.
+    Pending { deadline_ms: u64 },
+    /// Pressed again within the deadline.
+    Second,
+}
+
+/// Timing state of one button.
+#[derive(Clone, Copy, Debug, Default)]
+struct Tracker {
+    /// Last level seen and when it changed to it.
+    raw: bool,
+    raw_since_ms: u64,
+    /// Debounced level and when it went down.
+    down: bool,
+    down_ms: u64,
+    /// Long press and repeats sent for this press.
+    holds: u64,
+    clicks: Clicks,
+    /// Part of a chord, no gestures until released.
+    chorded: bool,
+}
+
+impl Tracker {
+    /// The new debounced level, if it changed.
+    fn debounce(&mut self, level: bool, now_ms: u64, debounce_ms: u64) -> Option<bool> {
+        if level != self.raw {
+            self.raw = level;
+            self.raw_since_ms = now_ms;
+        }
+        if self.raw == self.down || now_ms - self.raw_since_ms < debounce_ms {
+            return None;
+        }
+        self.down = self.raw;
+        Some(self.down)
+    }
+
+    /// When the next long press or repeat is due while held.
+    fn hold_due_ms(&self, timing: &Timing) -> Option<u64> {
+        (self.down && !self.chorded)
+            .then(|| self.down_ms + timing.long_press_ms + self.holds * timing.repeat_ms)
+    }
+}
+
+/// Events of the two buttons from their levels over time.
+#[derive(Clone, Debug, Default)]
+pub struct Input {
+    timing: Timing,
+    buttons: [Tracker; 2],
+    queue: heapless::Deque<Event, QUEUE_LEN>,
+}
+
+impl Input {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_timing(mut self, timing: Timing) -> Self {
+        self.timing = timing;
+        self
+    }
+
+    pub fn timing(&self) -> &Timing {
+        &self.timing
+    }
+
+    /// Debounced levels.
+    pub fn state(&self) -> ButtonState {
+        ButtonState {
+            left: self.buttons[0].down,
+            right: self.buttons[1].down,
+        }
+    }
+
+    /// Takes the levels at `now_ms` and queues the events they cause.
+    pub fn update(&mut self, levels: ButtonState, now_ms: u64) {
+        let debounce_ms = self.timing.debounce_ms;
+        for (index, level) in [levels.left, levels.right].into_iter().enumerate() {
+            match self.buttons[index].debounce(level, now_ms, debounce_ms) {
+                Some(true) => self.press(index),
+                Some(false) => self.release(index),
+                None => {}
+            }
+        }
+        for index in 0..2 {
+            self.tick(index, now_ms);
+        }
+    }
+
+    /// Oldest event not taken yet.
+    pub fn pop(&mut self) -> Option<Event> {
+        self.queue.pop_front()
+    }
+
+    /// When [`Input::update`] has to run again without a level change, for
+    /// a debounce, long press, repeat or click to be sent.
+    pub fn next_deadline_ms(&self) -> Option<u64> {
+        self.buttons
+            .iter()
+            .flat_map(|tracker| {
+                let debounce = (tracker.raw != tracker.down)
+                    .then_some(tracker.raw_since_ms + self.timing.debounce_ms);
+                let click = match tracker.clicks {
+                    Clicks::Pending { deadline_ms } => Some(deadline_ms),
+                    _ => None,
+                };
+                [debounce, tracker.hold_due_ms(&self.timing), click]
+            })
+            .flatten()
+            .min()
+    }
+
+    fn push(&mut self, event: Event) {
+        self.queue.push_back(event).ok();
+    }
+
+    /// Press and release are timed from the first change of the level.
+    fn press(&mut self, index: usize) {
+        let button = BUTTONS[index];
+        let tracker = &mut self.buttons[index];
+        let at_ms = tracker.raw_since_ms;
+        tracker.down_ms = at_ms;
+        tracker.holds = 0;
+        let clicks = tracker.clicks;
+        tracker.clicks = match clicks {
+            Clicks::Pending { deadline_ms } if at_ms < deadline_ms => Clicks::Second,
+            Clicks::Pending { .. } => Clicks::None,
+            clicks => clicks,
+        };
+        if let Clicks::Pending { .. } = clicks {
+            if tracker.clicks == Clicks::None {
+                // Not updated since the deadline of the click
+                self.push(Event::Click(button));
+            }
+        }
+        self.push(Event::Pressed(button));
+
+        let other = &self.buttons[1 - index];
+        if other.down && other.holds == 0 {
+            for tracker in &mut self.buttons {
+                tracker.chorded = true;
+                tracker.clicks = Clicks::None;
+            }
+            self.push(Event::Chord);
+        }
+    }
+
+    fn release(&mut self, index: usize) {
+        let button = BUTTONS[index];
+        self.push(Event::Released(button));
+        let double_click_ms = self.timing.double_click_ms;
+        let tracker = &mut self.buttons[index];
+        let short = !tracker.chorded && tracker.holds == 0;
+        let clicks = tracker.clicks;
+        tracker.chorded = false;
+        tracker.clicks = Clicks::None;
+        if !short {
+            return;
+        }
+        match clicks {
+            Clicks::Second => self.push(Event::DoubleClick(button)),
+            _ if double_click_ms == 0 => self.push(Event::Click(button)),
+            _ => {
+                let deadline_ms = self.buttons[index].raw_since_ms + double_click_ms;
+                self.buttons[index].clicks = Clicks::Pending { deadline_ms };
+            }
+        }
+    }
+
+    fn tick(&mut self, index: usize, now_ms: u64) {
+        let button = BUTTONS[index];
+        let tracker = self.buttons[index];
+        if let Clicks::Pending { deadline_ms } = tracker.clicks {
+            if now_ms >= deadline_ms {
+                self.buttons[index].clicks = Clicks::None;
+                self.push(Event::Click(button));
+            }
+        }
+        let Some(due_ms) = tracker.hold_due_ms(&self.timing) else {
+            return;
+        };
+        if now_ms < due_ms {
+            return;
+        }
+        if tracker.holds == 0 {
+            // The first click of a double click held down the second time
+            if tracker.clicks == Clicks::Second {
+                self.buttons[index].clicks = Clicks::None;
+                self.push(Event::Click(button));
+            }
+            self.push(Event::LongPress(button));
+        } else {
+            self.push(Event::Repeat(button));
+        }
+        self.buttons[index].holds += 1;
+    }
+}
+
+const BUTTONS: [Button; 2] = [Button::Left, Button::Right];
@@ -0,0 +1,507 @@
+//! The examples as [`App`]s.
+
+use super::App;
+use crate::{
+    input::{Button, Event},
+    screens::{
+        self,
+        clock::{ClockTime, DefaultFaces, FaceCycle, Faces},
+        ButtonState, Scene,
+    },
+    sensors::{hts221::Reading, Hts221Reader, I2c},
+    settings::{
+        schema::{Config, Setting, Units, MAX_TEXT_LEN},
+        Editor, Store,
+    },
+    style,
+    time::{self, TimeZone},
+    wifi::{Controller, State, WifiManager},
+};
+use embedded_graphics::{
+    pixelcolor::BinaryColor,
+    prelude::*,
+    text::{Baseline, Text},
+};
+use embedded_storage::nor_flash::NorFlash;
+
+/// Default time between two readings of the temperature app.
+pub const READINGS_INTERVAL_MS: u64 = 5000;
+/// Time between two looks at the pins of the GPIO app.
+pub const GPIO_INTERVAL_MS: u64 = 300;
+
+/// Pins shown by the GPIO app, with their place in the grid. The flash,
+/// I2C and non-existent pins are left out.
+pub const GPIO_PINS: [(i32, u8); 20] = [
+    (0, 0),
+    (1, 1),
+    (2, 2),
+    (3, 3),
+    (4, 4),
+    (5, 5),
+    (9, 9),
+    (12, 12),
+    (13, 13),
+    (14, 14),
+    (15, 15),
+    (16, 16),
+    (17, 17),
+    (19, 19),
+    (21, 21),
+    (22, 22),
+    (25, 25),
+    (26, 26),
+    (27, 27),
+    (28, 32),
+];
+
+/// Runs a [`Scene`] every [`Scene::FRAME_MS`], with the buttons held down.
+pub struct SceneApp<S> {
+    name: &'static str,
+    scene: S,
+    held: ButtonState,
+    /// Went down since the last frame, so short presses are not missed.
+    pressed: ButtonState,
+    next_frame_ms: u64,
+}
+
+impl<S: Scene> SceneApp<S> {
+    pub fn new(name: &'static str, scene: S) -> Self {
+        Self {
+            name,
+            scene,
+            held: ButtonState::default(),
+            pressed: ButtonState::default(),
+            next_frame_ms: 0,
+        }
+    }
+
+    pub fn scene(&self) -> &S {
+        &self.scene
+    }
+}
+
+impl<S: Scene> App for SceneApp<S> {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn init(&mut self, now_ms: u64) {
+        self.held = ButtonState::default();
+        self.pressed = ButtonState::default();
+        self.next_frame_ms = now_ms + u64::from(S::FRAME_MS);
+    }
+
+    fn handle_event(&mut self, event: Event, _now_ms: u64) -> bool {
+        let (button, down) = match event {
+            Event::Pressed(button) => (button, true),
+            Event::Released(button) => (button, false),
+            _ => return false,
+        };
+        let (held, pressed) = match button {
+            Button::Left => (&mut self.held.left, &mut self.pressed.left),
+            Button::Right => (&mut self.held.right, &mut self.pressed.right),
+        };
+        *held = down;
+        *pressed |= down;
+        false
+    }
+
+    fn update(&mut self, now_ms: u64) -> bool {
+        if now_ms < self.next_frame_ms {
+            return false;
+        }
+        self.scene.update(ButtonState {
+            left: self.held.left || self.pressed.left,
+            right: self.held.right || self.pressed.right,
+        });
+        self.pressed = ButtonState::default();
+        // Frames are skipped rather than caught up with
+        self.next_frame_ms = (self.next_frame_ms + u64::from(S::FRAME_MS)).max(now_ms + 1);
+        true
+    }
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        self.scene.draw(target)
+    }
+}
+
+/// The clock faces, left switches to the next one.
+///
+/// Shows the wall clock once it was synced, e.g. over NTP while the WiFi is
+/// connected, and counts from midnight UTC at boot until then.
+pub struct Clock<F = DefaultFaces> {
+    faces: FaceCycle<F>,
+    wall_clock: time::Clock,
+    /// POSIX TZ string, UTC when it does not parse.
+    time_zone: heapless::String<MAX_TEXT_LEN>,
+    timestamp: i64,
+}
+
+impl Clock {
+    pub fn new() -> Self {
+        Self::with_faces(DefaultFaces::default())
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: Faces> Clock<F> {
+    pub fn with_faces(faces: F) -> Self {
+        Self {
+            faces: FaceCycle::new(faces),
+            wall_clock: time::Clock::new(),
+            time_zone: heapless::String::new(),
+            timestamp: 0,
+        }
+    }
+
+    /// Takes the time zone of `config`.
+    pub fn with_config(mut self, config: &Config) -> Self {
+        self.set_config(config);
+        self
+    }
+
+    /// Applies from the next draw.
+    pub fn set_config(&mut self, config: &Config) {
+        self.time_zone = config.time_zone.clone();
+    }
+
+    pub fn wall_clock(&self) -> &time::Clock {
+        &self.wall_clock
+    }
+
+    /// The wall clock, synced against the launcher's `now_ms` in microseconds.
+    pub fn wall_clock_mut(&mut self) -> &mut time::Clock {
+        &mut self.wall_clock
+    }
+
+    /// Unix time of the second shown.
+    pub fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+}
+
+impl<F: Faces> App for Clock<F> {
+    fn name(&self) -> &'static str {
+        "Clock"
+    }
+
+    fn init(&mut self, now_ms: u64) {
+        self.update(now_ms);
+    }
+
+    fn handle_event(&mut self, event: Event, _now_ms: u64) -> bool {
+        if event != Event::Click(Button::Left) {
+            return false;
+        }
+        self.faces.next();
+        true
+    }
+
+    fn update(&mut self, now_ms: u64) -> bool {
+        let timestamp = self
+            .wall_clock
+            .unix_seconds(now_ms * 1000)
+            .unwrap_or((now_ms / 1000) as i64);
+        let changed = timestamp != self.timestamp;
+        self.timestamp = timestamp;
+        changed
+    }
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let time_zone = TimeZone::parse(&self.time_zone).unwrap_or(TimeZone::UTC);
+        self.faces
+            .draw(target, &ClockTime::new(self.timestamp, &time_zone))
+    }
+}
+
+/// Temperature and humidity of the HTS221, read every
+/// [`READINGS_INTERVAL_MS`] while open.
+pub struct Temperature<I> {
+    i2c: I,
+    hts221: Hts221Reader,
+    units: Units,
+    interval_ms: u64,
+    reading: Option<Result<Reading, &'static str>>,
+    next_reading_ms: u64,
+}
+
+impl<I: I2c> Temperature<I> {
+    pub fn new(i2c: I, hts221: Hts221Reader) -> Self {
+        Self {
+            i2c,
+            hts221,
+            units: Units::Celsius,
+            interval_ms: READINGS_INTERVAL_MS,
+            reading: None,
+            next_reading_ms: 0,
+        }
+    }
+
+    /// Takes the units and the sample interval of `config`.
+    pub fn with_config(mut self, config: &Config) -> Self {
+        self.set_config(config);
+        self
+    }
+
+    /// Applies from the next reading.
+    pub fn set_config(&mut self, config: &Config) {
+        self.units = config.units;
+        self.interval_ms = config.sample_interval_ms;
+    }
+
+    /// Last reading, or why it failed.
+    pub fn reading(&self) -> Option<Result<Reading, &'static str>> {
+        self.reading
+    }
+}
+
+impl<I: I2c> App for Temperature<I> {
+    fn name(&self) -> &'static str {
+        "Temperature"
+    }
+
+    fn init(&mut self, now_ms: u64) {
+        self.next_reading_ms = now_ms;
+    }
+
+    fn handle_event(&mut self, _event: Event, _now_ms: u64) -> bool {
+        false
+    }
+
+    fn update(&mut self, now_ms: u64) -> bool {
+        if now_ms < self.next_reading_ms {
+            return false;
+        }
+        self.next_reading_ms = now_ms + self.interval_ms;
+        self.reading = Some(self.hts221.read(&mut self.i2c).map_err(|err| err.message()));
+        true
+    }
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        match self.reading {
+            Some(Ok(reading)) => screens::temperature::draw_with_unit(
+                target,
+                self.units.from_dc(reading.temperature_dc) / 10,
+                self.units.symbol(),
+                reading.humidity_dpct / 10,
+            ),
+            Some(Err(message)) => {
+                Text::with_baseline(
+                    "Temperature/Humidity",
+                    Point::zero(),
+                    style::TEXT,
+                    Baseline::Top,
+                )
+                .draw(target)?;
+                Text::with_baseline(message, Point::new(0, 16), style::TEXT, Baseline::Top)
+                    .draw(target)
+                    .map(drop)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// Levels of [`GPIO_PINS`], looked at every [`GPIO_INTERVAL_MS`] while open.
+///
+/// `read` returns the pins reading low as a mask, bit `n` for GPIO `n`.
+pub struct Gpio<R> {
+    read: R,
+    low: u64,
+    next_read_ms: u64,
+}
+
+impl<R: FnMut() -> u64> Gpio<R> {
+    pub fn new(read: R) -> Self {
+        Self {
+            read,
+            low: 0,
+            next_read_ms: 0,
+        }
+    }
+}
+
+impl<R: FnMut() -> u64> App for Gpio<R> {
+    fn name(&self) -> &'static str {
+        "GPIO"
+    }
+
+    fn init(&mut self, now_ms: u64) {
+        self.next_read_ms = now_ms;
+    }
+
+    fn handle_event(&mut self, _event: Event, _now_ms: u64) -> bool {
+        false
+    }
+
+    fn update(&mut self, now_ms: u64) -> bool {
+        if now_ms < self.next_read_ms {
+            return false;
+        }
+        self.next_read_ms = now_ms + GPIO_INTERVAL_MS;
+        let low = (self.read)();
+        let changed = low != self.low;
+        self.low = low;
+        changed
+    }
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let states = GPIO_PINS.map(|(position, pin)| (position, self.low & 1 << pin != 0));
+        screens::gpio::draw(target, &states)
+    }
+}
+
+/// State of the [`WifiManager`], which the owner starts and polls through
+/// [`manager_mut`](Self::manager_mut), also while the app is closed.
+pub struct Wifi<'a, C: Controller> {
+    manager: WifiManager<'a, C>,
+    /// State on the screen.
+    shown: State,
+}
+
+impl<'a, C: Controller> Wifi<'a, C> {
+    pub fn new(manager: WifiManager<'a, C>) -> Self {
+        let shown = manager.state();
+        Self { manager, shown }
+    }
+
+    pub fn manager(&self) -> &WifiManager<'a, C> {
+        &self.manager
+    }
+
+    pub fn manager_mut(&mut self) -> &mut WifiManager<'a, C> {
+        &mut self.manager
+    }
+}
+
+impl<C: Controller> App for Wifi<'_, C> {
+    fn name(&self) -> &'static str {
+        "WiFi"
+    }
+
+    fn init(&mut self, _now_ms: u64) {
+        self.shown = self.manager.state();
+    }
+
+    fn handle_event(&mut self, _event: Event, _now_ms: u64) -> bool {
+        false
+    }
+
+    fn update(&mut self, _now_ms: u64) -> bool {
+        let state = self.manager.state();
+        let changed = state != self.shown;
+        self.shown = state;
+        changed
+    }
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        screens::wifi::draw_state(target, &self.manager.state())
+    }
+}
+
+/// The settings of a schema, written to the store once saved in the
+/// [`Editor`].
+pub struct Settings<F> {
+    store: Store<F>,
+    editor: Editor<'static>,
+    /// Why the last value was not saved, shown until the next button.
+    error: Option<&'static str>,
+    changed: bool,
+}
+
+impl<F: NorFlash> Settings<F> {
+    /// Starts from the stored values, the defaults when they cannot be read.
+    pub fn new(mut store: Store<F>, schema: &'static [Setting]) -> Self {
+        let values = store.settings(schema).unwrap_or_default();
+        Self {
+            store,
+            editor: Editor::new(schema, &values),
+            error: None,
+            changed: false,
+        }
+    }
+
+    pub fn editor(&self) -> &Editor<'static> {
+        &self.editor
+    }
+
+    pub fn store_mut(&mut self) -> &mut Store<F> {
+        &mut self.store
+    }
+
+    /// The board settings with the values saved so far.
+    pub fn config(&self) -> Config {
+        Config::new(self.editor.schema(), self.editor.values())
+    }
+
+    /// Whether a value was saved since the last call, also when it could not
+    /// be written to the store.
+    pub fn take_changed(&mut self) -> bool {
+        core::mem::take(&mut self.changed)
+    }
+}
+
+impl<F: NorFlash> App for Settings<F> {
+    fn name(&self) -> &'static str {
+        "Settings"
+    }
+
+    fn init(&mut self, _now_ms: u64) {
+        self.editor.cancel();
+        self.error = None;
+    }
+
+    fn handle_event(&mut self, event: Event, _now_ms: u64) -> bool {
+        if !self.editor.handle_event(event) {
+            return false;
+        }
+        self.error = None;
+        if let Some(index) = self.editor.take_saved() {
+            let setting = &self.editor.schema()[index];
+            let value = &self.editor.values()[index];
+            if let Err(err) = self.store.save_setting(setting, value) {
+                self.error = Some(err.message());
+            }
+            self.changed = true;
+        }
+        true
+    }
+
+    fn update(&mut self, _now_ms: u64) -> bool {
+        false
+    }
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let Some(message) = self.error else {
+            return screens::settings::draw(target, &self.editor);
+        };
+        Text::with_baseline("Not saved:", Point::zero(), style::TEXT, Baseline::Top)
+            .draw(target)?;
+        Text::with_baseline(message, Point::new(0, 11), style::TEXT, Baseline::Top)
+            .draw(target)
+            .map(drop)
+    }
+}
@@ -0,0 +1,292 @@
+//! One firmware for all examples, picked from a menu.
+//!
+//! Every example is an [`App`], the [`Launcher`] lists them in a [`Menu`]
+//! and runs the one opened with the two buttons:
+//!
+//! - menu: a click on left selects the next app, holding it scrolls, a click
+//!   on right opens the app
+//! - app: holding either button goes back to the menu, everything else is
+//!   passed on to the app
+//!
+//! Apps are kept in a tuple, like the faces of the
+//! [`Clock`](crate::screens::Clock), and only the open one is updated:
+//!
+//! ```ignore
+//! let mut launcher = Launcher::new((
+//!     SceneApp::new("Animation", Animation::new()),
+//!     SceneApp::new("Car", Car::new()),
+//! ));
+//! loop {
+//!     input.update(buttons.state(), now_ms);
+//!     let mut redraw = launcher.update(now_ms);
+//!     while let Some(event) = input.pop() {
+//!         redraw |= launcher.handle_event(event, now_ms);
+//!     }
+//! }
+//! ```
+
+pub mod apps;
+
+use embedded_graphics::{pixelcolor::BinaryColor, prelude::*};
+
+use crate::{
+    input::{Button, Event},
+    screens::menu,
+};
+
+pub use self::apps::SceneApp;
+
+/// Most apps in a [`Launcher`], the longest tuple [`Apps`] is implemented for.
+pub const MAX_APPS: usize = 8;
+
+/// A screen of its own in the launcher.
+pub trait App {
+    /// Shown in the menu.
+    fn name(&self) -> &'static str;
+
+    /// Called every time the app is opened from the menu.
+    fn init(&mut self, now_ms: u64);
+
+    /// Takes a button event. Returns whether the screen changed.
+    fn handle_event(&mut self, event: Event, now_ms: u64) -> bool;
+
+    /// Advances the app to `now_ms`. Returns whether the screen changed.
+    fn update(&mut self, now_ms: u64) -> bool;
+
+    /// Draws the app on a cleared target.
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>;
+}
+
+/// Ordered set of apps, implemented for tuples of up to [`MAX_APPS`] [`App`]s.
+///
+/// Every method does nothing when `index` is out of range.
+pub trait Apps {
+    const COUNT: usize;
+
+    fn name(&self, index: usize) -> &'static str;
+
+    fn init(&mut self, index: usize, now_ms: u64);
+
+    fn handle_event(&mut self, index: usize, event: Event, now_ms: u64) -> bool;
+
+    fn update(&mut self, index: usize, now_ms: u64) -> bool;
+
+    fn draw<D>(&self, index: usize, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>;
+}
+
+macro_rules! impl_apps {
+    ($($app:ident $index:tt),+) => {
+        impl<$($app: App),+> Apps for ($($app,)+) {
+            const COUNT: usize = [$($index),+].len();
+
+            fn name(&self, index: usize) -> &'static str {
+                match index {
+                    $($index => self.$index.name(),)+
+                    _ => "",
+                }
+            }
+
+            fn init(&mut self, index: usize, now_ms: u64) {
+                match index {
+                    $($index => self.$index.init(now_ms),)+
+                    _ => {}
+                }
+            }
+
+            fn handle_event(&mut self, index: usize, event: Event, now_ms: u64) -> bool {
+                match index {
+                    $($index => self.$index.handle_event(event, now_ms),)+
+                    _ => false,
+                }
+            }
+
+            fn update(&mut self, index: usize, now_ms: u64) -> bool {
+                match index {
+                    $($index => self.$index.update(now_ms),)+
+                    _ => false,
+                }
+            }
+
+            fn draw<D>(&self, index: usize, target: &mut D) -> Result<(), D::Error>
+            where
+                D: DrawTarget<Color = BinaryColor>,
+            {
+                match index {
+                    $($index => self.$index.draw(target),)+
+                    _ => Ok(()),
+                }
+            }
+        }
+    };
+}
+
+impl_apps!(A 0);
+impl_apps!(A 0, B 1);
+impl_apps!(A 0, B 1, C 2);
+impl_apps!(A 0, B 1, C 2, E 3);
+impl_apps!(A 0, B 1, C 2, E 3, F 4);
+impl_apps!(A 0, B 1, C 2, E 3, F 4, G 5);
+impl_apps!(A 0, B 1, C 2, E 3, F 4, G 5, H 6);
+impl_apps!(A 0, B 1, C 2, E 3, F 4, G 5, H 6, I 7);
+
+/// Selection in a list scrolled [`Menu::ROWS`] at a time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Menu {
+    len: usize,
+    selected: usize,
+    /// First item shown.
+    top: usize,
+}
+
+impl Menu {
+    /// Items that fit on the display.
+    pub const ROWS: usize = 3;
+
+    pub fn new(len: usize) -> Self {
+        Self {
+            len,
+            selected: 0,
+            top: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Index of the selected item.
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Index of the first item shown.
+    pub fn top(&self) -> usize {
+        self.top
+    }
+
+    /// Selects the next item, the first one after the last.
+    pub fn next(&mut self) {
+        if !self.is_empty() {
+            self.select((self.selected + 1) % self.len);
+        }
+    }
+
+    /// Selects the item at `index`, or the last one, and scrolls it into view.
+    pub fn select(&mut self, index: usize) {
+        self.selected = index.min(self.len.saturating_sub(1));
+        if self.selected < self.top {
+            self.top = self.selected;
+        } else if self.selected >= self.top + Self::ROWS {
+            self.top = self.selected + 1 - Self::ROWS;
+        }
+    }
+}
+
+/// The menu of all apps, or the app opened from it.
+pub struct Launcher<A> {
+    apps: A,
+    menu: Menu,
+    opened: Option<usize>,
+    /// Held down to go back, its repeats do not scroll the menu.
+    holding: Option<Button>,
+}
+
+impl<A: Apps> Launcher<A> {
+    pub fn new(apps: A) -> Self {
+        Self {
+            apps,
+            menu: Menu::new(A::COUNT),
+            opened: None,
+            holding: None,
+        }
+    }
+
+    pub fn menu(&self) -> &Menu {
+        &self.menu
+    }
+
+    /// Index of the app shown, `None` in the menu.
+    pub fn opened(&self) -> Option<usize> {
+        self.opened
+    }
+
+    pub fn apps(&self) -> &A {
+        &self.apps
+    }
+
+    /// For passing readings to the apps while they are not open.
+    pub fn apps_mut(&mut self) -> &mut A {
+        &mut self.apps
+    }
+
+    /// Opens the app at `index`, from the menu or another app.
+    pub fn open(&mut self, index: usize, now_ms: u64) {
+        if index < A::COUNT {
+            self.menu.select(index);
+            self.opened = Some(index);
+            self.apps.init(index, now_ms);
+        }
+    }
+
+    /// Goes back to the menu, with the app that was open selected.
+    pub fn close(&mut self) {
+        self.opened = None;
+    }
+
+    /// Takes a button event. Returns whether the screen changed.
+    pub fn handle_event(&mut self, event: Event, now_ms: u64) -> bool {
+        if let Some(button) = self.holding {
+            match event {
+                Event::Repeat(repeated) if repeated == button => return false,
+                Event::Released(released) if released == button => self.holding = None,
+                _ => {}
+            }
+        }
+        match (self.opened, event) {
+            (Some(_), Event::LongPress(button)) => {
+                self.holding = Some(button);
+                self.close();
+                true
+            }
+            (Some(index), event) => self.apps.handle_event(index, event, now_ms),
+            (None, Event::Click(Button::Left) | Event::Repeat(Button::Left)) => {
+                self.menu.next();
+                A::COUNT > 1
+            }
+            (None, Event::Click(Button::Right)) => {
+                self.open(self.menu.selected(), now_ms);
+                true
+            }
+            (None, _) => false,
+        }
+    }
+
+    /// Advances the open app. Returns whether the screen changed.
+    pub fn update(&mut self, now_ms: u64) -> bool {
+        match self.opened {
+            Some(index) => self.apps.update(index, now_ms),
+            None => false,
+        }
+    }
+
+    /// Draws the menu or the open app on a cleared target.
+    pub fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        if let Some(index) = self.opened {
+            return self.apps.draw(index, target);
+        }
+        let names: heapless::Vec<&str, MAX_APPS> =
+            (0..A::COUNT).map(|index| self.apps.name(index)).collect();
+        menu::draw(target, &self.menu, &names)
+    }
+}
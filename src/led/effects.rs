@@ -0,0 +1,319 @@
+//! Animations of the LED as functions of time.
+//!
+//! An [`Effect`] gives the color of every LED of a chain at a time in
+//! milliseconds. Nothing waits or keeps state, so the loop drawing the
+//! display decides how often the LED is written, and the same time always
+//! gives the same colors. Effects wrap each other: [`Breathe`], [`Blink`]
+//! and [`Comet`] change the brightness of any effect, [`Status`] combines
+//! them into what the board is doing.
+//!
+//! [`Output`] corrects the gamma and caps the brightness before the colors
+//! are written:
+//!
+//! ```ignore
+//! let output = Output::new().with_brightness(16);
+//! let effect = Breathe::new(Rainbow::new(10_000), 3000);
+//! let mut leds = [Rgb::default(); 1];
+//! output.render(&effect, now_ms, &mut leds);
+//! ws2812.write_colors(leds)?;
+//! ```
+
+use super::Rgb;
+
+/// Brightness of [`Output::new`], out of 255. The LED is bright enough to
+/// light up a room at full power.
+pub const DEFAULT_BRIGHTNESS: u8 = 32;
+
+/// Half a second on, half a second off.
+pub const BLINK_SLOW: &[u32] = &[500, 500];
+/// Five times a second.
+pub const BLINK_FAST: &[u32] = &[100, 100];
+/// Two short blinks a second.
+pub const BLINK_DOUBLE: &[u32] = &[100, 100, 100, 700];
+
+pub const RED: Rgb = Rgb::new(255, 0, 0);
+pub const GREEN: Rgb = Rgb::new(0, 255, 0);
+pub const BLUE: Rgb = Rgb::new(0, 0, 255);
+pub const OFF: Rgb = Rgb::new(0, 0, 0);
+
+/// Colors of a chain of LEDs over time.
+pub trait Effect {
+    /// Color of LED `index` of `len` at `now_ms`.
+    fn color(&self, now_ms: u64, index: usize, len: usize) -> Rgb;
+}
+
+impl<E: Effect + ?Sized> Effect for &E {
+    fn color(&self, now_ms: u64, index: usize, len: usize) -> Rgb {
+        (**self).color(now_ms, index, len)
+    }
+}
+
+/// The same color all the time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Solid(pub Rgb);
+
+impl Effect for Solid {
+    fn color(&self, _now_ms: u64, _index: usize, _len: usize) -> Rgb {
+        self.0
+    }
+}
+
+/// An effect fading in and out, dark at the start of every period.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Breathe<E> {
+    effect: E,
+    period_ms: u64,
+}
+
+impl<E: Effect> Breathe<E> {
+    pub const fn new(effect: E, period_ms: u64) -> Self {
+        Self { effect, period_ms }
+    }
+}
+
+impl<E: Effect> Effect for Breathe<E> {
+    fn color(&self, now_ms: u64, index: usize, len: usize) -> Rgb {
+        let period_ms = self.period_ms.max(2);
+        let half_ms = period_ms / 2;
+        let at_ms = now_ms % period_ms;
+        let level = if at_ms < half_ms {
+            at_ms * 255 / half_ms
+        } else {
+            (period_ms - at_ms) * 255 / (period_ms - half_ms)
+        };
+        scale(self.effect.color(now_ms, index, len), level as u8)
+    }
+}
+
+/// An effect switched on and off by a pattern of durations, on first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Blink<E> {
+    effect: E,
+    pattern: &'static [u32],
+}
+
+impl<E: Effect> Blink<E> {
+    /// `pattern` alternates on and off, e.g. [`BLINK_DOUBLE`].
+    pub const fn new(effect: E, pattern: &'static [u32]) -> Self {
+        Self { effect, pattern }
+    }
+
+    /// Whether the pattern is on at `now_ms`, always without durations.
+    pub fn is_on(&self, now_ms: u64) -> bool {
+        let period_ms: u64 = self.pattern.iter().map(|&ms| u64::from(ms)).sum();
+        if period_ms == 0 {
+            return true;
+        }
+        let mut at_ms = now_ms % period_ms;
+        for (step, &ms) in self.pattern.iter().enumerate() {
+            if at_ms < u64::from(ms) {
+                return step % 2 == 0;
+            }
+            at_ms -= u64::from(ms);
+        }
+        unreachable!()
+    }
+}
+
+impl<E: Effect> Effect for Blink<E> {
+    fn color(&self, now_ms: u64, index: usize, len: usize) -> Rgb {
+        if self.is_on(now_ms) {
+            self.effect.color(now_ms, index, len)
+        } else {
+            OFF
+        }
+    }
+}
+
+/// Every hue once a period, spread over the chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rainbow {
+    period_ms: u64,
+}
+
+impl Rainbow {
+    pub const fn new(period_ms: u64) -> Self {
+        Self { period_ms }
+    }
+}
+
+impl Effect for Rainbow {
+    fn color(&self, now_ms: u64, index: usize, len: usize) -> Rgb {
+        let period_ms = self.period_ms.max(1);
+        let hue = now_ms % period_ms * 256 / period_ms + (index * 256 / len.max(1)) as u64;
+        hsv(hue as u8, 255, 255)
+    }
+}
+
+/// A bright head running along the chain once a period with a fading tail.
+/// On a single LED it flashes and fades out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Comet<E> {
+    effect: E,
+    period_ms: u64,
+    tail: usize,
+}
+
+impl<E: Effect> Comet<E> {
+    /// With a tail of 3 LEDs.
+    pub const fn new(effect: E, period_ms: u64) -> Self {
+        Self {
+            effect,
+            period_ms,
+            tail: 3,
+        }
+    }
+
+    /// LEDs from the head until the tail is dark.
+    pub const fn with_tail(mut self, tail: usize) -> Self {
+        self.tail = tail;
+        self
+    }
+}
+
+impl<E: Effect> Effect for Comet<E> {
+    fn color(&self, now_ms: u64, index: usize, len: usize) -> Rgb {
+        // In 256ths of an LED
+        let period_ms = self.period_ms.max(1);
+        let length = len.max(1) as u64 * 256;
+        let head = now_ms % period_ms * length / period_ms;
+        let behind = (head + length - index as u64 * 256 % length) % length;
+        let tail = self.tail.max(1) as u64 * 256;
+        let level = tail.saturating_sub(behind) * 255 / tail;
+        scale(self.effect.color(now_ms, index, len), level as u8)
+    }
+}
+
+/// What the board is doing, as commonly shown by a status LED.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Status {
+    #[default]
+    Off,
+    /// Breathing blue.
+    Connecting,
+    /// A short green blink every 3 seconds.
+    Connected,
+    /// Blinking red twice a second.
+    Error,
+}
+
+impl Effect for Status {
+    fn color(&self, now_ms: u64, index: usize, len: usize) -> Rgb {
+        match self {
+            Status::Off => OFF,
+            Status::Connecting => Breathe::new(Solid(BLUE), 2000).color(now_ms, index, len),
+            Status::Connected => Blink::new(Solid(GREEN), &[100, 2900]).color(now_ms, index, len),
+            Status::Error => Blink::new(Solid(RED), BLINK_DOUBLE).color(now_ms, index, len),
+        }
+    }
+}
+
+/// Gamma correction and a brightness cap for the colors of an effect.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Output {
+    brightness: u8,
+    gamma: bool,
+}
+
+impl Default for Output {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Output {
+    /// [`DEFAULT_BRIGHTNESS`] with gamma correction.
+    pub const fn new() -> Self {
+        Self {
+            brightness: DEFAULT_BRIGHTNESS,
+            gamma: true,
+        }
+    }
+
+    /// Brightest any channel gets, out of 255.
+    pub const fn with_brightness(mut self, brightness: u8) -> Self {
+        self.brightness = brightness;
+        self
+    }
+
+    pub const fn with_gamma(mut self, gamma: bool) -> Self {
+        self.gamma = gamma;
+        self
+    }
+
+    pub fn brightness(&self) -> u8 {
+        self.brightness
+    }
+
+    /// `color` as written to the LED.
+    pub fn correct(&self, color: Rgb) -> Rgb {
+        let color = if self.gamma {
+            Rgb::new(gamma(color.r), gamma(color.g), gamma(color.b))
+        } else {
+            color
+        };
+        scale(color, self.brightness)
+    }
+
+    /// Fills `leds` with the corrected colors of `effect` at `now_ms`.
+    pub fn render<E: Effect + ?Sized>(&self, effect: &E, now_ms: u64, leds: &mut [Rgb]) {
+        let len = leds.len();
+        for (index, led) in leds.iter_mut().enumerate() {
+            *led = self.correct(effect.color(now_ms, index, len));
+        }
+    }
+}
+
+/// Color of a hue, saturation and value, each out of 255. Hue 0 is red,
+/// 85 about green and 171 about blue.
+pub fn hsv(hue: u8, saturation: u8, value: u8) -> Rgb {
+    let (saturation, value) = (u32::from(saturation), u32::from(value));
+    // Six regions, the position in the region in 256ths
+    let sixths = u32::from(hue) * 6;
+    let (region, position) = (sixths / 256, sixths % 256);
+    let channel = |share: u32| (value * (255 * 256 - saturation * share) / (255 * 256)) as u8;
+    let (v, p) = (value as u8, channel(256));
+    let (falling, rising) = (channel(position), channel(256 - position));
+    let (r, g, b) = match region {
+        0 => (v, rising, p),
+        1 => (falling, v, p),
+        2 => (p, v, rising),
+        3 => (p, falling, v),
+        4 => (rising, p, v),
+        _ => (v, p, falling),
+    };
+    Rgb::new(r, g, b)
+}
+
+/// Perceived brightness to LED output, an exponent of 2.5.
+pub fn gamma(value: u8) -> u8 {
+    GAMMA[value as usize]
+}
+
+/// `color` with every channel scaled by `level` out of 255.
+pub fn scale(color: Rgb, level: u8) -> Rgb {
+    let channel = |value: u8| ((u16::from(value) * (u16::from(level) + 1)) >> 8) as u8;
+    Rgb::new(channel(color.r), channel(color.g), channel(color.b))
+}
+
+const GAMMA: [u8; 256] = gamma_table();
+
+/// `255 * (v / 255)^2.5`, as `v^2 * sqrt(v / 255) / 255` in integers.
+const fn gamma_table() -> [u8; 256] {
+    let mut table = [0u8; 256];
+    let mut value = 0;
+    while value < 256 {
+        let root = isqrt(value * 65536 / 255);
+        table[value as usize] = ((value * value * root + 255 * 128) / (255 * 256)) as u8;
+        value += 1;
+    }
+    table
+}
+
+const fn isqrt(value: u32) -> u32 {
+    let mut root = 0;
+    while (root + 1) * (root + 1) <= value {
+        root += 1;
+    }
+    root
+}
@@ -0,0 +1,99 @@
+use hal::{
+    gpio::OutputPin,
+    peripheral::Peripheral,
+    rmt::{self, TxChannel, TxChannelConfig, TxChannelCreator},
+};
+use smart_leds::{SmartLedsWrite, RGB8};
+
+use super::{buffer_len, encode_chain, Polarity, Rgb, BOARD_POLARITY};
+
+#[derive(Debug)]
+pub enum Error {
+    Rmt(rmt::Error),
+    /// More colors than the buffer has room for.
+    TooManyLeds,
+}
+
+/// WS2812 LEDs on an RMT channel, with pulse codes for `CODES / 24` of
+/// them, see [`buffer_len`]. One by default, the LED of the board.
+pub struct Ws2812<C, const CODES: usize = { buffer_len(1) }> {
+    /// Taken while transmitting.
+    channel: Option<C>,
+    polarity: Polarity,
+    codes: [u32; CODES],
+}
+
+impl<C: TxChannel> Ws2812<C> {
+    /// The LED of the board, on [`crate::pins::LED`].
+    pub fn new<'d, P: OutputPin + 'd>(
+        channel: impl TxChannelCreator<'d, C, P>,
+        pin: impl Peripheral<P = P> + 'd,
+    ) -> Result<Self, Error> {
+        Self::with_polarity(channel, pin, BOARD_POLARITY)
+    }
+}
+
+impl<C: TxChannel, const CODES: usize> Ws2812<C, CODES> {
+    /// A chain on any pin. The RMT has to run at [`super::CLOCK_MHZ`].
+    pub fn with_polarity<'d, P: OutputPin + 'd>(
+        channel: impl TxChannelCreator<'d, C, P>,
+        pin: impl Peripheral<P = P> + 'd,
+        polarity: Polarity,
+    ) -> Result<Self, Error> {
+        let config = TxChannelConfig {
+            clk_divider: 1,
+            idle_output: true,
+            idle_output_level: polarity.level(false),
+            carrier_modulation: false,
+            ..TxChannelConfig::default()
+        };
+        let channel = channel.configure(pin, config).map_err(Error::Rmt)?;
+        Ok(Self {
+            channel: Some(channel),
+            polarity,
+            codes: [0; CODES],
+        })
+    }
+
+    /// Sets every LED of the chain, first to last, and waits until the
+    /// colors show.
+    pub fn write_colors<I>(&mut self, colors: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = Rgb>,
+    {
+        let len = encode_chain(colors, self.polarity, &mut self.codes).ok_or(Error::TooManyLeds)?;
+        // Only gone when a transmission panicked
+        let channel = self.channel.take().unwrap();
+        match channel.transmit(&self.codes[..len]).wait() {
+            Ok(channel) => {
+                self.channel = Some(channel);
+                Ok(())
+            }
+            Err((err, channel)) => {
+                self.channel = Some(channel);
+                Err(Error::Rmt(err))
+            }
+        }
+    }
+
+    /// Sets the first LED.
+    pub fn set(&mut self, color: Rgb) -> Result<(), Error> {
+        self.write_colors([color])
+    }
+}
+
+impl<C: TxChannel, const CODES: usize> SmartLedsWrite for Ws2812<C, CODES> {
+    type Error = Error;
+    type Color = RGB8;
+
+    fn write<T, I>(&mut self, iterator: T) -> Result<(), Error>
+    where
+        T: IntoIterator<Item = I>,
+        I: Into<RGB8>,
+    {
+        self.write_colors(iterator.into_iter().map(|color| {
+            let color = color.into();
+            Rgb::new(color.r, color.g, color.b)
+        }))
+    }
+}
@@ -0,0 +1,154 @@
+//! The WS2812 RGB LED on GPIO25.
+//!
+//! The LED takes 24 bits, green, red and blue, most significant bit first.
+//! Every bit is a high pulse followed by a low one, longer high for a one
+//! than for a zero, and a low line for [`LATCH_TICKS`] shows the colors.
+//! The RMT peripheral plays such pulses from a buffer of pulse codes, which
+//! [`encode`] and [`encode_chain`] fill for a clock of [`CLOCK_MHZ`].
+//!
+//! On the ESP-Buddy the data line goes through an inverting transistor, see
+//! [`BOARD_POLARITY`]: the RMT output idles high and every level is swapped.
+//!
+//! With the `esp32` feature, [`Ws2812`] drives the LED and implements
+//! `SmartLedsWrite`, so the `smart_leds` helpers work with it:
+//!
+//! ```ignore
+//! let rmt = Rmt::new(peripherals.RMT, led::CLOCK_MHZ.MHz()).unwrap();
+//! let mut led = Ws2812::new(rmt.channel0, buddy.led).unwrap();
+//! led.write(brightness(gamma([RGB8::new(255, 0, 0)].into_iter()), 16))
+//!     .unwrap();
+//! ```
+//!
+//! [`effects`] animates the LED from the time instead of delays.
+
+pub mod effects;
+#[cfg(feature = "esp32")]
+mod esp_hal;
+
+#[cfg(feature = "esp32")]
+pub use self::esp_hal::{Error, Ws2812};
+
+use core::fmt;
+
+/// RMT clock the pulse lengths are counted in, the APB clock undivided.
+pub const CLOCK_MHZ: u32 = 80;
+
+/// High part of a zero, 0.4 us.
+pub const T0H_TICKS: u16 = 32;
+/// Low part of a zero, 0.85 us.
+pub const T0L_TICKS: u16 = 68;
+/// High part of a one, 0.8 us.
+pub const T1H_TICKS: u16 = 64;
+/// Low part of a one, 0.45 us.
+pub const T1L_TICKS: u16 = 36;
+/// Low line showing the colors, 300 us. The WS2812B needs 280 us, older
+/// parts 50 us.
+pub const LATCH_TICKS: u16 = 24_000;
+
+/// Bits of one LED.
+pub const BITS_PER_LED: usize = 24;
+
+/// Pulse codes for `leds` LEDs, with the latch.
+pub const fn buffer_len(leds: usize) -> usize {
+    leds * BITS_PER_LED + 1
+}
+
+/// A color of the LED.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// Parses `#rrggbb`, the `#` is optional.
+    pub fn parse(color: &str) -> Option<Self> {
+        let hex = color.strip_prefix('#').unwrap_or(color);
+        if hex.len() != 6 || !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).ok();
+        Some(Self::new(channel(0)?, channel(2)?, channel(4)?))
+    }
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+/// Which level of the RMT output is high on the data line.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Polarity {
+    #[default]
+    Normal,
+    Inverted,
+}
+
+impl Polarity {
+    /// RMT output level for a `high` data line.
+    pub fn level(self, high: bool) -> bool {
+        match self {
+            Polarity::Normal => high,
+            Polarity::Inverted => !high,
+        }
+    }
+}
+
+/// The ESP-Buddy inverts the data line, as noted in `python/oled-example.py`.
+pub const BOARD_POLARITY: Polarity = Polarity::Inverted;
+
+/// One RMT pulse code: `level0` for `ticks0`, then `level1` for `ticks1`.
+/// Lengths are 15 bits, a length of 0 ends the transmission.
+pub fn pulse_code(level0: bool, ticks0: u16, level1: bool, ticks1: u16) -> u32 {
+    u32::from(level1) << 31
+        | u32::from(ticks1 & 0x7fff) << 16
+        | u32::from(level0) << 15
+        | u32::from(ticks0 & 0x7fff)
+}
+
+/// Pulse code of one bit.
+pub fn bit_code(bit: bool, polarity: Polarity) -> u32 {
+    let (high, low) = if bit {
+        (T1H_TICKS, T1L_TICKS)
+    } else {
+        (T0H_TICKS, T0L_TICKS)
+    };
+    pulse_code(polarity.level(true), high, polarity.level(false), low)
+}
+
+/// Pulse code holding the line low for [`LATCH_TICKS`], ending the
+/// transmission.
+pub fn latch_code(polarity: Polarity) -> u32 {
+    let low = polarity.level(false);
+    pulse_code(low, LATCH_TICKS, low, 0)
+}
+
+/// Pulse codes of one LED, green, red and blue, most significant bit first.
+pub fn encode(color: Rgb, polarity: Polarity) -> [u32; BITS_PER_LED] {
+    let bits = u32::from(color.g) << 16 | u32::from(color.r) << 8 | u32::from(color.b);
+    core::array::from_fn(|index| bit_code(bits & 1 << (BITS_PER_LED - 1 - index) != 0, polarity))
+}
+
+/// Fills `codes` with the LEDs of a chain followed by the latch. Returns the
+/// number of codes, `None` when they do not fit.
+pub fn encode_chain<I>(colors: I, polarity: Polarity, codes: &mut [u32]) -> Option<usize>
+where
+    I: IntoIterator<Item = Rgb>,
+{
+    let mut len = 0;
+    for color in colors {
+        codes
+            .get_mut(len..len + BITS_PER_LED)?
+            .copy_from_slice(&encode(color, polarity));
+        len += BITS_PER_LED;
+    }
+    *codes.get_mut(len)? = latch_code(polarity);
+    Some(len + 1)
+}
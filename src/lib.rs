@@ -0,0 +1,44 @@
+//! Board support for ESP-Buddy.
+//!
+//! Based on: https://github.com/espressif/esp-mdf/tree/master/examples/development_kit/buddy
+//!
+//! Applications start from a single call:
+//!
+//! ```ignore
+//! let peripherals = hal::init(hal::Config::default());
+//! let mut buddy = esp_buddy::Buddy::new(
+//!     peripherals.I2C0,
+//!     peripherals.GPIO,
+//!     peripherals.IO_MUX,
+//!     peripherals.RNG,
+//! );
+//! ```
+
+#![no_std]
+
+pub mod alarm;
+#[cfg(feature = "esp32")]
+mod buddy;
+pub mod framebuffer;
+pub mod gfx;
+pub mod history;
+pub mod http;
+pub mod input;
+pub mod launcher;
+pub mod led;
+pub mod mqtt;
+pub mod ntp;
+pub mod pins;
+pub mod portal;
+pub mod psychrometrics;
+pub mod remote;
+pub mod scanner;
+pub mod screens;
+pub mod sensors;
+pub mod settings;
+pub mod style;
+pub mod time;
+pub mod wifi;
+
+#[cfg(feature = "esp32")]
+pub use buddy::*;
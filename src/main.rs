@@ -1,48 +1,166 @@
 #![no_std]
 #![no_main]
 
-// Based on: https://github.com/espressif/esp-mdf/tree/master/examples/development_kit/buddy
+//! All examples in one firmware, picked from the launcher menu: left selects
+//! the next app, right opens it, holding either button goes back. Settings
+//! saved in the settings app apply right away. WiFi connects from boot and
+//! the clock is synced over NTP once connected, whichever app is open.
+
+use core::mem::MaybeUninit;
 
-use embedded_graphics::{
-    mono_font::{ascii::FONT_6X10, MonoTextStyleBuilder},
-    pixelcolor::BinaryColor,
-    prelude::*,
-    text::{Baseline, Text},
-};
 use esp_backtrace as _;
-use hal::{gpio, i2c, prelude::*};
-use ssd1306::{prelude::*, I2CDisplayInterface, Ssd1306};
+use esp_buddy::{
+    configure_display,
+    input::{Input, Interrupts, Timing},
+    launcher::{
+        apps::{Clock, Gpio, Settings, Temperature, Wifi},
+        Launcher, SceneApp,
+    },
+    ntp,
+    screens::{Animation, Car, Snow},
+    sensors::Hts221Reader,
+    settings::{self, schema::SCHEMA},
+    wifi::{EspWifi, State, WifiManager},
+    Buddy,
+};
+use esp_wifi::wifi::{utils::create_network_interface, WifiStaDevice};
+use esp_wifi::wifi_interface::WifiStack;
+use esp_wifi::{current_millis, EspWifiInitFor};
+use hal::{clock::CpuClock, peripherals::GPIO, prelude::*, timer::timg::TimerGroup};
+use smoltcp::{iface::SocketStorage, socket::udp::PacketMetadata};
+
+/// Time between two looks at the edges, deadlines and apps.
+const POLL_MS: u32 = 5;
+
+#[global_allocator]
+static ALLOCATOR: esp_alloc::EspHeap = esp_alloc::EspHeap::empty();
+
+fn init_heap() {
+    const HEAP_SIZE: usize = 5 * 1024;
+    static mut HEAP: MaybeUninit<[u8; HEAP_SIZE]> = MaybeUninit::uninit();
+
+    unsafe {
+        ALLOCATOR.init(HEAP.as_mut_ptr() as *mut u8, HEAP_SIZE - 1024);
+    }
+}
 
 #[entry]
 fn main() -> ! {
-    let peripherals = hal::init(hal::Config::default());
-
-    let io = gpio::Io::new(peripherals.GPIO, peripherals.IO_MUX);
+    init_heap();
 
-    let sda = io.pins.gpio18;
-    let scl = io.pins.gpio23;
+    let peripherals = hal::init({
+        let mut config = hal::Config::default();
+        config.cpu_clock = CpuClock::max();
+        config
+    });
 
-    let i2c = i2c::I2c::new(peripherals.I2C0, sda, scl, 100u32.kHz());
+    let mut buddy = Buddy::new(
+        peripherals.I2C0,
+        peripherals.GPIO,
+        peripherals.IO_MUX,
+        peripherals.RNG,
+    );
+    let display = &mut buddy.display;
+    let buttons = Interrupts::new(buddy.buttons);
 
-    let interface = I2CDisplayInterface::new(i2c);
-    let mut display = Ssd1306::new(interface, DisplaySize128x32, DisplayRotation::Rotate0)
-        .into_buffered_graphics_mode();
-    display.init().unwrap();
+    let mut store = settings::open().unwrap();
+    let config = store.config().unwrap_or_default();
+    let calibration = store.hts221_calibration().unwrap_or_default();
+    let hts221 = Hts221Reader::new().with_calibration(calibration);
 
-    let text_style = MonoTextStyleBuilder::new()
-        .font(&FONT_6X10)
-        .text_color(BinaryColor::On)
-        .build();
+    // WiFi connects from boot, the WiFi app only shows the state
+    let timer = TimerGroup::new(peripherals.TIMG1).timer0;
+    let init = esp_wifi::init(
+        EspWifiInitFor::Wifi,
+        timer,
+        buddy.rng,
+        peripherals.RADIO_CLK,
+    )
+    .unwrap();
+    let stored = store.networks().unwrap();
+    let networks = settings::known_networks(&stored, settings::BUILT_IN_NETWORK);
+    let mut socket_set_entries: [SocketStorage; 3] = Default::default();
+    let (iface, device, controller, sockets) = create_network_interface(
+        &init,
+        peripherals.WIFI,
+        WifiStaDevice,
+        &mut socket_set_entries,
+    )
+    .unwrap();
+    let wifi_stack = WifiStack::new(iface, device, sockets, current_millis);
+    let mut wifi = WifiManager::new(EspWifi::new(controller, &wifi_stack), &networks);
+    wifi.start();
+    let mut rx_meta = [PacketMetadata::EMPTY; 4];
+    let mut rx_buffer = [0u8; 1536];
+    let mut tx_meta = [PacketMetadata::EMPTY; 4];
+    let mut tx_buffer = [0u8; 1536];
+    let mut ntp_socket =
+        wifi_stack.get_udp_socket(&mut rx_meta, &mut rx_buffer, &mut tx_meta, &mut tx_buffer);
+    ntp_socket.bind(ntp::LOCAL_PORT).unwrap();
+    let mut ntp_client = ntp::Client::new(&ntp::DEFAULT_SERVERS);
 
-    Text::with_baseline("Hello world!", Point::zero(), text_style, Baseline::Top)
-        .draw(&mut display)
-        .unwrap();
+    // The board owns the pins, the GPIO app only looks at the input registers.
+    // Pins that are not set up as inputs read low.
+    let gpio = unsafe { GPIO::steal() };
+    let read_gpios = move || {
+        let levels =
+            u64::from(gpio.in_().read().bits()) | u64::from(gpio.in1().read().bits()) << 32;
+        !levels
+    };
 
-    Text::with_baseline("Hello Rust!", Point::new(0, 16), text_style, Baseline::Top)
-        .draw(&mut display)
-        .unwrap();
+    let mut launcher = Launcher::new((
+        SceneApp::new("Animation", Animation::new()),
+        SceneApp::new("Car", Car::new()),
+        SceneApp::new("Snow", Snow::new(buddy.rng)),
+        Clock::new().with_config(&config),
+        Gpio::new(read_gpios),
+        Temperature::new(buddy.i2c.acquire_i2c(), hts221).with_config(&config),
+        Wifi::new(wifi),
+        Settings::new(store, &SCHEMA),
+    ));
+    // No double clicks, so a click on the menu moves right away
+    let mut input = Input::new().with_timing(Timing {
+        double_click_ms: 0,
+        ..Timing::default()
+    });
 
-    display.flush().unwrap();
+    let mut redraw = true;
+    loop {
+        let now_ms = hal::time::now().duration_since_epoch().to_millis();
+        let due = input
+            .next_deadline_ms()
+            .is_some_and(|deadline_ms| now_ms >= deadline_ms);
+        if buttons.take_edge() || due {
+            input.update(buttons.state(), now_ms);
+        }
+        while let Some(event) = input.pop() {
+            redraw |= launcher.handle_event(event, now_ms);
+        }
+        // WiFi and the clock keep going while other apps are open
+        let (_, _, _, clock, _, temperature, wifi, settings) = launcher.apps_mut();
+        let wifi = wifi.manager_mut();
+        wifi.poll(now_ms);
+        let connected = matches!(wifi.state(), State::Connected { .. });
+        let now_us = now_ms * 1000;
+        if connected && clock.wall_clock().needs_sync(now_us) {
+            if let Ok(Some(sample)) = ntp_client.poll(&mut ntp_socket, now_us) {
+                clock.wall_clock_mut().sync(now_us, &sample);
+            }
+        }
+        if settings.take_changed() {
+            let config = settings.config();
+            configure_display(display, config.rotation, config.contrast_pct);
+            clock.set_config(&config);
+            temperature.set_config(&config);
+        }
+        redraw |= launcher.update(now_ms);
 
-    loop {}
+        if redraw {
+            redraw = false;
+            display.clear();
+            launcher.draw(display).unwrap();
+            display.flush().unwrap();
+        }
+        buddy.delay.delay_millis(POLL_MS);
+    }
 }
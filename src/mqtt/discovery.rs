@@ -0,0 +1,274 @@
+//! Home Assistant MQTT discovery.
+//!
+//! Publishing the retained [`Component::config`] of every component under
+//! its [`Component::config_topic`] makes the board show up as one device,
+//! with the sensors, the display text, the LED as a light and the buttons
+//! as device triggers. An empty retained payload removes a component again.
+//!
+//! Sensors found on the bus come on top, one entity per [`Field`] with
+//! [`field_config`] under [`field_config_topic`].
+
+use core::fmt::{self, Display, Write};
+
+use super::{Topics, MAX_TOPIC_LEN, OFF, OFFLINE, ON, ONLINE};
+use crate::{
+    http::api::MAX_TEXT_LEN,
+    sensors::{registry::Field, Quantity},
+};
+
+/// Default discovery prefix of Home Assistant.
+pub const PREFIX: &str = "homeassistant";
+
+/// Longest payload written by [`Component::config`].
+pub const MAX_CONFIG_LEN: usize = 1024;
+
+/// What Home Assistant shows about the board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Device<'a> {
+    pub name: &'a str,
+    pub model: &'a str,
+    pub manufacturer: &'a str,
+    pub sw_version: &'a str,
+}
+
+impl Default for Device<'_> {
+    fn default() -> Self {
+        Self {
+            name: "ESP-Buddy",
+            model: "ESP-Buddy",
+            manufacturer: "Espressif",
+            sw_version: env!("CARGO_PKG_VERSION"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Component {
+    Temperature,
+    Humidity,
+    Rssi,
+    Display,
+    Led,
+    LeftButton,
+    RightButton,
+}
+
+impl Component {
+    pub const ALL: [Self; 7] = [
+        Self::Temperature,
+        Self::Humidity,
+        Self::Rssi,
+        Self::Display,
+        Self::Led,
+        Self::LeftButton,
+        Self::RightButton,
+    ];
+
+    /// Home Assistant platform and the id of the component on the board.
+    fn platform_and_id(self) -> (&'static str, &'static str) {
+        match self {
+            Component::Temperature => ("sensor", Topics::TEMPERATURE),
+            Component::Humidity => ("sensor", Topics::HUMIDITY),
+            Component::Rssi => ("sensor", Topics::RSSI),
+            Component::Display => ("text", Topics::DISPLAY),
+            Component::Led => ("light", Topics::LED),
+            Component::LeftButton => ("device_automation", "left"),
+            Component::RightButton => ("device_automation", "right"),
+        }
+    }
+
+    /// `homeassistant/<platform>/<device id>/<component>/config`.
+    pub fn config_topic(self, topics: &Topics) -> heapless::String<{ MAX_TOPIC_LEN + 32 }> {
+        let (platform, id) = self.platform_and_id();
+        let mut topic = heapless::String::new();
+        write!(
+            topic,
+            "{}/{}/{}/{}/config",
+            PREFIX,
+            platform,
+            topics.device_id(),
+            id
+        )
+        .expect("device id too long for a topic");
+        topic
+    }
+
+    /// Writes the JSON configuration of the component.
+    pub fn config(self, topics: &Topics, device: &Device, out: &mut impl Write) -> fmt::Result {
+        let (_, id) = self.platform_and_id();
+        out.write_char('{')?;
+        match self {
+            Component::LeftButton | Component::RightButton => {
+                let subtype = match self {
+                    Component::LeftButton => "button_1",
+                    _ => "button_2",
+                };
+                write!(
+                    out,
+                    "\"automation_type\":\"trigger\",\"type\":\"button_short_press\",\
+                     \"subtype\":\"{}\",\"topic\":{},\"payload\":\"{}\",",
+                    subtype,
+                    Str(&topics.state(Topics::BUTTON)),
+                    id,
+                )?;
+            }
+            _ => {
+                let name = match self {
+                    Component::Temperature => "Temperature",
+                    Component::Humidity => "Humidity",
+                    Component::Rssi => "Signal strength",
+                    Component::Display => "Display",
+                    _ => "LED",
+                };
+                write!(
+                    out,
+                    "\"name\":\"{}\",\"unique_id\":\"{}_{}\",\"availability_topic\":{},",
+                    name,
+                    topics.device_id(),
+                    id,
+                    Str(&topics.availability()),
+                )?;
+                write!(
+                    out,
+                    "\"payload_available\":\"{}\",\"payload_not_available\":\"{}\",",
+                    ONLINE, OFFLINE
+                )?;
+                self.entity_config(topics, out)?;
+            }
+        }
+        write_device(topics, device, out)
+    }
+
+    fn entity_config(self, topics: &Topics, out: &mut impl Write) -> fmt::Result {
+        let (_, id) = self.platform_and_id();
+        match self {
+            Component::Temperature | Component::Humidity | Component::Rssi => {
+                let (device_class, unit) = match self {
+                    Component::Temperature => ("temperature", "°C"),
+                    Component::Humidity => ("humidity", "%"),
+                    _ => ("signal_strength", "dBm"),
+                };
+                write!(
+                    out,
+                    "\"state_topic\":{},\"device_class\":\"{}\",\
+                     \"unit_of_measurement\":\"{}\",\"state_class\":\"measurement\",",
+                    Str(&topics.state(id)),
+                    device_class,
+                    unit,
+                )?;
+                if self == Component::Rssi {
+                    out.write_str("\"entity_category\":\"diagnostic\",")?;
+                }
+                Ok(())
+            }
+            Component::Display => write!(
+                out,
+                "\"state_topic\":{},\"command_topic\":{},\"max\":{},",
+                Str(&topics.state(id)),
+                Str(&topics.set(id)),
+                MAX_TEXT_LEN,
+            ),
+            Component::Led => write!(
+                out,
+                "\"state_topic\":{},\"command_topic\":{},\"rgb_state_topic\":{},\
+                 \"rgb_command_topic\":{},\"payload_on\":\"{}\",\"payload_off\":\"{}\",",
+                Str(&topics.state(Topics::LED)),
+                Str(&topics.set(Topics::LED)),
+                Str(&topics.state(Topics::LED_RGB)),
+                Str(&topics.set(Topics::LED_RGB)),
+                ON,
+                OFF,
+            ),
+            Component::LeftButton | Component::RightButton => Ok(()),
+        }
+    }
+}
+
+/// `homeassistant/sensor/<device id>/<field key>/config`.
+pub fn field_config_topic(
+    topics: &Topics,
+    field: &Field,
+) -> heapless::String<{ MAX_TOPIC_LEN + 32 }> {
+    let mut topic = heapless::String::new();
+    write!(
+        topic,
+        "{}/sensor/{}/{}/config",
+        PREFIX,
+        topics.device_id(),
+        field.key
+    )
+    .expect("device id too long for a topic");
+    topic
+}
+
+/// Writes the JSON configuration of a sensor entity for `field`, with the
+/// state under [`Topics::field`].
+pub fn field_config(
+    topics: &Topics,
+    device: &Device,
+    field: &Field,
+    out: &mut impl Write,
+) -> fmt::Result {
+    let (device_class, unit) = match field.quantity {
+        Quantity::Temperature => ("temperature", "°C"),
+        Quantity::Humidity => ("humidity", "%"),
+        Quantity::Pressure => ("pressure", "hPa"),
+        Quantity::Illuminance => ("illuminance", "lx"),
+    };
+    write!(
+        out,
+        "{{\"name\":\"{} {:02x} {}\",\"unique_id\":\"{}_{}\",\"availability_topic\":{},",
+        field.sensor,
+        field.address,
+        field.quantity.name(),
+        topics.device_id(),
+        field.key,
+        Str(&topics.availability()),
+    )?;
+    write!(
+        out,
+        "\"payload_available\":\"{}\",\"payload_not_available\":\"{}\",",
+        ONLINE, OFFLINE
+    )?;
+    write!(
+        out,
+        "\"state_topic\":{},\"device_class\":\"{}\",\
+         \"unit_of_measurement\":\"{}\",\"state_class\":\"measurement\",",
+        Str(&topics.field(&field.key)),
+        device_class,
+        unit,
+    )?;
+    write_device(topics, device, out)
+}
+
+/// The device shared by all entities, closing the configuration.
+fn write_device(topics: &Topics, device: &Device, out: &mut impl Write) -> fmt::Result {
+    write!(
+        out,
+        "\"device\":{{\"identifiers\":[\"esp-buddy-{}\"],\"name\":{},\"model\":{},\
+         \"manufacturer\":{},\"sw_version\":{}}}}}",
+        topics.device_id(),
+        Str(device.name),
+        Str(device.model),
+        Str(device.manufacturer),
+        Str(device.sw_version),
+    )
+}
+
+/// JSON string with quotes and escapes.
+struct Str<'a>(&'a str);
+
+impl Display for Str<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_char('"')?;
+        for c in self.0.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+                c => f.write_char(c)?,
+            }
+        }
+        f.write_char('"')
+    }
+}
@@ -0,0 +1,24 @@
+use embedded_io::{Read, ReadReady, Write};
+use esp_wifi::{
+    wifi::WifiDeviceMode,
+    wifi_interface::{IoError, Socket},
+};
+
+use super::Transport;
+
+impl<MODE: WifiDeviceMode> Transport for Socket<'_, '_, MODE> {
+    type Error = IoError;
+
+    fn send(&mut self, data: &[u8]) -> Result<(), IoError> {
+        self.write_all(data)?;
+        self.flush()
+    }
+
+    fn receive(&mut self, data: &mut [u8]) -> Result<usize, IoError> {
+        self.work();
+        if !self.read_ready()? {
+            return Ok(0);
+        }
+        self.read(data)
+    }
+}
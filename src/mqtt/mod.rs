@@ -0,0 +1,569 @@
+//! MQTT 3.1.1 client publishing the readings of the board to Home Assistant.
+//!
+//! The packet codec is pure, the [`Client`] only needs a TCP [`Transport`]
+//! that is already connected to the broker and a monotonic clock in
+//! milliseconds. [`Topics`] names what the board publishes and listens to,
+//! [`discovery`] announces it to Home Assistant.
+//!
+//! ```ignore
+//! let mut socket = wifi_stack.get_socket(&mut rx_buffer, &mut tx_buffer);
+//! socket.open(broker, mqtt::PORT).unwrap();
+//!
+//! let topics = mqtt::Topics::new("a0b1c2d3e4f5");
+//! let availability = topics.availability();
+//! let mut client = mqtt::Client::new("esp-buddy-a0b1c2d3e4f5", &mut mqtt_rx, &mut mqtt_tx)
+//!     .with_will(&availability, mqtt::OFFLINE);
+//! client.connect(&mut socket, current_millis()).unwrap();
+//! loop {
+//!     match client.poll(&mut socket, current_millis()) {
+//!         Ok(Some(mqtt::Event::Connected { .. })) => {
+//!             client.subscribe(&mut socket, &topics.commands(), mqtt::QoS::AtMostOnce).unwrap();
+//!         }
+//!         Ok(Some(mqtt::Event::Message { topic, payload })) => match topics.command(topic, payload) {
+//!             Some(mqtt::Command::ShowText(text)) => println!("{}", text),
+//!             _ => {}
+//!         },
+//!         _ => {}
+//!     }
+//! }
+//! ```
+
+use core::fmt::Write;
+
+use crate::{led::Rgb, sensors::Tenths};
+
+pub mod discovery;
+#[cfg(feature = "esp32")]
+mod esp_wifi;
+mod packet;
+
+pub use packet::{
+    Connect, Packet, PacketError, Publish, QoS, Will, MAX_REMAINING_LEN, SUBSCRIPTION_FAILURE,
+};
+
+/// TCP port of brokers without TLS.
+pub const PORT: u16 = 1883;
+
+/// Prefix of the topics of every board.
+pub const TOPIC_PREFIX: &str = "esp-buddy";
+
+/// Longest topic built by [`Topics`].
+pub const MAX_TOPIC_LEN: usize = 64;
+
+/// Payloads of the availability topic.
+pub const ONLINE: &str = "online";
+pub const OFFLINE: &str = "offline";
+
+/// Payloads of the light state and command topics.
+pub const ON: &str = "ON";
+pub const OFF: &str = "OFF";
+
+pub type Topic = heapless::String<MAX_TOPIC_LEN>;
+
+/// Byte stream to the broker used by the [`Client`].
+pub trait Transport {
+    type Error;
+
+    fn send(&mut self, data: &[u8]) -> Result<(), Self::Error>;
+
+    /// Reads what arrived so far into `data`, returns 0 when nothing is
+    /// waiting.
+    fn receive(&mut self, data: &mut [u8]) -> Result<usize, Self::Error>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error<E> {
+    Transport(E),
+    /// The broker sent something that does not decode.
+    Packet(PacketError),
+    /// A packet longer than the receive buffer.
+    TooLarge,
+    /// CONNACK with a return code other than 0: 1 unacceptable protocol
+    /// version, 2 identifier rejected, 3 server unavailable, 4 bad user name
+    /// or password, 5 not authorized.
+    Refused(u8),
+    /// No CONNACK or PINGRESP within the timeout.
+    Timeout,
+    NotConnected,
+}
+
+/// What the broker sent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event<'a> {
+    Connected {
+        session_present: bool,
+    },
+    /// The subscription was granted the given QoS, `None` when refused.
+    Subscribed {
+        packet_id: u16,
+        qos: Option<QoS>,
+    },
+    /// A QoS 1 publication arrived at the broker.
+    Published {
+        packet_id: u16,
+    },
+    Message {
+        topic: &'a str,
+        payload: &'a [u8],
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    Disconnected,
+    Connecting { since_ms: u64 },
+    Connected,
+}
+
+/// What [`Client::poll`] needs of a packet, without borrowing the buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Received {
+    ConnAck { session_present: bool, code: u8 },
+    Publish { qos: QoS, packet_id: u16 },
+    PubAck { packet_id: u16 },
+    SubAck { packet_id: u16, code: u8 },
+    PingResp,
+    Unexpected,
+}
+
+impl From<Packet<'_>> for Received {
+    fn from(packet: Packet) -> Self {
+        match packet {
+            Packet::ConnAck {
+                session_present,
+                code,
+            } => Received::ConnAck {
+                session_present,
+                code,
+            },
+            Packet::Publish(publish) => Received::Publish {
+                qos: publish.qos,
+                packet_id: publish.packet_id,
+            },
+            Packet::PubAck { packet_id } => Received::PubAck { packet_id },
+            Packet::SubAck { packet_id, code } => Received::SubAck { packet_id, code },
+            Packet::PingResp => Received::PingResp,
+            _ => Received::Unexpected,
+        }
+    }
+}
+
+/// Non-blocking MQTT client.
+///
+/// Messages are published with QoS 0 or 1 but never retransmitted, which is
+/// what a clean session over a single TCP connection allows.
+pub struct Client<'a> {
+    options: Connect<'a>,
+    rx: &'a mut [u8],
+    rx_len: usize,
+    /// Length of the packet returned by the last poll, dropped by the next.
+    consumed: usize,
+    tx: &'a mut [u8],
+    state: State,
+    timeout_ms: u64,
+    last_ping_ms: u64,
+    ping_pending: bool,
+    next_packet_id: u16,
+}
+
+impl<'a> Client<'a> {
+    pub const DEFAULT_KEEP_ALIVE_S: u16 = 60;
+
+    /// Time to wait for a CONNACK or PINGRESP.
+    pub const DEFAULT_TIMEOUT_MS: u64 = 5000;
+
+    /// Creates a client with a clean session. Packets from the broker have to
+    /// fit into `rx`, packets to the broker into `tx`.
+    pub fn new(client_id: &'a str, rx: &'a mut [u8], tx: &'a mut [u8]) -> Self {
+        Self {
+            options: Connect {
+                client_id,
+                keep_alive_s: Self::DEFAULT_KEEP_ALIVE_S,
+                clean_session: true,
+                will: None,
+                username: None,
+                password: None,
+            },
+            rx,
+            rx_len: 0,
+            consumed: 0,
+            tx,
+            state: State::Disconnected,
+            timeout_ms: Self::DEFAULT_TIMEOUT_MS,
+            last_ping_ms: 0,
+            ping_pending: false,
+            next_packet_id: 1,
+        }
+    }
+
+    /// Seconds between pings, 0 turns them off.
+    pub fn with_keep_alive_s(mut self, keep_alive_s: u16) -> Self {
+        self.options.keep_alive_s = keep_alive_s;
+        self
+    }
+
+    pub fn with_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = timeout_ms;
+        self
+    }
+
+    pub fn with_credentials(mut self, username: &'a str, password: &'a str) -> Self {
+        self.options.username = Some(username);
+        self.options.password = Some(password.as_bytes());
+        self
+    }
+
+    /// Retained message the broker publishes when the connection is lost.
+    pub fn with_will(mut self, topic: &'a str, payload: &'a str) -> Self {
+        self.options.will = Some(Will {
+            topic,
+            payload: payload.as_bytes(),
+            retain: true,
+        });
+        self
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.state == State::Connected
+    }
+
+    /// Sends CONNECT, the answer is returned by [`Client::poll`].
+    pub fn connect<T: Transport>(
+        &mut self,
+        transport: &mut T,
+        now_ms: u64,
+    ) -> Result<(), Error<T::Error>> {
+        self.rx_len = 0;
+        self.consumed = 0;
+        self.ping_pending = false;
+        self.state = State::Disconnected;
+        self.send(transport, &Packet::Connect(self.options))?;
+        self.state = State::Connecting { since_ms: now_ms };
+        Ok(())
+    }
+
+    /// Publishes `payload`, returns the packet identifier of a QoS 1 message.
+    pub fn publish<T: Transport>(
+        &mut self,
+        transport: &mut T,
+        topic: &str,
+        payload: &[u8],
+        qos: QoS,
+        retain: bool,
+    ) -> Result<u16, Error<T::Error>> {
+        self.check_connected()?;
+        let packet_id = match qos {
+            QoS::AtMostOnce => 0,
+            QoS::AtLeastOnce => self.packet_id(),
+        };
+        let publish = Publish {
+            topic,
+            payload,
+            qos,
+            retain,
+            dup: false,
+            packet_id,
+        };
+        self.send(transport, &Packet::Publish(publish))?;
+        Ok(packet_id)
+    }
+
+    /// Subscribes to `filter`, returns the packet identifier of the
+    /// [`Event::Subscribed`] that follows.
+    pub fn subscribe<T: Transport>(
+        &mut self,
+        transport: &mut T,
+        filter: &str,
+        qos: QoS,
+    ) -> Result<u16, Error<T::Error>> {
+        self.check_connected()?;
+        let packet_id = self.packet_id();
+        let subscribe = Packet::Subscribe {
+            packet_id,
+            topic: filter,
+            qos,
+        };
+        self.send(transport, &subscribe)?;
+        Ok(packet_id)
+    }
+
+    /// Sends DISCONNECT, so the broker drops the will. The caller closes the
+    /// connection.
+    pub fn disconnect<T: Transport>(&mut self, transport: &mut T) -> Result<(), Error<T::Error>> {
+        self.check_connected()?;
+        self.state = State::Disconnected;
+        self.send(transport, &Packet::Disconnect)
+    }
+
+    /// Reads from the broker and returns the next event, `Ok(None)` when
+    /// nothing complete arrived. Pings the broker every keep-alive period.
+    ///
+    /// QoS 1 messages are acknowledged. Errors other than
+    /// [`Error::Transport`] leave the client disconnected; the caller should
+    /// close the connection and start over.
+    pub fn poll<T: Transport>(
+        &mut self,
+        transport: &mut T,
+        now_ms: u64,
+    ) -> Result<Option<Event<'_>>, Error<T::Error>> {
+        if self.state == State::Disconnected {
+            return Err(Error::NotConnected);
+        }
+        self.rx.copy_within(self.consumed..self.rx_len, 0);
+        self.rx_len -= self.consumed;
+        self.consumed = 0;
+
+        while self.rx_len < self.rx.len() {
+            let read = transport
+                .receive(&mut self.rx[self.rx_len..])
+                .map_err(Error::Transport)?;
+            if read == 0 {
+                break;
+            }
+            self.rx_len += read;
+        }
+
+        let (received, len) = match Packet::decode(&self.rx[..self.rx_len]) {
+            Ok(Some((packet, len))) => (Received::from(packet), len),
+            Ok(None) if self.rx_len == self.rx.len() => return Err(self.fail(Error::TooLarge)),
+            Ok(None) => return self.keep_alive(transport, now_ms).map(|()| None),
+            Err(err) => return Err(self.fail(Error::Packet(err))),
+        };
+        self.consumed = len;
+
+        match (self.state, received) {
+            (
+                State::Connecting { .. },
+                Received::ConnAck {
+                    session_present,
+                    code: 0,
+                },
+            ) => {
+                self.state = State::Connected;
+                self.last_ping_ms = now_ms;
+                Ok(Some(Event::Connected { session_present }))
+            }
+            (State::Connecting { .. }, Received::ConnAck { code, .. }) => {
+                Err(self.fail(Error::Refused(code)))
+            }
+            (State::Connected, Received::Publish { qos, packet_id }) => {
+                if qos == QoS::AtLeastOnce {
+                    self.send(transport, &Packet::PubAck { packet_id })?;
+                }
+                // Decoded again to hand out a borrow of the buffer
+                match Packet::decode(&self.rx[..len]) {
+                    Ok(Some((Packet::Publish(publish), _))) => Ok(Some(Event::Message {
+                        topic: publish.topic,
+                        payload: publish.payload,
+                    })),
+                    _ => unreachable!(),
+                }
+            }
+            (State::Connected, Received::SubAck { packet_id, code }) => {
+                Ok(Some(Event::Subscribed {
+                    packet_id,
+                    qos: match code {
+                        0 => Some(QoS::AtMostOnce),
+                        1 => Some(QoS::AtLeastOnce),
+                        _ => None,
+                    },
+                }))
+            }
+            (State::Connected, Received::PubAck { packet_id }) => {
+                Ok(Some(Event::Published { packet_id }))
+            }
+            (State::Connected, Received::PingResp) => {
+                self.ping_pending = false;
+                Ok(None)
+            }
+            // Packets only clients send, or sent before CONNACK
+            _ => Err(self.fail(Error::Packet(PacketError::Malformed))),
+        }
+    }
+
+    fn keep_alive<T: Transport>(
+        &mut self,
+        transport: &mut T,
+        now_ms: u64,
+    ) -> Result<(), Error<T::Error>> {
+        match self.state {
+            State::Connecting { since_ms }
+                if now_ms.saturating_sub(since_ms) >= self.timeout_ms =>
+            {
+                Err(self.fail(Error::Timeout))
+            }
+            State::Connected if self.ping_pending => {
+                if now_ms.saturating_sub(self.last_ping_ms) >= self.timeout_ms {
+                    return Err(self.fail(Error::Timeout));
+                }
+                Ok(())
+            }
+            State::Connected if self.options.keep_alive_s > 0 => {
+                let keep_alive_ms = u64::from(self.options.keep_alive_s) * 1000;
+                if now_ms.saturating_sub(self.last_ping_ms) >= keep_alive_ms {
+                    self.send(transport, &Packet::PingReq)?;
+                    self.last_ping_ms = now_ms;
+                    self.ping_pending = true;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn send<T: Transport>(
+        &mut self,
+        transport: &mut T,
+        packet: &Packet,
+    ) -> Result<(), Error<T::Error>> {
+        let len = packet.encode(self.tx).map_err(|err| match err {
+            PacketError::BufferTooSmall => Error::TooLarge,
+            err => Error::Packet(err),
+        })?;
+        transport.send(&self.tx[..len]).map_err(Error::Transport)
+    }
+
+    fn check_connected<E>(&self) -> Result<(), Error<E>> {
+        match self.state {
+            State::Connected => Ok(()),
+            _ => Err(Error::NotConnected),
+        }
+    }
+
+    fn packet_id(&mut self) -> u16 {
+        let packet_id = self.next_packet_id;
+        // 0 is not a valid identifier
+        self.next_packet_id = self.next_packet_id.checked_add(1).unwrap_or(1);
+        packet_id
+    }
+
+    fn fail<E>(&mut self, err: Error<E>) -> Error<E> {
+        self.state = State::Disconnected;
+        err
+    }
+}
+
+/// What a command topic asks the board to do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command<'a> {
+    ShowText(&'a str),
+    /// Light on or off, keeping its color.
+    Led(bool),
+    LedColor(Rgb),
+}
+
+/// Topics of one board, `esp-buddy/<device id>/...`:
+///
+/// - `status`: [`ONLINE`] or [`OFFLINE`], retained
+/// - `temperature`, `humidity`: degrees Celsius and percent, one decimal
+/// - `rssi`: signal strength in dBm
+/// - `sensors/<key>`: values of the sensors found on the bus, one decimal
+/// - `button`: `left` or `right` for every press
+/// - `display`, `led`, `led/rgb`: text shown, [`ON`] or [`OFF`] and `r,g,b`
+/// - `set/display`, `set/led`, `set/led/rgb`: commands, same payloads
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Topics<'a> {
+    device_id: &'a str,
+}
+
+impl<'a> Topics<'a> {
+    pub const TEMPERATURE: &'static str = "temperature";
+    pub const HUMIDITY: &'static str = "humidity";
+    pub const RSSI: &'static str = "rssi";
+    pub const BUTTON: &'static str = "button";
+    pub const DISPLAY: &'static str = "display";
+    pub const LED: &'static str = "led";
+    pub const LED_RGB: &'static str = "led/rgb";
+
+    /// Topics for `device_id`, which should be short and only contain
+    /// characters valid in topics and JSON strings, e.g. the MAC address in
+    /// hex.
+    pub fn new(device_id: &'a str) -> Self {
+        Self { device_id }
+    }
+
+    pub fn device_id(&self) -> &'a str {
+        self.device_id
+    }
+
+    /// State topic, `name` is one of the constants.
+    pub fn state(&self, name: &str) -> Topic {
+        self.topic(format_args!("{}", name))
+    }
+
+    /// State topic of a sensor found on the bus, see
+    /// [`Registry::fields`](crate::sensors::Registry::fields).
+    pub fn field(&self, key: &str) -> Topic {
+        self.topic(format_args!("sensors/{}", key))
+    }
+
+    /// Command topic of a state topic.
+    pub fn set(&self, name: &str) -> Topic {
+        self.topic(format_args!("set/{}", name))
+    }
+
+    pub fn availability(&self) -> Topic {
+        self.topic(format_args!("status"))
+    }
+
+    /// Filter matching all command topics.
+    pub fn commands(&self) -> Topic {
+        self.topic(format_args!("set/#"))
+    }
+
+    /// Parses a message on a command topic, `None` for other topics and
+    /// invalid payloads.
+    pub fn command<'p>(&self, topic: &str, payload: &'p [u8]) -> Option<Command<'p>> {
+        let name = topic
+            .strip_prefix(TOPIC_PREFIX)?
+            .strip_prefix('/')?
+            .strip_prefix(self.device_id)?
+            .strip_prefix("/set/")?;
+        let payload = core::str::from_utf8(payload).ok()?;
+        match name {
+            Self::DISPLAY if payload.len() <= crate::http::api::MAX_TEXT_LEN => {
+                Some(Command::ShowText(payload))
+            }
+            Self::LED => match payload.trim() {
+                ON => Some(Command::Led(true)),
+                OFF => Some(Command::Led(false)),
+                _ => None,
+            },
+            Self::LED_RGB => parse_rgb(payload.trim()).map(Command::LedColor),
+            _ => None,
+        }
+    }
+
+    fn topic(&self, name: core::fmt::Arguments) -> Topic {
+        let mut topic = Topic::new();
+        write!(topic, "{}/{}/{}", TOPIC_PREFIX, self.device_id, name)
+            .expect("device id too long for a topic");
+        topic
+    }
+}
+
+/// `r,g,b` as Home Assistant sends it, or `#rrggbb`.
+fn parse_rgb(color: &str) -> Option<Rgb> {
+    if color.starts_with('#') {
+        return Rgb::parse(color);
+    }
+    let mut channels = color
+        .split(',')
+        .map(|channel| channel.trim().parse::<u8>().ok());
+    let rgb = Rgb::new(channels.next()??, channels.next()??, channels.next()??);
+    channels.next().is_none().then_some(rgb)
+}
+
+/// Payload with one decimal, for tenths of a degree or percent.
+pub fn format_tenths(tenths: i32) -> heapless::String<12> {
+    let mut payload = heapless::String::new();
+    write!(payload, "{}", Tenths(tenths)).unwrap();
+    payload
+}
+
+/// Payload of the `led/rgb` state topic.
+pub fn format_rgb(color: Rgb) -> heapless::String<12> {
+    let mut payload = heapless::String::new();
+    write!(payload, "{},{},{}", color.r, color.g, color.b).unwrap();
+    payload
+}
@@ -0,0 +1,414 @@
+//! MQTT 3.1.1 control packets (OASIS standard, section 3).
+//!
+//! Only what a small client needs: QoS 0 and 1, one topic filter per
+//! subscription. Every packet is encoded with [`Packet::encode`] and decoded
+//! with [`Packet::decode`], both directions for the tests.
+
+const PROTOCOL_NAME: &[u8] = b"\x00\x04MQTT";
+const PROTOCOL_LEVEL: u8 = 4;
+
+/// Largest value of the remaining length, four bytes of 7 bits.
+pub const MAX_REMAINING_LEN: usize = 268_435_455;
+
+const CONNECT: u8 = 1;
+const CONNACK: u8 = 2;
+const PUBLISH: u8 = 3;
+const PUBACK: u8 = 4;
+const SUBSCRIBE: u8 = 8;
+const SUBACK: u8 = 9;
+const PINGREQ: u8 = 12;
+const PINGRESP: u8 = 13;
+const DISCONNECT: u8 = 14;
+
+const FLAG_USERNAME: u8 = 0x80;
+const FLAG_PASSWORD: u8 = 0x40;
+const FLAG_WILL_RETAIN: u8 = 0x20;
+const FLAG_WILL: u8 = 0x04;
+const FLAG_CLEAN_SESSION: u8 = 0x02;
+
+/// Return code of a SUBACK for a refused subscription.
+pub const SUBSCRIPTION_FAILURE: u8 = 0x80;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PacketError {
+    BufferTooSmall,
+    /// Fixed header flags, lengths or fields that do not add up.
+    Malformed,
+    /// Topic or client id that is not UTF-8.
+    InvalidUtf8,
+    /// QoS 2, or a packet type this client does not handle.
+    Unsupported(u8),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum QoS {
+    AtMostOnce = 0,
+    AtLeastOnce = 1,
+}
+
+impl QoS {
+    fn from_bits(bits: u8) -> Result<Self, PacketError> {
+        match bits {
+            0 => Ok(QoS::AtMostOnce),
+            1 => Ok(QoS::AtLeastOnce),
+            bits => Err(PacketError::Unsupported(bits)),
+        }
+    }
+}
+
+/// Message the broker publishes when the client goes away.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Will<'a> {
+    pub topic: &'a str,
+    pub payload: &'a [u8],
+    pub retain: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Connect<'a> {
+    pub client_id: &'a str,
+    pub keep_alive_s: u16,
+    pub clean_session: bool,
+    pub will: Option<Will<'a>>,
+    pub username: Option<&'a str>,
+    pub password: Option<&'a [u8]>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Publish<'a> {
+    pub topic: &'a str,
+    pub payload: &'a [u8],
+    pub qos: QoS,
+    pub retain: bool,
+    pub dup: bool,
+    /// Identifier of a QoS 1 message, ignored for QoS 0.
+    pub packet_id: u16,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Packet<'a> {
+    Connect(Connect<'a>),
+    ConnAck {
+        session_present: bool,
+        /// 0 when accepted, see [`super::Error::Refused`] for the others.
+        code: u8,
+    },
+    Publish(Publish<'a>),
+    PubAck {
+        packet_id: u16,
+    },
+    Subscribe {
+        packet_id: u16,
+        topic: &'a str,
+        qos: QoS,
+    },
+    SubAck {
+        packet_id: u16,
+        /// Granted QoS, or [`SUBSCRIPTION_FAILURE`].
+        code: u8,
+    },
+    PingReq,
+    PingResp,
+    Disconnect,
+}
+
+impl<'a> Packet<'a> {
+    /// Decodes the first packet of `data` and returns it with its length,
+    /// `Ok(None)` until it is complete.
+    pub fn decode(data: &'a [u8]) -> Result<Option<(Self, usize)>, PacketError> {
+        let Some(&first) = data.first() else {
+            return Ok(None);
+        };
+        let Some((remaining_len, len_bytes)) = decode_remaining_len(&data[1..])? else {
+            return Ok(None);
+        };
+        let start = 1 + len_bytes;
+        let Some(body) = data.get(start..start + remaining_len) else {
+            return Ok(None);
+        };
+
+        let (kind, flags) = (first >> 4, first & 0x0f);
+        // Flags are fixed for everything but PUBLISH
+        let expected_flags = match kind {
+            SUBSCRIBE => Some(0b0010),
+            PUBLISH => None,
+            _ => Some(0),
+        };
+        if expected_flags.is_some_and(|expected| flags != expected) {
+            return Err(PacketError::Malformed);
+        }
+
+        let mut reader = Reader { data: body };
+        let packet = match kind {
+            CONNECT => Packet::Connect(decode_connect(&mut reader)?),
+            CONNACK => {
+                let [flags, code] = reader.array()?;
+                Packet::ConnAck {
+                    session_present: flags & 1 != 0,
+                    code,
+                }
+            }
+            PUBLISH => {
+                let qos = QoS::from_bits((flags >> 1) & 0b11)?;
+                let topic = reader.str()?;
+                let packet_id = match qos {
+                    QoS::AtMostOnce => 0,
+                    QoS::AtLeastOnce => reader.u16()?,
+                };
+                Packet::Publish(Publish {
+                    topic,
+                    payload: reader.rest(),
+                    qos,
+                    retain: flags & 1 != 0,
+                    dup: flags & 0b1000 != 0,
+                    packet_id,
+                })
+            }
+            PUBACK => Packet::PubAck {
+                packet_id: reader.u16()?,
+            },
+            SUBSCRIBE => {
+                let packet_id = reader.u16()?;
+                let topic = reader.str()?;
+                let [qos] = reader.array()?;
+                Packet::Subscribe {
+                    packet_id,
+                    topic,
+                    qos: QoS::from_bits(qos)?,
+                }
+            }
+            SUBACK => {
+                let packet_id = reader.u16()?;
+                let [code] = reader.array()?;
+                Packet::SubAck { packet_id, code }
+            }
+            PINGREQ => Packet::PingReq,
+            PINGRESP => Packet::PingResp,
+            DISCONNECT => Packet::Disconnect,
+            kind => return Err(PacketError::Unsupported(kind)),
+        };
+        if !reader.data.is_empty() && !matches!(packet, Packet::Publish(_)) {
+            // Subscriptions to several topics end up here, too
+            return Err(PacketError::Malformed);
+        }
+        Ok(Some((packet, start + remaining_len)))
+    }
+
+    /// Writes the packet to `buf` and returns its length.
+    pub fn encode(&self, buf: &mut [u8]) -> Result<usize, PacketError> {
+        let (first, remaining_len) = match self {
+            Packet::Connect(connect) => {
+                let mut len = PROTOCOL_NAME.len() + 4 + 2 + connect.client_id.len();
+                if let Some(will) = &connect.will {
+                    len += 2 + will.topic.len() + 2 + will.payload.len();
+                }
+                len += connect.username.map_or(0, |username| 2 + username.len());
+                len += connect.password.map_or(0, |password| 2 + password.len());
+                (CONNECT << 4, len)
+            }
+            Packet::ConnAck { .. } => (CONNACK << 4, 2),
+            Packet::Publish(publish) => {
+                let flags =
+                    (publish.dup as u8) << 3 | (publish.qos as u8) << 1 | publish.retain as u8;
+                let packet_id_len = if publish.qos == QoS::AtMostOnce { 0 } else { 2 };
+                let len = 2 + publish.topic.len() + packet_id_len + publish.payload.len();
+                (PUBLISH << 4 | flags, len)
+            }
+            Packet::PubAck { .. } => (PUBACK << 4, 2),
+            Packet::Subscribe { topic, .. } => (SUBSCRIBE << 4 | 0b0010, 2 + 2 + topic.len() + 1),
+            Packet::SubAck { .. } => (SUBACK << 4, 3),
+            Packet::PingReq => (PINGREQ << 4, 0),
+            Packet::PingResp => (PINGRESP << 4, 0),
+            Packet::Disconnect => (DISCONNECT << 4, 0),
+        };
+        if remaining_len > MAX_REMAINING_LEN {
+            return Err(PacketError::Malformed);
+        }
+
+        let mut writer = Writer { buf, len: 0 };
+        writer.bytes(&[first])?;
+        let mut len = remaining_len;
+        loop {
+            let byte = (len % 128) as u8;
+            len /= 128;
+            writer.bytes(&[if len > 0 { byte | 0x80 } else { byte }])?;
+            if len == 0 {
+                break;
+            }
+        }
+
+        match self {
+            Packet::Connect(connect) => {
+                let mut flags = 0;
+                if connect.clean_session {
+                    flags |= FLAG_CLEAN_SESSION;
+                }
+                if let Some(will) = &connect.will {
+                    flags |= FLAG_WILL;
+                    if will.retain {
+                        flags |= FLAG_WILL_RETAIN;
+                    }
+                }
+                if connect.username.is_some() {
+                    flags |= FLAG_USERNAME;
+                }
+                if connect.password.is_some() {
+                    flags |= FLAG_PASSWORD;
+                }
+                writer.bytes(PROTOCOL_NAME)?;
+                writer.bytes(&[PROTOCOL_LEVEL, flags])?;
+                writer.u16(connect.keep_alive_s)?;
+                writer.prefixed(connect.client_id.as_bytes())?;
+                if let Some(will) = &connect.will {
+                    writer.prefixed(will.topic.as_bytes())?;
+                    writer.prefixed(will.payload)?;
+                }
+                if let Some(username) = connect.username {
+                    writer.prefixed(username.as_bytes())?;
+                }
+                if let Some(password) = connect.password {
+                    writer.prefixed(password)?;
+                }
+            }
+            Packet::ConnAck {
+                session_present,
+                code,
+            } => writer.bytes(&[*session_present as u8, *code])?,
+            Packet::Publish(publish) => {
+                writer.prefixed(publish.topic.as_bytes())?;
+                if publish.qos != QoS::AtMostOnce {
+                    writer.u16(publish.packet_id)?;
+                }
+                writer.bytes(publish.payload)?;
+            }
+            Packet::PubAck { packet_id } => writer.u16(*packet_id)?,
+            Packet::Subscribe {
+                packet_id,
+                topic,
+                qos,
+            } => {
+                writer.u16(*packet_id)?;
+                writer.prefixed(topic.as_bytes())?;
+                writer.bytes(&[*qos as u8])?;
+            }
+            Packet::SubAck { packet_id, code } => {
+                writer.u16(*packet_id)?;
+                writer.bytes(&[*code])?;
+            }
+            Packet::PingReq | Packet::PingResp | Packet::Disconnect => {}
+        }
+        Ok(writer.len)
+    }
+}
+
+fn decode_connect<'a>(reader: &mut Reader<'a>) -> Result<Connect<'a>, PacketError> {
+    let [name @ .., level, flags] = reader.array::<{ PROTOCOL_NAME.len() + 2 }>()?;
+    if name != PROTOCOL_NAME || level != PROTOCOL_LEVEL || flags & 1 != 0 {
+        return Err(PacketError::Malformed);
+    }
+    let keep_alive_s = reader.u16()?;
+    let client_id = reader.str()?;
+    let will = if flags & FLAG_WILL != 0 {
+        Some(Will {
+            topic: reader.str()?,
+            payload: reader.prefixed()?,
+            retain: flags & FLAG_WILL_RETAIN != 0,
+        })
+    } else {
+        None
+    };
+    let username = match flags & FLAG_USERNAME {
+        0 => None,
+        _ => Some(reader.str()?),
+    };
+    let password = match flags & FLAG_PASSWORD {
+        0 => None,
+        _ => Some(reader.prefixed()?),
+    };
+    Ok(Connect {
+        client_id,
+        keep_alive_s,
+        clean_session: flags & FLAG_CLEAN_SESSION != 0,
+        will,
+        username,
+        password,
+    })
+}
+
+/// Remaining length and the number of bytes it took, `None` until complete.
+fn decode_remaining_len(data: &[u8]) -> Result<Option<(usize, usize)>, PacketError> {
+    let mut len = 0;
+    for (index, &byte) in data.iter().enumerate().take(4) {
+        len |= ((byte & 0x7f) as usize) << (7 * index);
+        if byte & 0x80 == 0 {
+            return Ok(Some((len, index + 1)));
+        }
+    }
+    if data.len() >= 4 {
+        return Err(PacketError::Malformed);
+    }
+    Ok(None)
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], PacketError> {
+        if self.data.len() < len {
+            return Err(PacketError::Malformed);
+        }
+        let (taken, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], PacketError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn u16(&mut self) -> Result<u16, PacketError> {
+        Ok(u16::from_be_bytes(self.array()?))
+    }
+
+    fn prefixed(&mut self) -> Result<&'a [u8], PacketError> {
+        let len = self.u16()?;
+        self.take(len.into())
+    }
+
+    fn str(&mut self) -> Result<&'a str, PacketError> {
+        core::str::from_utf8(self.prefixed()?).map_err(|_| PacketError::InvalidUtf8)
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        core::mem::take(&mut self.data)
+    }
+}
+
+struct Writer<'b> {
+    buf: &'b mut [u8],
+    len: usize,
+}
+
+impl Writer<'_> {
+    fn bytes(&mut self, bytes: &[u8]) -> Result<(), PacketError> {
+        self.buf
+            .get_mut(self.len..self.len + bytes.len())
+            .ok_or(PacketError::BufferTooSmall)?
+            .copy_from_slice(bytes);
+        self.len += bytes.len();
+        Ok(())
+    }
+
+    fn u16(&mut self, value: u16) -> Result<(), PacketError> {
+        self.bytes(&value.to_be_bytes())
+    }
+
+    /// Bytes with their length in front, as strings are sent.
+    fn prefixed(&mut self, bytes: &[u8]) -> Result<(), PacketError> {
+        let len = u16::try_from(bytes.len()).map_err(|_| PacketError::Malformed)?;
+        self.u16(len)?;
+        self.bytes(bytes)
+    }
+}
@@ -0,0 +1,24 @@
+use esp_wifi::{
+    wifi::WifiDeviceMode,
+    wifi_interface::{IoError, UdpSocket},
+};
+use smoltcp::{socket::udp::RecvError, wire::IpAddress};
+
+use super::Transport;
+
+impl<MODE: WifiDeviceMode> Transport for UdpSocket<'_, '_, MODE> {
+    type Error = IoError;
+
+    fn send(&mut self, server: IpAddress, port: u16, data: &[u8]) -> Result<(), IoError> {
+        UdpSocket::send(self, server, port, data)
+    }
+
+    fn receive(&mut self, data: &mut [u8]) -> Result<Option<(usize, IpAddress)>, IoError> {
+        self.work();
+        match UdpSocket::receive(self, data) {
+            Ok((len, address, _port)) => Ok(Some((len, address))),
+            Err(IoError::UdpRecvError(RecvError::Exhausted)) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
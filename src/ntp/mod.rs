@@ -0,0 +1,255 @@
+//! SNTP client.
+//!
+//! The packet encoding and the clock math are pure functions, the [`Client`]
+//! only needs a UDP [`Transport`] and a local monotonic clock in microseconds.
+//!
+//! ```ignore
+//! let mut socket = wifi_stack.get_udp_socket(&mut rx_meta, &mut rx_buffer, &mut tx_meta, &mut tx_buffer);
+//! socket.bind(ntp::LOCAL_PORT).unwrap();
+//!
+//! let mut client = ntp::Client::new(&ntp::DEFAULT_SERVERS);
+//! let sample = loop {
+//!     if let Ok(Some(sample)) = client.poll(&mut socket, current_millis() * 1000) {
+//!         break sample;
+//!     }
+//! };
+//! let unix_micros = sample.unix_micros(current_millis() * 1000);
+//! ```
+
+use smoltcp::wire::{IpAddress, Ipv4Address};
+
+#[cfg(feature = "esp32")]
+mod esp_wifi;
+mod packet;
+
+pub use packet::{decode, decode_response, encode_request, Packet, PacketError, PACKET_SIZE};
+
+/// Seconds between the NTP epoch (1900) and the Unix epoch (1970).
+pub const NTP_TIMESTAMP_DELTA: u64 = 2_208_988_800;
+
+/// UDP port of NTP servers.
+pub const NTP_PORT: u16 = 123;
+
+/// UDP port the client socket should be bound to.
+pub const LOCAL_PORT: u16 = 50123;
+
+/// Anycast servers of time.cloudflare.com and time.google.com.
+pub const DEFAULT_SERVERS: [IpAddress; 4] = [
+    IpAddress::Ipv4(Ipv4Address::new(162, 159, 200, 1)),
+    IpAddress::Ipv4(Ipv4Address::new(216, 239, 35, 0)),
+    IpAddress::Ipv4(Ipv4Address::new(162, 159, 200, 123)),
+    IpAddress::Ipv4(Ipv4Address::new(216, 239, 35, 4)),
+];
+
+const MICROS_PER_SECOND: i64 = 1_000_000;
+
+/// 64-bit NTP timestamp: seconds since 1900 and a binary fraction of a second.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Timestamp {
+    pub seconds: u32,
+    pub fraction: u32,
+}
+
+impl Timestamp {
+    pub const ZERO: Self = Self::new(0, 0);
+
+    pub const fn new(seconds: u32, fraction: u32) -> Self {
+        Self { seconds, fraction }
+    }
+
+    /// Converts microseconds since the Unix epoch.
+    pub fn from_unix_micros(micros: i64) -> Self {
+        let seconds = micros.div_euclid(MICROS_PER_SECOND) + NTP_TIMESTAMP_DELTA as i64;
+        let micros = micros.rem_euclid(MICROS_PER_SECOND) as u64;
+        Self {
+            // Truncation wraps into the next era in 2036, as on the wire
+            seconds: seconds as u32,
+            fraction: ((micros << 32) / MICROS_PER_SECOND as u64) as u32,
+        }
+    }
+
+    /// Converts to microseconds since the Unix epoch.
+    ///
+    /// Timestamps with the most significant bit clear are taken from the era
+    /// starting in 2036 (RFC 4330, section 3), which covers 1968 to 2104.
+    pub fn to_unix_micros(self) -> i64 {
+        let mut seconds = self.seconds as i64;
+        if self.seconds & 0x8000_0000 == 0 {
+            seconds += 1 << 32;
+        }
+        let micros = (self.fraction as i64 * MICROS_PER_SECOND) >> 32;
+        (seconds - NTP_TIMESTAMP_DELTA as i64) * MICROS_PER_SECOND + micros
+    }
+
+    pub fn to_be_bytes(self) -> [u8; 8] {
+        let mut bytes = [0u8; 8];
+        bytes[..4].copy_from_slice(&self.seconds.to_be_bytes());
+        bytes[4..].copy_from_slice(&self.fraction.to_be_bytes());
+        bytes
+    }
+}
+
+/// Clock offset and round-trip delay in microseconds from the four
+/// timestamps of an exchange:
+///
+/// - `t1` request sent, local clock
+/// - `t2` request received, server clock
+/// - `t3` reply sent, server clock
+/// - `t4` reply received, local clock
+///
+/// The offset is what has to be added to the local clock to get server time.
+pub fn offset_and_delay(t1: i64, t2: i64, t3: i64, t4: i64) -> (i64, i64) {
+    let offset = ((t2 - t1) + (t3 - t4)) / 2;
+    let delay = (t4 - t1) - (t3 - t2);
+    (offset, delay)
+}
+
+/// Result of one successful exchange with a server.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sample {
+    pub server: IpAddress,
+    pub stratum: u8,
+    /// Server time minus local time, in microseconds.
+    pub offset_us: i64,
+    /// Round-trip delay, in microseconds.
+    pub delay_us: i64,
+}
+
+impl Sample {
+    /// Microseconds since the Unix epoch at the given local time.
+    pub fn unix_micros(&self, local_us: u64) -> i64 {
+        local_us as i64 + self.offset_us
+    }
+}
+
+/// Datagram socket used by the [`Client`].
+pub trait Transport {
+    type Error;
+
+    fn send(&mut self, server: IpAddress, port: u16, data: &[u8]) -> Result<(), Self::Error>;
+
+    /// Returns the length and sender of a received datagram, `None` when
+    /// nothing is waiting.
+    fn receive(&mut self, data: &mut [u8]) -> Result<Option<(usize, IpAddress)>, Self::Error>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error<E> {
+    Transport(E),
+    /// No valid reply within the timeout.
+    Timeout(IpAddress),
+    /// The server replied with an unusable packet.
+    Packet(IpAddress, PacketError),
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Pending {
+    server: IpAddress,
+    originate: Timestamp,
+    sent_at_us: u64,
+}
+
+/// Non-blocking SNTP client rotating through a list of servers.
+pub struct Client<'a> {
+    servers: &'a [IpAddress],
+    next_server: usize,
+    timeout_us: u64,
+    pending: Option<Pending>,
+}
+
+impl<'a> Client<'a> {
+    /// Time to wait for a reply before trying the next server.
+    pub const DEFAULT_TIMEOUT_MS: u64 = 2000;
+
+    /// Creates a client for the given servers, which must not be empty.
+    pub fn new(servers: &'a [IpAddress]) -> Self {
+        assert!(!servers.is_empty(), "no NTP server configured");
+        Self {
+            servers,
+            next_server: 0,
+            timeout_us: Self::DEFAULT_TIMEOUT_MS * 1000,
+            pending: None,
+        }
+    }
+
+    pub fn with_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.timeout_us = timeout_ms * 1000;
+        self
+    }
+
+    /// Server of the request in flight, or of the next request.
+    pub fn server(&self) -> IpAddress {
+        match self.pending {
+            Some(pending) => pending.server,
+            None => self.servers[self.next_server],
+        }
+    }
+
+    /// Drives the exchange, `now_us` is the local monotonic clock.
+    ///
+    /// Sends a request when none is in flight and returns `Ok(None)` until
+    /// the reply arrives. A timeout or a rejected reply is returned as an
+    /// error once and the next call starts over with the next server.
+    pub fn poll<T: Transport>(
+        &mut self,
+        transport: &mut T,
+        now_us: u64,
+    ) -> Result<Option<Sample>, Error<T::Error>> {
+        let Some(pending) = self.pending else {
+            let server = self.servers[self.next_server];
+            let originate = Timestamp::from_unix_micros(now_us as i64);
+            transport
+                .send(server, NTP_PORT, &encode_request(originate))
+                .map_err(Error::Transport)?;
+            self.pending = Some(Pending {
+                server,
+                originate,
+                sent_at_us: now_us,
+            });
+            return Ok(None);
+        };
+
+        let mut buf = [0u8; PACKET_SIZE];
+        while let Some((len, from)) = transport.receive(&mut buf).map_err(Error::Transport)? {
+            if from != pending.server {
+                continue;
+            }
+            // Replies to an earlier, timed out request do not match and are skipped
+            let packet = match decode_response(&buf[..len], pending.originate) {
+                Ok(packet) => packet,
+                Err(PacketError::OriginateMismatch) => continue,
+                Err(err) => {
+                    self.next();
+                    return Err(Error::Packet(pending.server, err));
+                }
+            };
+
+            self.pending = None;
+            let (offset_us, delay_us) = offset_and_delay(
+                pending.sent_at_us as i64,
+                packet.receive.to_unix_micros(),
+                packet.transmit.to_unix_micros(),
+                now_us as i64,
+            );
+            return Ok(Some(Sample {
+                server: pending.server,
+                stratum: packet.stratum,
+                offset_us,
+                delay_us,
+            }));
+        }
+
+        if now_us.saturating_sub(pending.sent_at_us) >= self.timeout_us {
+            self.next();
+            return Err(Error::Timeout(pending.server));
+        }
+
+        Ok(None)
+    }
+
+    /// Abandons the request in flight and moves on to the next server.
+    fn next(&mut self) {
+        self.pending = None;
+        self.next_server = (self.next_server + 1) % self.servers.len();
+    }
+}
@@ -0,0 +1,130 @@
+//! SNTPv4 packet encoding and decoding (RFC 4330).
+
+use super::Timestamp;
+
+/// Size of an NTP packet without extension fields.
+pub const PACKET_SIZE: usize = 48;
+
+/// Leap indicator meaning the server clock is not synchronized.
+const LEAP_ALARM: u8 = 3;
+const VERSION: u8 = 4;
+const MODE_CLIENT: u8 = 3;
+const MODE_SERVER: u8 = 4;
+
+/// Reasons to reject a server response.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PacketError {
+    /// Shorter than [`PACKET_SIZE`].
+    TooShort,
+    /// Version other than 3 or 4.
+    UnsupportedVersion(u8),
+    /// Not a server reply.
+    UnexpectedMode(u8),
+    /// Stratum 0, the server asks us to go away. Holds the ASCII kiss code,
+    /// e.g. `RATE` or `DENY`.
+    KissOfDeath([u8; 4]),
+    /// The server clock is not synchronized.
+    Unsynchronized,
+    /// The reply does not echo the transmit timestamp of our request.
+    OriginateMismatch,
+    /// The server did not fill in its transmit timestamp.
+    ZeroTransmit,
+}
+
+/// Decoded header of an NTP packet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Packet {
+    pub leap: u8,
+    pub version: u8,
+    pub mode: u8,
+    pub stratum: u8,
+    /// Log2 of the poll interval in seconds.
+    pub poll: i8,
+    /// Log2 of the clock precision in seconds.
+    pub precision: i8,
+    /// Round-trip delay to the reference clock, 16.16 fixed point seconds.
+    pub root_delay: u32,
+    /// Dispersion to the reference clock, 16.16 fixed point seconds.
+    pub root_dispersion: u32,
+    /// Reference clock identifier, or the kiss code with stratum 0.
+    pub reference_id: [u8; 4],
+    pub reference: Timestamp,
+    /// Transmit timestamp of the request, echoed by the server (T1).
+    pub originate: Timestamp,
+    /// Time the request arrived at the server (T2).
+    pub receive: Timestamp,
+    /// Time the reply left the server (T3).
+    pub transmit: Timestamp,
+}
+
+/// Encodes a client request carrying `transmit` as its transmit timestamp.
+///
+/// The server echoes the timestamp in the originate field of the reply, which
+/// [`decode_response`] uses to match the reply to the request.
+pub fn encode_request(transmit: Timestamp) -> [u8; PACKET_SIZE] {
+    let mut buf = [0u8; PACKET_SIZE];
+    buf[0] = VERSION << 3 | MODE_CLIENT;
+    buf[40..48].copy_from_slice(&transmit.to_be_bytes());
+    buf
+}
+
+/// Decodes any NTP packet without validating it.
+pub fn decode(buf: &[u8]) -> Result<Packet, PacketError> {
+    let buf: &[u8; PACKET_SIZE] = buf
+        .get(..PACKET_SIZE)
+        .and_then(|buf| buf.try_into().ok())
+        .ok_or(PacketError::TooShort)?;
+
+    let word = |offset: usize| {
+        u32::from_be_bytes([
+            buf[offset],
+            buf[offset + 1],
+            buf[offset + 2],
+            buf[offset + 3],
+        ])
+    };
+    let timestamp = |offset: usize| Timestamp::new(word(offset), word(offset + 4));
+
+    Ok(Packet {
+        leap: buf[0] >> 6,
+        version: (buf[0] >> 3) & 0b111,
+        mode: buf[0] & 0b111,
+        stratum: buf[1],
+        poll: buf[2] as i8,
+        precision: buf[3] as i8,
+        root_delay: word(4),
+        root_dispersion: word(8),
+        reference_id: [buf[12], buf[13], buf[14], buf[15]],
+        reference: timestamp(16),
+        originate: timestamp(24),
+        receive: timestamp(32),
+        transmit: timestamp(40),
+    })
+}
+
+/// Decodes and validates the reply to a request sent with
+/// [`encode_request`]`(originate)`.
+pub fn decode_response(buf: &[u8], originate: Timestamp) -> Result<Packet, PacketError> {
+    let packet = decode(buf)?;
+
+    if !matches!(packet.version, 3 | 4) {
+        return Err(PacketError::UnsupportedVersion(packet.version));
+    }
+    if packet.mode != MODE_SERVER {
+        return Err(PacketError::UnexpectedMode(packet.mode));
+    }
+    if packet.stratum == 0 {
+        return Err(PacketError::KissOfDeath(packet.reference_id));
+    }
+    if packet.leap == LEAP_ALARM || packet.stratum > 15 {
+        return Err(PacketError::Unsynchronized);
+    }
+    if packet.originate != originate {
+        return Err(PacketError::OriginateMismatch);
+    }
+    if packet.transmit == Timestamp::ZERO {
+        return Err(PacketError::ZeroTransmit);
+    }
+
+    Ok(packet)
+}
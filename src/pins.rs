@@ -0,0 +1,48 @@
+//! Pin assignment of the ESP-Buddy board.
+//!
+//! A board revision only needs to change this module: the numbers below are
+//! used as const generics of [`BoardPins`], so picking a different GPIO in
+//! [`BoardPins::new`] without updating the number (or the other way around)
+//! does not compile.
+
+#[cfg(feature = "esp32")]
+use hal::gpio::{GpioPin, Pins};
+
+/// I2C0 data line shared by the display and the sensors.
+pub const I2C_SDA: u8 = 18;
+/// I2C0 clock line shared by the display and the sensors.
+pub const I2C_SCL: u8 = 23;
+/// Left button, active low (BOOT button).
+pub const BUTTON_LEFT: u8 = 0;
+/// Right button, active low.
+pub const BUTTON_RIGHT: u8 = 4;
+/// WS2812 RGB LED data line.
+pub const LED: u8 = 25;
+
+/// I2C address of the SSD1306 display.
+pub const DISPLAY_ADDRESS: u8 = 0x3c;
+/// I2C bus frequency in kHz.
+pub const I2C_FREQUENCY_KHZ: u32 = 100;
+
+/// Pins used by the board, taken out of [`hal::gpio::Io`].
+#[cfg(feature = "esp32")]
+pub struct BoardPins {
+    pub sda: GpioPin<I2C_SDA>,
+    pub scl: GpioPin<I2C_SCL>,
+    pub button_left: GpioPin<BUTTON_LEFT>,
+    pub button_right: GpioPin<BUTTON_RIGHT>,
+    pub led: GpioPin<LED>,
+}
+
+#[cfg(feature = "esp32")]
+impl BoardPins {
+    pub fn new(pins: Pins) -> Self {
+        Self {
+            sda: pins.gpio18,
+            scl: pins.gpio23,
+            button_left: pins.gpio0,
+            button_right: pins.gpio4,
+            led: pins.gpio25,
+        }
+    }
+}
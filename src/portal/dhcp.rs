@@ -0,0 +1,263 @@
+//! DHCP server handing out addresses on the portal network.
+//!
+//! Just enough of RFC 2131 for phones and laptops joining the access point:
+//! every client gets the next free address after the server, the server as
+//! router and DNS server, and the portal URL as captive portal (RFC 8910).
+
+use core::net::Ipv4Addr;
+
+pub const SERVER_PORT: u16 = 67;
+pub const CLIENT_PORT: u16 = 68;
+
+/// Lease time in seconds.
+pub const LEASE_TIME_S: u32 = 3600;
+
+/// Replies are padded to the minimum BOOTP message size.
+pub const MIN_MESSAGE_SIZE: usize = 300;
+
+const OPTIONS_START: usize = 240;
+const MAGIC_COOKIE: [u8; 4] = [99, 130, 83, 99];
+const BOOT_REQUEST: u8 = 1;
+const BOOT_REPLY: u8 = 2;
+const HTYPE_ETHERNET: u8 = 1;
+
+const OPTION_PAD: u8 = 0;
+const OPTION_SUBNET_MASK: u8 = 1;
+const OPTION_ROUTER: u8 = 3;
+const OPTION_DNS_SERVER: u8 = 6;
+const OPTION_REQUESTED_IP: u8 = 50;
+const OPTION_LEASE_TIME: u8 = 51;
+const OPTION_MESSAGE_TYPE: u8 = 53;
+const OPTION_SERVER_ID: u8 = 54;
+const OPTION_CAPTIVE_PORTAL: u8 = 114;
+const OPTION_END: u8 = 255;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageType {
+    Discover = 1,
+    Offer = 2,
+    Request = 3,
+    Decline = 4,
+    Ack = 5,
+    Nak = 6,
+    Release = 7,
+    Inform = 8,
+}
+
+impl MessageType {
+    fn from_u8(value: u8) -> Option<Self> {
+        Some(match value {
+            1 => Self::Discover,
+            2 => Self::Offer,
+            3 => Self::Request,
+            4 => Self::Decline,
+            5 => Self::Ack,
+            6 => Self::Nak,
+            7 => Self::Release,
+            8 => Self::Inform,
+            _ => return None,
+        })
+    }
+}
+
+/// Fields of a client message the server looks at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Message {
+    pub message_type: MessageType,
+    pub xid: u32,
+    pub flags: u16,
+    pub client_ip: Ipv4Addr,
+    pub mac: [u8; 6],
+    pub requested_ip: Option<Ipv4Addr>,
+    pub server_id: Option<Ipv4Addr>,
+}
+
+/// Parses a BOOTREQUEST from an Ethernet client, `None` for anything else.
+pub fn parse(data: &[u8]) -> Option<Message> {
+    if data.len() < OPTIONS_START
+        || data[0] != BOOT_REQUEST
+        || data[1] != HTYPE_ETHERNET
+        || data[2] != 6
+        || data[236..240] != MAGIC_COOKIE
+    {
+        return None;
+    }
+
+    let mut message_type = None;
+    let mut requested_ip = None;
+    let mut server_id = None;
+    let mut options = &data[OPTIONS_START..];
+    while let [code, rest @ ..] = options {
+        match *code {
+            OPTION_PAD => {
+                options = rest;
+                continue;
+            }
+            OPTION_END => break,
+            _ => {}
+        }
+        let (&len, rest) = rest.split_first()?;
+        let value = rest.get(..len as usize)?;
+        match (*code, value) {
+            (OPTION_MESSAGE_TYPE, &[value]) => message_type = MessageType::from_u8(value),
+            (OPTION_REQUESTED_IP, &[a, b, c, d]) => requested_ip = Some(Ipv4Addr::new(a, b, c, d)),
+            (OPTION_SERVER_ID, &[a, b, c, d]) => server_id = Some(Ipv4Addr::new(a, b, c, d)),
+            _ => {}
+        }
+        options = &rest[len as usize..];
+    }
+
+    let mut mac = [0u8; 6];
+    mac.copy_from_slice(&data[28..34]);
+    Some(Message {
+        message_type: message_type?,
+        xid: u32::from_be_bytes([data[4], data[5], data[6], data[7]]),
+        flags: u16::from_be_bytes([data[10], data[11]]),
+        client_ip: Ipv4Addr::new(data[12], data[13], data[14], data[15]),
+        mac,
+        requested_ip,
+        server_id,
+    })
+}
+
+/// Leases for up to `N` clients in a /24 network starting right after the
+/// server address. Leases are reused round robin when all are taken.
+pub struct Server<'a, const N: usize> {
+    ip: Ipv4Addr,
+    captive_portal: Option<&'a str>,
+    /// Client MAC address by lease.
+    clients: [Option<[u8; 6]>; N],
+    next_reused: usize,
+}
+
+impl<'a, const N: usize> Server<'a, N> {
+    pub fn new(ip: Ipv4Addr) -> Self {
+        Self {
+            ip,
+            captive_portal: None,
+            clients: [None; N],
+            next_reused: 0,
+        }
+    }
+
+    /// Announces `url` as captive portal, at most 255 bytes.
+    pub fn with_captive_portal(mut self, url: &'a str) -> Self {
+        self.captive_portal = Some(url);
+        self
+    }
+
+    /// Address leased to the client with `mac`, if any.
+    pub fn lease(&self, mac: &[u8; 6]) -> Option<Ipv4Addr> {
+        let index = self
+            .clients
+            .iter()
+            .position(|client| *client == Some(*mac))?;
+        Some(self.address(index))
+    }
+
+    /// Handles a client message and writes the reply to `buf`, which should
+    /// be broadcast to [`CLIENT_PORT`]. Returns the length of the reply,
+    /// `None` when there is nothing to reply.
+    pub fn handle(&mut self, data: &[u8], buf: &mut [u8]) -> Option<usize> {
+        let message = parse(data)?;
+        let (reply, yiaddr) = match message.message_type {
+            MessageType::Discover => (MessageType::Offer, self.allocate(&message.mac)),
+            MessageType::Request => {
+                // Selecting another server
+                if message.server_id.is_some_and(|id| id != self.ip) {
+                    return None;
+                }
+                let requested = match message.requested_ip {
+                    Some(ip) => ip,
+                    None => message.client_ip,
+                };
+                match self.lease(&message.mac) {
+                    Some(ip) if ip == requested => (MessageType::Ack, ip),
+                    _ => (MessageType::Nak, Ipv4Addr::UNSPECIFIED),
+                }
+            }
+            MessageType::Release | MessageType::Decline => {
+                for client in &mut self.clients {
+                    if *client == Some(message.mac) {
+                        *client = None;
+                    }
+                }
+                return None;
+            }
+            _ => return None,
+        };
+
+        let mut writer = Writer { buf, len: 0 };
+        writer.put(&[BOOT_REPLY, HTYPE_ETHERNET, 6, 0])?;
+        writer.put(&message.xid.to_be_bytes())?;
+        writer.put(&[0, 0])?;
+        writer.put(&message.flags.to_be_bytes())?;
+        writer.put(&[0; 4])?;
+        writer.put(&yiaddr.octets())?;
+        writer.put(&self.ip.octets())?;
+        writer.put(&[0; 4])?;
+        writer.put(&message.mac)?;
+        // Rest of chaddr, sname and file
+        writer.put(&[0; 10 + 64 + 128])?;
+        writer.put(&MAGIC_COOKIE)?;
+
+        writer.option(OPTION_MESSAGE_TYPE, &[reply as u8])?;
+        writer.option(OPTION_SERVER_ID, &self.ip.octets())?;
+        if reply != MessageType::Nak {
+            writer.option(OPTION_LEASE_TIME, &LEASE_TIME_S.to_be_bytes())?;
+            writer.option(OPTION_SUBNET_MASK, &[255, 255, 255, 0])?;
+            writer.option(OPTION_ROUTER, &self.ip.octets())?;
+            writer.option(OPTION_DNS_SERVER, &self.ip.octets())?;
+            if let Some(url) = self.captive_portal {
+                writer.option(OPTION_CAPTIVE_PORTAL, url.as_bytes())?;
+            }
+        }
+        writer.put(&[OPTION_END])?;
+        while writer.len < MIN_MESSAGE_SIZE {
+            writer.put(&[OPTION_PAD])?;
+        }
+
+        Some(writer.len)
+    }
+
+    fn allocate(&mut self, mac: &[u8; 6]) -> Ipv4Addr {
+        if let Some(ip) = self.lease(mac) {
+            return ip;
+        }
+        let index = match self.clients.iter().position(Option::is_none) {
+            Some(index) => index,
+            None => {
+                let index = self.next_reused;
+                self.next_reused = (index + 1) % N;
+                index
+            }
+        };
+        self.clients[index] = Some(*mac);
+        self.address(index)
+    }
+
+    fn address(&self, index: usize) -> Ipv4Addr {
+        let [a, b, c, d] = self.ip.octets();
+        Ipv4Addr::new(a, b, c, d.wrapping_add(1 + index as u8))
+    }
+}
+
+struct Writer<'b> {
+    buf: &'b mut [u8],
+    len: usize,
+}
+
+impl Writer<'_> {
+    fn put(&mut self, bytes: &[u8]) -> Option<()> {
+        self.buf
+            .get_mut(self.len..self.len + bytes.len())?
+            .copy_from_slice(bytes);
+        self.len += bytes.len();
+        Some(())
+    }
+
+    fn option(&mut self, code: u8, value: &[u8]) -> Option<()> {
+        self.put(&[code, u8::try_from(value.len()).ok()?])?;
+        self.put(value)
+    }
+}
@@ -0,0 +1,98 @@
+//! DNS server answering every name with the address of the portal.
+//!
+//! Phones resolve a connectivity check host right after joining a network.
+//! Getting the portal instead of the expected answer makes them open the
+//! sign-in page on their own.
+
+use core::net::Ipv4Addr;
+
+pub const HEADER_SIZE: usize = 12;
+
+/// Time to live of the answers in seconds, short so that the real addresses
+/// are used soon after the device left provisioning.
+pub const TTL_S: u32 = 10;
+
+const FLAG_RESPONSE: u16 = 0x8000;
+const FLAG_AUTHORITATIVE: u16 = 0x0400;
+const FLAG_RECURSION_DESIRED: u16 = 0x0100;
+const OPCODE_MASK: u16 = 0x7800;
+const RCODE_NOT_IMPLEMENTED: u16 = 4;
+
+const TYPE_A: u16 = 1;
+const TYPE_ANY: u16 = 255;
+const CLASS_IN: u16 = 1;
+/// Compression pointer to the name of the question right after the header.
+const NAME_POINTER: [u8; 2] = [0xc0, HEADER_SIZE as u8];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DnsError {
+    /// Shorter than its header and question.
+    Truncated,
+    /// A response or a query without a question.
+    NotAQuery,
+    /// The name of the question is malformed or compressed.
+    InvalidName,
+    BufferTooSmall,
+}
+
+/// Writes the reply to `query` to `buf` and returns its length.
+///
+/// Only the first question is answered: with `ip` for an `A` or `ANY`
+/// question of class `IN`, without an answer otherwise, so that e.g. an
+/// `AAAA` lookup falls back to IPv4. Other opcodes than a standard query get
+/// "not implemented".
+pub fn reply(query: &[u8], ip: Ipv4Addr, buf: &mut [u8]) -> Result<usize, DnsError> {
+    let header = query.get(..HEADER_SIZE).ok_or(DnsError::Truncated)?;
+    let flags = u16::from_be_bytes([header[2], header[3]]);
+    let questions = u16::from_be_bytes([header[4], header[5]]);
+    if flags & FLAG_RESPONSE != 0 || questions == 0 {
+        return Err(DnsError::NotAQuery);
+    }
+
+    // Labels up to the root, each at most 63 bytes, no compression
+    let mut end = HEADER_SIZE;
+    loop {
+        let len = *query.get(end).ok_or(DnsError::Truncated)? as usize;
+        if len > 63 {
+            return Err(DnsError::InvalidName);
+        }
+        end += 1 + len;
+        if len == 0 {
+            break;
+        }
+    }
+    let question = query.get(HEADER_SIZE..end + 4).ok_or(DnsError::Truncated)?;
+    let qtype = u16::from_be_bytes([question[question.len() - 4], question[question.len() - 3]]);
+    let qclass = u16::from_be_bytes([question[question.len() - 2], question[question.len() - 1]]);
+
+    let opcode = flags & OPCODE_MASK;
+    let answer = opcode == 0 && qclass == CLASS_IN && matches!(qtype, TYPE_A | TYPE_ANY);
+    let rcode = if opcode == 0 {
+        0
+    } else {
+        RCODE_NOT_IMPLEMENTED
+    };
+    let len = HEADER_SIZE + question.len() + if answer { 16 } else { 0 };
+    let buf = buf.get_mut(..len).ok_or(DnsError::BufferTooSmall)?;
+
+    let flags =
+        FLAG_RESPONSE | FLAG_AUTHORITATIVE | opcode | (flags & FLAG_RECURSION_DESIRED) | rcode;
+    buf[..2].copy_from_slice(&header[..2]);
+    buf[2..4].copy_from_slice(&flags.to_be_bytes());
+    buf[4..6].copy_from_slice(&1u16.to_be_bytes());
+    buf[6..8].copy_from_slice(&(answer as u16).to_be_bytes());
+    buf[8..12].fill(0);
+    buf[HEADER_SIZE..HEADER_SIZE + question.len()].copy_from_slice(question);
+
+    if answer {
+        let record = &mut buf[HEADER_SIZE + question.len()..];
+        record[..2].copy_from_slice(&NAME_POINTER);
+        record[2..4].copy_from_slice(&TYPE_A.to_be_bytes());
+        record[4..6].copy_from_slice(&CLASS_IN.to_be_bytes());
+        record[6..10].copy_from_slice(&TTL_S.to_be_bytes());
+        record[10..12].copy_from_slice(&4u16.to_be_bytes());
+        record[12..16].copy_from_slice(&ip.octets());
+    }
+
+    Ok(len)
+}
@@ -0,0 +1,187 @@
+use embedded_svc::{
+    ipv4,
+    wifi::{AccessPointConfiguration, AuthMethod, ClientConfiguration, Configuration},
+};
+use esp_storage::FlashStorage;
+use esp_wifi::{
+    current_millis,
+    wifi::utils::create_ap_sta_network_interface,
+    wifi_interface::{IoError, WifiStack},
+    EspWifiInitialization,
+};
+use hal::{delay::Delay, peripherals::WIFI, reset::software_reset};
+use smoltcp::{
+    iface::SocketStorage,
+    socket::udp::{PacketMetadata, RecvError},
+    wire::IpAddress,
+};
+
+use super::{dhcp, dns, respond, AP_IP, AP_SSID, DNS_PORT, HTTP_PORT, URL};
+use crate::{
+    http, screens,
+    settings::Store,
+    wifi::{Controller, EspWifi},
+    Display,
+};
+
+/// Clients of the access point.
+const MAX_CLIENTS: usize = 4;
+
+/// Opens the portal and serves it until a network is submitted, then saves
+/// the network and restarts.
+pub fn run(
+    init: &EspWifiInitialization,
+    wifi: WIFI,
+    display: &mut Display,
+    store: &mut Store<FlashStorage>,
+) -> ! {
+    let mut ap_socket_entries: [SocketStorage; 4] = Default::default();
+    let mut sta_socket_entries: [SocketStorage; 1] = Default::default();
+    let interfaces = create_ap_sta_network_interface(
+        init,
+        wifi,
+        &mut ap_socket_entries,
+        &mut sta_socket_entries,
+    )
+    .unwrap();
+
+    let mut controller = interfaces.controller;
+    // Station mode next to the access point for scanning
+    controller
+        .set_configuration(&Configuration::Mixed(
+            ClientConfiguration::default(),
+            AccessPointConfiguration {
+                ssid: AP_SSID.try_into().unwrap(),
+                auth_method: AuthMethod::None,
+                max_connections: MAX_CLIENTS as u16,
+                ..Default::default()
+            },
+        ))
+        .unwrap();
+
+    let sta_stack = WifiStack::new(
+        interfaces.sta_interface,
+        interfaces.sta_device,
+        interfaces.sta_socket_set,
+        current_millis,
+    );
+    let mut station = EspWifi::new(controller, &sta_stack);
+    station.start().unwrap();
+    let mut access_points = station.scan().unwrap_or_default();
+    access_points.sort_unstable_by_key(|access_point| -access_point.rssi);
+
+    let ap_stack = WifiStack::new(
+        interfaces.ap_interface,
+        interfaces.ap_device,
+        interfaces.ap_socket_set,
+        current_millis,
+    );
+    ap_stack
+        .set_iface_configuration(&ipv4::Configuration::Client(
+            ipv4::ClientConfiguration::Fixed(ipv4::ClientSettings {
+                ip: AP_IP,
+                subnet: ipv4::Subnet {
+                    gateway: AP_IP,
+                    mask: ipv4::Mask(24),
+                },
+                dns: None,
+                secondary_dns: None,
+            }),
+        ))
+        .unwrap();
+
+    display.clear();
+    screens::wifi::draw_portal(display, AP_SSID, URL).unwrap();
+    display.flush().unwrap();
+
+    let mut dns_rx_meta = [PacketMetadata::EMPTY; 4];
+    let mut dns_rx_buffer = [0u8; 512];
+    let mut dns_tx_meta = [PacketMetadata::EMPTY; 4];
+    let mut dns_tx_buffer = [0u8; 512];
+    let mut dns_socket = ap_stack.get_udp_socket(
+        &mut dns_rx_meta,
+        &mut dns_rx_buffer,
+        &mut dns_tx_meta,
+        &mut dns_tx_buffer,
+    );
+    dns_socket.bind(DNS_PORT).unwrap();
+
+    let mut dhcp_rx_meta = [PacketMetadata::EMPTY; 2];
+    let mut dhcp_rx_buffer = [0u8; 1024];
+    let mut dhcp_tx_meta = [PacketMetadata::EMPTY; 2];
+    let mut dhcp_tx_buffer = [0u8; 1024];
+    let mut dhcp_socket = ap_stack.get_udp_socket(
+        &mut dhcp_rx_meta,
+        &mut dhcp_rx_buffer,
+        &mut dhcp_tx_meta,
+        &mut dhcp_tx_buffer,
+    );
+    dhcp_socket.bind(dhcp::SERVER_PORT).unwrap();
+    let mut dhcp_server = dhcp::Server::<MAX_CLIENTS>::new(AP_IP).with_captive_portal(URL);
+
+    let mut http_rx_buffer = [0u8; 1536];
+    let mut http_tx_buffer = [0u8; 1536];
+    let mut http_socket = ap_stack.get_socket(&mut http_rx_buffer, &mut http_tx_buffer);
+
+    let mut packet = [0u8; 576];
+    let mut reply = [0u8; 576];
+    let mut http_connection = http::Connection::default();
+    let mut request = [0u8; 1024];
+    let mut response = [0u8; 4096];
+    loop {
+        ap_stack.work();
+
+        dns_socket.work();
+        if let Some((len, address, port)) = received(dns_socket.receive(&mut packet)) {
+            if let Ok(reply_len) = dns::reply(&packet[..len], AP_IP, &mut reply) {
+                dns_socket.send(address, port, &reply[..reply_len]).ok();
+            }
+        }
+
+        dhcp_socket.work();
+        if let Some((len, _, _)) = received(dhcp_socket.receive(&mut packet)) {
+            if let Some(reply_len) = dhcp_server.handle(&packet[..len], &mut reply) {
+                let broadcast = IpAddress::v4(255, 255, 255, 255);
+                dhcp_socket
+                    .send(broadcast, dhcp::CLIENT_PORT, &reply[..reply_len])
+                    .ok();
+            }
+        }
+
+        let saved = http::serve(
+            &mut http_socket,
+            HTTP_PORT,
+            &mut http_connection,
+            &mut request,
+            &mut response,
+            |request, buf| match request {
+                Ok(request) => respond(&request, &access_points, buf),
+                Err(err) => (http::error_response(buf, err), None),
+            },
+        )
+        .flatten();
+
+        if let Some(network) = saved {
+            match store.save_network(&network) {
+                Ok(()) => {
+                    // Let the confirmation page go out before the radio stops
+                    Delay::new().delay_millis(1000);
+                    software_reset();
+                }
+                Err(err) => log::error!("Saving the network failed: {:?}", err),
+            }
+        }
+    }
+}
+
+/// Datagram from a non-blocking receive, `None` when nothing is waiting.
+fn received(result: Result<(usize, IpAddress, u16), IoError>) -> Option<(usize, IpAddress, u16)> {
+    match result {
+        Ok(received) => Some(received),
+        Err(IoError::UdpRecvError(RecvError::Exhausted)) => None,
+        Err(err) => {
+            log::warn!("Portal receive failed: {:?}", err);
+            None
+        }
+    }
+}
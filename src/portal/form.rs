@@ -0,0 +1,54 @@
+//! `application/x-www-form-urlencoded` bodies, as posted by HTML forms.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FormError {
+    /// A `%` not followed by two hex digits, or not UTF-8 once decoded.
+    InvalidEncoding,
+    /// The decoded value does not fit.
+    TooLong,
+}
+
+/// The `name=value` pairs of a body, still encoded. A pair without `=` has
+/// an empty value.
+pub fn pairs(body: &str) -> impl Iterator<Item = (&str, &str)> {
+    body.split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+}
+
+/// Decodes `+` to a space and `%XX` to the byte `XX`.
+pub fn decode<const N: usize>(encoded: &str) -> Result<heapless::String<N>, FormError> {
+    let mut decoded = heapless::Vec::<u8, N>::new();
+    let mut bytes = encoded.bytes();
+    while let Some(byte) = bytes.next() {
+        let byte = match byte {
+            b'+' => b' ',
+            b'%' => {
+                let high = bytes.next().and_then(hex_digit);
+                let low = bytes.next().and_then(hex_digit);
+                match (high, low) {
+                    (Some(high), Some(low)) => high << 4 | low,
+                    _ => return Err(FormError::InvalidEncoding),
+                }
+            }
+            byte => byte,
+        };
+        decoded.push(byte).map_err(|_| FormError::TooLong)?;
+    }
+    heapless::String::from_utf8(decoded).map_err(|_| FormError::InvalidEncoding)
+}
+
+/// Decoded value of the first field called `name`.
+pub fn value<const N: usize>(
+    body: &str,
+    name: &str,
+) -> Result<Option<heapless::String<N>>, FormError> {
+    pairs(body)
+        .find(|&(field, _)| field == name)
+        .map(|(_, value)| decode(value))
+        .transpose()
+}
+
+fn hex_digit(byte: u8) -> Option<u8> {
+    (byte as char).to_digit(16).map(|digit| digit as u8)
+}
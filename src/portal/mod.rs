@@ -0,0 +1,117 @@
+//! Captive portal to provision Wi-Fi credentials.
+//!
+//! Without a known network the device opens the access point [`AP_SSID`]
+//! and serves a page at [`URL`] listing the networks in range. The
+//! [`dhcp`] server gives joining phones an address and the [`dns`] server
+//! resolves every name to the portal, so that they open the page on their
+//! own. The submitted network is saved to the [`settings`](crate::settings)
+//! and the device restarts in station mode.
+//!
+//! ```ignore
+//! let mut store = settings::open().unwrap();
+//! if store.networks().unwrap().is_empty() {
+//!     portal::run(&init, peripherals.WIFI, &mut buddy.display, &mut store);
+//! }
+//! ```
+
+use core::net::Ipv4Addr;
+
+pub mod dhcp;
+pub mod dns;
+#[cfg(feature = "esp32")]
+mod esp_wifi;
+pub mod form;
+mod page;
+
+#[cfg(feature = "esp32")]
+pub use self::esp_wifi::run;
+
+use self::form::FormError;
+use crate::{
+    http::{self, Method, Request, Status},
+    settings::StoredNetwork,
+    wifi::AccessPoint,
+};
+
+/// Open access point of the portal.
+pub const AP_SSID: &str = "ESP-Buddy";
+
+/// Address of the device on the portal network.
+pub const AP_IP: Ipv4Addr = Ipv4Addr::new(192, 168, 4, 1);
+
+pub const URL: &str = "http://192.168.4.1/";
+
+pub const HTTP_PORT: u16 = 80;
+pub const DNS_PORT: u16 = 53;
+
+/// Priority of the networks saved by the portal.
+pub const PRIORITY: u8 = 0;
+
+const HTML: &str = "text/html; charset=utf-8";
+
+/// Network submitted with the form, or the message to show.
+pub fn credentials(body: &[u8]) -> Result<StoredNetwork, &'static str> {
+    let body = core::str::from_utf8(body).map_err(|_| "Invalid form")?;
+    let ssid = match form::value::<32>(body, "ssid") {
+        Ok(Some(ssid)) if !ssid.is_empty() => ssid,
+        Ok(_) => form::value(body, "other")
+            .map_err(|error| form_message(error, "SSID too long"))?
+            .unwrap_or_default(),
+        Err(error) => return Err(form_message(error, "SSID too long")),
+    };
+    let password = form::value::<64>(body, "password")
+        .map_err(|error| form_message(error, "Password too long"))?
+        .unwrap_or_default();
+    // WPA passphrases have 8 to 63 characters, or 64 hex digits
+    if !password.is_empty() && password.len() < 8 {
+        return Err("Password too short");
+    }
+    StoredNetwork::new(&ssid, &password, PRIORITY).ok_or("Choose a network")
+}
+
+fn form_message(error: FormError, too_long: &'static str) -> &'static str {
+    match error {
+        FormError::InvalidEncoding => "Invalid form",
+        FormError::TooLong => too_long,
+    }
+}
+
+/// Writes the response to `request` to `buf` and returns its length, along
+/// with the network to save once the form was submitted.
+///
+/// The form is served at `/` and posted to `/save`, everything else is
+/// redirected to the form, which is what makes the operating systems
+/// detect the portal.
+pub fn respond(
+    request: &Request,
+    access_points: &[AccessPoint],
+    buf: &mut [u8],
+) -> (usize, Option<StoredNetwork>) {
+    match (request.method, request.path) {
+        (Method::Get, "/") => {
+            let len = http::write_response(buf, Status::Ok, &[], HTML, |out| {
+                page::networks(out, access_points, None)
+            });
+            (len, None)
+        }
+        (Method::Post, "/save") => match credentials(request.body) {
+            Ok(network) => {
+                let len = http::write_response(buf, Status::Ok, &[], HTML, |out| {
+                    page::saved(out, &network.ssid)
+                });
+                (len, Some(network))
+            }
+            Err(error) => {
+                let len = http::write_response(buf, Status::BadRequest, &[], HTML, |out| {
+                    page::networks(out, access_points, Some(error))
+                });
+                (len, None)
+            }
+        },
+        _ => {
+            let location = [("Location", URL)];
+            let len = http::write_response(buf, Status::Found, &location, HTML, |_| Ok(()));
+            (len, None)
+        }
+    }
+}
@@ -0,0 +1,81 @@
+//! HTML of the portal.
+
+use core::fmt::{self, Display, Write};
+
+use crate::wifi::AccessPoint;
+
+/// Text with the HTML special characters escaped.
+struct Escaped<'a>(&'a str);
+
+impl Display for Escaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '&' => f.write_str("&amp;")?,
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '"' => f.write_str("&quot;")?,
+                '\'' => f.write_str("&#39;")?,
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+const HEAD: &str = "<!DOCTYPE html><html><head><meta charset=utf-8>\
+<meta name=viewport content=\"width=device-width,initial-scale=1\">\
+<title>ESP-Buddy Wi-Fi</title>\
+<style>body{font-family:sans-serif;margin:1em auto;max-width:24em}\
+label{display:block;margin:.4em 0}input[type=text],input[type=password]{width:100%}\
+small{color:#777}</style></head><body><h1>ESP-Buddy Wi-Fi</h1>";
+
+const TAIL: &str = "</body></html>";
+
+/// Form with the networks found by the scan and a field for a hidden one.
+pub(super) fn networks(
+    out: &mut impl Write,
+    access_points: &[AccessPoint],
+    error: Option<&str>,
+) -> fmt::Result {
+    out.write_str(HEAD)?;
+    if let Some(error) = error {
+        write!(out, "<p><b>{}</b></p>", Escaped(error))?;
+    }
+    out.write_str("<form method=post action=/save>")?;
+    for (index, access_point) in access_points.iter().enumerate() {
+        write!(
+            out,
+            "<label><input type=radio name=ssid value=\"{ssid}\"{checked}> {ssid} \
+             <small>{rssi} dBm{lock}</small></label>",
+            ssid = Escaped(&access_point.ssid),
+            checked = if index == 0 { " checked" } else { "" },
+            rssi = access_point.rssi,
+            lock = if access_point.open { "" } else { " &#128274;" },
+        )?;
+    }
+    write!(
+        out,
+        "<label><input type=radio name=ssid value=\"\"{}> Other: \
+         <input type=text name=other maxlength=32></label>\
+         <label>Password <input type=password name=password maxlength=64></label>\
+         <button>Save and restart</button></form>",
+        if access_points.is_empty() {
+            " checked"
+        } else {
+            ""
+        },
+    )?;
+    out.write_str(TAIL)
+}
+
+/// Confirmation shown before the restart.
+pub(super) fn saved(out: &mut impl Write, ssid: &str) -> fmt::Result {
+    out.write_str(HEAD)?;
+    write!(
+        out,
+        "<p>Saved <b>{}</b>. The device restarts and connects to it.</p>",
+        Escaped(ssid)
+    )?;
+    out.write_str(TAIL)
+}
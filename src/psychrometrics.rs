@@ -0,0 +1,217 @@
+//! Comfort metrics derived from temperature and relative humidity.
+//!
+//! Like the readings, values are in tenths: of a degree Celsius, of a
+//! percent, of a gram per cubic meter. The vapour pressure over water comes
+//! from the Magnus formula with the coefficients of Alduchov and Eskridge
+//! (1996), good to 0.1 % from -40 to 50 °C. The heat index follows the
+//! algorithm of the US National Weather Service.
+//!
+//! ```
+//! use esp_buddy::psychrometrics::{Climate, Comfort};
+//!
+//! let climate = Climate::new(215, 455);
+//! assert_eq!(climate.dew_point_dc, Some(92));
+//! assert_eq!(climate.comfort, Comfort::Comfortable);
+//! ```
+
+use core::f32::consts::{LN_2, LOG2_E, SQRT_2};
+
+use crate::sensors::hts221::Reading;
+
+const MAGNUS_A: f32 = 17.625;
+const MAGNUS_B: f32 = 243.04;
+/// Saturation vapour pressure at 0 °C in hPa.
+const MAGNUS_C: f32 = 6.1094;
+
+/// Lowest and highest temperature of the comfort zone, in tenths.
+pub const COMFORT_TEMPERATURE_DC: (i32, i32) = (200, 260);
+/// Lowest and highest relative humidity of the comfort zone, in tenths.
+pub const COMFORT_HUMIDITY_DPCT: (i32, i32) = (300, 600);
+/// Highest dew point of the comfort zone, in tenths. Above it the air
+/// feels muggy whatever the temperature.
+pub const COMFORT_DEW_POINT_DC: i32 = 160;
+
+/// How an office feels, after the usual comfort zone of 20 to 26 °C, 30 to
+/// 60 % and a dew point of at most 16 °C.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Comfort {
+    Cold,
+    Hot,
+    /// Too much moisture, by relative humidity or dew point.
+    Humid,
+    Dry,
+    Comfortable,
+}
+
+impl Comfort {
+    /// Temperature first, then humidity.
+    pub fn classify(temperature_dc: i32, humidity_dpct: i32) -> Self {
+        let dew_point_dc = dew_point_dc(temperature_dc, humidity_dpct);
+        if temperature_dc < COMFORT_TEMPERATURE_DC.0 {
+            Comfort::Cold
+        } else if temperature_dc > COMFORT_TEMPERATURE_DC.1 {
+            Comfort::Hot
+        } else if humidity_dpct > COMFORT_HUMIDITY_DPCT.1
+            || dew_point_dc.is_some_and(|dew_point| dew_point > COMFORT_DEW_POINT_DC)
+        {
+            Comfort::Humid
+        } else if humidity_dpct < COMFORT_HUMIDITY_DPCT.0 {
+            Comfort::Dry
+        } else {
+            Comfort::Comfortable
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Comfort::Cold => "Too cold",
+            Comfort::Hot => "Too hot",
+            Comfort::Humid => "Too humid",
+            Comfort::Dry => "Too dry",
+            Comfort::Comfortable => "Comfortable",
+        }
+    }
+}
+
+/// A reading with everything derived from it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Climate {
+    pub temperature_dc: i32,
+    pub humidity_dpct: i32,
+    /// `None` for perfectly dry air.
+    pub dew_point_dc: Option<i32>,
+    /// Apparent temperature.
+    pub heat_index_dc: i32,
+    /// Tenths of a gram of water vapour per cubic meter.
+    pub absolute_humidity_dg: i32,
+    pub comfort: Comfort,
+}
+
+impl Climate {
+    pub fn new(temperature_dc: i32, humidity_dpct: i32) -> Self {
+        Self {
+            temperature_dc,
+            humidity_dpct,
+            dew_point_dc: dew_point_dc(temperature_dc, humidity_dpct),
+            heat_index_dc: heat_index_dc(temperature_dc, humidity_dpct),
+            absolute_humidity_dg: absolute_humidity_dg(temperature_dc, humidity_dpct),
+            comfort: Comfort::classify(temperature_dc, humidity_dpct),
+        }
+    }
+}
+
+impl From<Reading> for Climate {
+    fn from(reading: Reading) -> Self {
+        Self::new(reading.temperature_dc, reading.humidity_dpct)
+    }
+}
+
+/// Temperature at which the air would be saturated, `None` at 0 %.
+pub fn dew_point_dc(temperature_dc: i32, humidity_dpct: i32) -> Option<i32> {
+    if humidity_dpct <= 0 {
+        return None;
+    }
+    let t = celsius(temperature_dc);
+    let gamma = ln(humidity_dpct as f32 / 1000.0) + MAGNUS_A * t / (MAGNUS_B + t);
+    Some(tenths(MAGNUS_B * gamma / (MAGNUS_A - gamma)))
+}
+
+/// Grams of water vapour in a cubic meter of air, in tenths.
+pub fn absolute_humidity_dg(temperature_dc: i32, humidity_dpct: i32) -> i32 {
+    let t = celsius(temperature_dc);
+    let vapour_hpa = saturation_vapour_pressure_hpa(t) * humidity_dpct as f32 / 1000.0;
+    // Ideal gas: molar mass of water over the gas constant, 2.167 g K / J,
+    // times 100 Pa per hPa
+    tenths(216.7 * vapour_hpa / (t + 273.15))
+}
+
+/// How hot it feels. The heat index is defined from 80 °F, about 27 °C,
+/// below it is the temperature. Above, the NWS use a simple formula and,
+/// where it gives 80 °F or more, the regression of Rothfusz with its
+/// adjustments.
+pub fn heat_index_dc(temperature_dc: i32, humidity_dpct: i32) -> i32 {
+    let t = celsius(temperature_dc) * 1.8 + 32.0;
+    if t < 80.0 {
+        return temperature_dc;
+    }
+    let rh = humidity_dpct as f32 / 10.0;
+
+    let simple = 0.5 * (t + 61.0 + (t - 68.0) * 1.2 + rh * 0.094);
+    let fahrenheit = if (simple + t) / 2.0 < 80.0 {
+        simple
+    } else {
+        let mut hi = -42.379 + 2.049_015_2 * t + 10.143_331 * rh
+            - 0.224_755_4 * t * rh
+            - 0.006_837_83 * t * t
+            - 0.054_817_17 * rh * rh
+            + 0.001_228_74 * t * t * rh
+            + 0.000_852_82 * t * rh * rh
+            - 0.000_001_99 * t * t * rh * rh;
+        if rh < 13.0 && (80.0..=112.0).contains(&t) {
+            let from_95 = if t < 95.0 { 95.0 - t } else { t - 95.0 };
+            let spread = (17.0 - from_95) / 17.0;
+            hi -= (13.0 - rh) / 4.0 * exp(ln(spread) / 2.0);
+        } else if rh > 85.0 && (80.0..=87.0).contains(&t) {
+            hi += (rh - 85.0) / 10.0 * ((87.0 - t) / 5.0);
+        }
+        hi
+    };
+    tenths((fahrenheit - 32.0) / 1.8)
+}
+
+fn saturation_vapour_pressure_hpa(celsius: f32) -> f32 {
+    MAGNUS_C * exp(MAGNUS_A * celsius / (MAGNUS_B + celsius))
+}
+
+fn celsius(tenths: i32) -> f32 {
+    tenths as f32 / 10.0
+}
+
+/// Rounded to tenths, halves away from zero.
+fn tenths(value: f32) -> i32 {
+    let scaled = value * 10.0;
+    (if scaled < 0.0 {
+        scaled - 0.5
+    } else {
+        scaled + 0.5
+    }) as i32
+}
+
+// `core` has no `exp` and `ln` for floats, these are good to a few units in
+// the last place over the range of the formulas above.
+
+/// `e^x` for `x` from -87 to 88.
+fn exp(x: f32) -> f32 {
+    // x = k ln 2 + r with |r| <= ln 2 / 2
+    let scaled = x * LOG2_E;
+    let k = (if scaled < 0.0 {
+        scaled - 0.5
+    } else {
+        scaled + 0.5
+    }) as i32;
+    let r = x - k as f32 * LN_2;
+    let mut term = 1.0;
+    let mut sum = 1.0;
+    for n in 1..=8 {
+        term *= r / n as f32;
+        sum += term;
+    }
+    sum * f32::from_bits(((k + 127) as u32) << 23)
+}
+
+/// Natural logarithm of a positive, normal `x`.
+fn ln(x: f32) -> f32 {
+    // x = m 2^e with m from √½ to √2
+    let bits = x.to_bits();
+    let mut e = ((bits >> 23) & 0xff) as i32 - 127;
+    let mut m = f32::from_bits((bits & 0x007f_ffff) | 0x3f80_0000);
+    if m > SQRT_2 {
+        m /= 2.0;
+        e += 1;
+    }
+    // ln m = 2 artanh s
+    let s = (m - 1.0) / (m + 1.0);
+    let s2 = s * s;
+    let series = 1.0 + s2 * (1.0 / 3.0 + s2 * (1.0 / 5.0 + s2 * (1.0 / 7.0 + s2 / 9.0)));
+    2.0 * s * series + e as f32 * LN_2
+}
@@ -0,0 +1,61 @@
+use embedded_io::{Read, ReadReady};
+use esp_wifi::{
+    wifi::WifiDeviceMode,
+    wifi_interface::{IoError, Socket, UdpSocket},
+};
+use smoltcp::socket::udp::RecvError;
+
+use super::{protocol::MAX_PACKET_LEN, Panel, StreamReader};
+
+/// Hands a waiting datagram to `panel`, returns whether it was shown.
+///
+/// The socket has to be bound to [`protocol::PORT`](super::protocol::PORT).
+pub fn receive<MODE: WifiDeviceMode>(
+    socket: &mut UdpSocket<'_, '_, MODE>,
+    panel: &mut Panel,
+) -> bool {
+    let mut packet = [0u8; MAX_PACKET_LEN];
+    socket.work();
+    match socket.receive(&mut packet) {
+        Ok((len, _, _)) => panel.handle(&packet[..len]).is_ok(),
+        Err(IoError::UdpRecvError(RecvError::Exhausted)) => false,
+        Err(err) => {
+            log::warn!("Remote display receive failed: {:?}", err);
+            false
+        }
+    }
+}
+
+/// Reads what a TCP client sent so far and shows the complete packets,
+/// returns whether one was shown.
+///
+/// Listens on `port` while the socket is closed. The connection is closed
+/// when the client closes it or sends something that is not a stream of
+/// packets.
+pub fn serve<MODE: WifiDeviceMode>(
+    socket: &mut Socket<'_, '_, MODE>,
+    port: u16,
+    reader: &mut StreamReader,
+    panel: &mut Panel,
+) -> bool {
+    socket.work();
+    if !socket.is_open() {
+        reader.reset();
+        socket.listen(port).ok();
+    }
+    if !socket.is_connected() || !socket.read_ready().unwrap_or(false) {
+        return false;
+    }
+
+    match socket.read(reader.spare()) {
+        Ok(0) | Err(_) => {
+            socket.close();
+            false
+        }
+        Ok(read) => reader.advance(read, panel).unwrap_or_else(|err| {
+            log::warn!("Remote display stream failed: {:?}", err);
+            socket.close();
+            false
+        }),
+    }
+}
@@ -0,0 +1,125 @@
+//! Remote display: frames and text pushed over the network.
+//!
+//! Clients send packets in the format of [`protocol`], over UDP or over a TCP
+//! connection, to [`protocol::PORT`]. The [`Panel`] keeps the latest frame,
+//! text is drawn top left in [`style::TEXT`].
+//!
+//! ```ignore
+//! let mut panel = remote::Panel::new();
+//! if remote::receive(&mut udp_socket, &mut panel) {
+//!     panel.draw(display).unwrap();
+//! }
+//! ```
+
+use embedded_graphics::{
+    image::{Image, ImageRaw},
+    pixelcolor::BinaryColor,
+    prelude::*,
+    text::{Baseline, Text},
+};
+pub use esp_buddy_protocol as protocol;
+
+#[cfg(feature = "esp32")]
+mod esp_wifi;
+
+#[cfg(feature = "esp32")]
+pub use self::esp_wifi::{receive, serve};
+
+use crate::{framebuffer::Framebuffer, style};
+use protocol::{Error, Packet, LENGTH_PREFIX_LEN, MAX_PACKET_LEN};
+
+/// Content of the remote display.
+#[derive(Clone, Debug, Default)]
+pub struct Panel {
+    frame: Framebuffer,
+}
+
+impl Panel {
+    pub const fn new() -> Self {
+        Self {
+            frame: Framebuffer::new(),
+        }
+    }
+
+    /// Shows the frame or text of `packet`. The panel stays as it was if the
+    /// packet is invalid.
+    pub fn handle(&mut self, packet: &[u8]) -> Result<(), Error> {
+        match Packet::decode(packet)? {
+            Packet::Text(text) => {
+                self.frame = Framebuffer::new();
+                Text::with_baseline(text, Point::zero(), style::TEXT, Baseline::Top)
+                    .draw(&mut self.frame)
+                    .ok();
+            }
+            packet => {
+                let mut frame = *self.frame.as_bytes();
+                packet.frame(&mut frame)?;
+                self.frame = Framebuffer::from_bytes(frame);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn framebuffer(&self) -> &Framebuffer {
+        &self.frame
+    }
+
+    pub fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let raw = ImageRaw::<BinaryColor>::new(self.frame.as_bytes(), protocol::WIDTH as u32);
+        Image::new(&raw, Point::zero()).draw(target)
+    }
+}
+
+/// Reassembles the packets of a TCP connection.
+#[derive(Clone, Debug)]
+pub struct StreamReader {
+    buf: [u8; LENGTH_PREFIX_LEN + MAX_PACKET_LEN],
+    len: usize,
+}
+
+impl StreamReader {
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; LENGTH_PREFIX_LEN + MAX_PACKET_LEN],
+            len: 0,
+        }
+    }
+
+    /// Space to receive the next bytes into.
+    pub fn spare(&mut self) -> &mut [u8] {
+        &mut self.buf[self.len..]
+    }
+
+    /// Takes `read` more bytes from [`StreamReader::spare`] and hands the
+    /// complete packets to `panel`. Returns whether one was shown.
+    ///
+    /// An invalid length prefix loses track of the packets, the connection
+    /// should be closed then.
+    pub fn advance(&mut self, read: usize, panel: &mut Panel) -> Result<bool, Error> {
+        self.len = (self.len + read).min(self.buf.len());
+        let mut start = 0;
+        let mut shown = false;
+        while let Some((packet, len)) = protocol::split_stream(&self.buf[start..self.len])? {
+            // A bad packet only loses itself
+            shown |= panel.handle(packet).is_ok();
+            start += len;
+        }
+        self.buf.copy_within(start..self.len, 0);
+        self.len -= start;
+        Ok(shown)
+    }
+
+    /// Forgets a partial packet, for a new connection.
+    pub fn reset(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl Default for StreamReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,349 @@
+//! I2C bus diagnostics.
+//!
+//! [`scan`] finds everything that acknowledges its address, like
+//! `i2cdetect`: a write without data to every address from
+//! [`FIRST_ADDRESS`] to [`LAST_ADDRESS`]. [`name`] tells what usually sits at
+//! an address. [`Diagnostics`] keeps the devices found with their error
+//! rates, and dumps and writes their registers with the two buttons:
+//!
+//! - list: left selects the next device, right opens its registers, holding
+//!   right scans again
+//! - registers: left selects the next register, holding it skips a row of
+//!   8 at a time, right edits the register, holding right goes back
+//! - edit: left increments the value, holding it counts up by 16, right
+//!   writes the value, holding right cancels
+//!
+//! Registers are read one at a time with a write of the register address and
+//! a read, which works for most chips with 8-bit registers.
+
+use crate::{screens::ButtonState, sensors::I2c};
+
+/// Lowest address that is not reserved.
+pub const FIRST_ADDRESS: u8 = 0x08;
+/// Highest address that is not reserved.
+pub const LAST_ADDRESS: u8 = 0x77;
+
+/// Most devices kept by [`Diagnostics`], every address that can answer.
+pub const MAX_DEVICES: usize = (LAST_ADDRESS - FIRST_ADDRESS + 1) as usize;
+
+/// How long a button is held down before it counts as held.
+pub const HOLD_MS: u64 = 800;
+/// How often a held left button repeats.
+pub const REPEAT_MS: u64 = 200;
+/// How often [`Diagnostics::update`] checks the devices again.
+pub const CHECK_INTERVAL_MS: u64 = 100;
+
+/// Chips commonly found at each address, from the boards and breakouts used
+/// with the ESP-Buddy.
+const KNOWN: [(u8, &str); 22] = [
+    (0x1d, "ADXL345"),
+    (0x23, "BH1750"),
+    (0x29, "VL53L0X"),
+    (0x38, "AHT20"),
+    (0x39, "APDS-9960"),
+    (0x3c, "SSD1306"),
+    (0x3d, "SSD1306"),
+    (0x40, "INA219"),
+    (0x44, "SHT3x"),
+    (0x45, "SHT3x"),
+    (0x48, "ADS1115"),
+    (0x50, "EEPROM"),
+    (0x53, "ADXL345"),
+    (0x57, "EEPROM"),
+    (0x5c, "LPS22/BH1750"),
+    (0x5d, "LPS22"),
+    (0x5f, "HTS221"),
+    (0x62, "SCD4x"),
+    (0x68, "DS3231/MPU6050"),
+    (0x69, "MPU6050"),
+    (0x76, "BME280"),
+    (0x77, "BME280"),
+];
+
+/// What usually answers at `address`.
+pub fn name(address: u8) -> Option<&'static str> {
+    KNOWN
+        .iter()
+        .find(|(known, _)| *known == address)
+        .map(|(_, name)| *name)
+}
+
+/// Addresses that acknowledge, ascending.
+pub fn scan<I: I2c>(i2c: &mut I) -> heapless::Vec<u8, MAX_DEVICES> {
+    (FIRST_ADDRESS..=LAST_ADDRESS)
+        .filter(|&address| i2c.write(address, &[]).is_ok())
+        .collect()
+}
+
+/// A device found on the bus, with the transfers to it since.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Device {
+    pub address: u8,
+    pub name: Option<&'static str>,
+    pub transfers: u32,
+    /// Transfers that were not acknowledged.
+    pub errors: u32,
+}
+
+impl Device {
+    fn new(address: u8) -> Self {
+        Self {
+            address,
+            name: name(address),
+            transfers: 0,
+            errors: 0,
+        }
+    }
+
+    /// Share of failed transfers in tenths of a percent.
+    pub fn error_rate_dpct(&self) -> u32 {
+        if self.transfers == 0 {
+            return 0;
+        }
+        (u64::from(self.errors) * 1000 / u64::from(self.transfers)) as u32
+    }
+
+    fn record<T, E>(&mut self, result: Result<T, E>) -> Option<T> {
+        self.transfers += 1;
+        if result.is_err() {
+            self.errors += 1;
+        }
+        result.ok()
+    }
+}
+
+/// What the buttons act on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum View {
+    List,
+    Registers,
+    /// Value to write to the selected register.
+    Edit(u8),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Gesture {
+    /// Released before [`HOLD_MS`].
+    Click,
+    /// Held for [`HOLD_MS`], then again every [`REPEAT_MS`], counting from 1.
+    Hold(u32),
+}
+
+/// Clicks and holds of one button.
+#[derive(Clone, Copy, Debug, Default)]
+struct Button {
+    down_ms: Option<u64>,
+    holds: u32,
+}
+
+impl Button {
+    fn update(&mut self, down: bool, now_ms: u64) -> Option<Gesture> {
+        match (down, self.down_ms) {
+            (true, None) => {
+                self.down_ms = Some(now_ms);
+                self.holds = 0;
+                None
+            }
+            (true, Some(down_ms)) => {
+                let due_ms = HOLD_MS + u64::from(self.holds) * REPEAT_MS;
+                (now_ms - down_ms >= due_ms).then(|| {
+                    self.holds += 1;
+                    Gesture::Hold(self.holds)
+                })
+            }
+            (false, down_ms) => {
+                self.down_ms = None;
+                (down_ms.is_some() && self.holds == 0).then_some(Gesture::Click)
+            }
+        }
+    }
+}
+
+/// The diagnostics app: devices found, their error rates and registers.
+pub struct Diagnostics {
+    devices: heapless::Vec<Device, MAX_DEVICES>,
+    scans: u32,
+    selected: usize,
+    view: View,
+    register: u8,
+    /// Last values read, `None` where the read failed.
+    registers: [Option<u8>; 256],
+    left: Button,
+    right: Button,
+    next_check_ms: u64,
+}
+
+impl Default for Diagnostics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self {
+            devices: heapless::Vec::new(),
+            scans: 0,
+            selected: 0,
+            view: View::List,
+            register: 0,
+            registers: [None; 256],
+            left: Button::default(),
+            right: Button::default(),
+            next_check_ms: 0,
+        }
+    }
+
+    pub fn devices(&self) -> &[Device] {
+        &self.devices
+    }
+
+    /// Scans done, the first at [`Diagnostics::scan`].
+    pub fn scans(&self) -> u32 {
+        self.scans
+    }
+
+    pub fn selected(&self) -> Option<&Device> {
+        self.devices.get(self.selected)
+    }
+
+    pub fn view(&self) -> View {
+        self.view
+    }
+
+    /// Register selected in the register and edit views.
+    pub fn register(&self) -> u8 {
+        self.register
+    }
+
+    /// Value of `register` of the selected device as last read.
+    pub fn value(&self, register: u8) -> Option<u8> {
+        self.registers[register as usize]
+    }
+
+    /// Scans the bus. Devices found before keep their counts, and stay in
+    /// the list when they do not answer, with the failed probe counted.
+    pub fn scan<I: I2c>(&mut self, i2c: &mut I) {
+        let found = scan(i2c);
+        let selected = self.selected().map(|device| device.address);
+        self.scans += 1;
+        for device in &mut self.devices {
+            device.record(if found.contains(&device.address) {
+                Ok(())
+            } else {
+                Err(())
+            });
+        }
+        for &address in &found {
+            if self.devices.iter().all(|device| device.address != address) {
+                let mut device = Device::new(address);
+                device.record(Ok::<(), ()>(()));
+                // Cannot be full, there is room for every address
+                self.devices.push(device).ok();
+            }
+        }
+        self.devices.sort_unstable_by_key(|device| device.address);
+        self.selected = selected
+            .and_then(|address| {
+                self.devices
+                    .iter()
+                    .position(|device| device.address == address)
+            })
+            .unwrap_or(0);
+    }
+
+    /// Probes every device found once more.
+    pub fn check<I: I2c>(&mut self, i2c: &mut I) {
+        for device in &mut self.devices {
+            device.record(i2c.write(device.address, &[]));
+        }
+    }
+
+    /// Reads every register of the selected device.
+    pub fn dump<I: I2c>(&mut self, i2c: &mut I) {
+        let Some(device) = self.devices.get_mut(self.selected) else {
+            return;
+        };
+        for (register, value) in self.registers.iter_mut().enumerate() {
+            let mut buf = [0u8];
+            *value = device
+                .record(i2c.write_read(device.address, &[register as u8], &mut buf))
+                .map(|()| buf[0]);
+        }
+    }
+
+    /// Writes `value` to the selected register and reads it back.
+    pub fn write<I: I2c>(&mut self, i2c: &mut I, value: u8) {
+        let register = self.register;
+        let Some(device) = self.devices.get_mut(self.selected) else {
+            return;
+        };
+        device.record(i2c.write(device.address, &[register, value]));
+        let mut buf = [0u8];
+        self.registers[register as usize] = device
+            .record(i2c.write_read(device.address, &[register], &mut buf))
+            .map(|()| buf[0]);
+    }
+
+    /// Handles the buttons and checks the devices every
+    /// [`CHECK_INTERVAL_MS`] in the list. Returns whether the screen
+    /// changed.
+    pub fn update<I: I2c>(&mut self, i2c: &mut I, buttons: ButtonState, now_ms: u64) -> bool {
+        let left = self.left.update(buttons.left, now_ms);
+        let right = self.right.update(buttons.right, now_ms);
+        let mut changed = left.is_some() || right.is_some();
+
+        match self.view {
+            View::List => {
+                if left == Some(Gesture::Click) && !self.devices.is_empty() {
+                    self.selected = (self.selected + 1) % self.devices.len();
+                }
+                match right {
+                    Some(Gesture::Click) if !self.devices.is_empty() => {
+                        self.register = 0;
+                        self.dump(i2c);
+                        self.view = View::Registers;
+                    }
+                    Some(Gesture::Hold(1)) => self.scan(i2c),
+                    _ => {}
+                }
+                if now_ms >= self.next_check_ms {
+                    self.next_check_ms = now_ms + CHECK_INTERVAL_MS;
+                    self.check(i2c);
+                    changed = true;
+                }
+            }
+            View::Registers => {
+                match left {
+                    Some(Gesture::Click) => self.register = self.register.wrapping_add(1),
+                    Some(Gesture::Hold(_)) => self.register = (self.register & !7).wrapping_add(8),
+                    None => {}
+                }
+                match right {
+                    Some(Gesture::Click) => {
+                        self.view = View::Edit(self.value(self.register).unwrap_or(0));
+                    }
+                    Some(Gesture::Hold(1)) => self.view = View::List,
+                    _ => {}
+                }
+            }
+            View::Edit(value) => {
+                let value = match left {
+                    Some(Gesture::Click) => value.wrapping_add(1),
+                    Some(Gesture::Hold(_)) => value.wrapping_add(0x10),
+                    None => value,
+                };
+                self.view = View::Edit(value);
+                match right {
+                    Some(Gesture::Click) => {
+                        self.write(i2c, value);
+                        self.view = View::Registers;
+                    }
+                    Some(Gesture::Hold(1)) => self.view = View::Registers,
+                    _ => {}
+                }
+            }
+        }
+        changed
+    }
+}
@@ -0,0 +1,184 @@
+//! Ringing alarm, alarm editor and timer readouts.
+
+use core::fmt::Write;
+
+use embedded_graphics::{
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::{Line, PrimitiveStyle},
+    text::{Baseline, Text},
+};
+
+use crate::{
+    alarm::{Alarm, Weekdays},
+    style,
+};
+
+fn days_label(days: Weekdays) -> &'static str {
+    match days {
+        Weekdays::NONE => "Once",
+        Weekdays::WORKDAYS => "Mon-Fri",
+        Weekdays::WEEKEND => "Sat-Sun",
+        Weekdays::EVERY_DAY => "Daily",
+        _ => "Custom",
+    }
+}
+
+fn draw_hh_mm<D>(target: &mut D, alarm: &Alarm, position: Point) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    let mut text: heapless::String<8> = heapless::String::new();
+    write!(text, "{:02}:{:02}", alarm.hour, alarm.minute).unwrap();
+    Text::with_baseline(&text, position, style::LARGE, Baseline::Top).draw(target)?;
+
+    Ok(())
+}
+
+/// Shown while an alarm rings, the firmware flashes it by inverting the display.
+pub fn draw_ringing<D>(target: &mut D, alarm: &Alarm) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    draw_hh_mm(target, alarm, Point::zero())?;
+    Text::with_baseline("ALARM", Point::new(64, 5), style::TEXT, Baseline::Top).draw(target)?;
+    Text::with_baseline(
+        "L:snooze R:dismiss",
+        Point::new(0, 22),
+        style::TEXT,
+        Baseline::Top,
+    )
+    .draw(target)?;
+
+    Ok(())
+}
+
+/// Label above a duration, `MM:SS.t` below an hour and `H:MM:SS` above.
+pub fn draw_duration<D>(target: &mut D, label: &str, ms: u64) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    Text::with_baseline(label, Point::zero(), style::TEXT, Baseline::Top).draw(target)?;
+
+    let seconds = ms / 1000;
+    let mut text: heapless::String<16> = heapless::String::new();
+    if seconds < 3600 {
+        write!(
+            text,
+            "{:02}:{:02}.{}",
+            seconds / 60,
+            seconds % 60,
+            ms % 1000 / 100
+        )
+        .unwrap();
+    } else {
+        write!(
+            text,
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+        .unwrap();
+    }
+    Text::with_baseline(&text, Point::new(0, 12), style::LARGE, Baseline::Top).draw(target)?;
+
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Field {
+    Hour,
+    Minute,
+    Days,
+    Enabled,
+}
+
+/// Sets an alarm with two buttons: one moves through the fields, the other
+/// increments the selected field.
+pub struct AlarmEditor {
+    alarm: Alarm,
+    field: Field,
+}
+
+impl AlarmEditor {
+    const DAYS: [Weekdays; 4] = [
+        Weekdays::NONE,
+        Weekdays::WORKDAYS,
+        Weekdays::WEEKEND,
+        Weekdays::EVERY_DAY,
+    ];
+
+    pub fn new(alarm: Alarm) -> Self {
+        Self {
+            alarm,
+            field: Field::Hour,
+        }
+    }
+
+    pub fn alarm(&self) -> Alarm {
+        self.alarm
+    }
+
+    pub fn field(&self) -> Field {
+        self.field
+    }
+
+    /// Selects the next field, returns `true` when done with the last one.
+    pub fn next_field(&mut self) -> bool {
+        self.field = match self.field {
+            Field::Hour => Field::Minute,
+            Field::Minute => Field::Days,
+            Field::Days => Field::Enabled,
+            Field::Enabled => {
+                self.field = Field::Hour;
+                return true;
+            }
+        };
+        false
+    }
+
+    /// Increments the selected field, wrapping around.
+    pub fn increment(&mut self) {
+        let alarm = &mut self.alarm;
+        match self.field {
+            Field::Hour => alarm.hour = (alarm.hour + 1) % 24,
+            Field::Minute => alarm.minute = (alarm.minute + 1) % 60,
+            Field::Days => {
+                // Custom sets start over with the first preset
+                let next = Self::DAYS
+                    .iter()
+                    .position(|&days| days == alarm.days)
+                    .map_or(0, |index| (index + 1) % Self::DAYS.len());
+                alarm.days = Self::DAYS[next];
+            }
+            Field::Enabled => alarm.enabled = !alarm.enabled,
+        }
+    }
+
+    pub fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        Text::with_baseline("Set alarm", Point::zero(), style::TEXT, Baseline::Top).draw(target)?;
+        draw_hh_mm(target, &self.alarm, Point::new(0, 12))?;
+
+        let days = days_label(self.alarm.days);
+        let enabled = if self.alarm.enabled { "On" } else { "Off" };
+        Text::with_baseline(days, Point::new(64, 11), style::TEXT, Baseline::Top).draw(target)?;
+        Text::with_baseline(enabled, Point::new(64, 21), style::TEXT, Baseline::Top)
+            .draw(target)?;
+
+        let (x, y, width) = match self.field {
+            Field::Hour => (0, 31, 20),
+            Field::Minute => (30, 31, 20),
+            Field::Days => (64, 20, 6 * days.len() as i32),
+            Field::Enabled => (64, 31, 6 * enabled.len() as i32),
+        };
+        Line::new(Point::new(x, y), Point::new(x + width - 1, y))
+            .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+            .draw(target)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+use embedded_graphics::{
+    pixelcolor::BinaryColor,
+    prelude::*,
+    text::{Baseline, Text},
+};
+
+use super::{ButtonState, Scene};
+use crate::style;
+
+/// Two arrows passing each other under a title.
+pub struct Animation {
+    position_x: i32,
+}
+
+impl Animation {
+    const START_X: i32 = -30;
+    const END_X: i32 = 128;
+
+    pub fn new() -> Self {
+        Self {
+            position_x: Self::START_X,
+        }
+    }
+}
+
+impl Default for Animation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scene for Animation {
+    const FRAME_MS: u32 = 25;
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        Text::with_baseline(
+            "Maker Faire Brno 2023",
+            Point::zero(),
+            style::TEXT,
+            Baseline::Top,
+        )
+        .draw(target)?;
+
+        Text::with_baseline(
+            "_-=]>",
+            Point::new(self.position_x, 16),
+            style::TEXT,
+            Baseline::Top,
+        )
+        .draw(target)?;
+        Text::with_baseline(
+            "<[=-_",
+            Point::new(128 - self.position_x - 30, 16),
+            style::TEXT,
+            Baseline::Top,
+        )
+        .draw(target)?;
+
+        Ok(())
+    }
+
+    fn update(&mut self, _buttons: ButtonState) {
+        self.position_x += 1;
+        if self.position_x > Self::END_X {
+            self.position_x = Self::START_X;
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use core::fmt::Write;
+
+use embedded_graphics::{
+    pixelcolor::BinaryColor,
+    prelude::*,
+    text::{Alignment, Baseline, Text, TextStyleBuilder},
+};
+
+use super::{ButtonState, Scene, DISPLAY_SIZE};
+use crate::{
+    input::{Event, Input},
+    style,
+};
+
+/// Shows which of the two buttons is held down and the last gesture.
+#[derive(Default)]
+pub struct Buttons {
+    state: ButtonState,
+    input: Input,
+    /// Time of the last [`Scene::update`], counted in frames.
+    now_ms: u64,
+    last: Option<Event>,
+}
+
+impl Buttons {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn input(&self) -> &Input {
+        &self.input
+    }
+
+    /// Last click, double click, long press, repeat or chord.
+    pub fn last(&self) -> Option<Event> {
+        self.last
+    }
+
+    /// Takes the levels at `now_ms`. Returns whether the screen changed.
+    pub fn update_at(&mut self, buttons: ButtonState, now_ms: u64) -> bool {
+        let mut changed = buttons != self.state;
+        self.state = buttons;
+        self.input.update(buttons, now_ms);
+        while let Some(event) = self.input.pop() {
+            if !matches!(event, Event::Pressed(_) | Event::Released(_)) {
+                self.last = Some(event);
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+impl Scene for Buttons {
+    const FRAME_MS: u32 = 30;
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        Text::with_baseline("Buttons example", Point::zero(), style::TEXT, Baseline::Top)
+            .draw(target)?;
+
+        if self.state.left {
+            Text::with_baseline("Left", Point::new(0, 16), style::TEXT, Baseline::Top)
+                .draw(target)?;
+        }
+
+        if self.state.right {
+            Text::with_baseline("Right", Point::new(60, 16), style::TEXT, Baseline::Top)
+                .draw(target)?;
+        }
+
+        if let Some(event) = self.last {
+            let mut text: heapless::String<20> = heapless::String::new();
+            write!(text, "{}", event).unwrap();
+            let bottom_right = Point::new(DISPLAY_SIZE.width as i32 - 1, 26);
+            let text_style = TextStyleBuilder::new()
+                .alignment(Alignment::Right)
+                .baseline(Baseline::Top)
+                .build();
+            Text::with_text_style(&text, bottom_right, style::SMALL, text_style).draw(target)?;
+        }
+
+        Ok(())
+    }
+
+    fn update(&mut self, buttons: ButtonState) {
+        self.now_ms += u64::from(Self::FRAME_MS);
+        self.update_at(buttons, self.now_ms);
+    }
+}
@@ -0,0 +1,51 @@
+use embedded_graphics::{pixelcolor::BinaryColor, prelude::*};
+
+use super::{ButtonState, Scene};
+use crate::gfx::sprite::{Bitmap, Sprite};
+
+/// Truck with its trailer, a PBM image that `scripts/sprite.sh` makes from a PNG.
+const TRUCK: Bitmap<'static> = Bitmap::from_pbm(include_bytes!("car.pbm"));
+
+/// Espressif truck driving across the screen.
+pub struct Car {
+    truck: Sprite<'static>,
+}
+
+impl Car {
+    /// Left of the display, the truck drives in from there.
+    const START_X: i32 = -128;
+    /// The truck is out of sight past this.
+    const END_X: i32 = 135;
+    const TOP: i32 = 1;
+
+    pub fn new() -> Self {
+        Self {
+            truck: Sprite::new(TRUCK).with_position(Point::new(Self::START_X, Self::TOP)),
+        }
+    }
+}
+
+impl Default for Car {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scene for Car {
+    const FRAME_MS: u32 = 25;
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        self.truck.draw(target)
+    }
+
+    fn update(&mut self, _buttons: ButtonState) {
+        self.truck.move_by(Point::new(1, 0));
+        if self.truck.position().x > Self::END_X {
+            self.truck
+                .set_position(Point::new(Self::START_X, Self::TOP));
+        }
+    }
+}
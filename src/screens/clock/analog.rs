@@ -0,0 +1,92 @@
+use core::fmt::Write;
+
+use embedded_graphics::{
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::{Circle, Line, PrimitiveStyle},
+    text::{Baseline, Text},
+};
+
+use super::{ClockTime, Face};
+use crate::{style, time::month_short_name};
+
+/// sin(6° * i) * 1000 for the first quarter of the dial.
+const SINE: [i32; 16] = [
+    0, 105, 208, 309, 407, 500, 588, 669, 743, 809, 866, 914, 951, 978, 995, 1000,
+];
+
+/// Sine of a dial position 0-59, scaled by 1000.
+fn sine(position: u32) -> i32 {
+    let position = (position % 60) as usize;
+    match position {
+        0..=15 => SINE[position],
+        16..=30 => SINE[30 - position],
+        31..=45 => -SINE[position - 30],
+        _ => -SINE[60 - position],
+    }
+}
+
+/// Point `length` pixels from `center` towards dial position 0-59.
+fn dial_point(center: Point, position: u32, length: i32) -> Point {
+    let scale = |value: i32| (value * length + value.signum() * 500) / 1000;
+    center + Point::new(scale(sine(position)), -scale(sine(position + 15)))
+}
+
+/// Dial with hour, minute and second hands next to time and date.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Analog;
+
+impl Analog {
+    const CENTER: Point = Point::new(15, 15);
+    const RADIUS: i32 = 15;
+}
+
+impl Face for Analog {
+    fn draw<D>(&self, target: &mut D, time: &ClockTime) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let time = time.local.date_time;
+        let stroke = PrimitiveStyle::with_stroke(BinaryColor::On, 1);
+
+        Circle::with_center(Self::CENTER, 2 * Self::RADIUS as u32 + 1)
+            .into_styled(stroke)
+            .draw(target)?;
+        for hour in 0..12 {
+            Pixel(
+                dial_point(Self::CENTER, hour * 5, Self::RADIUS - 3),
+                BinaryColor::On,
+            )
+            .draw(target)?;
+        }
+
+        let hour_position = (time.hour as u32 % 12) * 5 + time.minute as u32 / 12;
+        let hands = [
+            (hour_position, Self::RADIUS - 8),
+            (time.minute as u32, Self::RADIUS - 4),
+            (time.second as u32, Self::RADIUS - 2),
+        ];
+        for (position, length) in hands {
+            Line::new(Self::CENTER, dial_point(Self::CENTER, position, length))
+                .into_styled(stroke)
+                .draw(target)?;
+        }
+
+        let mut text: heapless::String<16> = heapless::String::new();
+        write!(text, "{:02}:{:02}", time.hour, time.minute).unwrap();
+        Text::with_baseline(&text, Point::new(44, 0), style::LARGE, Baseline::Top).draw(target)?;
+
+        text.clear();
+        write!(
+            text,
+            "{} {} {}",
+            time.weekday.short_name(),
+            time.day,
+            month_short_name(time.month)
+        )
+        .unwrap();
+        Text::with_baseline(&text, Point::new(44, 21), style::TEXT, Baseline::Top).draw(target)?;
+
+        Ok(())
+    }
+}
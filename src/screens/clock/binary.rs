@@ -0,0 +1,58 @@
+use embedded_graphics::{
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::{Circle, PrimitiveStyle},
+};
+
+use super::{ClockTime, Face};
+
+/// Binary coded decimal clock, one column of bits per digit of `HH:MM:SS`.
+///
+/// Set bits are filled dots, the most significant bit is at the top and
+/// only bits a digit can use are drawn.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Binary;
+
+impl Binary {
+    const DOT: u32 = 6;
+    const PITCH: i32 = 8;
+    /// Extra space between hours, minutes and seconds.
+    const GAP: i32 = 6;
+    const LEFT: i32 = (128 - (6 * Self::PITCH + 2 * Self::GAP)) / 2;
+}
+
+impl Face for Binary {
+    fn draw<D>(&self, target: &mut D, time: &ClockTime) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let time = time.local.date_time;
+        let filled = PrimitiveStyle::with_fill(BinaryColor::On);
+        let empty = PrimitiveStyle::with_stroke(BinaryColor::On, 1);
+
+        let pairs = [(time.hour, 2), (time.minute, 3), (time.second, 3)];
+        for (group, (value, tens_bits)) in pairs.into_iter().enumerate() {
+            for (column, (digit, bits)) in [(value / 10, tens_bits), (value % 10, 4)]
+                .into_iter()
+                .enumerate()
+            {
+                let x = Self::LEFT
+                    + (group * 2 + column) as i32 * Self::PITCH
+                    + group as i32 * Self::GAP;
+                for bit in 0..bits {
+                    let y = (3 - bit) * Self::PITCH;
+                    let style = if digit & (1 << bit) != 0 {
+                        filled
+                    } else {
+                        empty
+                    };
+                    Circle::new(Point::new(x, y + 1), Self::DOT)
+                        .into_styled(style)
+                        .draw(target)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
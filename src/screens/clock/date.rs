@@ -0,0 +1,51 @@
+use core::fmt::Write;
+
+use embedded_graphics::{
+    pixelcolor::BinaryColor,
+    prelude::*,
+    text::{Alignment, Baseline, Text, TextStyle, TextStyleBuilder},
+};
+
+use super::{ClockTime, Face};
+use crate::{style, time::month_short_name};
+
+const CENTERED: TextStyle = TextStyleBuilder::new()
+    .alignment(Alignment::Center)
+    .baseline(Baseline::Top)
+    .build();
+
+/// Time above weekday and date, e.g. `Fri 18 Oct 2024`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DateWeekday;
+
+impl Face for DateWeekday {
+    fn draw<D>(&self, target: &mut D, time: &ClockTime) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let time = time.local.date_time;
+        let mut text: heapless::String<24> = heapless::String::new();
+
+        write!(
+            text,
+            "{:02}:{:02}:{:02}",
+            time.hour, time.minute, time.second
+        )
+        .unwrap();
+        Text::with_text_style(&text, Point::new(64, 0), style::LARGE, CENTERED).draw(target)?;
+
+        text.clear();
+        write!(
+            text,
+            "{} {} {} {}",
+            time.weekday.short_name(),
+            time.day,
+            month_short_name(time.month),
+            time.year
+        )
+        .unwrap();
+        Text::with_text_style(&text, Point::new(64, 21), style::TEXT, CENTERED).draw(target)?;
+
+        Ok(())
+    }
+}
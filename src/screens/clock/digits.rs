@@ -0,0 +1,17 @@
+use embedded_graphics::{pixelcolor::BinaryColor, prelude::*};
+
+use super::{draw_time, ClockTime, Face};
+
+/// `HH:MM:SS` in the large font.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LargeDigits;
+
+impl Face for LargeDigits {
+    fn draw<D>(&self, target: &mut D, time: &ClockTime) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let time = time.local.date_time;
+        draw_time(target, time.hour, time.minute, time.second)
+    }
+}
@@ -0,0 +1,69 @@
+use core::fmt::Write;
+
+use embedded_graphics::{
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::{Line, PrimitiveStyle},
+    text::{Baseline, Text},
+};
+
+use super::{ClockTime, Face};
+use crate::{
+    style,
+    time::{LocalTime, TimeZone},
+};
+
+/// Local time above the time of a second zone, UTC by default.
+#[derive(Clone, Copy, Debug)]
+pub struct DualZone {
+    other: TimeZone<'static>,
+}
+
+impl Default for DualZone {
+    fn default() -> Self {
+        Self::new(TimeZone::UTC)
+    }
+}
+
+impl DualZone {
+    pub fn new(other: TimeZone<'static>) -> Self {
+        Self { other }
+    }
+}
+
+/// `CEST  Fri 14:05:09`, the weekday tells when the zones are a day apart.
+/// Abbreviations longer than five characters are cut to fit the row.
+fn draw_row<D>(target: &mut D, local: &LocalTime, y: i32) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    let time = local.date_time;
+    let mut text: heapless::String<24> = heapless::String::new();
+    write!(
+        text,
+        "{:<5.5} {} {:02}:{:02}:{:02}",
+        local.abbreviation,
+        time.weekday.short_name(),
+        time.hour,
+        time.minute,
+        time.second
+    )
+    .unwrap();
+
+    Text::with_baseline(&text, Point::new(0, y), style::TEXT, Baseline::Top).draw(target)?;
+
+    Ok(())
+}
+
+impl Face for DualZone {
+    fn draw<D>(&self, target: &mut D, time: &ClockTime) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        draw_row(target, &time.local, 2)?;
+        Line::new(Point::new(0, 15), Point::new(127, 15))
+            .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+            .draw(target)?;
+        draw_row(target, &self.other.local(time.timestamp), 18)
+    }
+}
@@ -0,0 +1,215 @@
+//! Clock faces.
+//!
+//! Each layout is a [`Face`]; a tuple of faces forms the [`Faces`] chosen at
+//! build time and the [`FaceCycle`] switches between them with the left
+//! button. Own faces only need to implement [`Face`]:
+//!
+//! ```ignore
+//! let mut faces = FaceCycle::new((LargeDigits, Analog, MyFace));
+//! ```
+
+use core::fmt::Write;
+
+use embedded_graphics::{
+    pixelcolor::BinaryColor,
+    prelude::*,
+    text::{Baseline, Text},
+};
+
+use super::{ButtonState, Presses, Scene};
+use crate::{
+    style,
+    time::{LocalTime, TimeZone},
+};
+
+mod analog;
+mod binary;
+mod date;
+mod digits;
+mod dual;
+
+pub use analog::Analog;
+pub use binary::Binary;
+pub use date::DateWeekday;
+pub use digits::LargeDigits;
+pub use dual::DualZone;
+
+/// Draws `HH:MM:SS` in the large font.
+pub fn draw_time<D>(target: &mut D, hours: u8, minutes: u8, seconds: u8) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    let mut text: heapless::String<16> = heapless::String::new();
+    write!(text, "{:02}:{:02}:{:02}", hours, minutes, seconds).unwrap();
+
+    Text::with_baseline(&text, Point::zero(), style::LARGE, Baseline::Top).draw(target)?;
+
+    Ok(())
+}
+
+/// Instant shown by a face.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClockTime<'a> {
+    /// Seconds since the Unix epoch.
+    pub timestamp: i64,
+    pub local: LocalTime<'a>,
+}
+
+impl<'a> ClockTime<'a> {
+    pub fn new(timestamp: i64, time_zone: &TimeZone<'a>) -> Self {
+        Self {
+            timestamp,
+            local: time_zone.local(timestamp),
+        }
+    }
+}
+
+/// One clock layout.
+pub trait Face {
+    /// Draws the face on a cleared target.
+    fn draw<D>(&self, target: &mut D, time: &ClockTime) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>;
+}
+
+/// Ordered set of faces, implemented for tuples of up to eight [`Face`]s.
+pub trait Faces {
+    const COUNT: usize;
+
+    /// Draws the face at `index`, nothing when out of range.
+    fn draw_face<D>(&self, index: usize, target: &mut D, time: &ClockTime) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>;
+}
+
+macro_rules! impl_faces {
+    ($($face:ident $index:tt),+) => {
+        impl<$($face: Face),+> Faces for ($($face,)+) {
+            const COUNT: usize = [$($index),+].len();
+
+            fn draw_face<D>(&self, index: usize, target: &mut D, time: &ClockTime) -> Result<(), D::Error>
+            where
+                D: DrawTarget<Color = BinaryColor>,
+            {
+                match index {
+                    $($index => self.$index.draw(target, time),)+
+                    _ => Ok(()),
+                }
+            }
+        }
+    };
+}
+
+impl_faces!(A 0);
+impl_faces!(A 0, B 1);
+impl_faces!(A 0, B 1, C 2);
+impl_faces!(A 0, B 1, C 2, E 3);
+impl_faces!(A 0, B 1, C 2, E 3, F 4);
+impl_faces!(A 0, B 1, C 2, E 3, F 4, G 5);
+impl_faces!(A 0, B 1, C 2, E 3, F 4, G 5, H 6);
+impl_faces!(A 0, B 1, C 2, E 3, F 4, G 5, H 6, I 7);
+
+/// All built-in faces, in the order the left button cycles through them.
+pub type DefaultFaces = (LargeDigits, DateWeekday, Analog, Binary, DualZone);
+
+/// Switches to the next face each time the left button goes down.
+pub struct FaceCycle<F> {
+    faces: F,
+    current: usize,
+    presses: Presses,
+}
+
+impl<F: Faces> FaceCycle<F> {
+    pub fn new(faces: F) -> Self {
+        Self {
+            faces,
+            current: 0,
+            presses: Presses::new(),
+        }
+    }
+
+    /// Starts with the face at `index` instead of the first one.
+    pub fn with_current(mut self, index: usize) -> Self {
+        self.current = index % F::COUNT;
+        self
+    }
+
+    /// Index of the face shown.
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    pub fn next(&mut self) {
+        self.current = (self.current + 1) % F::COUNT;
+    }
+
+    /// Returns whether the face changed, holding the button does not repeat.
+    pub fn update(&mut self, buttons: ButtonState) -> bool {
+        let pressed = self.presses.update(buttons).left;
+        if pressed {
+            self.next();
+        }
+        pressed
+    }
+
+    pub fn draw<D>(&self, target: &mut D, time: &ClockTime) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        self.faces.draw_face(self.current, target, time)
+    }
+}
+
+/// Clock ticking from a starting Unix timestamp, faces switched by the left button.
+pub struct Clock<F = DefaultFaces> {
+    timestamp: i64,
+    time_zone: TimeZone<'static>,
+    faces: FaceCycle<F>,
+    frame: u32,
+}
+
+impl Clock {
+    /// Clock showing UTC on the built-in faces.
+    pub fn new(timestamp: i64) -> Self {
+        Self::with_faces(timestamp, DefaultFaces::default())
+    }
+}
+
+impl<F: Faces> Clock<F> {
+    pub fn with_faces(timestamp: i64, faces: F) -> Self {
+        Self {
+            timestamp,
+            time_zone: TimeZone::UTC,
+            faces: FaceCycle::new(faces),
+            frame: 0,
+        }
+    }
+
+    /// Shows local time of `time_zone` instead of UTC.
+    pub fn with_time_zone(mut self, time_zone: TimeZone<'static>) -> Self {
+        self.time_zone = time_zone;
+        self
+    }
+}
+
+impl<F: Faces> Scene for Clock<F> {
+    const FRAME_MS: u32 = 50;
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        self.faces
+            .draw(target, &ClockTime::new(self.timestamp, &self.time_zone))
+    }
+
+    fn update(&mut self, buttons: ButtonState) {
+        self.faces.update(buttons);
+
+        self.frame += 1;
+        if self.frame == 1000 / Self::FRAME_MS {
+            self.frame = 0;
+            self.timestamp += 1;
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use core::fmt::{self, Write};
+
+use embedded_graphics::{
+    pixelcolor::BinaryColor,
+    prelude::*,
+    text::{Alignment, Baseline, Text, TextStyle, TextStyleBuilder},
+};
+
+use super::DISPLAY_SIZE;
+use crate::{psychrometrics::Climate, sensors::Tenths, style};
+
+const TOP_RIGHT: TextStyle = TextStyleBuilder::new()
+    .alignment(Alignment::Right)
+    .baseline(Baseline::Top)
+    .build();
+
+/// Left and right edge of the two columns of metrics.
+const COLUMNS: [(i32, i32); 2] = [(0, 61), (66, DISPLAY_SIZE.width as i32 - 1)];
+
+/// Top of the two rows of metrics.
+const ROWS: [i32; 2] = [14, 23];
+
+/// Draws the comfort class and temperature, with humidity, absolute
+/// humidity, dew point and heat index below.
+pub fn draw<D>(target: &mut D, climate: &Climate) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    Text::with_baseline(
+        climate.comfort.label(),
+        Point::zero(),
+        style::TEXT,
+        Baseline::Top,
+    )
+    .draw(target)?;
+    let mut temperature: heapless::String<12> = heapless::String::new();
+    write!(temperature, "{} C", Tenths(climate.temperature_dc)).unwrap();
+    let right = Point::new(COLUMNS[1].1, 0);
+    Text::with_text_style(&temperature, right, style::TEXT, TOP_RIGHT).draw(target)?;
+
+    let humidity = Tenths(climate.humidity_dpct);
+    let absolute = Tenths(climate.absolute_humidity_dg);
+    let heat_index = Tenths(climate.heat_index_dc);
+    cell(target, (0, 0), "Humidity", format_args!("{}%", humidity))?;
+    cell(target, (0, 1), "Abs hum", format_args!("{}g/m3", absolute))?;
+    match climate.dew_point_dc {
+        Some(dew_point) => cell(
+            target,
+            (1, 0),
+            "Dew pt",
+            format_args!("{} C", Tenths(dew_point)),
+        ),
+        None => cell(target, (1, 0), "Dew pt", format_args!("-- C")),
+    }?;
+    cell(target, (1, 1), "Feels", format_args!("{} C", heat_index))
+}
+
+/// Label left and value right in the cell at `(column, row)`.
+fn cell<D>(
+    target: &mut D,
+    (column, row): (usize, usize),
+    label: &str,
+    value: fmt::Arguments,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    let (left, right) = COLUMNS[column];
+    let y = ROWS[row];
+    let mut text: heapless::String<12> = heapless::String::new();
+    text.write_fmt(value).unwrap();
+    Text::with_baseline(label, Point::new(left, y), style::SMALL, Baseline::Top).draw(target)?;
+    Text::with_text_style(&text, Point::new(right, y), style::SMALL, TOP_RIGHT).draw(target)?;
+    Ok(())
+}
+
+/// Draws `message`, e.g. why the sensor has no reading, in place of the
+/// metrics.
+pub fn draw_error<D>(target: &mut D, message: &str) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    Text::with_baseline("Comfort", Point::zero(), style::TEXT, Baseline::Top).draw(target)?;
+    Text::with_baseline(message, Point::new(0, 16), style::TEXT, Baseline::Top).draw(target)?;
+    Ok(())
+}
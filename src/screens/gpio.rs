@@ -0,0 +1,45 @@
+use embedded_graphics::{
+    pixelcolor::BinaryColor,
+    prelude::*,
+    text::{Baseline, Text},
+};
+
+use crate::style;
+
+/// Draws the level of one GPIO into a grid of 16 columns below the title.
+pub fn gpio_state<D>(target: &mut D, gpio_number: i32, state: bool) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    let pos_y = (gpio_number / 16) * 10 + 10;
+    let text_state = {
+        if state {
+            "1"
+        } else {
+            "0"
+        }
+    };
+    Text::with_baseline(
+        text_state,
+        Point::new(8 * (gpio_number % 16), pos_y),
+        style::TEXT,
+        Baseline::Top,
+    )
+    .draw(target)?;
+
+    Ok(())
+}
+
+/// Draws the title and the `(grid position, level)` of every GPIO.
+pub fn draw<D>(target: &mut D, states: &[(i32, bool)]) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    Text::with_baseline("GPIOs example", Point::zero(), style::TEXT, Baseline::Top).draw(target)?;
+
+    for &(gpio_number, state) in states {
+        gpio_state(target, gpio_number, state)?;
+    }
+
+    Ok(())
+}
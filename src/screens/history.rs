@@ -0,0 +1,284 @@
+use core::fmt::Write;
+
+use embedded_graphics::{
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::{Line, PrimitiveStyle},
+    text::{Alignment, Baseline, Text, TextStyle, TextStyleBuilder},
+};
+
+use super::{ButtonState, Presses};
+use crate::{
+    history::{Bucket, History, MINUTE_MS},
+    sensors::Tenths,
+    settings::schema::Units,
+    style,
+};
+
+/// Size of a sparkline, below one line of [`style::TEXT`].
+pub const GRAPH_SIZE: Size = Size::new(128, 22);
+
+/// Axis labels, 3 characters of [`style::SMALL`] and a space.
+const LABEL_WIDTH: i32 = 13;
+
+/// Columns of the plot right of the labels and the axis.
+pub const PLOT_WIDTH: usize = GRAPH_SIZE.width as usize - LABEL_WIDTH as usize - 1;
+
+/// Smallest range of the axis in tenths, so noise does not fill the graph.
+const MIN_RANGE: i32 = 20;
+
+const TOP_RIGHT: TextStyle = TextStyleBuilder::new()
+    .alignment(Alignment::Right)
+    .baseline(Baseline::Top)
+    .build();
+
+const BOTTOM_RIGHT: TextStyle = TextStyleBuilder::new()
+    .alignment(Alignment::Right)
+    .baseline(Baseline::Bottom)
+    .build();
+
+/// Bounds of the axis in tenths: whole units around all columns, at least
+/// [`MIN_RANGE`] apart. `None` without any readings.
+pub fn axis_range(columns: &[Option<Bucket>]) -> Option<(i32, i32)> {
+    let merged = Bucket::merge(columns.iter().flatten().copied())?;
+    let mut low = i32::from(merged.min).div_euclid(10) * 10;
+    let mut high = (i32::from(merged.max) + 9).div_euclid(10) * 10;
+    while high - low < MIN_RANGE {
+        high += 10;
+        if high - low < MIN_RANGE {
+            low -= 10;
+        }
+    }
+    Some((low, high))
+}
+
+/// Draws `columns`, e.g. from [`History::downsample`] into [`PLOT_WIDTH`]
+/// columns, as a sparkline of [`GRAPH_SIZE`] at `top_left`.
+///
+/// Every column is a vertical line from its minimum to its maximum. The
+/// axis is scaled by [`axis_range`], with its bounds as labels on the left.
+pub fn draw_sparkline<D>(
+    target: &mut D,
+    top_left: Point,
+    columns: &[Option<Bucket>],
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    let stroke = PrimitiveStyle::with_stroke(BinaryColor::On, 1);
+    let height = GRAPH_SIZE.height as i32;
+    let axis_x = top_left.x + LABEL_WIDTH;
+    let bottom = top_left.y + height - 1;
+    Line::new(Point::new(axis_x, top_left.y), Point::new(axis_x, bottom))
+        .into_styled(stroke)
+        .draw(target)?;
+
+    let Some((low, high)) = axis_range(columns) else {
+        Text::with_baseline(
+            "no readings yet",
+            Point::new(axis_x + 4, top_left.y + height / 2 - 3),
+            style::SMALL,
+            Baseline::Top,
+        )
+        .draw(target)?;
+        return Ok(());
+    };
+
+    let mut label: heapless::String<8> = heapless::String::new();
+    for (value, y, text_style) in [
+        (high, top_left.y, TOP_RIGHT),
+        (low, bottom + 1, BOTTOM_RIGHT),
+    ] {
+        label.clear();
+        write!(label, "{}", value / 10).unwrap();
+        let position = Point::new(axis_x - 2, y);
+        Text::with_text_style(&label, position, style::SMALL, text_style).draw(target)?;
+    }
+
+    // Rounded to the nearest row, the range is never 0
+    let range = high - low;
+    let row = |value: i16| {
+        top_left.y + ((high - i32::from(value)) * (height - 1) * 2 + range) / (2 * range)
+    };
+    for (x, column) in (axis_x + 1..).zip(columns.iter().take(PLOT_WIDTH)) {
+        if let Some(bucket) = column {
+            Line::new(
+                Point::new(x, row(bucket.max)),
+                Point::new(x, row(bucket.min)),
+            )
+            .into_styled(stroke)
+            .draw(target)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// What a graph shows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Quantity {
+    /// Tenths of a degree, in the units of the [`Graphs`].
+    Temperature,
+    /// Tenths of a percent of relative humidity.
+    Humidity,
+}
+
+impl Quantity {
+    fn label(self) -> &'static str {
+        match self {
+            Quantity::Temperature => "Temp",
+            Quantity::Humidity => "Humidity",
+        }
+    }
+
+    fn unit(self, units: Units) -> &'static str {
+        match (self, units) {
+            (Quantity::Temperature, Units::Celsius) => " C",
+            (Quantity::Temperature, Units::Fahrenheit) => " F",
+            (Quantity::Humidity, _) => "%",
+        }
+    }
+}
+
+/// Time a graph covers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Span {
+    Hour,
+    SixHours,
+    Day,
+}
+
+impl Span {
+    pub fn minutes(self) -> usize {
+        match self {
+            Span::Hour => 60,
+            Span::SixHours => 6 * 60,
+            Span::Day => 24 * 60,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Span::Hour => "1h",
+            Span::SixHours => "6h",
+            Span::Day => "24h",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Span::Hour => Span::SixHours,
+            Span::SixHours => Span::Day,
+            Span::Day => Span::Hour,
+        }
+    }
+}
+
+/// Temperature or humidity history with the latest value above the graph.
+///
+/// The right button switches between temperature and humidity, the left one
+/// cycles through the last hour, 6 hours and 24 hours.
+#[derive(Clone, Copy, Debug)]
+pub struct Graphs {
+    quantity: Quantity,
+    units: Units,
+    span: Span,
+    presses: Presses,
+    error: Option<&'static str>,
+}
+
+impl Default for Graphs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Graphs {
+    pub fn new() -> Self {
+        Self {
+            quantity: Quantity::Temperature,
+            units: Units::Celsius,
+            span: Span::Day,
+            presses: Presses::new(),
+            error: None,
+        }
+    }
+
+    /// Labels temperatures with `units`, which they are recorded in.
+    pub fn with_units(mut self, units: Units) -> Self {
+        self.units = units;
+        self
+    }
+
+    pub fn quantity(&self) -> Quantity {
+        self.quantity
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Shows `error`, e.g. from the sensor, in place of the latest value
+    /// until it is cleared with `None`.
+    pub fn set_error(&mut self, error: Option<&'static str>) {
+        self.error = error;
+    }
+
+    /// Handles the buttons, returns whether the graph changed.
+    pub fn update(&mut self, buttons: ButtonState) -> bool {
+        let pressed = self.presses.update(buttons);
+        if pressed.left {
+            self.span = self.span.next();
+        }
+        if pressed.right {
+            self.quantity = match self.quantity {
+                Quantity::Temperature => Quantity::Humidity,
+                Quantity::Humidity => Quantity::Temperature,
+            };
+        }
+        pressed.left || pressed.right
+    }
+
+    pub fn draw<D, const N: usize>(
+        &self,
+        target: &mut D,
+        temperature: &History<N>,
+        humidity: &History<N>,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let history = match self.quantity {
+            Quantity::Temperature => temperature,
+            Quantity::Humidity => humidity,
+        };
+
+        let mut header: heapless::String<24> = heapless::String::new();
+        match (self.error, history.latest()) {
+            (Some(error), _) => write!(header, "{}", error),
+            (None, Some(latest)) => write!(
+                header,
+                "{} {}{}",
+                self.quantity.label(),
+                Tenths(latest.avg.into()),
+                self.quantity.unit(self.units)
+            ),
+            (None, None) => write!(
+                header,
+                "{} --{}",
+                self.quantity.label(),
+                self.quantity.unit(self.units)
+            ),
+        }
+        .unwrap();
+        Text::with_baseline(&header, Point::zero(), style::TEXT, Baseline::Top).draw(target)?;
+        let span_position = Point::new(GRAPH_SIZE.width as i32 - 1, 0);
+        Text::with_text_style(self.span.label(), span_position, style::TEXT, TOP_RIGHT)
+            .draw(target)?;
+
+        let span = (self.span.minutes() as u64 * MINUTE_MS / history.interval_ms()) as usize;
+        let mut columns = [None; PLOT_WIDTH];
+        history.downsample(span.max(1), &mut columns);
+        draw_sparkline(target, Point::new(0, 10), &columns)
+    }
+}
@@ -0,0 +1,78 @@
+use embedded_graphics::{
+    mono_font::{ascii::FONT_6X10, MonoTextStyle},
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::{Alignment, Baseline, Text, TextStyle, TextStyleBuilder},
+};
+
+use super::DISPLAY_SIZE;
+use crate::{launcher::Menu, style};
+
+const TOP_RIGHT: TextStyle = TextStyleBuilder::new()
+    .alignment(Alignment::Right)
+    .baseline(Baseline::Top)
+    .build();
+
+/// Text on a lit background, for the selected item.
+const INVERTED: MonoTextStyle<'static, BinaryColor> =
+    MonoTextStyle::new(&FONT_6X10, BinaryColor::Off);
+
+/// Top of the [`Menu::ROWS`] rows.
+const ROWS: [i32; Menu::ROWS] = [0, 11, 22];
+const ROW_HEIGHT: u32 = 10;
+
+/// Space on the right for the scroll bar.
+const SCROLL_BAR_WIDTH: u32 = 3;
+
+/// Draws the items around the selected one, which is highlighted, with a
+/// scroll bar when they do not all fit.
+pub fn draw<D>(target: &mut D, menu: &Menu, items: &[&str]) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    draw_with_values(target, menu, items, &[])
+}
+
+/// Like [`draw`], with the value of each item right-aligned.
+pub fn draw_with_values<D>(
+    target: &mut D,
+    menu: &Menu,
+    items: &[&str],
+    values: &[&str],
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    let width = DISPLAY_SIZE.width - SCROLL_BAR_WIDTH;
+    for ((index, item), y) in items.iter().enumerate().skip(menu.top()).zip(ROWS) {
+        let text_style = if index == menu.selected() {
+            Rectangle::new(Point::new(0, y), Size::new(width, ROW_HEIGHT))
+                .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+                .draw(target)?;
+            INVERTED
+        } else {
+            style::TEXT
+        };
+        Text::with_baseline(item, Point::new(2, y), text_style, Baseline::Top).draw(target)?;
+        if let Some(value) = values.get(index) {
+            let right = Point::new(width as i32 - 2, y);
+            Text::with_text_style(value, right, text_style, TOP_RIGHT).draw(target)?;
+        }
+    }
+
+    let len = items.len() as u32;
+    if len > Menu::ROWS as u32 {
+        let height = DISPLAY_SIZE.height;
+        let top = menu.top() as u32 * height / len;
+        let thumb = Menu::ROWS as u32 * height / len;
+        Rectangle::new(
+            Point::new((DISPLAY_SIZE.width - 2) as i32, top as i32),
+            Size::new(2, thumb),
+        )
+        .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+        .draw(target)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+//! Screens of the examples.
+//!
+//! Everything here draws to any [`DrawTarget`] with [`BinaryColor`], so the
+//! same code renders on the SSD1306 and in the desktop simulator.
+
+use embedded_graphics::{pixelcolor::BinaryColor, prelude::*};
+
+pub mod alarm;
+mod animation;
+mod buttons;
+mod car;
+pub mod clock;
+pub mod comfort;
+pub mod gpio;
+pub mod history;
+pub mod menu;
+pub mod scanner;
+pub mod sensors;
+pub mod settings;
+mod snow;
+pub mod temperature;
+mod timers;
+pub mod wifi;
+
+pub use animation::Animation;
+pub use buttons::Buttons;
+pub use car::Car;
+pub use clock::Clock;
+pub use snow::Snow;
+pub use timers::Timers;
+
+/// Size of the ESP-Buddy display in pixels.
+pub const DISPLAY_SIZE: Size = Size::new(128, 32);
+
+/// Levels of the two buttons, `true` while pressed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ButtonState {
+    pub left: bool,
+    pub right: bool,
+}
+
+/// Turns button levels into presses, counted once when a button goes down.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Presses {
+    previous: ButtonState,
+}
+
+impl Presses {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the buttons that went down since the last call.
+    pub fn update(&mut self, buttons: ButtonState) -> ButtonState {
+        let pressed = ButtonState {
+            left: buttons.left && !self.previous.left,
+            right: buttons.right && !self.previous.right,
+        };
+        self.previous = buttons;
+        pressed
+    }
+}
+
+/// An animated screen.
+///
+/// The runner clears the target, calls [`Scene::draw`], shows the frame,
+/// waits [`Scene::FRAME_MS`] and calls [`Scene::update`] with the buttons.
+pub trait Scene {
+    /// Time between two frames in milliseconds.
+    const FRAME_MS: u32;
+
+    /// Draws the current frame on a cleared target.
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>;
+
+    /// Advances the scene to the next frame.
+    fn update(&mut self, buttons: ButtonState);
+}
@@ -0,0 +1,159 @@
+use core::fmt::Write;
+
+use embedded_graphics::{
+    mono_font::{ascii::FONT_4X6, MonoTextStyle},
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::{Alignment, Baseline, Text, TextStyle, TextStyleBuilder},
+};
+
+use super::DISPLAY_SIZE;
+use crate::{
+    scanner::{Diagnostics, View},
+    sensors::Tenths,
+    style,
+};
+
+const TOP_RIGHT: TextStyle = TextStyleBuilder::new()
+    .alignment(Alignment::Right)
+    .baseline(Baseline::Top)
+    .build();
+
+/// Small text on a lit background, for the selected register.
+const INVERTED: MonoTextStyle<'static, BinaryColor> =
+    MonoTextStyle::new(&FONT_4X6, BinaryColor::Off);
+
+/// Top of the three rows below the header.
+const ROWS: [i32; 3] = [12, 19, 26];
+
+/// Left of the first register value in a row, and the distance to the next.
+const VALUES_LEFT: i32 = 16;
+const VALUE_STEP: i32 = 14;
+
+/// Draws the view of the diagnostics app.
+pub fn draw<D>(target: &mut D, diagnostics: &Diagnostics) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    match diagnostics.view() {
+        View::List => draw_list(target, diagnostics),
+        View::Registers | View::Edit(_) => draw_registers(target, diagnostics),
+    }
+}
+
+/// Devices found with their error rates, three at a time.
+fn draw_list<D>(target: &mut D, diagnostics: &Diagnostics) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    let devices = diagnostics.devices();
+    let mut header: heapless::String<21> = heapless::String::new();
+    write!(header, "I2C {} found", devices.len()).unwrap();
+    Text::with_baseline(&header, Point::zero(), style::TEXT, Baseline::Top).draw(target)?;
+    let mut scans: heapless::String<12> = heapless::String::new();
+    write!(scans, "scan {}", diagnostics.scans()).unwrap();
+    let right = Point::new(DISPLAY_SIZE.width as i32 - 1, 0);
+    Text::with_text_style(&scans, right, style::TEXT, TOP_RIGHT).draw(target)?;
+
+    let Some(selected) = diagnostics.selected() else {
+        return Text::with_baseline("no devices", Point::new(0, 16), style::TEXT, Baseline::Top)
+            .draw(target)
+            .map(drop);
+    };
+    let index = devices
+        .iter()
+        .position(|device| device.address == selected.address)
+        .unwrap_or(0);
+    let first = index / ROWS.len() * ROWS.len();
+    for (device, y) in devices[first..].iter().zip(ROWS) {
+        let mut label: heapless::String<20> = heapless::String::new();
+        write!(
+            label,
+            "{} {:02x} {}",
+            if device.address == selected.address {
+                '>'
+            } else {
+                ' '
+            },
+            device.address,
+            device.name.unwrap_or("?")
+        )
+        .unwrap();
+        let mut rate: heapless::String<20> = heapless::String::new();
+        write!(
+            rate,
+            "{}% of {}",
+            Tenths(device.error_rate_dpct() as i32),
+            device.transfers
+        )
+        .unwrap();
+        Text::with_baseline(&label, Point::new(0, y), style::SMALL, Baseline::Top).draw(target)?;
+        let right = Point::new(DISPLAY_SIZE.width as i32 - 1, y);
+        Text::with_text_style(&rate, right, style::SMALL, TOP_RIGHT).draw(target)?;
+    }
+    Ok(())
+}
+
+/// Registers around the selected one, 8 per row, with the value to write
+/// in the header while editing.
+fn draw_registers<D>(target: &mut D, diagnostics: &Diagnostics) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    let register = diagnostics.register();
+    let value = diagnostics.value(register);
+    let mut header: heapless::String<21> = heapless::String::new();
+    if let View::Edit(new) = diagnostics.view() {
+        write!(header, "Set {:02x}: {} > {:02x}", register, Hex(value), new).unwrap();
+    } else if let Some(device) = diagnostics.selected() {
+        write!(
+            header,
+            "{:02x} {}",
+            device.address,
+            device.name.unwrap_or("?")
+        )
+        .unwrap();
+        let mut selected: heapless::String<8> = heapless::String::new();
+        write!(selected, "{:02x}={}", register, Hex(value)).unwrap();
+        let right = Point::new(DISPLAY_SIZE.width as i32 - 1, 0);
+        Text::with_text_style(&selected, right, style::TEXT, TOP_RIGHT).draw(target)?;
+    }
+    Text::with_baseline(&header, Point::zero(), style::TEXT, Baseline::Top).draw(target)?;
+
+    let mut row = register & !7;
+    for y in ROWS {
+        let mut label: heapless::String<4> = heapless::String::new();
+        write!(label, "{:02x}:", row).unwrap();
+        Text::with_baseline(&label, Point::new(0, y), style::SMALL, Baseline::Top).draw(target)?;
+        for column in 0..8u8 {
+            let at = row.wrapping_add(column);
+            let mut text: heapless::String<2> = heapless::String::new();
+            write!(text, "{}", Hex(diagnostics.value(at))).unwrap();
+            let position = Point::new(VALUES_LEFT + i32::from(column) * VALUE_STEP, y);
+            let text_style = if at == register {
+                Rectangle::new(position - Point::new(1, 1), Size::new(10, 7))
+                    .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+                    .draw(target)?;
+                INVERTED
+            } else {
+                style::SMALL
+            };
+            Text::with_baseline(&text, position, text_style, Baseline::Top).draw(target)?;
+        }
+        row = row.wrapping_add(8);
+    }
+    Ok(())
+}
+
+/// A register value in hex, `--` when it could not be read.
+struct Hex(Option<u8>);
+
+impl core::fmt::Display for Hex {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.0 {
+            Some(value) => write!(f, "{:02x}", value),
+            None => f.write_str("--"),
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use core::fmt::Write;
+
+use embedded_graphics::{
+    pixelcolor::BinaryColor,
+    prelude::*,
+    text::{Alignment, Baseline, Text, TextStyle, TextStyleBuilder},
+};
+
+use super::{ButtonState, Presses, DISPLAY_SIZE};
+use crate::{
+    sensors::{
+        registry::{Entry, Registry},
+        Tenths,
+    },
+    style,
+};
+
+const TOP_RIGHT: TextStyle = TextStyleBuilder::new()
+    .alignment(Alignment::Right)
+    .baseline(Baseline::Top)
+    .build();
+
+/// Top of the rows of values, one per quantity.
+const ROWS: [i32; 3] = [12, 19, 26];
+
+/// One page per sensor of a [`Registry`], with its values below the name
+/// and address.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Pages {
+    page: usize,
+    presses: Presses,
+}
+
+impl Pages {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn page(&self) -> usize {
+        self.page
+    }
+
+    /// Right shows the next of `count` pages, left the previous one.
+    /// Returns whether the page changed.
+    pub fn update(&mut self, buttons: ButtonState, count: usize) -> bool {
+        let pressed = self.presses.update(buttons);
+        if count == 0 {
+            return false;
+        }
+        let page = self.page;
+        if pressed.right {
+            self.page = (self.page + 1) % count;
+        }
+        if pressed.left {
+            self.page = (self.page + count - 1) % count;
+        }
+        self.page != page
+    }
+
+    pub fn draw<D>(&self, target: &mut D, registry: &Registry) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        if registry.is_empty() {
+            return message(target, "Sensors", "none found");
+        }
+        let page = self.page % registry.len();
+        let mut number: heapless::String<8> = heapless::String::new();
+        write!(number, "{}/{}", page + 1, registry.len()).unwrap();
+        let right = Point::new(DISPLAY_SIZE.width as i32 - 1, 0);
+        Text::with_text_style(&number, right, style::TEXT, TOP_RIGHT).draw(target)?;
+        draw_entry(target, &registry.entries()[page])
+    }
+}
+
+fn draw_entry<D>(target: &mut D, entry: &Entry) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    let title = entry.title();
+    let values = match (&entry.values, entry.error) {
+        (_, Some(error)) => return message(target, &title, error),
+        (Some(values), None) => values,
+        (None, None) => return message(target, &title, "waiting for sensor"),
+    };
+    Text::with_baseline(&title, Point::zero(), style::TEXT, Baseline::Top).draw(target)?;
+    let quantities = entry.device.quantities();
+    for ((quantity, value), y) in quantities.iter().zip(values).zip(ROWS) {
+        let mut text: heapless::String<16> = heapless::String::new();
+        write!(text, "{} {}", Tenths(*value), quantity.unit()).unwrap();
+        let right = Point::new(DISPLAY_SIZE.width as i32 - 1, y);
+        Text::with_baseline(
+            quantity.name(),
+            Point::new(0, y),
+            style::SMALL,
+            Baseline::Top,
+        )
+        .draw(target)?;
+        Text::with_text_style(&text, right, style::SMALL, TOP_RIGHT).draw(target)?;
+    }
+    Ok(())
+}
+
+/// `title` with `text` below in place of the values.
+fn message<D>(target: &mut D, title: &str, text: &str) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    Text::with_baseline(title, Point::zero(), style::TEXT, Baseline::Top).draw(target)?;
+    Text::with_baseline(text, Point::new(0, 16), style::TEXT, Baseline::Top).draw(target)?;
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use core::fmt::Write;
+
+use embedded_graphics::{
+    mono_font::{ascii::FONT_6X10, MonoTextStyle},
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::{Baseline, Text},
+};
+
+use super::{menu, DISPLAY_SIZE};
+use crate::{
+    settings::{
+        schema::{Kind, Setting, Value, MAX_SETTINGS, MAX_TEXT_LEN},
+        Edit, Editor,
+    },
+    style,
+};
+
+/// Text on a lit background, for the character at the cursor.
+const INVERTED: MonoTextStyle<'static, BinaryColor> =
+    MonoTextStyle::new(&FONT_6X10, BinaryColor::Off);
+
+/// Width of a character of [`style::TEXT`].
+const CHAR_WIDTH: u32 = 6;
+/// Most characters of a value in the list, longer ones end with `~`.
+const LIST_VALUE_LEN: usize = 10;
+
+/// Draws the list of settings or the edit screen of one.
+pub fn draw<D>(target: &mut D, editor: &Editor) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    match editor.edit() {
+        Some(edit) => draw_edit(target, editor.selected(), edit),
+        None => draw_list(target, editor),
+    }
+}
+
+/// Labels with their values, three at a time.
+fn draw_list<D>(target: &mut D, editor: &Editor) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    let mut labels: heapless::Vec<&str, MAX_SETTINGS> = heapless::Vec::new();
+    let mut shown: heapless::Vec<heapless::String<LIST_VALUE_LEN>, MAX_SETTINGS> =
+        heapless::Vec::new();
+    for (setting, value) in editor.schema().iter().zip(editor.values()) {
+        let mut text: heapless::String<{ MAX_TEXT_LEN + 8 }> = heapless::String::new();
+        write!(text, "{}", setting.display(value)).unwrap();
+        let mut cut = heapless::String::new();
+        if text.len() > LIST_VALUE_LEN {
+            cut.push_str(&text[..LIST_VALUE_LEN - 1]).unwrap();
+            cut.push('~').unwrap();
+        } else {
+            cut.push_str(&text).unwrap();
+        }
+        labels.push(setting.label).ok();
+        shown.push(cut).ok();
+    }
+    let values: heapless::Vec<&str, MAX_SETTINGS> = shown.iter().map(|value| &**value).collect();
+    menu::draw_with_values(target, editor.menu(), &labels, &values)
+}
+
+/// Label, the value being edited and what the buttons do.
+fn draw_edit<D>(target: &mut D, setting: &Setting, edit: &Edit) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    Text::with_baseline(setting.label, Point::zero(), style::TEXT, Baseline::Top).draw(target)?;
+
+    let value_position = Point::new(0, 11);
+    let hint = match (&setting.kind, &edit.value) {
+        (Kind::Text { .. }, Value::Text(text)) => {
+            draw_cursor(target, value_position, text, edit.cursor)?;
+            if edit.cursor < text.len() {
+                "left: character  right: next"
+            } else {
+                "left: add  right: save"
+            }
+        }
+        (_, value) => {
+            let mut text: heapless::String<{ MAX_TEXT_LEN + 8 }> = heapless::String::new();
+            write!(text, "{}", setting.display(value)).unwrap();
+            Text::with_baseline(&text, value_position, style::TEXT, Baseline::Top).draw(target)?;
+            "left: change  right: save"
+        }
+    };
+    Text::with_baseline(hint, Point::new(0, 26), style::SMALL, Baseline::Top).draw(target)?;
+
+    Ok(())
+}
+
+/// The part of `text` around the cursor, the character at the cursor
+/// highlighted, or a block after the end.
+fn draw_cursor<D>(
+    target: &mut D,
+    position: Point,
+    text: &str,
+    cursor: usize,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    let columns = (DISPLAY_SIZE.width / CHAR_WIDTH) as usize;
+    let start = (cursor + 1).saturating_sub(columns);
+    let end = text.len().min(start + columns);
+    Text::with_baseline(&text[start..end], position, style::TEXT, Baseline::Top).draw(target)?;
+
+    let at = position + Point::new(((cursor - start) as u32 * CHAR_WIDTH) as i32, 0);
+    Rectangle::new(at, Size::new(CHAR_WIDTH, 10))
+        .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+        .draw(target)?;
+    if let Some(current) = text.get(cursor..cursor + 1) {
+        Text::with_baseline(current, at, INVERTED, Baseline::Top).draw(target)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use embedded_graphics::{
+    pixelcolor::BinaryColor,
+    prelude::*,
+    text::{Baseline, Text},
+};
+use rand_core::RngCore;
+
+use super::{ButtonState, Scene};
+use crate::style;
+
+/// Number of snowflakes on the screen.
+const SNOWFLAKES: usize = 10;
+
+/// Snowflakes falling at 45 degrees.
+///
+/// The board shows this scene with the display rotated by 180 degrees.
+pub struct Snow<R> {
+    rng: R,
+    snowflakes: [(i32, i32); SNOWFLAKES],
+}
+
+impl<R: RngCore> Snow<R> {
+    pub fn new(rng: R) -> Self {
+        Self {
+            rng,
+            snowflakes: [(0, 0); SNOWFLAKES],
+        }
+    }
+}
+
+impl<R: RngCore> Scene for Snow<R> {
+    const FRAME_MS: u32 = 100;
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        for snowflake in self.snowflakes.iter() {
+            Text::with_baseline(
+                "*",
+                Point::new(snowflake.0, snowflake.1),
+                style::SMALL,
+                Baseline::Top,
+            )
+            .draw(target)?;
+        }
+
+        Ok(())
+    }
+
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)]
+    fn update(&mut self, _buttons: ButtonState) {
+        for snowflake in self.snowflakes.iter_mut() {
+            // Randomly generate new snowflakes at the top
+            if self.rng.next_u32() % 20 == 0 {
+                snowflake.0 = (self.rng.next_u32() % 128) as i32;
+                snowflake.1 = 0;
+            } else {
+                // Adjust for 45-degree tilt
+                snowflake.1 += 1;
+                snowflake.0 -= 1;
+
+                // Check bounds and reset if needed
+                if snowflake.1 > 31 {
+                    snowflake.1 = 0;
+                    snowflake.0 = (self.rng.next_u32() % 128) as i32; // Reset x position too
+                }
+                if snowflake.0 < 0 {
+                    snowflake.0 = 127; // Wrap around if it goes off the left edge
+                }
+            }
+        }
+    }
+}
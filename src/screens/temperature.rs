@@ -0,0 +1,42 @@
+use core::fmt::Write;
+
+use embedded_graphics::{
+    pixelcolor::BinaryColor,
+    prelude::*,
+    text::{Baseline, Text},
+};
+
+use crate::style;
+
+/// Draws the temperature in °C and the relative humidity in %.
+pub fn draw<D>(target: &mut D, deg_c: i32, rh: i32) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    draw_with_unit(target, deg_c, "C", rh)
+}
+
+/// Draws the temperature in degrees of `unit` and the relative humidity in %.
+pub fn draw_with_unit<D>(target: &mut D, degrees: i32, unit: &str, rh: i32) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    Text::with_baseline(
+        "Temperature/Humidity",
+        Point::zero(),
+        style::TEXT,
+        Baseline::Top,
+    )
+    .draw(target)?;
+
+    let mut rh_string: heapless::String<32> = heapless::String::new();
+    let mut deg_string: heapless::String<32> = heapless::String::new();
+
+    write!(rh_string, "{}%", rh).unwrap();
+    write!(deg_string, "{} {}", degrees, unit).unwrap();
+
+    Text::with_baseline(&deg_string, Point::new(0, 16), style::TEXT, Baseline::Top).draw(target)?;
+    Text::with_baseline(&rh_string, Point::new(72, 16), style::TEXT, Baseline::Top).draw(target)?;
+
+    Ok(())
+}
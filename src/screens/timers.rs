@@ -0,0 +1,117 @@
+use core::fmt::Write;
+
+use embedded_graphics::{pixelcolor::BinaryColor, prelude::*};
+
+use super::{alarm::draw_duration, ButtonState, Presses, Scene};
+use crate::alarm::{Pomodoro, Stopwatch};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    Pomodoro,
+    Stopwatch,
+}
+
+/// Pomodoro timer and stopwatch.
+///
+/// The left button switches between them, the right one starts and pauses,
+/// holding it resets. Time advances by [`Scene::FRAME_MS`] per frame.
+pub struct Timers {
+    mode: Mode,
+    pomodoro: Pomodoro,
+    stopwatch: Stopwatch,
+    now_ms: u64,
+    presses: Presses,
+    right_held_ms: u32,
+}
+
+impl Default for Timers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Timers {
+    /// How long to hold the right button to reset.
+    const RESET_HOLD_MS: u32 = 1000;
+
+    pub fn new() -> Self {
+        Self {
+            mode: Mode::Pomodoro,
+            pomodoro: Pomodoro::new(),
+            stopwatch: Stopwatch::new(),
+            now_ms: 0,
+            presses: Presses::new(),
+            right_held_ms: 0,
+        }
+    }
+
+    fn toggle(&mut self) {
+        match self.mode {
+            Mode::Pomodoro => self.pomodoro.countdown_mut().toggle(self.now_ms),
+            Mode::Stopwatch => self.stopwatch.toggle(self.now_ms),
+        }
+    }
+
+    fn reset(&mut self) {
+        match self.mode {
+            Mode::Pomodoro => self.pomodoro = Pomodoro::new(),
+            Mode::Stopwatch => self.stopwatch.reset(),
+        }
+    }
+}
+
+impl Scene for Timers {
+    const FRAME_MS: u32 = 50;
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let mut label: heapless::String<24> = heapless::String::new();
+        match self.mode {
+            Mode::Pomodoro => {
+                let countdown = self.pomodoro.countdown();
+                write!(
+                    label,
+                    "{} #{}",
+                    self.pomodoro.phase().name(),
+                    self.pomodoro.sessions() + 1
+                )
+                .unwrap();
+                if !countdown.is_running() {
+                    label.push_str(" paused").unwrap();
+                }
+                draw_duration(target, &label, countdown.remaining_ms(self.now_ms))
+            }
+            Mode::Stopwatch => {
+                label.push_str("Stopwatch").unwrap();
+                draw_duration(target, &label, self.stopwatch.elapsed_ms(self.now_ms))
+            }
+        }
+    }
+
+    fn update(&mut self, buttons: ButtonState) {
+        self.now_ms += Self::FRAME_MS as u64;
+
+        if self.presses.update(buttons).left {
+            self.mode = match self.mode {
+                Mode::Pomodoro => Mode::Stopwatch,
+                Mode::Stopwatch => Mode::Pomodoro,
+            };
+        }
+
+        if buttons.right {
+            self.right_held_ms += Self::FRAME_MS;
+            if self.right_held_ms == Self::RESET_HOLD_MS {
+                self.reset();
+            }
+        } else {
+            if (1..Self::RESET_HOLD_MS).contains(&self.right_held_ms) {
+                self.toggle();
+            }
+            self.right_held_ms = 0;
+        }
+
+        self.pomodoro.update(self.now_ms);
+    }
+}
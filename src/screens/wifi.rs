@@ -0,0 +1,91 @@
+use core::{fmt::Write, net::Ipv4Addr};
+
+use embedded_graphics::{
+    pixelcolor::BinaryColor,
+    prelude::*,
+    text::{Baseline, Text},
+};
+
+use crate::{
+    style,
+    wifi::{FailReason, State},
+};
+
+/// Shown while waiting for the access point.
+pub fn draw_connecting<D>(target: &mut D) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    Text::with_baseline(
+        "WiFi example\nWait to get connected",
+        Point::zero(),
+        style::TEXT,
+        Baseline::Top,
+    )
+    .draw(target)?;
+
+    Ok(())
+}
+
+/// Shown once DHCP assigned an address.
+pub fn draw_connected<D>(target: &mut D, ip: Ipv4Addr) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    let mut ip_addr: heapless::String<16> = heapless::String::new();
+    write!(ip_addr, "{}", ip).unwrap();
+
+    Text::with_baseline(
+        "WiFi example\nConnected.\nIP:",
+        Point::zero(),
+        style::TEXT,
+        Baseline::Top,
+    )
+    .draw(target)?;
+    Text::new(&ip_addr, Point::new(21, 28), style::TEXT).draw(target)?;
+
+    Ok(())
+}
+
+/// Shown while the connection attempt failed and waits for the next one.
+pub fn draw_failed<D>(target: &mut D, reason: FailReason) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    let mut text: heapless::String<64> = heapless::String::new();
+    write!(text, "WiFi failed:\n{}\nRetrying...", reason.description()).unwrap();
+
+    Text::with_baseline(&text, Point::zero(), style::TEXT, Baseline::Top).draw(target)?;
+
+    Ok(())
+}
+
+/// Shown while the captive portal waits for credentials.
+pub fn draw_portal<D>(target: &mut D, ssid: &str, url: &str) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    let mut text: heapless::String<96> = heapless::String::new();
+    write!(text, "WiFi setup, join:\n{}\n{}", ssid, url).unwrap();
+
+    Text::with_baseline(&text, Point::zero(), style::TEXT, Baseline::Top).draw(target)?;
+
+    Ok(())
+}
+
+/// Renders any state of the [`WifiManager`](crate::wifi::WifiManager).
+pub fn draw_state<D>(target: &mut D, state: &State) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    let text = match *state {
+        State::Idle => "WiFi off",
+        State::Scanning => "Scanning for WiFi...",
+        State::Connecting => return draw_connecting(target),
+        State::Connected { ip } => return draw_connected(target, ip),
+        State::Failed { reason } => return draw_failed(target, reason),
+    };
+    Text::with_baseline(text, Point::zero(), style::TEXT, Baseline::Top).draw(target)?;
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+//! ROHM BH1750 ambient light sensor.
+//!
+//! The chip takes one-byte opcodes and has no id: whatever at one of its
+//! addresses takes the opcodes and sends a result counts as a BH1750. The
+//! [`Registry`](super::Registry) probes the LPS22 first, which shares
+//! `0x5c`.
+
+use super::{div_round, Error, I2c, Measurements, Quantity, Sensor};
+
+/// Address with `ADDR` low.
+pub const ADDRESS: u8 = 0x23;
+/// Address with `ADDR` high.
+pub const ADDRESS_ALT: u8 = 0x5c;
+
+pub const POWER_ON: u8 = 0x01;
+/// One lux resolution, a result every 120 ms.
+pub const CONTINUOUS_HIGH_RES: u8 = 0x10;
+
+/// Illuminance in tenths of a lux.
+#[derive(Clone, Debug)]
+pub struct Bh1750 {
+    address: u8,
+    started: bool,
+}
+
+impl Bh1750 {
+    pub fn new(address: u8) -> Self {
+        Self {
+            address,
+            started: false,
+        }
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.started
+    }
+
+    /// Powers the chip on and starts continuous measurements.
+    pub fn start<I: I2c>(&mut self, i2c: &mut I) -> Result<(), Error<I::Error>> {
+        self.started = false;
+        i2c.write(self.address, &[POWER_ON]).map_err(Error::I2c)?;
+        i2c.write(self.address, &[CONTINUOUS_HIGH_RES])
+            .map_err(Error::I2c)?;
+        self.started = true;
+        Ok(())
+    }
+
+    /// The last result, 0 until the first measurement is done.
+    pub fn read<I: I2c>(&mut self, i2c: &mut I) -> Result<i32, Error<I::Error>> {
+        if !self.started {
+            self.start(i2c)?;
+        }
+        let mut data = [0u8; 2];
+        if let Err(err) = i2c.read(self.address, &mut data) {
+            self.started = false;
+            return Err(Error::I2c(err));
+        }
+        // 1.2 counts per lux
+        Ok(div_round(i32::from(u16::from_be_bytes(data)) * 100, 12))
+    }
+}
+
+impl Sensor for Bh1750 {
+    const NAME: &'static str = "BH1750";
+    const ADDRESSES: &'static [u8] = &[ADDRESS, ADDRESS_ALT];
+    const QUANTITIES: &'static [Quantity] = &[Quantity::Illuminance];
+
+    fn probe<I: I2c>(i2c: &mut I, address: u8) -> Option<Self> {
+        let mut sensor = Self::new(address);
+        sensor.start(i2c).ok()?;
+        i2c.read(address, &mut [0u8; 2]).ok()?;
+        Some(sensor)
+    }
+
+    fn address(&self) -> u8 {
+        self.address
+    }
+
+    fn read<I: I2c>(&mut self, i2c: &mut I) -> Result<Measurements, Error<I::Error>> {
+        let lux_dlx = Bh1750::read(self, i2c)?;
+        Ok(Measurements::from_slice(&[lux_dlx]).unwrap())
+    }
+}
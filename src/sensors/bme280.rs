@@ -0,0 +1,241 @@
+//! Bosch BME280 temperature, humidity and pressure sensor.
+//!
+//! Like the HTS221, the chip has a factory calibration, here 32 trimming
+//! values read once when it starts. The compensation is the integer one of
+//! the Bosch datasheet. Transfers always continue with the next register.
+
+use super::{div_round, Error, I2c, Measurements, Quantity, Sensor};
+
+/// Address with `SDO` low.
+pub const ADDRESS: u8 = 0x76;
+/// Address with `SDO` high.
+pub const ADDRESS_ALT: u8 = 0x77;
+
+/// Value of [`CHIP_ID`]. The BMP280, without humidity, has `0x58`.
+pub const DEVICE_ID: u8 = 0x60;
+
+pub const CHIP_ID: u8 = 0xd0;
+/// Trimming values of temperature and pressure, 26 registers up to `0xa1`
+/// with the first humidity one.
+pub const CALIBRATION: u8 = 0x88;
+/// The other trimming values of humidity, 7 registers.
+pub const CALIBRATION_HUMIDITY: u8 = 0xe1;
+pub const CTRL_HUM: u8 = 0xf2;
+pub const CTRL_MEAS: u8 = 0xf4;
+pub const CONFIG: u8 = 0xf5;
+/// Pressure, temperature and humidity, 8 registers.
+pub const DATA: u8 = 0xf7;
+
+/// One humidity sample, takes effect with the next write of [`CTRL_MEAS`].
+const HUMIDITY_OVERSAMPLING: u8 = 0x01;
+/// One temperature and one pressure sample, normal mode.
+const MEASUREMENT: u8 = 0x20 | 0x04 | 0x03;
+/// A second between measurements, no filter.
+const STANDBY_1000_MS: u8 = 0xa0;
+/// Output of a skipped measurement.
+const SKIPPED: i32 = 0x80000;
+
+/// Factory trimming values, named as in the datasheet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Coefficients {
+    pub t1: u16,
+    pub t2: i16,
+    pub t3: i16,
+    pub p1: u16,
+    pub p2: i16,
+    pub p3: i16,
+    pub p4: i16,
+    pub p5: i16,
+    pub p6: i16,
+    pub p7: i16,
+    pub p8: i16,
+    pub p9: i16,
+    pub h1: u8,
+    pub h2: i16,
+    pub h3: u8,
+    pub h4: i16,
+    pub h5: i16,
+    pub h6: i8,
+}
+
+impl Coefficients {
+    /// Decodes the registers from [`CALIBRATION`] and
+    /// [`CALIBRATION_HUMIDITY`] on, `None` without a pressure scale.
+    pub fn decode(registers: &[u8; 26], humidity: &[u8; 7]) -> Option<Self> {
+        let u = |at: usize| u16::from_le_bytes([registers[at], registers[at + 1]]);
+        let i = |at: usize| i16::from_le_bytes([registers[at], registers[at + 1]]);
+        let coefficients = Self {
+            t1: u(0),
+            t2: i(2),
+            t3: i(4),
+            p1: u(6),
+            p2: i(8),
+            p3: i(10),
+            p4: i(12),
+            p5: i(14),
+            p6: i(16),
+            p7: i(18),
+            p8: i(20),
+            p9: i(22),
+            h1: registers[25],
+            h2: i16::from_le_bytes([humidity[0], humidity[1]]),
+            h3: humidity[2],
+            // 12 bits each, sharing the nibbles of 0xe5
+            h4: i16::from(humidity[3] as i8) << 4 | i16::from(humidity[4] & 0x0f),
+            h5: i16::from(humidity[5] as i8) << 4 | i16::from(humidity[4] >> 4),
+            h6: humidity[6] as i8,
+        };
+        (coefficients.p1 != 0).then_some(coefficients)
+    }
+
+    /// Temperature in hundredths and the fine temperature the other
+    /// compensations take.
+    pub fn temperature(&self, adc: i32) -> (i32, i32) {
+        let t1 = i32::from(self.t1);
+        let var1 = (((adc >> 3) - (t1 << 1)) * i32::from(self.t2)) >> 11;
+        let var2 = (((((adc >> 4) - t1) * ((adc >> 4) - t1)) >> 12) * i32::from(self.t3)) >> 14;
+        let fine = var1 + var2;
+        ((fine * 5 + 128) >> 8, fine)
+    }
+
+    /// Pressure in pascal, times 256.
+    pub fn pressure(&self, adc: i32, fine: i32) -> u32 {
+        let mut var1 = i64::from(fine) - 128000;
+        let mut var2 = var1 * var1 * i64::from(self.p6);
+        var2 += (var1 * i64::from(self.p5)) << 17;
+        var2 += i64::from(self.p4) << 35;
+        var1 = ((var1 * var1 * i64::from(self.p3)) >> 8) + ((var1 * i64::from(self.p2)) << 12);
+        var1 = (((1i64 << 47) + var1) * i64::from(self.p1)) >> 33;
+        if var1 == 0 {
+            return 0;
+        }
+        let mut p = 1048576 - i64::from(adc);
+        p = (((p << 31) - var2) * 3125) / var1;
+        var1 = (i64::from(self.p9) * (p >> 13) * (p >> 13)) >> 25;
+        var2 = (i64::from(self.p8) * p) >> 19;
+        (((p + var1 + var2) >> 8) + (i64::from(self.p7) << 4)) as u32
+    }
+
+    /// Relative humidity in percent, times 1024.
+    pub fn humidity(&self, adc: i32, fine: i32) -> u32 {
+        let x = fine - 76800;
+        let scale = (((((x * i32::from(self.h6)) >> 10)
+            * (((x * i32::from(self.h3)) >> 11) + 32768))
+            >> 10)
+            + 2097152)
+            * i32::from(self.h2)
+            + 8192;
+        let mut x = (((adc << 14) - (i32::from(self.h4) << 20) - i32::from(self.h5) * x + 16384)
+            >> 15)
+            * (scale >> 14);
+        x -= ((((x >> 15) * (x >> 15)) >> 7) * i32::from(self.h1)) >> 4;
+        (x.clamp(0, 419430400) >> 12) as u32
+    }
+}
+
+/// Temperature and humidity in tenths and pressure in tenths of a
+/// hectopascal from a BME280.
+#[derive(Clone, Debug)]
+pub struct Bme280 {
+    address: u8,
+    coefficients: Option<Coefficients>,
+}
+
+impl Bme280 {
+    pub fn new(address: u8) -> Self {
+        Self {
+            address,
+            coefficients: None,
+        }
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.coefficients.is_some()
+    }
+
+    /// Checks the chip, reads its calibration and starts measurements.
+    pub fn start<I: I2c>(&mut self, i2c: &mut I) -> Result<(), Error<I::Error>> {
+        self.coefficients = None;
+        let mut id = [0u8];
+        i2c.write_read(self.address, &[CHIP_ID], &mut id)
+            .map_err(Error::I2c)?;
+        if id[0] != DEVICE_ID {
+            return Err(Error::WrongDevice(id[0]));
+        }
+
+        let mut registers = [0u8; 26];
+        i2c.write_read(self.address, &[CALIBRATION], &mut registers)
+            .map_err(Error::I2c)?;
+        let mut humidity = [0u8; 7];
+        i2c.write_read(self.address, &[CALIBRATION_HUMIDITY], &mut humidity)
+            .map_err(Error::I2c)?;
+        let coefficients =
+            Coefficients::decode(&registers, &humidity).ok_or(Error::BadCoefficients)?;
+
+        i2c.write(self.address, &[CTRL_HUM, HUMIDITY_OVERSAMPLING])
+            .map_err(Error::I2c)?;
+        i2c.write(self.address, &[CONFIG, STANDBY_1000_MS])
+            .map_err(Error::I2c)?;
+        i2c.write(self.address, &[CTRL_MEAS, MEASUREMENT])
+            .map_err(Error::I2c)?;
+        self.coefficients = Some(coefficients);
+        Ok(())
+    }
+
+    /// Temperature, humidity and pressure of the last measurement.
+    pub fn read<I: I2c>(&mut self, i2c: &mut I) -> Result<[i32; 3], Error<I::Error>> {
+        if !self.is_started() {
+            self.start(i2c)?;
+        }
+        let Some(coefficients) = self.coefficients else {
+            return Err(Error::NotReady);
+        };
+        let mut data = [0u8; 8];
+        if let Err(err) = i2c.write_read(self.address, &[DATA], &mut data) {
+            self.coefficients = None;
+            return Err(Error::I2c(err));
+        }
+        let adc = |at: usize| {
+            i32::from(data[at]) << 12 | i32::from(data[at + 1]) << 4 | i32::from(data[at + 2]) >> 4
+        };
+        let (pressure, temperature) = (adc(0), adc(3));
+        let humidity = i32::from(u16::from_be_bytes([data[6], data[7]]));
+        if temperature == SKIPPED {
+            // Registers still at their reset value
+            return Err(Error::NotReady);
+        }
+
+        let (temperature_cc, fine) = coefficients.temperature(temperature);
+        let pressure_pa_x256 = coefficients.pressure(pressure, fine);
+        let humidity_pct_x1024 = coefficients.humidity(humidity, fine);
+        Ok([
+            div_round(temperature_cc, 10),
+            div_round(humidity_pct_x1024 as i32 * 10, 1024),
+            // Tenths of a hectopascal are tens of pascal
+            (pressure_pa_x256 + 1280) as i32 / 2560,
+        ])
+    }
+}
+
+impl Sensor for Bme280 {
+    const NAME: &'static str = "BME280";
+    const ADDRESSES: &'static [u8] = &[ADDRESS, ADDRESS_ALT];
+    const QUANTITIES: &'static [Quantity] = &[
+        Quantity::Temperature,
+        Quantity::Humidity,
+        Quantity::Pressure,
+    ];
+
+    fn probe<I: I2c>(i2c: &mut I, address: u8) -> Option<Self> {
+        let mut sensor = Self::new(address);
+        sensor.start(i2c).is_ok().then_some(sensor)
+    }
+
+    fn address(&self) -> u8 {
+        self.address
+    }
+
+    fn read<I: I2c>(&mut self, i2c: &mut I) -> Result<Measurements, Error<I::Error>> {
+        Ok(Measurements::from_slice(&Bme280::read(self, i2c)?).unwrap())
+    }
+}
@@ -0,0 +1,24 @@
+use embedded_hal_02::blocking::i2c::{Read, Write, WriteRead};
+
+use super::I2c;
+
+/// Any embedded-hal 0.2 bus, e.g. the [`SharedI2c`](crate::SharedI2c)
+/// proxies of the board.
+impl<B, E> I2c for B
+where
+    B: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+{
+    type Error = E;
+
+    fn write(&mut self, address: u8, data: &[u8]) -> Result<(), E> {
+        Write::write(self, address, data)
+    }
+
+    fn write_read(&mut self, address: u8, data: &[u8], buf: &mut [u8]) -> Result<(), E> {
+        WriteRead::write_read(self, address, data, buf)
+    }
+
+    fn read(&mut self, address: u8, buf: &mut [u8]) -> Result<(), E> {
+        Read::read(self, address, buf)
+    }
+}
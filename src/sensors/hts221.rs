@@ -0,0 +1,400 @@
+//! ST HTS221 temperature and humidity sensor.
+//!
+//! The chip converts with a factory calibration of two reference points per
+//! quantity, read once when the reader starts. Readings are interpolated in
+//! tenths with rounding, then corrected by the user [`Calibration`].
+//!
+//! ```ignore
+//! let mut i2c = buddy.i2c.acquire_i2c();
+//! let mut reader = Hts221Reader::new()
+//!     .with_data_rate(DataRate::Hz1)
+//!     .with_calibration(store.hts221_calibration().unwrap());
+//! match reader.read(&mut i2c) {
+//!     Ok(reading) => println!("{} {}", reading.temperature_dc, reading.humidity_dpct),
+//!     Err(err) => println!("{}", err.message()),
+//! }
+//! ```
+
+use core::fmt;
+
+pub use super::Error;
+use super::{div_round, Correction, I2c, Measurements, Quantity, Sensor, Tenths};
+
+/// 7-bit address, fixed on the chip.
+pub const ADDRESS: u8 = 0x5f;
+
+/// Value of [`WHO_AM_I`].
+pub const DEVICE_ID: u8 = 0xbc;
+
+pub const WHO_AM_I: u8 = 0x0f;
+pub const AV_CONF: u8 = 0x10;
+pub const CTRL_REG1: u8 = 0x20;
+pub const CTRL_REG2: u8 = 0x21;
+pub const STATUS: u8 = 0x27;
+/// First of the calibration registers, 16 up to `0x3f`.
+pub const CALIBRATION: u8 = 0x30;
+
+/// Set in the register address, reads and writes continue with the next
+/// register.
+const AUTO_INCREMENT: u8 = 0x80;
+
+const POWER_ON: u8 = 0x80;
+/// Outputs are not updated between reading their low and high byte.
+const BLOCK_DATA_UPDATE: u8 = 0x04;
+const HEATER: u8 = 0x02;
+const ONE_SHOT: u8 = 0x01;
+const HUMIDITY_READY: u8 = 0x02;
+const TEMPERATURE_READY: u8 = 0x01;
+
+/// How often the chip converts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DataRate {
+    /// Only when asked to, by every [`Hts221Reader::read`].
+    OneShot,
+    #[default]
+    Hz1,
+    Hz7,
+    Hz12_5,
+}
+
+impl DataRate {
+    fn bits(self) -> u8 {
+        match self {
+            DataRate::OneShot => 0b00,
+            DataRate::Hz1 => 0b01,
+            DataRate::Hz7 => 0b10,
+            DataRate::Hz12_5 => 0b11,
+        }
+    }
+}
+
+/// Samples the chip averages per reading. More samples mean less noise,
+/// longer conversions and more current.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Averaging {
+    /// `AVGT`, 2 << code samples.
+    temperature: u8,
+    /// `AVGH`, 4 << code samples.
+    humidity: u8,
+}
+
+impl Default for Averaging {
+    /// The reset value of the chip, 16 temperature and 32 humidity samples.
+    fn default() -> Self {
+        Self {
+            temperature: 3,
+            humidity: 3,
+        }
+    }
+}
+
+impl Averaging {
+    /// Powers of two from 2 to 256 temperature and from 4 to 512 humidity
+    /// samples, `None` otherwise.
+    pub fn new(temperature_samples: u16, humidity_samples: u16) -> Option<Self> {
+        let code = |samples: u16, min: u16| {
+            let code = (samples / min).trailing_zeros();
+            (samples.is_power_of_two() && samples >= min && code < 8).then_some(code as u8)
+        };
+        Some(Self {
+            temperature: code(temperature_samples, 2)?,
+            humidity: code(humidity_samples, 4)?,
+        })
+    }
+
+    pub fn temperature_samples(&self) -> u16 {
+        2 << self.temperature
+    }
+
+    pub fn humidity_samples(&self) -> u16 {
+        4 << self.humidity
+    }
+
+    fn bits(self) -> u8 {
+        self.temperature << 3 | self.humidity
+    }
+}
+
+/// User corrections, e.g. for the heat of the board around the sensor.
+/// Stored in the settings, see [`Store::hts221_calibration`].
+///
+/// [`Store::hts221_calibration`]: crate::settings::Store::hts221_calibration
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Calibration {
+    pub temperature: Correction,
+    pub humidity: Correction,
+}
+
+/// One conversion, corrected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Reading {
+    /// Tenths of a degree Celsius.
+    pub temperature_dc: i32,
+    /// Tenths of a percent of relative humidity, 0 to 1000.
+    pub humidity_dpct: i32,
+}
+
+impl fmt::Display for Reading {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} C {}%",
+            Tenths(self.temperature_dc),
+            Tenths(self.humidity_dpct)
+        )
+    }
+}
+
+/// Factory calibration: outputs at two temperatures and two humidities.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Coefficients {
+    t0_x8: i32,
+    t1_x8: i32,
+    t0_out: i32,
+    t1_out: i32,
+    h0_x2: i32,
+    h1_x2: i32,
+    h0_out: i32,
+    h1_out: i32,
+}
+
+impl Coefficients {
+    /// Decodes the registers from [`CALIBRATION`] on.
+    fn decode(registers: &[u8; 16]) -> Option<Self> {
+        let out = |at: usize| i32::from(i16::from_le_bytes([registers[at], registers[at + 1]]));
+        let msb = i32::from(registers[5]);
+        let coefficients = Self {
+            h0_x2: registers[0].into(),
+            h1_x2: registers[1].into(),
+            t0_x8: i32::from(registers[2]) | (msb & 0x03) << 8,
+            t1_x8: i32::from(registers[3]) | (msb & 0x0c) << 6,
+            h0_out: out(6),
+            h1_out: out(10),
+            t0_out: out(12),
+            t1_out: out(14),
+        };
+        (coefficients.t0_out != coefficients.t1_out && coefficients.h0_out != coefficients.h1_out)
+            .then_some(coefficients)
+    }
+
+    fn temperature_dc(&self, out: i16) -> i32 {
+        interpolate(
+            (self.t0_out, self.t0_x8),
+            (self.t1_out, self.t1_x8),
+            out.into(),
+            8,
+        )
+    }
+
+    fn humidity_dpct(&self, out: i16) -> i32 {
+        interpolate(
+            (self.h0_out, self.h0_x2),
+            (self.h1_out, self.h1_x2),
+            out.into(),
+            2,
+        )
+    }
+}
+
+/// Tenths at `out` on the line through two points of output and value,
+/// with the value in `1 / per_unit`.
+fn interpolate(
+    (out0, value0): (i32, i32),
+    (out1, value1): (i32, i32),
+    out: i32,
+    per_unit: i32,
+) -> i32 {
+    // Up to 16 + 10 bits per product, times 10 still fits
+    let mut numerator = (value0 * (out1 - out0) + (out - out0) * (value1 - value0)) * 10;
+    let mut denominator = (out1 - out0) * per_unit;
+    if denominator < 0 {
+        numerator = -numerator;
+        denominator = -denominator;
+    }
+    div_round(numerator, denominator)
+}
+
+/// Temperature and humidity in tenths from the HTS221.
+///
+/// The reader starts the chip on the first [`Hts221Reader::read`] and again
+/// after every bus error, so a sensor that was unplugged or reset comes
+/// back by itself.
+#[derive(Clone, Debug)]
+pub struct Hts221Reader {
+    data_rate: DataRate,
+    averaging: Averaging,
+    calibration: Calibration,
+    heater: bool,
+    coefficients: Option<Coefficients>,
+    /// A one-shot conversion was started.
+    converting: bool,
+    last: Option<Reading>,
+}
+
+impl Default for Hts221Reader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hts221Reader {
+    pub fn new() -> Self {
+        Self {
+            data_rate: DataRate::default(),
+            averaging: Averaging::default(),
+            calibration: Calibration::default(),
+            heater: false,
+            coefficients: None,
+            converting: false,
+            last: None,
+        }
+    }
+
+    pub fn with_data_rate(mut self, data_rate: DataRate) -> Self {
+        self.data_rate = data_rate;
+        self
+    }
+
+    pub fn with_averaging(mut self, averaging: Averaging) -> Self {
+        self.averaging = averaging;
+        self
+    }
+
+    pub fn with_calibration(mut self, calibration: Calibration) -> Self {
+        self.calibration = calibration;
+        self
+    }
+
+    pub fn calibration(&self) -> Calibration {
+        self.calibration
+    }
+
+    /// Applies to the next readings.
+    pub fn set_calibration(&mut self, calibration: Calibration) {
+        self.calibration = calibration;
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.coefficients.is_some()
+    }
+
+    pub fn heater(&self) -> bool {
+        self.heater
+    }
+
+    /// Checks the chip, reads its calibration and configures it.
+    pub fn start<I: I2c>(&mut self, i2c: &mut I) -> Result<(), Error<I::Error>> {
+        self.coefficients = None;
+        self.converting = false;
+        self.last = None;
+
+        let mut id = [0u8];
+        i2c.write_read(ADDRESS, &[WHO_AM_I], &mut id)
+            .map_err(Error::I2c)?;
+        if id[0] != DEVICE_ID {
+            return Err(Error::WrongDevice(id[0]));
+        }
+
+        let mut registers = [0u8; 16];
+        i2c.write_read(ADDRESS, &[CALIBRATION | AUTO_INCREMENT], &mut registers)
+            .map_err(Error::I2c)?;
+        let coefficients = Coefficients::decode(&registers).ok_or(Error::BadCoefficients)?;
+
+        let control = POWER_ON | BLOCK_DATA_UPDATE | self.data_rate.bits();
+        i2c.write(ADDRESS, &[AV_CONF, self.averaging.bits()])
+            .map_err(Error::I2c)?;
+        i2c.write(ADDRESS, &[CTRL_REG1, control])
+            .map_err(Error::I2c)?;
+        i2c.write(ADDRESS, &[CTRL_REG2, self.control2(false)])
+            .map_err(Error::I2c)?;
+        self.coefficients = Some(coefficients);
+        Ok(())
+    }
+
+    /// Switches the heater, which drives condensation out of the sensor.
+    /// Readings are off while it heats and for a while after.
+    pub fn set_heater<I: I2c>(&mut self, i2c: &mut I, on: bool) -> Result<(), Error<I::Error>> {
+        self.heater = on;
+        if !self.is_started() {
+            // Applied when the reader starts
+            return Ok(());
+        }
+        let result = i2c.write(ADDRESS, &[CTRL_REG2, self.control2(false)]);
+        self.check(result)
+    }
+
+    /// The newest reading, the previous one again when the chip has not
+    /// converted since. With [`DataRate::OneShot`] every call starts the
+    /// next conversion, so the reading is the one started by the call
+    /// before.
+    pub fn read<I: I2c>(&mut self, i2c: &mut I) -> Result<Reading, Error<I::Error>> {
+        if !self.is_started() {
+            self.start(i2c)?;
+        }
+        let Some(coefficients) = self.coefficients else {
+            return Err(Error::NotReady);
+        };
+
+        // Status, humidity and temperature
+        let mut data = [0u8; 5];
+        let result = i2c.write_read(ADDRESS, &[STATUS | AUTO_INCREMENT], &mut data);
+        self.check(result)?;
+        let ready = HUMIDITY_READY | TEMPERATURE_READY;
+        if data[0] & ready == ready {
+            let humidity = i16::from_le_bytes([data[1], data[2]]);
+            let temperature = i16::from_le_bytes([data[3], data[4]]);
+            let Calibration {
+                temperature: temperature_correction,
+                humidity: humidity_correction,
+            } = self.calibration;
+            let humidity_dpct = coefficients.humidity_dpct(humidity).clamp(0, 1000);
+            self.last = Some(Reading {
+                temperature_dc: temperature_correction
+                    .apply(coefficients.temperature_dc(temperature)),
+                humidity_dpct: humidity_correction.apply(humidity_dpct).clamp(0, 1000),
+            });
+            self.converting = false;
+        }
+
+        if self.data_rate == DataRate::OneShot && !self.converting {
+            let result = i2c.write(ADDRESS, &[CTRL_REG2, self.control2(true)]);
+            self.check(result)?;
+            self.converting = true;
+        }
+
+        self.last.ok_or(Error::NotReady)
+    }
+
+    fn control2(&self, one_shot: bool) -> u8 {
+        (if self.heater { HEATER } else { 0 }) | if one_shot { ONE_SHOT } else { 0 }
+    }
+
+    /// Starts over after a bus error.
+    fn check<E>(&mut self, result: Result<(), E>) -> Result<(), Error<E>> {
+        if result.is_err() {
+            self.coefficients = None;
+        }
+        result.map_err(Error::I2c)
+    }
+}
+
+impl Sensor for Hts221Reader {
+    const NAME: &'static str = "HTS221";
+    const ADDRESSES: &'static [u8] = &[ADDRESS];
+    const QUANTITIES: &'static [Quantity] = &[Quantity::Temperature, Quantity::Humidity];
+
+    /// A reader with the default settings, started.
+    fn probe<I: I2c>(i2c: &mut I, address: u8) -> Option<Self> {
+        let mut reader = Self::new();
+        (address == ADDRESS && reader.start(i2c).is_ok()).then_some(reader)
+    }
+
+    fn address(&self) -> u8 {
+        ADDRESS
+    }
+
+    fn read<I: I2c>(&mut self, i2c: &mut I) -> Result<Measurements, Error<I::Error>> {
+        let reading = Hts221Reader::read(self, i2c)?;
+        Ok(Measurements::from_slice(&[reading.temperature_dc, reading.humidity_dpct]).unwrap())
+    }
+}
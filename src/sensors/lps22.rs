@@ -0,0 +1,107 @@
+//! ST LPS22HB/LPS22HH pressure sensor.
+//!
+//! The two versions share the registers used here and differ in
+//! `WHO_AM_I`. Transfers continue with the next register by default.
+
+use super::{div_round, Error, I2c, Measurements, Quantity, Sensor};
+
+/// Address with `SA0` low.
+pub const ADDRESS: u8 = 0x5c;
+/// Address with `SA0` high, the default of most breakouts.
+pub const ADDRESS_ALT: u8 = 0x5d;
+
+/// Values of [`WHO_AM_I`] of the LPS22HB and the LPS22HH.
+pub const DEVICE_IDS: [u8; 2] = [0xb1, 0xb3];
+
+pub const WHO_AM_I: u8 = 0x0f;
+pub const CTRL_REG1: u8 = 0x10;
+/// Status, then pressure from `0x28` and temperature from `0x2b`.
+pub const STATUS: u8 = 0x27;
+
+/// One conversion per second, outputs not updated between reading their
+/// bytes.
+const CONTROL: u8 = 0x10 | 0x02;
+const PRESSURE_READY: u8 = 0x01;
+const TEMPERATURE_READY: u8 = 0x02;
+
+/// Pressure in tenths of a hectopascal and temperature in tenths.
+#[derive(Clone, Debug)]
+pub struct Lps22 {
+    address: u8,
+    started: bool,
+    last: Option<(i32, i32)>,
+}
+
+impl Lps22 {
+    pub fn new(address: u8) -> Self {
+        Self {
+            address,
+            started: false,
+            last: None,
+        }
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.started
+    }
+
+    /// Checks the chip and starts conversions.
+    pub fn start<I: I2c>(&mut self, i2c: &mut I) -> Result<(), Error<I::Error>> {
+        self.started = false;
+        self.last = None;
+        let mut id = [0u8];
+        i2c.write_read(self.address, &[WHO_AM_I], &mut id)
+            .map_err(Error::I2c)?;
+        if !DEVICE_IDS.contains(&id[0]) {
+            return Err(Error::WrongDevice(id[0]));
+        }
+        i2c.write(self.address, &[CTRL_REG1, CONTROL])
+            .map_err(Error::I2c)?;
+        self.started = true;
+        Ok(())
+    }
+
+    /// Pressure and temperature, the previous ones again when the chip has
+    /// not converted since.
+    pub fn read<I: I2c>(&mut self, i2c: &mut I) -> Result<(i32, i32), Error<I::Error>> {
+        if !self.started {
+            self.start(i2c)?;
+        }
+        let mut data = [0u8; 6];
+        if let Err(err) = i2c.write_read(self.address, &[STATUS], &mut data) {
+            self.started = false;
+            return Err(Error::I2c(err));
+        }
+        let ready = PRESSURE_READY | TEMPERATURE_READY;
+        if data[0] & ready == ready {
+            // 24 bits, 4096 per hectopascal
+            let pressure = i32::from_le_bytes([0, data[1], data[2], data[3]]) >> 8;
+            let temperature = i16::from_le_bytes([data[4], data[5]]);
+            self.last = Some((
+                div_round(pressure * 10, 4096),
+                div_round(temperature.into(), 10),
+            ));
+        }
+        self.last.ok_or(Error::NotReady)
+    }
+}
+
+impl Sensor for Lps22 {
+    const NAME: &'static str = "LPS22";
+    const ADDRESSES: &'static [u8] = &[ADDRESS, ADDRESS_ALT];
+    const QUANTITIES: &'static [Quantity] = &[Quantity::Pressure, Quantity::Temperature];
+
+    fn probe<I: I2c>(i2c: &mut I, address: u8) -> Option<Self> {
+        let mut sensor = Self::new(address);
+        sensor.start(i2c).is_ok().then_some(sensor)
+    }
+
+    fn address(&self) -> u8 {
+        self.address
+    }
+
+    fn read<I: I2c>(&mut self, i2c: &mut I) -> Result<Measurements, Error<I::Error>> {
+        let (pressure_dhpa, temperature_dc) = Lps22::read(self, i2c)?;
+        Ok(Measurements::from_slice(&[pressure_dhpa, temperature_dc]).unwrap())
+    }
+}
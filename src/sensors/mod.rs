@@ -0,0 +1,182 @@
+//! Sensors on the shared I2C bus.
+//!
+//! Drivers talk to the bus through the [`I2c`] trait, implemented for the
+//! bus proxies of the board, so their register handling and conversions run
+//! on the host against a mock bus. Like the `hts221` crate, they take the bus
+//! on every call instead of owning a proxy.
+//!
+//! Readings are fixed point, in tenths of the unit, the same as over HTTP
+//! and MQTT.
+//!
+//! Every driver is a [`Sensor`]: it tells its chip apart from others at the
+//! same address and reads a fixed list of [`Quantity`]s. The [`Registry`]
+//! probes the known addresses at boot and keeps what it finds.
+
+pub mod bh1750;
+pub mod bme280;
+#[cfg(feature = "esp32")]
+mod embedded_hal;
+pub mod hts221;
+pub mod lps22;
+pub mod registry;
+pub mod sht3x;
+
+pub use bh1750::Bh1750;
+pub use bme280::Bme280;
+pub use hts221::Hts221Reader;
+pub use lps22::Lps22;
+pub use registry::{Device, Registry};
+pub use sht3x::Sht3x;
+
+use core::fmt;
+
+/// Blocking I2C bus with 7-bit addresses.
+pub trait I2c {
+    type Error;
+
+    fn write(&mut self, address: u8, data: &[u8]) -> Result<(), Self::Error>;
+
+    /// Writes `data`, then reads `buf` in one transaction with a repeated
+    /// start.
+    fn write_read(&mut self, address: u8, data: &[u8], buf: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// Reads `buf` without writing first, for chips answering commands.
+    fn read(&mut self, address: u8, buf: &mut [u8]) -> Result<(), Self::Error>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error<E> {
+    I2c(E),
+    /// Another chip answers at the address, with this id.
+    WrongDevice(u8),
+    /// The factory calibration is unusable.
+    BadCoefficients,
+    /// No conversion finished since the sensor started.
+    NotReady,
+    /// The data does not match the CRC sent with it.
+    Checksum,
+}
+
+impl<E> Error<E> {
+    /// Short text for the display, at most 18 characters.
+    pub fn message(&self) -> &'static str {
+        match self {
+            Error::I2c(_) => "no sensor",
+            Error::WrongDevice(_) => "unknown sensor",
+            Error::BadCoefficients => "bad calibration",
+            Error::NotReady => "waiting for sensor",
+            Error::Checksum => "bad checksum",
+        }
+    }
+}
+
+/// What a sensor measures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Quantity {
+    Temperature,
+    Humidity,
+    Pressure,
+    Illuminance,
+}
+
+impl Quantity {
+    pub fn name(self) -> &'static str {
+        match self {
+            Quantity::Temperature => "Temperature",
+            Quantity::Humidity => "Humidity",
+            Quantity::Pressure => "Pressure",
+            Quantity::Illuminance => "Light",
+        }
+    }
+
+    /// Unit of the display, the values are in tenths of it.
+    pub fn unit(self) -> &'static str {
+        match self {
+            Quantity::Temperature => "C",
+            Quantity::Humidity => "%",
+            Quantity::Pressure => "hPa",
+            Quantity::Illuminance => "lx",
+        }
+    }
+
+    /// Lowercase id used in telemetry field names.
+    pub fn key(self) -> &'static str {
+        match self {
+            Quantity::Temperature => "temperature",
+            Quantity::Humidity => "humidity",
+            Quantity::Pressure => "pressure",
+            Quantity::Illuminance => "illuminance",
+        }
+    }
+}
+
+/// Most quantities read by one sensor.
+pub const MAX_QUANTITIES: usize = 3;
+
+/// Values of one read, in the order of [`Sensor::QUANTITIES`], in tenths.
+pub type Measurements = heapless::Vec<i32, MAX_QUANTITIES>;
+
+/// A driver the [`Registry`] can find on the bus.
+pub trait Sensor: Sized {
+    /// Name of the chip, e.g. `"BME280"`.
+    const NAME: &'static str;
+    /// Addresses the chip can be strapped to.
+    const ADDRESSES: &'static [u8];
+    /// What [`Sensor::read`] returns, in order.
+    const QUANTITIES: &'static [Quantity];
+
+    /// Identifies the chip at `address` and starts it, `None` when nothing
+    /// or another chip answers.
+    fn probe<I: I2c>(i2c: &mut I, address: u8) -> Option<Self>;
+
+    fn address(&self) -> u8;
+
+    fn read<I: I2c>(&mut self, i2c: &mut I) -> Result<Measurements, Error<I::Error>>;
+}
+
+/// User correction of a reading: scaled by `gain_permille / 1000`, then
+/// shifted by `offset`, both in the unit of the reading.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Correction {
+    /// Tenths of the unit.
+    pub offset: i16,
+    /// Thousandths, 1000 leaves the reading as is.
+    pub gain_permille: u16,
+}
+
+impl Default for Correction {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+impl Correction {
+    pub const NONE: Self = Self {
+        offset: 0,
+        gain_permille: 1000,
+    };
+
+    pub fn apply(&self, tenths: i32) -> i32 {
+        div_round(tenths * i32::from(self.gain_permille), 1000) + i32::from(self.offset)
+    }
+}
+
+/// Division rounding halves away from zero, `denominator` is positive.
+pub(crate) fn div_round(numerator: i32, denominator: i32) -> i32 {
+    if numerator < 0 {
+        (numerator - denominator / 2) / denominator
+    } else {
+        (numerator + denominator / 2) / denominator
+    }
+}
+
+/// Fixed point number with one decimal, as readings are shown and sent.
+pub(crate) struct Tenths(pub(crate) i32);
+
+impl fmt::Display for Tenths {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let value = self.0.unsigned_abs();
+        write!(f, "{}{}.{}", sign, value / 10, value % 10)
+    }
+}
@@ -0,0 +1,228 @@
+//! Sensors found on the bus.
+//!
+//! [`Registry::scan`] probes every address a known chip can have, once at
+//! boot, and keeps one [`Entry`] per chip that answers. The display pages
+//! and telemetry fields follow from the entries, so plugging in a breakout
+//! is all it takes to see its values.
+//!
+//! ```ignore
+//! let mut i2c = buddy.i2c.acquire_i2c();
+//! let mut registry = Registry::scan(&mut i2c);
+//! registry.poll(&mut i2c);
+//! for field in registry.fields() {
+//!     println!("{} {:?}", field.key, field.value);
+//! }
+//! ```
+
+use core::fmt::Write;
+
+use super::{
+    Bh1750, Bme280, Error, Hts221Reader, I2c, Lps22, Measurements, Quantity, Sensor, Sht3x,
+};
+
+/// Most sensors kept by a [`Registry`].
+pub const MAX_SENSORS: usize = 6;
+
+/// Every address probed by [`Registry::scan`], ascending. The display is at
+/// `0x3c` and never probed.
+pub const ADDRESSES: [u8; 8] = [0x23, 0x44, 0x45, 0x5c, 0x5d, 0x5f, 0x76, 0x77];
+
+/// Longest [`Field::key`].
+pub const MAX_KEY_LEN: usize = 24;
+
+/// One of the known sensors.
+#[derive(Clone, Debug)]
+pub enum Device {
+    Hts221(Hts221Reader),
+    Sht3x(Sht3x),
+    Bme280(Bme280),
+    Bh1750(Bh1750),
+    Lps22(Lps22),
+}
+
+impl Device {
+    /// Tries the chips that can be at `address`. The LPS22 goes before the
+    /// BH1750, which has no id and would take its place.
+    pub fn probe<I: I2c>(i2c: &mut I, address: u8) -> Option<Self> {
+        fn at<S: Sensor, I: I2c>(i2c: &mut I, address: u8) -> Option<S> {
+            S::ADDRESSES
+                .contains(&address)
+                .then(|| S::probe(i2c, address))
+                .flatten()
+        }
+        at(i2c, address)
+            .map(Device::Hts221)
+            .or_else(|| at(i2c, address).map(Device::Sht3x))
+            .or_else(|| at(i2c, address).map(Device::Bme280))
+            .or_else(|| at(i2c, address).map(Device::Lps22))
+            .or_else(|| at(i2c, address).map(Device::Bh1750))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Device::Hts221(_) => Hts221Reader::NAME,
+            Device::Sht3x(_) => Sht3x::NAME,
+            Device::Bme280(_) => Bme280::NAME,
+            Device::Bh1750(_) => Bh1750::NAME,
+            Device::Lps22(_) => Lps22::NAME,
+        }
+    }
+
+    pub fn address(&self) -> u8 {
+        match self {
+            Device::Hts221(sensor) => sensor.address(),
+            Device::Sht3x(sensor) => sensor.address(),
+            Device::Bme280(sensor) => sensor.address(),
+            Device::Bh1750(sensor) => sensor.address(),
+            Device::Lps22(sensor) => sensor.address(),
+        }
+    }
+
+    pub fn quantities(&self) -> &'static [Quantity] {
+        match self {
+            Device::Hts221(_) => Hts221Reader::QUANTITIES,
+            Device::Sht3x(_) => Sht3x::QUANTITIES,
+            Device::Bme280(_) => Bme280::QUANTITIES,
+            Device::Bh1750(_) => Bh1750::QUANTITIES,
+            Device::Lps22(_) => Lps22::QUANTITIES,
+        }
+    }
+
+    pub fn read<I: I2c>(&mut self, i2c: &mut I) -> Result<Measurements, Error<I::Error>> {
+        match self {
+            Device::Hts221(sensor) => Sensor::read(sensor, i2c),
+            Device::Sht3x(sensor) => Sensor::read(sensor, i2c),
+            Device::Bme280(sensor) => Sensor::read(sensor, i2c),
+            Device::Bh1750(sensor) => Sensor::read(sensor, i2c),
+            Device::Lps22(sensor) => Sensor::read(sensor, i2c),
+        }
+    }
+}
+
+/// A sensor with the outcome of its last read.
+#[derive(Clone, Debug)]
+pub struct Entry {
+    pub device: Device,
+    /// Values of the last successful read.
+    pub values: Option<Measurements>,
+    /// Why the last read failed, see [`Error::message`].
+    pub error: Option<&'static str>,
+}
+
+impl Entry {
+    /// `"BME280 76"`, the address in hex.
+    pub fn title(&self) -> heapless::String<12> {
+        let mut title = heapless::String::new();
+        write!(
+            title,
+            "{} {:02x}",
+            self.device.name(),
+            self.device.address()
+        )
+        .unwrap();
+        title
+    }
+}
+
+/// One value of one sensor, for telemetry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Field {
+    /// Name of the sensor, see [`Device::name`].
+    pub sensor: &'static str,
+    pub address: u8,
+    /// Unique on the board, e.g. `"bme280_76_pressure"`.
+    pub key: heapless::String<MAX_KEY_LEN>,
+    pub quantity: Quantity,
+    /// Tenths, `None` before the first successful read.
+    pub value: Option<i32>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Registry {
+    entries: heapless::Vec<Entry, MAX_SENSORS>,
+}
+
+impl Registry {
+    /// Probes every address of [`ADDRESSES`] and starts the sensors found.
+    pub fn scan<I: I2c>(i2c: &mut I) -> Self {
+        let mut registry = Self::default();
+        for &address in &ADDRESSES {
+            let Some(device) = Device::probe(i2c, address) else {
+                continue;
+            };
+            let entry = Entry {
+                device,
+                values: None,
+                error: None,
+            };
+            if registry.entries.push(entry).is_err() {
+                break;
+            }
+        }
+        registry
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// E.g. to calibrate the sensors found.
+    pub fn entries_mut(&mut self) -> &mut [Entry] {
+        &mut self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Reads every sensor. A failed read keeps the previous values and
+    /// sets the error until the next successful one.
+    pub fn poll<I: I2c>(&mut self, i2c: &mut I) {
+        for entry in &mut self.entries {
+            match entry.device.read(i2c) {
+                Ok(values) => {
+                    entry.values = Some(values);
+                    entry.error = None;
+                }
+                Err(err) => entry.error = Some(err.message()),
+            }
+        }
+    }
+
+    /// Every value of every sensor, in the order of the entries.
+    pub fn fields(&self) -> impl Iterator<Item = Field> + '_ {
+        self.entries.iter().flat_map(|entry| {
+            let device = &entry.device;
+            device
+                .quantities()
+                .iter()
+                .enumerate()
+                .map(move |(index, &quantity)| {
+                    let mut key = heapless::String::new();
+                    write!(
+                        key,
+                        "{}_{:02x}_{}",
+                        device.name(),
+                        device.address(),
+                        quantity.key()
+                    )
+                    .unwrap();
+                    key.make_ascii_lowercase();
+                    Field {
+                        sensor: device.name(),
+                        address: device.address(),
+                        key,
+                        quantity,
+                        value: entry
+                            .values
+                            .as_ref()
+                            .and_then(|values| values.get(index).copied()),
+                    }
+                })
+        })
+    }
+}
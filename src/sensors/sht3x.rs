@@ -0,0 +1,118 @@
+//! Sensirion SHT30/SHT31/SHT35 temperature and humidity sensor.
+//!
+//! The chip takes 16-bit commands instead of registers and sends every word
+//! with a CRC. It has no id, a status read with a valid CRC tells it apart.
+//! It measures once a second in periodic mode, [`Sht3x::read`] fetches the
+//! newest result.
+
+use super::{div_round, Error, I2c, Measurements, Quantity, Sensor};
+
+/// Address with `ADDR` low, the default of most breakouts.
+pub const ADDRESS: u8 = 0x44;
+/// Address with `ADDR` high.
+pub const ADDRESS_ALT: u8 = 0x45;
+
+pub const READ_STATUS: [u8; 2] = [0xf3, 0x2d];
+/// Periodic mode, one measurement per second, high repeatability.
+pub const PERIODIC_1MPS_HIGH: [u8; 2] = [0x21, 0x30];
+pub const FETCH_DATA: [u8; 2] = [0xe0, 0x00];
+
+/// CRC-8 of the chip, polynomial 0x31, initial value 0xff.
+pub fn crc8(data: &[u8]) -> u8 {
+    let mut crc = 0xffu8;
+    for &byte in data {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                crc << 1 ^ 0x31
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// The two bytes of a word followed by their CRC, `None` when it is wrong.
+fn word(data: &[u8]) -> Option<u16> {
+    (crc8(&data[..2]) == data[2]).then(|| u16::from_be_bytes([data[0], data[1]]))
+}
+
+/// Temperature and humidity in tenths from an SHT3x.
+///
+/// Like [`Hts221Reader`](super::Hts221Reader), it starts the chip again
+/// after a bus error.
+#[derive(Clone, Debug)]
+pub struct Sht3x {
+    address: u8,
+    started: bool,
+}
+
+impl Sht3x {
+    pub fn new(address: u8) -> Self {
+        Self {
+            address,
+            started: false,
+        }
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.started
+    }
+
+    /// Checks the status word and starts periodic measurements.
+    pub fn start<I: I2c>(&mut self, i2c: &mut I) -> Result<(), Error<I::Error>> {
+        self.started = false;
+        i2c.write(self.address, &READ_STATUS).map_err(Error::I2c)?;
+        let mut status = [0u8; 3];
+        i2c.read(self.address, &mut status).map_err(Error::I2c)?;
+        word(&status).ok_or(Error::Checksum)?;
+        i2c.write(self.address, &PERIODIC_1MPS_HIGH)
+            .map_err(Error::I2c)?;
+        self.started = true;
+        Ok(())
+    }
+
+    /// Temperature and humidity in tenths. The chip does not acknowledge
+    /// the read until its first measurement is done, which shows as a bus
+    /// error in the first second.
+    pub fn read<I: I2c>(&mut self, i2c: &mut I) -> Result<(i32, i32), Error<I::Error>> {
+        if !self.started {
+            self.start(i2c)?;
+        }
+        let mut data = [0u8; 6];
+        let result = i2c
+            .write(self.address, &FETCH_DATA)
+            .and_then(|()| i2c.read(self.address, &mut data));
+        if let Err(err) = result {
+            self.started = false;
+            return Err(Error::I2c(err));
+        }
+        let temperature = word(&data[..3]).ok_or(Error::Checksum)?;
+        let humidity = word(&data[3..]).ok_or(Error::Checksum)?;
+        // T = -45 + 175 raw / 65535, RH = 100 raw / 65535
+        let temperature_dc = div_round(1750 * i32::from(temperature), 65535) - 450;
+        let humidity_dpct = div_round(1000 * i32::from(humidity), 65535);
+        Ok((temperature_dc, humidity_dpct))
+    }
+}
+
+impl Sensor for Sht3x {
+    const NAME: &'static str = "SHT3x";
+    const ADDRESSES: &'static [u8] = &[ADDRESS, ADDRESS_ALT];
+    const QUANTITIES: &'static [Quantity] = &[Quantity::Temperature, Quantity::Humidity];
+
+    fn probe<I: I2c>(i2c: &mut I, address: u8) -> Option<Self> {
+        let mut sensor = Self::new(address);
+        sensor.start(i2c).is_ok().then_some(sensor)
+    }
+
+    fn address(&self) -> u8 {
+        self.address
+    }
+
+    fn read<I: I2c>(&mut self, i2c: &mut I) -> Result<Measurements, Error<I::Error>> {
+        let (temperature_dc, humidity_dpct) = Sht3x::read(self, i2c)?;
+        Ok(Measurements::from_slice(&[temperature_dc, humidity_dpct]).unwrap())
+    }
+}
@@ -0,0 +1,58 @@
+//! Sensor calibration, stored under the key `hts221.cal`.
+//!
+//! A value is the offset `i16` and the gain `u16` of the temperature, then
+//! of the humidity, little endian.
+
+use embedded_storage::nor_flash::NorFlash;
+
+use super::{Error, Store};
+use crate::sensors::{hts221::Calibration, Correction};
+
+const KEY: &str = "hts221.cal";
+const SIZE: usize = 8;
+
+fn decode(value: &[u8]) -> Option<Calibration> {
+    let value: &[u8; SIZE] = value.try_into().ok()?;
+    let correction = |at: usize| Correction {
+        offset: i16::from_le_bytes([value[at], value[at + 1]]),
+        gain_permille: u16::from_le_bytes([value[at + 2], value[at + 3]]),
+    };
+    Some(Calibration {
+        temperature: correction(0),
+        humidity: correction(4),
+    })
+}
+
+fn encode(calibration: &Calibration) -> [u8; SIZE] {
+    let mut value = [0u8; SIZE];
+    for (chunk, correction) in value
+        .chunks_exact_mut(4)
+        .zip([calibration.temperature, calibration.humidity])
+    {
+        chunk[..2].copy_from_slice(&correction.offset.to_le_bytes());
+        chunk[2..].copy_from_slice(&correction.gain_permille.to_le_bytes());
+    }
+    value
+}
+
+impl<F: NorFlash> Store<F> {
+    /// Stored calibration of the HTS221, none when not set or not readable.
+    pub fn hts221_calibration(&mut self) -> Result<Calibration, Error<F::Error>> {
+        let mut buf = [0u8; SIZE];
+        Ok(match self.get(KEY, &mut buf) {
+            Ok(Some(value)) => decode(value).unwrap_or_default(),
+            Ok(None) | Err(Error::BufferTooSmall) => Calibration::default(),
+            Err(err) => return Err(err),
+        })
+    }
+
+    pub fn save_hts221_calibration(
+        &mut self,
+        calibration: &Calibration,
+    ) -> Result<(), Error<F::Error>> {
+        if *calibration == Calibration::default() {
+            return self.remove(KEY);
+        }
+        self.set(KEY, &encode(calibration))
+    }
+}
@@ -0,0 +1,156 @@
+//! Two-button edit screens generated from a schema.
+//!
+//! - list: a click on left selects the next setting, a click on right edits
+//!   the setting
+//! - bool, enum and int: left changes the value, going around from the last
+//!   to the first, right saves it
+//! - text: left changes the character at the cursor, after the last of
+//!   [`TEXT_CHARS`] the text ends there; right moves the cursor to the next
+//!   character and saves at the end
+//!
+//! There is no cancel and holding left does not scroll: in the
+//! [`Launcher`](crate::launcher::Launcher) holding a button leaves the app,
+//! [`Editor::cancel`] then drops the edit.
+
+use super::schema::{Kind, Setting, Value, Values, MAX_TEXT_LEN, TEXT_CHARS};
+use crate::{
+    input::{Button, Event},
+    launcher::Menu,
+};
+
+/// Value being edited.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edit {
+    pub value: Value,
+    /// Index of the character of a text, its length at the end.
+    pub cursor: usize,
+}
+
+/// Edits the values of a schema, which are saved by the owner.
+pub struct Editor<'a> {
+    schema: &'a [Setting],
+    values: Values,
+    menu: Menu,
+    edit: Option<Edit>,
+    saved: Option<usize>,
+}
+
+impl<'a> Editor<'a> {
+    /// Values missing or not valid for their setting start as the default.
+    pub fn new(schema: &'a [Setting], values: &[Value]) -> Self {
+        let values = schema
+            .iter()
+            .enumerate()
+            .map(|(index, setting)| match values.get(index) {
+                Some(value) if setting.is_valid(value) => value.clone(),
+                _ => setting.default_value(),
+            })
+            .collect();
+        Self {
+            schema,
+            values,
+            menu: Menu::new(schema.len()),
+            edit: None,
+            saved: None,
+        }
+    }
+
+    pub fn schema(&self) -> &'a [Setting] {
+        self.schema
+    }
+
+    /// Saved values, in the order of the schema.
+    pub fn values(&self) -> &[Value] {
+        &self.values
+    }
+
+    pub fn menu(&self) -> &Menu {
+        &self.menu
+    }
+
+    /// Setting of the selected row or of the edit.
+    pub fn selected(&self) -> &'a Setting {
+        &self.schema[self.menu.selected()]
+    }
+
+    /// The edit in progress, `None` in the list.
+    pub fn edit(&self) -> Option<&Edit> {
+        self.edit.as_ref()
+    }
+
+    /// Goes back to the list without saving.
+    pub fn cancel(&mut self) {
+        self.edit = None;
+    }
+
+    /// Index of the setting saved since the last call, to be written to
+    /// the store.
+    pub fn take_saved(&mut self) -> Option<usize> {
+        self.saved.take()
+    }
+
+    /// Takes a button event. Returns whether the screen changed.
+    pub fn handle_event(&mut self, event: Event) -> bool {
+        if self.schema.is_empty() {
+            return false;
+        }
+        let index = self.menu.selected();
+        let setting = &self.schema[index];
+        let next = event == Event::Click(Button::Left);
+        let enter = event == Event::Click(Button::Right);
+        let Some(edit) = &mut self.edit else {
+            if next {
+                self.menu.next();
+            } else if enter {
+                self.edit = Some(Edit {
+                    value: self.values[index].clone(),
+                    cursor: 0,
+                });
+            }
+            return next || enter;
+        };
+
+        match (&setting.kind, &mut edit.value) {
+            (&Kind::Text { max_len, .. }, Value::Text(text)) => {
+                if next {
+                    next_char(text, edit.cursor, max_len);
+                } else if enter && edit.cursor < text.len() {
+                    edit.cursor += 1;
+                    return true;
+                }
+            }
+            (_, value) if next => *value = setting.next(value),
+            _ => {}
+        }
+        if enter {
+            if let Some(edit) = self.edit.take() {
+                self.values[index] = edit.value;
+                self.saved = Some(index);
+            }
+        }
+        next || enter
+    }
+}
+
+/// Changes the character at `cursor` to the next one of [`TEXT_CHARS`], or
+/// appends the first one at the end.
+fn next_char(text: &mut heapless::String<MAX_TEXT_LEN>, cursor: usize, max_len: usize) {
+    let first = TEXT_CHARS.chars().next().unwrap_or(' ');
+    let Some(current) = text[cursor..].chars().next() else {
+        if text.len() < max_len {
+            text.push(first).ok();
+        }
+        return;
+    };
+    let next = match TEXT_CHARS.find(current) {
+        Some(at) => TEXT_CHARS[at + current.len_utf8()..].chars().next(),
+        None => Some(first),
+    };
+    let tail: heapless::String<MAX_TEXT_LEN> =
+        text[cursor + current.len_utf8()..].try_into().unwrap();
+    text.truncate(cursor);
+    if let Some(next) = next {
+        text.push(next).ok();
+        text.push_str(&tail).ok();
+    }
+}
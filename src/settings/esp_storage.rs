@@ -0,0 +1,12 @@
+use esp_storage::{FlashStorage, FlashStorageError};
+
+use super::{Error, Store};
+
+/// Offset of the `settings` partition in `partitions.csv`.
+pub const PARTITION_OFFSET: u32 = 0x3f_0000;
+pub const PARTITION_SIZE: u32 = 0x1_0000;
+
+/// Mounts the store in the `settings` partition of the internal flash.
+pub fn open() -> Result<Store<FlashStorage>, Error<FlashStorageError>> {
+    Store::new(FlashStorage::new(), PARTITION_OFFSET, PARTITION_SIZE)
+}
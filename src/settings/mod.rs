@@ -0,0 +1,432 @@
+//! Persistent key/value settings in a flash partition.
+//!
+//! The [`Store`] is an append-only log on any [`NorFlash`]. Setting a key
+//! appends a record, the last record of a key wins. The region is split into
+//! two banks: when the active bank is full, the latest value of every key is
+//! copied to the other bank, which is only then marked valid. Each write thus
+//! costs one erase per bank-full of records, and an interrupted write or
+//! compaction leaves the previous values readable.
+//!
+//! ```ignore
+//! let mut store = settings::open().unwrap();
+//! store.set("tz", b"CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+//! let mut buf = [0u8; 64];
+//! let tz = store.get("tz", &mut buf).unwrap();
+//! ```
+//!
+//! Layout of a bank, all integers little endian:
+//!
+//! - header: magic `u32`, generation `u32`
+//! - records: kind `u8`, key length `u8`, value length `u16`, CRC-32 `u32` of
+//!   the preceding four bytes, the key and the value; then key and value,
+//!   padded with `0xff` to a multiple of four bytes
+//!
+//! Erased flash after the last record ends the log.
+
+use embedded_storage::nor_flash::NorFlash;
+
+mod calibration;
+mod editor;
+#[cfg(feature = "esp32")]
+mod esp_storage;
+mod networks;
+pub mod schema;
+
+#[cfg(feature = "esp32")]
+pub use self::esp_storage::{open, PARTITION_OFFSET, PARTITION_SIZE};
+pub use editor::{Edit, Editor};
+pub use networks::{known_networks, StoredNetwork, BUILT_IN_NETWORK, KNOWN_NETWORKS, MAX_NETWORKS};
+
+pub const MAX_KEY_LEN: usize = 32;
+pub const MAX_VALUE_LEN: usize = 256;
+
+/// "ESB1"
+const MAGIC: u32 = 0x3142_5345;
+const BANK_HEADER_SIZE: u32 = 8;
+const RECORD_HEADER_SIZE: usize = 8;
+const MAX_RECORD_SIZE: usize = RECORD_HEADER_SIZE + MAX_KEY_LEN + MAX_VALUE_LEN;
+/// Records start at multiples of this, which must be a multiple of the read
+/// and write size of the flash.
+const ALIGN: usize = 4;
+
+const KIND_VALUE: u8 = 0x01;
+const KIND_REMOVED: u8 = 0x02;
+const ERASED: u8 = 0xff;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error<E> {
+    Flash(E),
+    /// The latest values do not fit into a bank, or all network slots are
+    /// taken.
+    Full,
+    /// Keys are not empty.
+    EmptyKey,
+    KeyTooLong,
+    ValueTooLong,
+    /// The value is longer than the buffer passed to [`Store::get`].
+    BufferTooSmall,
+}
+
+impl<E> Error<E> {
+    /// Short text for the display.
+    pub fn message(&self) -> &'static str {
+        match self {
+            Error::Flash(_) => "flash error",
+            Error::Full => "settings full",
+            Error::EmptyKey => "empty key",
+            Error::KeyTooLong => "key too long",
+            Error::ValueTooLong => "value too long",
+            Error::BufferTooSmall => "buffer too small",
+        }
+    }
+}
+
+/// Record read from the log.
+#[derive(Clone, Copy, Debug)]
+struct Record {
+    kind: u8,
+    key_len: usize,
+    value_len: usize,
+}
+
+impl Record {
+    fn size(&self) -> usize {
+        padded(RECORD_HEADER_SIZE + self.key_len + self.value_len)
+    }
+}
+
+fn padded(len: usize) -> usize {
+    len.div_ceil(ALIGN) * ALIGN
+}
+
+/// CRC-32 (IEEE 802.3) of the concatenated parts.
+pub fn crc32(parts: &[&[u8]]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in parts.iter().flat_map(|part| part.iter()) {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+/// Key/value store on a flash region of two or more erase sectors.
+pub struct Store<F> {
+    flash: F,
+    offset: u32,
+    bank_size: u32,
+    active: u32,
+    generation: u32,
+    /// End of the log in the active bank.
+    end: u32,
+    /// A torn record ends the log, the next write compacts first.
+    clean: bool,
+}
+
+impl<F: NorFlash> Store<F> {
+    /// Mounts the store in `size` bytes of flash at `offset`, both multiples
+    /// of two erase sectors. Formats the region when it holds no store.
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)]
+    pub fn new(flash: F, offset: u32, size: u32) -> Result<Self, Error<F::Error>> {
+        let bank_size = size / 2;
+        assert!(
+            ALIGN % F::READ_SIZE == 0 && ALIGN % F::WRITE_SIZE == 0,
+            "unsupported flash read or write size"
+        );
+        assert!(
+            offset % F::ERASE_SIZE as u32 == 0
+                && bank_size % F::ERASE_SIZE as u32 == 0
+                && bank_size > 0,
+            "settings region is not aligned to erase sectors"
+        );
+
+        let mut store = Self {
+            flash,
+            offset,
+            bank_size,
+            active: 0,
+            generation: 0,
+            end: BANK_HEADER_SIZE,
+            clean: true,
+        };
+
+        let banks = [store.read_generation(0)?, store.read_generation(1)?];
+        match banks {
+            [Some(first), Some(second)] => {
+                // Wrapping comparison, the generation only ever counts up
+                let second_newer = (second.wrapping_sub(first) as i32) > 0;
+                store.active = second_newer as u32;
+                store.generation = if second_newer { second } else { first };
+            }
+            [Some(generation), None] => store.generation = generation,
+            [None, Some(generation)] => {
+                store.active = 1;
+                store.generation = generation;
+            }
+            [None, None] => {
+                store.format(0, 1)?;
+                store.generation = 1;
+            }
+        }
+
+        let mut buf = [0u8; MAX_RECORD_SIZE];
+        let mut position = BANK_HEADER_SIZE;
+        while let Some(record) = store.read_record(store.active, position, &mut buf)? {
+            position += record.size() as u32;
+        }
+        store.end = position;
+        store.clean = store.is_erased(store.active, position)?;
+
+        Ok(store)
+    }
+
+    pub fn into_inner(self) -> F {
+        self.flash
+    }
+
+    /// Bytes free in the active bank. Compaction may free more.
+    pub fn free(&self) -> usize {
+        if self.clean {
+            (self.bank_size - self.end) as usize
+        } else {
+            0
+        }
+    }
+
+    /// Copies the value of `key` to `buf` and returns it, `None` when the key
+    /// is not set.
+    pub fn get<'b>(
+        &mut self,
+        key: &str,
+        buf: &'b mut [u8],
+    ) -> Result<Option<&'b [u8]>, Error<F::Error>> {
+        let Some(position) = self.find(key)? else {
+            return Ok(None);
+        };
+        let mut record_buf = [0u8; MAX_RECORD_SIZE];
+        let Some(record) = self.read_record(self.active, position, &mut record_buf)? else {
+            return Ok(None);
+        };
+        let value = split(&record, &record_buf).1;
+        let target = buf.get_mut(..value.len()).ok_or(Error::BufferTooSmall)?;
+        target.copy_from_slice(value);
+        Ok(Some(target))
+    }
+
+    pub fn set(&mut self, key: &str, value: &[u8]) -> Result<(), Error<F::Error>> {
+        if value.len() > MAX_VALUE_LEN {
+            return Err(Error::ValueTooLong);
+        }
+        self.append(KIND_VALUE, key, value)
+    }
+
+    pub fn contains(&mut self, key: &str) -> Result<bool, Error<F::Error>> {
+        Ok(self.find(key)?.is_some())
+    }
+
+    /// Removes `key`, does nothing when it is not set.
+    pub fn remove(&mut self, key: &str) -> Result<(), Error<F::Error>> {
+        if !self.contains(key)? {
+            return Ok(());
+        }
+        self.append(KIND_REMOVED, key, &[])
+    }
+
+    /// Copies the latest values to the other bank and switches to it.
+    pub fn compact(&mut self) -> Result<(), Error<F::Error>> {
+        let target = 1 - self.active;
+        self.erase(target)?;
+
+        let mut buf = [0u8; MAX_RECORD_SIZE];
+        let mut position = BANK_HEADER_SIZE;
+        let mut end = BANK_HEADER_SIZE;
+        while let Some(record) = self.read_record(self.active, position, &mut buf)? {
+            position += record.size() as u32;
+            let mut key = [0u8; MAX_KEY_LEN];
+            key[..record.key_len].copy_from_slice(split(&record, &buf).0);
+            if record.kind != KIND_VALUE || self.is_superseded(position, &key[..record.key_len])? {
+                continue;
+            }
+
+            let size = record.size() as u32;
+            if end + size > self.bank_size {
+                return Err(Error::Full);
+            }
+            self.write(target, end, &buf[..size as usize])?;
+            end += size;
+        }
+
+        // The copy only counts once the header is in place
+        let generation = self.generation.wrapping_add(1);
+        self.write_header(target, generation)?;
+        self.active = target;
+        self.generation = generation;
+        self.end = end;
+        self.clean = true;
+        Ok(())
+    }
+
+    fn append(&mut self, kind: u8, key: &str, value: &[u8]) -> Result<(), Error<F::Error>> {
+        if key.is_empty() {
+            return Err(Error::EmptyKey);
+        }
+        if key.len() > MAX_KEY_LEN {
+            return Err(Error::KeyTooLong);
+        }
+
+        let mut buf = [ERASED; MAX_RECORD_SIZE];
+        let header = [
+            kind,
+            key.len() as u8,
+            value.len() as u8,
+            (value.len() >> 8) as u8,
+        ];
+        let crc = crc32(&[&header, key.as_bytes(), value]);
+        buf[..4].copy_from_slice(&header);
+        buf[4..8].copy_from_slice(&crc.to_le_bytes());
+        buf[8..8 + key.len()].copy_from_slice(key.as_bytes());
+        buf[8 + key.len()..8 + key.len() + value.len()].copy_from_slice(value);
+        let size = padded(RECORD_HEADER_SIZE + key.len() + value.len());
+
+        if size > self.free() {
+            self.compact()?;
+            if size > self.free() {
+                return Err(Error::Full);
+            }
+        }
+        self.write(self.active, self.end, &buf[..size])?;
+        self.end += size as u32;
+        Ok(())
+    }
+
+    /// Position of the latest value of `key` in the active bank.
+    fn find(&mut self, key: &str) -> Result<Option<u32>, Error<F::Error>> {
+        let mut buf = [0u8; MAX_RECORD_SIZE];
+        let mut found = None;
+        let mut position = BANK_HEADER_SIZE;
+        while let Some(record) = self.read_record(self.active, position, &mut buf)? {
+            if split(&record, &buf).0 == key.as_bytes() {
+                found = (record.kind == KIND_VALUE).then_some(position);
+            }
+            position += record.size() as u32;
+        }
+        Ok(found)
+    }
+
+    /// Whether a record of `key` follows `position` in the active bank.
+    fn is_superseded(&mut self, mut position: u32, key: &[u8]) -> Result<bool, Error<F::Error>> {
+        let mut buf = [0u8; MAX_RECORD_SIZE];
+        while let Some(record) = self.read_record(self.active, position, &mut buf)? {
+            if split(&record, &buf).0 == key {
+                return Ok(true);
+            }
+            position += record.size() as u32;
+        }
+        Ok(false)
+    }
+
+    /// Reads and checks the record at `position`, `None` at the end of the
+    /// log or at a torn record.
+    fn read_record(
+        &mut self,
+        bank: u32,
+        position: u32,
+        buf: &mut [u8; MAX_RECORD_SIZE],
+    ) -> Result<Option<Record>, Error<F::Error>> {
+        if position + RECORD_HEADER_SIZE as u32 > self.bank_size {
+            return Ok(None);
+        }
+        self.read(bank, position, &mut buf[..RECORD_HEADER_SIZE])?;
+
+        let record = Record {
+            kind: buf[0],
+            key_len: buf[1] as usize,
+            value_len: u16::from_le_bytes([buf[2], buf[3]]) as usize,
+        };
+        let valid = matches!(record.kind, KIND_VALUE | KIND_REMOVED)
+            && (1..=MAX_KEY_LEN).contains(&record.key_len)
+            && record.value_len <= MAX_VALUE_LEN
+            && position + record.size() as u32 <= self.bank_size;
+        if !valid {
+            return Ok(None);
+        }
+
+        let size = record.size();
+        self.read(
+            bank,
+            position + RECORD_HEADER_SIZE as u32,
+            &mut buf[RECORD_HEADER_SIZE..size],
+        )?;
+        let crc = u32::from_le_bytes([buf[4], buf[5], buf[6], buf[7]]);
+        let (key, value) = split(&record, buf);
+        if crc32(&[&buf[..4], key, value]) != crc {
+            return Ok(None);
+        }
+        Ok(Some(record))
+    }
+
+    fn read_generation(&mut self, bank: u32) -> Result<Option<u32>, Error<F::Error>> {
+        let mut header = [0u8; BANK_HEADER_SIZE as usize];
+        self.read(bank, 0, &mut header)?;
+        let magic = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+        let generation = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+        Ok((magic == MAGIC).then_some(generation))
+    }
+
+    fn is_erased(&mut self, bank: u32, position: u32) -> Result<bool, Error<F::Error>> {
+        let mut buf = [0u8; 32];
+        let mut position = position;
+        while position < self.bank_size {
+            let len = buf.len().min((self.bank_size - position) as usize);
+            self.read(bank, position, &mut buf[..len])?;
+            if buf[..len].iter().any(|&byte| byte != ERASED) {
+                return Ok(false);
+            }
+            position += len as u32;
+        }
+        Ok(true)
+    }
+
+    fn format(&mut self, bank: u32, generation: u32) -> Result<(), Error<F::Error>> {
+        self.erase(bank)?;
+        self.write_header(bank, generation)
+    }
+
+    fn write_header(&mut self, bank: u32, generation: u32) -> Result<(), Error<F::Error>> {
+        let mut header = [0u8; BANK_HEADER_SIZE as usize];
+        header[..4].copy_from_slice(&MAGIC.to_le_bytes());
+        header[4..].copy_from_slice(&generation.to_le_bytes());
+        self.write(bank, 0, &header)
+    }
+
+    fn bank_offset(&self, bank: u32) -> u32 {
+        self.offset + bank * self.bank_size
+    }
+
+    fn read(&mut self, bank: u32, position: u32, buf: &mut [u8]) -> Result<(), Error<F::Error>> {
+        let offset = self.bank_offset(bank) + position;
+        self.flash.read(offset, buf).map_err(Error::Flash)
+    }
+
+    fn write(&mut self, bank: u32, position: u32, buf: &[u8]) -> Result<(), Error<F::Error>> {
+        let offset = self.bank_offset(bank) + position;
+        self.flash.write(offset, buf).map_err(Error::Flash)
+    }
+
+    fn erase(&mut self, bank: u32) -> Result<(), Error<F::Error>> {
+        let offset = self.bank_offset(bank);
+        self.flash
+            .erase(offset, offset + self.bank_size)
+            .map_err(Error::Flash)
+    }
+}
+
+/// Key and value of a record read into `buf`.
+fn split<'b>(record: &Record, buf: &'b [u8]) -> (&'b [u8], &'b [u8]) {
+    let key_end = RECORD_HEADER_SIZE + record.key_len;
+    (
+        &buf[RECORD_HEADER_SIZE..key_end],
+        &buf[key_end..key_end + record.value_len],
+    )
+}
@@ -0,0 +1,169 @@
+//! Known Wi-Fi networks, stored under the keys `wifi.0` to `wifi.3`.
+//!
+//! A value is the priority `u8`, the SSID length `u8`, the SSID and the
+//! password.
+
+use core::fmt::Write;
+
+use embedded_storage::nor_flash::NorFlash;
+
+use super::{Error, Store};
+use crate::wifi::Network;
+
+/// Networks the store holds.
+pub const MAX_NETWORKS: usize = 4;
+
+/// Priority, SSID length, SSID and password.
+const MAX_NETWORK_SIZE: usize = 2 + 32 + 64;
+
+/// Stored networks plus the one built in at compile time.
+pub const KNOWN_NETWORKS: usize = MAX_NETWORKS + 1;
+
+/// Network from the `SSID` and `PASSWORD` environment variables at compile
+/// time, if any, used when none of the stored networks is in range.
+pub const BUILT_IN_NETWORK: Option<Network<'static>> = match option_env!("SSID") {
+    Some(ssid) => Some(Network::new(
+        ssid,
+        match option_env!("PASSWORD") {
+            Some(password) => password,
+            None => "",
+        },
+    )),
+    None => None,
+};
+
+/// Credentials of a network kept in flash.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoredNetwork {
+    pub ssid: heapless::String<32>,
+    pub password: heapless::String<64>,
+    /// Networks with a higher priority are tried first.
+    pub priority: u8,
+}
+
+impl StoredNetwork {
+    /// `None` when the SSID is empty or longer than 32 or the password
+    /// longer than 64 bytes.
+    pub fn new(ssid: &str, password: &str, priority: u8) -> Option<Self> {
+        if ssid.is_empty() {
+            return None;
+        }
+        Some(Self {
+            ssid: ssid.try_into().ok()?,
+            password: password.try_into().ok()?,
+            priority,
+        })
+    }
+
+    pub fn network(&self) -> Network<'_> {
+        Network::new(&self.ssid, &self.password)
+    }
+
+    fn decode(value: &[u8]) -> Option<Self> {
+        let (&[priority, ssid_len], rest) = value.split_first_chunk::<2>()?;
+        let (ssid, password) = rest.split_at_checked(ssid_len as usize)?;
+        Self::new(
+            core::str::from_utf8(ssid).ok()?,
+            core::str::from_utf8(password).ok()?,
+            priority,
+        )
+    }
+
+    fn encode<'b>(&self, buf: &'b mut [u8; MAX_NETWORK_SIZE]) -> &'b [u8] {
+        let ssid = self.ssid.as_bytes();
+        let password = self.password.as_bytes();
+        let end = 2 + ssid.len() + password.len();
+        buf[0] = self.priority;
+        buf[1] = ssid.len() as u8;
+        buf[2..2 + ssid.len()].copy_from_slice(ssid);
+        buf[2 + ssid.len()..end].copy_from_slice(password);
+        &buf[..end]
+    }
+}
+
+fn key(slot: usize) -> heapless::String<8> {
+    let mut key = heapless::String::new();
+    write!(key, "wifi.{}", slot).unwrap();
+    key
+}
+
+impl<F: NorFlash> Store<F> {
+    /// Stored networks, highest priority first. Values that do not decode
+    /// are skipped.
+    pub fn networks(
+        &mut self,
+    ) -> Result<heapless::Vec<StoredNetwork, MAX_NETWORKS>, Error<F::Error>> {
+        let mut networks = heapless::Vec::<StoredNetwork, MAX_NETWORKS>::new();
+        for slot in 0..MAX_NETWORKS {
+            let Some(network) = self.stored_network(slot)? else {
+                continue;
+            };
+            // Equal priorities keep the slot order
+            let index = networks
+                .iter()
+                .position(|stored| stored.priority < network.priority)
+                .unwrap_or(networks.len());
+            networks.insert(index, network).unwrap();
+        }
+        Ok(networks)
+    }
+
+    /// Stores a network, replacing the one with the same SSID. Fails with
+    /// [`Error::Full`] when [`MAX_NETWORKS`] other networks are stored.
+    pub fn save_network(&mut self, network: &StoredNetwork) -> Result<(), Error<F::Error>> {
+        let slot = match self.network_slot(Some(&network.ssid))? {
+            Some(slot) => slot,
+            None => self.network_slot(None)?.ok_or(Error::Full)?,
+        };
+        let mut buf = [0u8; MAX_NETWORK_SIZE];
+        self.set(&key(slot), network.encode(&mut buf))
+    }
+
+    /// Removes the network with the SSID, returns whether it was stored.
+    pub fn remove_network(&mut self, ssid: &str) -> Result<bool, Error<F::Error>> {
+        let Some(slot) = self.network_slot(Some(ssid))? else {
+            return Ok(false);
+        };
+        self.remove(&key(slot))?;
+        Ok(true)
+    }
+
+    fn stored_network(&mut self, slot: usize) -> Result<Option<StoredNetwork>, Error<F::Error>> {
+        let mut buf = [0u8; MAX_NETWORK_SIZE];
+        match self.get(&key(slot), &mut buf) {
+            Ok(value) => Ok(value.and_then(StoredNetwork::decode)),
+            Err(Error::BufferTooSmall) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Slot of the network with the SSID, or the first free slot for `None`.
+    fn network_slot(&mut self, ssid: Option<&str>) -> Result<Option<usize>, Error<F::Error>> {
+        for slot in 0..MAX_NETWORKS {
+            let stored = self.stored_network(slot)?;
+            if stored.as_ref().map(|stored| stored.ssid.as_str()) == ssid {
+                return Ok(Some(slot));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Networks for the [`WifiManager`](crate::wifi::WifiManager) in order of
+/// priority: the stored ones, then `fallback` unless it is stored already.
+pub fn known_networks<'a>(
+    stored: &'a [StoredNetwork],
+    fallback: Option<Network<'a>>,
+) -> heapless::Vec<Network<'a>, KNOWN_NETWORKS> {
+    let mut networks: heapless::Vec<_, KNOWN_NETWORKS> = stored
+        .iter()
+        .take(MAX_NETWORKS)
+        .map(StoredNetwork::network)
+        .collect();
+    if let Some(fallback) = fallback {
+        if !networks.iter().any(|network| network.ssid == fallback.ssid) {
+            networks.push(fallback).unwrap();
+        }
+    }
+    networks
+}
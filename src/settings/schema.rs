@@ -0,0 +1,421 @@
+//! Settings described by a schema, edited on the device.
+//!
+//! A [`Setting`] names its key in the [`Store`], its label on the display
+//! and its [`Kind`] with the default. The [`Editor`](super::Editor) builds
+//! the edit screens from that, and the store keeps only the values that
+//! differ from the default:
+//!
+//! - bool: `0` or `1`
+//! - enum: the index of the option
+//! - int: `i32`, little endian
+//! - text: the ASCII characters
+//!
+//! A stored value that no longer fits its setting, like an option removed
+//! or a range narrowed, reads as the default.
+
+use core::fmt;
+
+use embedded_storage::nor_flash::NorFlash;
+
+use super::{Error, Store};
+
+/// Most settings in a schema.
+pub const MAX_SETTINGS: usize = 8;
+/// Longest text setting.
+pub const MAX_TEXT_LEN: usize = 40;
+/// Characters of text settings, in the order the left button cycles through
+/// them. After the last one the text ends at the cursor.
+pub const TEXT_CHARS: &str =
+    "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+-,./:<>_ ";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Bool {
+        default: bool,
+    },
+    Enum {
+        options: &'static [&'static str],
+        default: u8,
+    },
+    /// `min` to `max` in steps of `step` from `min`, a `step` of 0 allows
+    /// any value in between and clicks through them one by one.
+    Int {
+        min: i32,
+        max: i32,
+        step: i32,
+        default: i32,
+        /// Shown after the number.
+        unit: &'static str,
+    },
+    /// Up to `max_len` of [`TEXT_CHARS`], at most [`MAX_TEXT_LEN`].
+    Text {
+        default: &'static str,
+        max_len: usize,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    Bool(bool),
+    /// Index of the option.
+    Enum(u8),
+    Int(i32),
+    Text(heapless::String<MAX_TEXT_LEN>),
+}
+
+impl Value {
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_option(&self) -> Option<u8> {
+        match *self {
+            Value::Enum(index) => Some(index),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i32> {
+        match *self {
+            Value::Int(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Value::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+}
+
+/// Values in the order of their schema.
+pub type Values = heapless::Vec<Value, MAX_SETTINGS>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Setting {
+    /// Key in the [`Store`], at most [`MAX_KEY_LEN`](super::MAX_KEY_LEN).
+    pub key: &'static str,
+    /// Shown in the list, fits next to the value when short.
+    pub label: &'static str,
+    pub kind: Kind,
+}
+
+impl Setting {
+    pub fn default_value(&self) -> Value {
+        match self.kind {
+            Kind::Bool { default } => Value::Bool(default),
+            Kind::Enum { default, .. } => Value::Enum(default),
+            Kind::Int { default, .. } => Value::Int(default),
+            Kind::Text { default, .. } => Value::Text(default.try_into().unwrap_or_default()),
+        }
+    }
+
+    /// Whether `value` is of the kind of this setting and in its range.
+    pub fn is_valid(&self, value: &Value) -> bool {
+        match (self.kind, value) {
+            (Kind::Bool { .. }, Value::Bool(_)) => true,
+            (Kind::Enum { options, .. }, &Value::Enum(index)) => usize::from(index) < options.len(),
+            (Kind::Int { min, max, step, .. }, &Value::Int(value)) => {
+                (min..=max).contains(&value) && (step == 0 || (value - min) % step == 0)
+            }
+            (Kind::Text { max_len, .. }, Value::Text(text)) => {
+                text.len() <= max_len && text.chars().all(|c| TEXT_CHARS.contains(c))
+            }
+            _ => false,
+        }
+    }
+
+    /// The value after a click on the left button: toggled, the next option
+    /// or the next step, from the last back to the first. Texts are edited
+    /// one character at a time by the [`Editor`](super::Editor).
+    pub fn next(&self, value: &Value) -> Value {
+        match (self.kind, value) {
+            (Kind::Bool { .. }, &Value::Bool(value)) => Value::Bool(!value),
+            (Kind::Enum { options, .. }, &Value::Enum(index)) => {
+                Value::Enum(((usize::from(index) + 1) % options.len()) as u8)
+            }
+            (Kind::Int { min, max, step, .. }, &Value::Int(value)) => {
+                let step = step.max(1);
+                Value::Int(if value + step > max {
+                    min
+                } else {
+                    value + step
+                })
+            }
+            _ => value.clone(),
+        }
+    }
+
+    /// Reads a stored value, `None` when it does not fit this setting.
+    pub fn decode(&self, bytes: &[u8]) -> Option<Value> {
+        let value = match (self.kind, bytes) {
+            (Kind::Bool { .. }, &[value]) if value <= 1 => Value::Bool(value == 1),
+            (Kind::Enum { .. }, &[index]) => Value::Enum(index),
+            (Kind::Int { .. }, bytes) => Value::Int(i32::from_le_bytes(bytes.try_into().ok()?)),
+            (Kind::Text { .. }, bytes) => {
+                Value::Text(core::str::from_utf8(bytes).ok()?.try_into().ok()?)
+            }
+            _ => return None,
+        };
+        self.is_valid(&value).then_some(value)
+    }
+
+    /// Bytes of a value to store.
+    pub fn encode(&self, value: &Value) -> heapless::Vec<u8, MAX_TEXT_LEN> {
+        let mut bytes = heapless::Vec::new();
+        match value {
+            Value::Bool(value) => bytes.push(u8::from(*value)).unwrap(),
+            Value::Enum(index) => bytes.push(*index).unwrap(),
+            Value::Int(value) => bytes.extend_from_slice(&value.to_le_bytes()).unwrap(),
+            Value::Text(text) => bytes.extend_from_slice(text.as_bytes()).unwrap(),
+        }
+        bytes
+    }
+
+    /// Shows `value` as in the list: `on`/`off`, the option, the number with
+    /// its unit or the text.
+    pub fn display<'a>(&'a self, value: &'a Value) -> impl fmt::Display + 'a {
+        Shown {
+            setting: self,
+            value,
+        }
+    }
+}
+
+struct Shown<'a> {
+    setting: &'a Setting,
+    value: &'a Value,
+}
+
+impl fmt::Display for Shown<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.setting.kind, self.value) {
+            (_, Value::Bool(value)) => f.write_str(if *value { "on" } else { "off" }),
+            (Kind::Enum { options, .. }, &Value::Enum(index)) => {
+                f.write_str(options.get(usize::from(index)).unwrap_or(&"?"))
+            }
+            (Kind::Int { unit, .. }, Value::Int(value)) => write!(f, "{}{}", value, unit),
+            (_, Value::Text(text)) => f.write_str(text),
+            _ => f.write_str("?"),
+        }
+    }
+}
+
+/// Value of `setting` in `values` of `schema`, found by its key.
+pub fn lookup<'v>(schema: &[Setting], values: &'v [Value], setting: &Setting) -> Option<&'v Value> {
+    let index = schema.iter().position(|entry| entry.key == setting.key)?;
+    values.get(index).filter(|value| setting.is_valid(value))
+}
+
+impl<F: NorFlash> Store<F> {
+    /// Stored value of `setting`, the default when not set or not valid.
+    pub fn setting(&mut self, setting: &Setting) -> Result<Value, Error<F::Error>> {
+        let mut buf = [0u8; MAX_TEXT_LEN];
+        Ok(match self.get(setting.key, &mut buf) {
+            Ok(Some(bytes)) => setting
+                .decode(bytes)
+                .unwrap_or_else(|| setting.default_value()),
+            Ok(None) | Err(Error::BufferTooSmall) => setting.default_value(),
+            Err(err) => return Err(err),
+        })
+    }
+
+    /// Stores `value`, or removes the key when it is the default.
+    pub fn save_setting(
+        &mut self,
+        setting: &Setting,
+        value: &Value,
+    ) -> Result<(), Error<F::Error>> {
+        if *value == setting.default_value() {
+            return self.remove(setting.key);
+        }
+        self.set(setting.key, &setting.encode(value))
+    }
+
+    /// Stored values of all settings of `schema`.
+    pub fn settings(&mut self, schema: &[Setting]) -> Result<Values, Error<F::Error>> {
+        let mut values = Values::new();
+        for setting in schema.iter().take(MAX_SETTINGS) {
+            values.push(self.setting(setting)?).ok();
+        }
+        Ok(values)
+    }
+
+    /// Settings of the board, see [`Config`].
+    pub fn config(&mut self) -> Result<Config, Error<F::Error>> {
+        Ok(Config::new(&SCHEMA, &self.settings(&SCHEMA)?))
+    }
+}
+
+/// Orientation of the display.
+pub const ROTATION: Setting = Setting {
+    key: "display.rotation",
+    label: "Rotation",
+    kind: Kind::Enum {
+        options: &["normal", "flipped"],
+        default: 0,
+    },
+};
+
+/// Contrast of the display in percent.
+pub const CONTRAST: Setting = Setting {
+    key: "display.contrast",
+    label: "Contrast",
+    kind: Kind::Int {
+        min: 0,
+        max: 100,
+        step: 10,
+        default: 40,
+        unit: "%",
+    },
+};
+
+/// Brightness of the LED effects, see [`Output`](crate::led::effects::Output).
+pub const LED_BRIGHTNESS: Setting = Setting {
+    key: "led.brightness",
+    label: "LED",
+    kind: Kind::Int {
+        min: 0,
+        max: 128,
+        step: 8,
+        default: crate::led::effects::DEFAULT_BRIGHTNESS as i32,
+        unit: "",
+    },
+};
+
+/// POSIX TZ string of the clock, see [`TimeZone`](crate::time::TimeZone).
+pub const TIME_ZONE: Setting = Setting {
+    key: "time.tz",
+    label: "Time zone",
+    kind: Kind::Text {
+        default: "CET-1CEST,M3.5.0,M10.5.0/3",
+        max_len: MAX_TEXT_LEN,
+    },
+};
+
+/// Unit of temperatures.
+pub const UNITS: Setting = Setting {
+    key: "units.temperature",
+    label: "Units",
+    kind: Kind::Enum {
+        options: &["C", "F"],
+        default: 0,
+    },
+};
+
+/// Time between two sensor readings in seconds.
+pub const SAMPLE_INTERVAL: Setting = Setting {
+    key: "sensors.interval",
+    label: "Interval",
+    kind: Kind::Int {
+        min: 1,
+        max: 60,
+        step: 1,
+        default: 5,
+        unit: "s",
+    },
+};
+
+/// Settings of the board, in the order they are listed.
+pub const SCHEMA: [Setting; 6] = [
+    ROTATION,
+    CONTRAST,
+    LED_BRIGHTNESS,
+    TIME_ZONE,
+    UNITS,
+    SAMPLE_INTERVAL,
+];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Rotation {
+    #[default]
+    Normal,
+    /// Turned by 180°.
+    Flipped,
+}
+
+impl Rotation {
+    pub fn flipped(self) -> Self {
+        match self {
+            Rotation::Normal => Rotation::Flipped,
+            Rotation::Flipped => Rotation::Normal,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Units {
+    #[default]
+    Celsius,
+    Fahrenheit,
+}
+
+impl Units {
+    /// Converts tenths of a degree Celsius.
+    pub fn from_dc(self, temperature_dc: i32) -> i32 {
+        match self {
+            Units::Celsius => temperature_dc,
+            Units::Fahrenheit => temperature_dc * 9 / 5 + 320,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Units::Celsius => "C",
+            Units::Fahrenheit => "F",
+        }
+    }
+}
+
+/// The board settings of [`SCHEMA`], typed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Config {
+    pub rotation: Rotation,
+    pub contrast_pct: u8,
+    pub led_brightness: u8,
+    pub time_zone: heapless::String<MAX_TEXT_LEN>,
+    pub units: Units,
+    pub sample_interval_ms: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new(&[], &[])
+    }
+}
+
+impl Config {
+    /// Takes the values of `schema`, defaults for the settings it does not
+    /// have.
+    pub fn new(schema: &[Setting], values: &[Value]) -> Self {
+        let value = |setting: &Setting| {
+            lookup(schema, values, setting)
+                .cloned()
+                .unwrap_or_else(|| setting.default_value())
+        };
+        let int = |setting: &Setting| value(setting).as_int().unwrap_or_default();
+        let option = |setting: &Setting| value(setting).as_option().unwrap_or_default();
+        Self {
+            rotation: match option(&ROTATION) {
+                0 => Rotation::Normal,
+                _ => Rotation::Flipped,
+            },
+            contrast_pct: int(&CONTRAST) as u8,
+            led_brightness: int(&LED_BRIGHTNESS) as u8,
+            time_zone: match value(&TIME_ZONE) {
+                Value::Text(text) => text,
+                _ => heapless::String::new(),
+            },
+            units: match option(&UNITS) {
+                0 => Units::Celsius,
+                _ => Units::Fahrenheit,
+            },
+            sample_interval_ms: int(&SAMPLE_INTERVAL) as u64 * 1000,
+        }
+    }
+}
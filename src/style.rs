@@ -0,0 +1,21 @@
+//! Text styles shared by the examples.
+
+use embedded_graphics::{
+    mono_font::{
+        ascii::{FONT_10X20, FONT_4X6, FONT_6X10},
+        MonoTextStyle,
+    },
+    pixelcolor::BinaryColor,
+};
+
+/// Default text, 3 lines of 21 characters fit on the 128x32 panel.
+pub const TEXT: MonoTextStyle<'static, BinaryColor> =
+    MonoTextStyle::new(&FONT_6X10, BinaryColor::On);
+
+/// Small text used for snowflakes and labels.
+pub const SMALL: MonoTextStyle<'static, BinaryColor> =
+    MonoTextStyle::new(&FONT_4X6, BinaryColor::On);
+
+/// Large text used by the clock.
+pub const LARGE: MonoTextStyle<'static, BinaryColor> =
+    MonoTextStyle::new(&FONT_10X20, BinaryColor::On);
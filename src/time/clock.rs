@@ -0,0 +1,182 @@
+//! Wall clock anchored to a monotonic timer.
+//!
+//! Instead of counting loop iterations the [`Clock`] keeps the Unix time at
+//! one instant of a monotonic timer (`esp_wifi::current_millis`, a HAL
+//! systimer) and derives the time from the elapsed ticks. NTP samples move
+//! the anchor: small errors are slewed in gradually so the displayed seconds
+//! never jump back, large ones are stepped, and the rate error of the timer
+//! is estimated from consecutive syncs.
+
+use crate::ntp::Sample;
+
+const MICROS_PER_SECOND: i64 = 1_000_000;
+
+/// Errors larger than this are stepped instead of slewed.
+pub const STEP_THRESHOLD_US: i64 = 500_000;
+
+/// Maximum slew rate, the same as ntpd: 100 ms are absorbed in 200 s.
+pub const MAX_SLEW_PPM: i64 = 500;
+
+/// Limit of the estimated rate error of the monotonic timer.
+pub const MAX_FREQUENCY_PPB: i64 = 500_000;
+
+/// Sync quality, for logging and display.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SyncStats {
+    /// Successful syncs, including the first one.
+    pub syncs: u32,
+    /// Syncs after the first one that stepped the clock instead of slewing it.
+    pub steps: u32,
+    /// Error of the clock found by the last sync, positive when it was behind.
+    pub last_offset_us: i64,
+    /// Largest absolute error found by any sync after the first one.
+    pub max_offset_us: i64,
+    /// Round-trip delay of the last NTP sample.
+    pub last_delay_us: i64,
+    /// Monotonic time of the last sync.
+    pub last_sync_us: Option<u64>,
+    /// Estimated rate error of the monotonic timer in parts per billion,
+    /// positive when it runs slow.
+    pub frequency_ppb: i64,
+}
+
+/// Drift-free wall clock, all times are monotonic timer microseconds.
+pub struct Clock {
+    anchor_us: u64,
+    anchor_unix_us: i64,
+    /// Correction slewed in since the anchor, at most [`MAX_SLEW_PPM`].
+    slew_us: i64,
+    resync_interval_us: u64,
+    last_second: Option<i64>,
+    stats: SyncStats,
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock {
+    /// Time between NTP syncs.
+    pub const DEFAULT_RESYNC_INTERVAL_S: u64 = 15 * 60;
+
+    /// Creates a clock that is unset until the first [`sync`](Self::sync).
+    pub fn new() -> Self {
+        Self {
+            anchor_us: 0,
+            anchor_unix_us: 0,
+            slew_us: 0,
+            resync_interval_us: Self::DEFAULT_RESYNC_INTERVAL_S * 1_000_000,
+            last_second: None,
+            stats: SyncStats::default(),
+        }
+    }
+
+    pub fn with_resync_interval_s(mut self, interval_s: u64) -> Self {
+        self.resync_interval_us = interval_s * 1_000_000;
+        self
+    }
+
+    pub fn is_synced(&self) -> bool {
+        self.stats.last_sync_us.is_some()
+    }
+
+    pub fn stats(&self) -> SyncStats {
+        self.stats
+    }
+
+    /// Whether the resync interval has passed, or the clock was never set.
+    pub fn needs_sync(&self, now_us: u64) -> bool {
+        match self.stats.last_sync_us {
+            Some(last) => now_us.saturating_sub(last) >= self.resync_interval_us,
+            None => true,
+        }
+    }
+
+    /// Elapsed time since the anchor, corrected by the estimated rate error.
+    fn elapsed_us(&self, now_us: u64) -> i64 {
+        let elapsed = now_us.saturating_sub(self.anchor_us) as i64;
+        let correction = elapsed as i128 * self.stats.frequency_ppb as i128 / 1_000_000_000;
+        elapsed + correction as i64
+    }
+
+    /// Part of the pending correction applied after `elapsed_us`.
+    fn slewed_us(&self, elapsed_us: i64) -> i64 {
+        let max = elapsed_us.saturating_mul(MAX_SLEW_PPM) / 1_000_000;
+        self.slew_us.clamp(-max, max)
+    }
+
+    /// Microseconds since the Unix epoch, `None` before the first sync.
+    pub fn unix_micros(&self, now_us: u64) -> Option<i64> {
+        self.stats.last_sync_us?;
+        let elapsed = self.elapsed_us(now_us);
+        Some(self.anchor_unix_us + elapsed + self.slewed_us(elapsed))
+    }
+
+    /// Seconds since the Unix epoch, `None` before the first sync.
+    pub fn unix_seconds(&self, now_us: u64) -> Option<i64> {
+        self.unix_micros(now_us)
+            .map(|micros| micros.div_euclid(MICROS_PER_SECOND))
+    }
+
+    /// Returns the Unix time once per second, when the second has changed
+    /// since the last call, so the display is redrawn only on boundaries.
+    pub fn tick(&mut self, now_us: u64) -> Option<i64> {
+        let second = self.unix_seconds(now_us)?;
+        if self.last_second == Some(second) {
+            return None;
+        }
+        self.last_second = Some(second);
+        Some(second)
+    }
+
+    /// Time until the next second starts, a full second when unset.
+    pub fn until_next_second_us(&self, now_us: u64) -> u64 {
+        match self.unix_micros(now_us) {
+            Some(micros) => (MICROS_PER_SECOND - micros.rem_euclid(MICROS_PER_SECOND)) as u64,
+            None => MICROS_PER_SECOND as u64,
+        }
+    }
+
+    /// Corrects the clock with an NTP sample taken against the same timer.
+    pub fn sync(&mut self, now_us: u64, sample: &Sample) {
+        let target = sample.unix_micros(now_us);
+        match self.unix_micros(now_us) {
+            Some(current) => {
+                let offset = target - current;
+                self.stats.last_offset_us = offset;
+                self.stats.max_offset_us = self.stats.max_offset_us.max(offset.abs());
+
+                if offset.abs() > STEP_THRESHOLD_US {
+                    self.stats.steps += 1;
+                    self.anchor_unix_us = target;
+                    self.slew_us = 0;
+                } else {
+                    // Whatever the pending slew does not explain is drift of the timer
+                    let elapsed = self.elapsed_us(now_us);
+                    let pending = self.slew_us - self.slewed_us(elapsed);
+                    let interval = now_us.saturating_sub(self.anchor_us) as i64;
+                    if interval > 0 {
+                        let drift_ppb =
+                            (offset - pending) as i128 * 1_000_000_000 / interval as i128;
+                        self.stats.frequency_ppb = (self.stats.frequency_ppb
+                            + drift_ppb as i64 / 4)
+                            .clamp(-MAX_FREQUENCY_PPB, MAX_FREQUENCY_PPB);
+                    }
+                    self.anchor_unix_us = current;
+                    self.slew_us = offset;
+                }
+            }
+            None => {
+                self.anchor_unix_us = target;
+                self.slew_us = 0;
+            }
+        }
+
+        self.anchor_us = now_us;
+        self.stats.syncs += 1;
+        self.stats.last_delay_us = sample.delay_us;
+        self.stats.last_sync_us = Some(now_us);
+    }
+}
@@ -0,0 +1,162 @@
+//! Civil calendar, time zones and wall clock without `std`.
+//!
+//! [`Clock`] keeps Unix time from a monotonic timer and NTP samples,
+//! [`DateTime`] converts Unix timestamps to proleptic Gregorian dates,
+//! [`TimeZone`] applies POSIX TZ rules such as `CET-1CEST,M3.5.0,M10.5.0/3`:
+//!
+//! ```
+//! use esp_buddy::time::TimeZone;
+//!
+//! let prague = TimeZone::parse("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+//! let local = prague.local(1_719_835_200); // 2024-07-01 12:00:00 UTC
+//! assert_eq!((local.date_time.hour, local.abbreviation), (14, "CEST"));
+//! ```
+
+mod clock;
+mod tz;
+
+pub use clock::{Clock, SyncStats, MAX_FREQUENCY_PPB, MAX_SLEW_PPM, STEP_THRESHOLD_US};
+pub use tz::{LocalTime, ParseError, TimeZone};
+
+pub const SECONDS_PER_MINUTE: i64 = 60;
+pub const SECONDS_PER_HOUR: i64 = 60 * SECONDS_PER_MINUTE;
+pub const SECONDS_PER_DAY: i64 = 24 * SECONDS_PER_HOUR;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Weekday {
+    Sunday,
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+}
+
+impl Weekday {
+    const ALL: [Weekday; 7] = [
+        Weekday::Sunday,
+        Weekday::Monday,
+        Weekday::Tuesday,
+        Weekday::Wednesday,
+        Weekday::Thursday,
+        Weekday::Friday,
+        Weekday::Saturday,
+    ];
+
+    /// Weekday of a day counted from 1970-01-01, which was a Thursday.
+    pub fn from_days(days: i64) -> Self {
+        Self::ALL[(days + 4).rem_euclid(7) as usize]
+    }
+
+    /// Days since Sunday, as used by POSIX TZ rules.
+    pub fn number_from_sunday(self) -> u8 {
+        self as u8
+    }
+
+    /// Three letter English abbreviation.
+    pub fn short_name(self) -> &'static str {
+        ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"][self as usize]
+    }
+}
+
+pub fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+/// Days in `month` (1-12) of `year`.
+pub fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Three letter English abbreviation of `month` (1-12).
+pub fn month_short_name(month: u8) -> &'static str {
+    const NAMES: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    NAMES[(month as usize + 11) % 12]
+}
+
+/// Days from 1970-01-01 to the given date, negative before.
+///
+/// Based on Howard Hinnant's `days_from_civil`.
+pub fn days_from_civil(year: i32, month: u8, day: u8) -> i64 {
+    let year = year as i64 - (month <= 2) as i64;
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Date of the given day counted from 1970-01-01, inverse of [`days_from_civil`].
+pub fn civil_from_days(days: i64) -> (i32, u8, u8) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u8;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u8;
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    (year as i32, month, day)
+}
+
+/// Calendar date and time of day.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DateTime {
+    pub year: i32,
+    /// 1-12
+    pub month: u8,
+    /// 1-31
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub weekday: Weekday,
+}
+
+impl DateTime {
+    /// Splits seconds since 1970-01-01 00:00:00 into date and time.
+    pub fn from_unix(timestamp: i64) -> Self {
+        let days = timestamp.div_euclid(SECONDS_PER_DAY);
+        let seconds = timestamp.rem_euclid(SECONDS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+
+        Self {
+            year,
+            month,
+            day,
+            hour: (seconds / SECONDS_PER_HOUR) as u8,
+            minute: (seconds % SECONDS_PER_HOUR / SECONDS_PER_MINUTE) as u8,
+            second: (seconds % SECONDS_PER_MINUTE) as u8,
+            weekday: Weekday::from_days(days),
+        }
+    }
+
+    /// Seconds since 1970-01-01 00:00:00, the weekday is ignored.
+    pub fn to_unix(&self) -> i64 {
+        days_from_civil(self.year, self.month, self.day) * SECONDS_PER_DAY
+            + self.hour as i64 * SECONDS_PER_HOUR
+            + self.minute as i64 * SECONDS_PER_MINUTE
+            + self.second as i64
+    }
+
+    /// Day of the year, 1-366.
+    pub fn day_of_year(&self) -> u16 {
+        (days_from_civil(self.year, self.month, self.day) - days_from_civil(self.year, 1, 1) + 1)
+            as u16
+    }
+}
@@ -0,0 +1,322 @@
+//! POSIX TZ strings, e.g. `CET-1CEST,M3.5.0,M10.5.0/3`.
+//!
+//! The format is `std offset [dst [offset] [,start[/time],end[/time]]]`.
+//! Offsets are west of Greenwich, so `CET-1` is one hour ahead of UTC. Names
+//! are three or more letters, or anything alphanumeric in angle brackets like
+//! `<+0330>`. Without a rule the US rule `M3.2.0,M11.1.0` applies, like glibc.
+
+use super::{
+    days_from_civil, days_in_month, is_leap_year, DateTime, Weekday, SECONDS_PER_DAY,
+    SECONDS_PER_HOUR, SECONDS_PER_MINUTE,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    InvalidName,
+    InvalidOffset,
+    InvalidRule,
+    TrailingCharacters,
+    /// Implementation defined `:characters` form, usually a path to a zone file.
+    Unsupported,
+}
+
+/// Day of a DST transition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RuleDate {
+    /// `Jn`: day 1-365, February 29 is never counted.
+    Julian(u16),
+    /// `n`: day 0-365, February 29 is counted in leap years.
+    ZeroBased(u16),
+    /// `Mm.w.d`: weekday `d` (0 = Sunday) of week `w` (1-4, 5 = last) of month `m`.
+    MonthWeekDay { month: u8, week: u8, weekday: u8 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Transition {
+    pub date: RuleDate,
+    /// Local wall time of the change in seconds after midnight. May be
+    /// negative or exceed a day, e.g. `M3.5.0/-1`.
+    pub time: i32,
+}
+
+impl Transition {
+    /// Default time of a transition, 02:00:00.
+    const DEFAULT_TIME: i32 = 2 * SECONDS_PER_HOUR as i32;
+
+    /// Local wall time of the transition in `year`, in seconds since 1970.
+    fn local_seconds(&self, year: i32) -> i64 {
+        let days = match self.date {
+            RuleDate::Julian(day) => {
+                let leap_day = (is_leap_year(year) && day >= 60) as i64;
+                days_from_civil(year, 1, 1) + day as i64 - 1 + leap_day
+            }
+            RuleDate::ZeroBased(day) => days_from_civil(year, 1, 1) + day as i64,
+            RuleDate::MonthWeekDay {
+                month,
+                week,
+                weekday,
+            } => {
+                let first = days_from_civil(year, month, 1);
+                let first_weekday = Weekday::from_days(first).number_from_sunday();
+                let mut day = (weekday + 7 - first_weekday) % 7 + (week - 1) * 7;
+                if day >= days_in_month(year, month) {
+                    day -= 7;
+                }
+                first + day as i64
+            }
+        };
+        days * SECONDS_PER_DAY + self.time as i64
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Zone<'a> {
+    name: &'a str,
+    /// Seconds east of UTC.
+    offset: i32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Dst<'a> {
+    zone: Zone<'a>,
+    start: Transition,
+    end: Transition,
+}
+
+/// Time zone with an optional daylight saving time rule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimeZone<'a> {
+    std: Zone<'a>,
+    dst: Option<Dst<'a>>,
+}
+
+/// Local time at some instant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LocalTime<'a> {
+    pub date_time: DateTime,
+    /// Seconds east of UTC.
+    pub offset: i32,
+    pub is_dst: bool,
+    pub abbreviation: &'a str,
+}
+
+impl<'a> TimeZone<'a> {
+    pub const UTC: TimeZone<'static> = TimeZone {
+        std: Zone {
+            name: "UTC",
+            offset: 0,
+        },
+        dst: None,
+    };
+
+    pub fn parse(tz: &'a str) -> Result<Self, ParseError> {
+        if tz.starts_with(':') {
+            return Err(ParseError::Unsupported);
+        }
+
+        let mut parser = Parser { tz, position: 0 };
+        let std = Zone {
+            name: parser.name()?,
+            offset: -parser.offset()?,
+        };
+        if parser.at_end() {
+            return Ok(Self { std, dst: None });
+        }
+
+        let name = parser.name()?;
+        let offset = match parser.peek() {
+            Some(b'+' | b'-' | b'0'..=b'9') => -parser.offset()?,
+            _ => std.offset + SECONDS_PER_HOUR as i32,
+        };
+        let (start, end) = if parser.eat(b',') {
+            let start = parser.transition()?;
+            if !parser.eat(b',') {
+                return Err(ParseError::InvalidRule);
+            }
+            (start, parser.transition()?)
+        } else {
+            let transition = |month, week| Transition {
+                date: RuleDate::MonthWeekDay {
+                    month,
+                    week,
+                    weekday: 0,
+                },
+                time: Transition::DEFAULT_TIME,
+            };
+            (transition(3, 2), transition(11, 1))
+        };
+        if !parser.at_end() {
+            return Err(ParseError::TrailingCharacters);
+        }
+
+        Ok(Self {
+            std,
+            dst: Some(Dst {
+                zone: Zone { name, offset },
+                start,
+                end,
+            }),
+        })
+    }
+
+    /// Standard time offset in seconds east of UTC.
+    pub fn std_offset(&self) -> i32 {
+        self.std.offset
+    }
+
+    /// Daylight saving time offset in seconds east of UTC.
+    pub fn dst_offset(&self) -> Option<i32> {
+        self.dst.map(|dst| dst.zone.offset)
+    }
+
+    /// Start and end of daylight saving time in `year` as Unix timestamps.
+    ///
+    /// On the southern hemisphere the end comes before the start.
+    pub fn transitions(&self, year: i32) -> Option<(i64, i64)> {
+        let dst = self.dst?;
+        Some((
+            dst.start.local_seconds(year) - self.std.offset as i64,
+            dst.end.local_seconds(year) - dst.zone.offset as i64,
+        ))
+    }
+
+    /// Whether daylight saving time is in effect at the Unix timestamp.
+    pub fn is_dst(&self, timestamp: i64) -> bool {
+        let year = DateTime::from_unix(timestamp + self.std.offset as i64).year;
+        match self.transitions(year) {
+            Some((start, end)) if start < end => start <= timestamp && timestamp < end,
+            Some((start, end)) => timestamp < end || start <= timestamp,
+            None => false,
+        }
+    }
+
+    /// Converts a Unix timestamp to local time.
+    pub fn local(&self, timestamp: i64) -> LocalTime<'a> {
+        let (zone, is_dst) = match self.dst {
+            Some(dst) if self.is_dst(timestamp) => (dst.zone, true),
+            _ => (self.std, false),
+        };
+
+        LocalTime {
+            date_time: DateTime::from_unix(timestamp + zone.offset as i64),
+            offset: zone.offset,
+            is_dst,
+            abbreviation: zone.name,
+        }
+    }
+}
+
+struct Parser<'a> {
+    tz: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.tz.as_bytes().get(self.position).copied()
+    }
+
+    fn at_end(&self) -> bool {
+        self.position == self.tz.len()
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        let found = self.peek() == Some(byte);
+        self.position += found as usize;
+        found
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(u8) -> bool) -> &'a str {
+        let start = self.position;
+        while self.peek().is_some_and(&predicate) {
+            self.position += 1;
+        }
+        &self.tz[start..self.position]
+    }
+
+    fn name(&mut self) -> Result<&'a str, ParseError> {
+        let name = if self.eat(b'<') {
+            let name = self
+                .take_while(|byte| byte.is_ascii_alphanumeric() || byte == b'+' || byte == b'-');
+            if !self.eat(b'>') {
+                return Err(ParseError::InvalidName);
+            }
+            name
+        } else {
+            self.take_while(|byte| byte.is_ascii_alphabetic())
+        };
+
+        if name.len() < 3 {
+            return Err(ParseError::InvalidName);
+        }
+        Ok(name)
+    }
+
+    fn number(&mut self, max: u32) -> Option<u32> {
+        let digits = self.take_while(|byte| byte.is_ascii_digit());
+        digits.parse().ok().filter(|&number| number <= max)
+    }
+
+    /// `[+|-]hh[:mm[:ss]]` in seconds.
+    fn hms(&mut self, max_hours: u32) -> Option<i32> {
+        let sign = if self.eat(b'-') {
+            -1
+        } else {
+            self.eat(b'+');
+            1
+        };
+
+        let mut seconds = self.number(max_hours)? as i64 * SECONDS_PER_HOUR;
+        if self.eat(b':') {
+            seconds += self.number(59)? as i64 * SECONDS_PER_MINUTE;
+            if self.eat(b':') {
+                seconds += self.number(59)? as i64;
+            }
+        }
+        Some(sign * seconds as i32)
+    }
+
+    fn offset(&mut self) -> Result<i32, ParseError> {
+        self.hms(24).ok_or(ParseError::InvalidOffset)
+    }
+
+    fn transition(&mut self) -> Result<Transition, ParseError> {
+        let date = if self.eat(b'J') {
+            self.number(365)
+                .filter(|&day| day >= 1)
+                .map(|day| RuleDate::Julian(day as u16))
+        } else if self.eat(b'M') {
+            self.month_week_day()
+        } else {
+            self.number(365).map(|day| RuleDate::ZeroBased(day as u16))
+        }
+        .ok_or(ParseError::InvalidRule)?;
+
+        let time = if self.eat(b'/') {
+            // Extended by RFC 8536 to -167..=167 hours
+            self.hms(167).ok_or(ParseError::InvalidRule)?
+        } else {
+            Transition::DEFAULT_TIME
+        };
+
+        Ok(Transition { date, time })
+    }
+
+    fn month_week_day(&mut self) -> Option<RuleDate> {
+        let month = self.number(12).filter(|&month| month >= 1)?;
+        if !self.eat(b'.') {
+            return None;
+        }
+        let week = self.number(5).filter(|&week| week >= 1)?;
+        if !self.eat(b'.') {
+            return None;
+        }
+        let weekday = self.number(6)?;
+
+        Some(RuleDate::MonthWeekDay {
+            month: month as u8,
+            week: week as u8,
+            weekday: weekday as u8,
+        })
+    }
+}
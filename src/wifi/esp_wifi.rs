@@ -0,0 +1,121 @@
+use core::net::Ipv4Addr;
+
+use embedded_svc::{
+    ipv4::Interface,
+    wifi::{ClientConfiguration, Configuration, Wifi},
+};
+use esp_wifi::{
+    binary,
+    wifi::{WifiController, WifiError, WifiStaDevice},
+    wifi_interface::WifiStack,
+};
+
+use super::{AccessPoint, Controller, Network, ScanResults, MAX_SCAN_RESULTS};
+
+#[derive(Debug)]
+pub enum EspWifiError {
+    Wifi(WifiError),
+    /// SSID longer than 32 or password longer than 64 bytes.
+    InvalidCredentials,
+}
+
+impl From<WifiError> for EspWifiError {
+    fn from(err: WifiError) -> Self {
+        EspWifiError::Wifi(err)
+    }
+}
+
+/// esp-wifi station with its network stack, which runs DHCP.
+pub struct EspWifi<'a, 'd> {
+    controller: WifiController<'d>,
+    stack: &'a WifiStack<'a, WifiStaDevice>,
+}
+
+impl<'a, 'd> EspWifi<'a, 'd> {
+    pub fn new(controller: WifiController<'d>, stack: &'a WifiStack<'a, WifiStaDevice>) -> Self {
+        Self { controller, stack }
+    }
+}
+
+impl Controller for EspWifi<'_, '_> {
+    type Error = EspWifiError;
+
+    fn start(&mut self) -> Result<(), EspWifiError> {
+        if !self.controller.is_started()? {
+            self.controller.start()?;
+        }
+        Ok(())
+    }
+
+    fn scan(&mut self) -> Result<ScanResults, EspWifiError> {
+        let (found, _count) = self.controller.scan_n::<MAX_SCAN_RESULTS>()?;
+        Ok(found
+            .into_iter()
+            .map(|info| AccessPoint {
+                ssid: info.ssid,
+                rssi: info.signal_strength,
+                channel: info.channel,
+                open: info.auth_method.is_none(),
+            })
+            .collect())
+    }
+
+    fn connect(&mut self, network: &Network) -> Result<(), EspWifiError> {
+        let configuration = Configuration::Client(ClientConfiguration {
+            ssid: network
+                .ssid
+                .try_into()
+                .map_err(|_| EspWifiError::InvalidCredentials)?,
+            password: network
+                .password
+                .try_into()
+                .map_err(|_| EspWifiError::InvalidCredentials)?,
+            ..Default::default()
+        });
+        self.controller.set_configuration(&configuration)?;
+        self.controller.connect()?;
+        Ok(())
+    }
+
+    fn disconnect(&mut self) -> Result<(), EspWifiError> {
+        self.controller.disconnect()?;
+        Ok(())
+    }
+
+    fn is_connected(&mut self) -> Result<bool, EspWifiError> {
+        // The driver reports a dropped or failed association as an error
+        match self.controller.is_connected() {
+            Ok(connected) => Ok(connected),
+            Err(WifiError::Disconnected) => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn ip(&mut self) -> Option<Ipv4Addr> {
+        self.stack.work();
+        if !self.stack.is_iface_up() {
+            return None;
+        }
+        let ip = self.stack.get_ip_info().ok()?.ip;
+        Some(Ipv4Addr::from(ip.octets()))
+    }
+
+    /// esp-wifi 0.10 has no call for the signal of the associated access
+    /// point, so this goes to the driver's `esp_wifi_sta_get_ap_info`
+    /// through the `binary` bindings, which are not part of its stable API.
+    /// Cargo.toml keeps esp-wifi at 0.10.x; check the binding on upgrades.
+    fn rssi(&mut self) -> Option<i8> {
+        // SAFETY: `record` is a plain C struct for which all zeroes is a valid
+        // value, and the driver only writes it before returning. The call
+        // reads state the driver guards with its own lock, and holding
+        // `&mut self` means the controller is started and not used elsewhere
+        // meanwhile. Not associated, it returns an error and `record` is
+        // ignored.
+        let (err, record) = unsafe {
+            let mut record: binary::include::wifi_ap_record_t = core::mem::zeroed();
+            let err = binary::include::esp_wifi_sta_get_ap_info(&mut record);
+            (err, record)
+        };
+        (err == binary::include::ESP_OK as i32).then_some(record.rssi)
+    }
+}
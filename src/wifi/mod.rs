@@ -0,0 +1,292 @@
+//! Wi-Fi station connection manager.
+//!
+//! [`WifiManager`] is a non-blocking state machine on top of a [`Controller`],
+//! polled with a monotonic time in milliseconds. It scans for the known
+//! networks, connects to the first one in range, waits for the link and the
+//! DHCP address with a timeout and retries with exponential backoff, also
+//! after the connection drops.
+//!
+//! ```ignore
+//! let stored = settings::open().unwrap().networks().unwrap();
+//! let networks = settings::known_networks(&stored, settings::BUILT_IN_NETWORK);
+//! let mut wifi = WifiManager::new(EspWifi::new(controller, &wifi_stack), &networks);
+//! wifi.start();
+//! loop {
+//!     if let Some(state) = wifi.poll(current_millis()) {
+//!         wifi::draw_state(display, &state).unwrap();
+//!     }
+//! }
+//! ```
+
+use core::net::Ipv4Addr;
+
+#[cfg(feature = "esp32")]
+mod esp_wifi;
+
+#[cfg(feature = "esp32")]
+pub use self::esp_wifi::{EspWifi, EspWifiError};
+
+/// Scan results kept, the strongest access points first.
+pub const MAX_SCAN_RESULTS: usize = 10;
+
+pub type ScanResults = heapless::Vec<AccessPoint, MAX_SCAN_RESULTS>;
+
+/// Access point found by a scan.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccessPoint {
+    pub ssid: heapless::String<32>,
+    /// Signal strength in dBm.
+    pub rssi: i8,
+    pub channel: u8,
+    /// No password needed.
+    pub open: bool,
+}
+
+/// Credentials of a known network.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Network<'a> {
+    pub ssid: &'a str,
+    pub password: &'a str,
+}
+
+impl<'a> Network<'a> {
+    pub const fn new(ssid: &'a str, password: &'a str) -> Self {
+        Self { ssid, password }
+    }
+}
+
+/// Station mode radio driven by the [`WifiManager`].
+pub trait Controller {
+    type Error;
+
+    /// Starts the radio, does nothing when already started.
+    fn start(&mut self) -> Result<(), Self::Error>;
+
+    fn scan(&mut self) -> Result<ScanResults, Self::Error>;
+
+    /// Starts connecting without waiting for the result.
+    fn connect(&mut self, network: &Network) -> Result<(), Self::Error>;
+
+    fn disconnect(&mut self) -> Result<(), Self::Error>;
+
+    /// Whether the link to the access point is up.
+    fn is_connected(&mut self) -> Result<bool, Self::Error>;
+
+    /// Address leased by DHCP, `None` until there is one. Also drives the
+    /// network stack, which renews the lease.
+    fn ip(&mut self) -> Option<Ipv4Addr>;
+
+    /// Signal strength of the access point in dBm, `None` when not
+    /// associated or when the radio does not report it.
+    fn rssi(&mut self) -> Option<i8>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FailReason {
+    /// None of the known networks showed up in the scan.
+    NotFound,
+    /// No link or no DHCP address within the connect timeout.
+    Timeout,
+    /// The access point dropped the connection.
+    Disconnected,
+    /// The controller returned an error, see [`WifiManager::last_error`].
+    Controller,
+}
+
+impl FailReason {
+    pub fn description(self) -> &'static str {
+        match self {
+            FailReason::NotFound => "network not found",
+            FailReason::Timeout => "connect timeout",
+            FailReason::Disconnected => "disconnected",
+            FailReason::Controller => "driver error",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum State {
+    Idle,
+    Scanning,
+    Connecting,
+    Connected {
+        ip: Ipv4Addr,
+    },
+    /// Waiting for the next attempt, see [`WifiManager::retry_in_ms`].
+    Failed {
+        reason: FailReason,
+    },
+}
+
+pub struct WifiManager<'a, C: Controller> {
+    controller: C,
+    networks: &'a [Network<'a>],
+    state: State,
+    /// Index of the network being connected or connected.
+    network: Option<usize>,
+    /// Failed attempts since the last connection.
+    failures: u32,
+    /// End of the connect timeout or of the backoff.
+    deadline_ms: u64,
+    connect_timeout_ms: u64,
+    scan_results: ScanResults,
+    last_error: Option<C::Error>,
+}
+
+impl<'a, C: Controller> WifiManager<'a, C> {
+    /// Time to get both the link and a DHCP address.
+    pub const DEFAULT_CONNECT_TIMEOUT_MS: u64 = 15_000;
+    /// Wait after the first failure, doubled after every further one.
+    pub const BACKOFF_BASE_MS: u64 = 1000;
+    pub const BACKOFF_MAX_MS: u64 = 60_000;
+
+    /// Creates an idle manager for the known networks, in order of priority.
+    pub fn new(controller: C, networks: &'a [Network<'a>]) -> Self {
+        Self {
+            controller,
+            networks,
+            state: State::Idle,
+            network: None,
+            failures: 0,
+            deadline_ms: 0,
+            connect_timeout_ms: Self::DEFAULT_CONNECT_TIMEOUT_MS,
+            scan_results: ScanResults::new(),
+            last_error: None,
+        }
+    }
+
+    pub fn with_connect_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.connect_timeout_ms = timeout_ms;
+        self
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    /// Network being connected or connected.
+    pub fn network(&self) -> Option<&Network<'a>> {
+        self.network.map(|index| &self.networks[index])
+    }
+
+    /// Results of the last scan.
+    pub fn scan_results(&self) -> &[AccessPoint] {
+        &self.scan_results
+    }
+
+    /// Current signal strength of the connected network, unlike the
+    /// [`scan_results`](Self::scan_results) taken before connecting.
+    pub fn rssi(&mut self) -> Option<i8> {
+        match self.state {
+            State::Connected { .. } => self.controller.rssi(),
+            _ => None,
+        }
+    }
+
+    /// Failed attempts since the last successful connection.
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    pub fn last_error(&self) -> Option<&C::Error> {
+        self.last_error.as_ref()
+    }
+
+    pub fn controller_mut(&mut self) -> &mut C {
+        &mut self.controller
+    }
+
+    /// Time until the next attempt while failed.
+    pub fn retry_in_ms(&self, now_ms: u64) -> Option<u64> {
+        match self.state {
+            State::Failed { .. } => Some(self.deadline_ms.saturating_sub(now_ms)),
+            _ => None,
+        }
+    }
+
+    /// Starts connecting, with the first scan at the next [`poll`](Self::poll).
+    pub fn start(&mut self) {
+        if self.state == State::Idle {
+            self.failures = 0;
+            self.state = State::Scanning;
+        }
+    }
+
+    /// Disconnects and stays idle until started again.
+    pub fn stop(&mut self) -> Result<(), C::Error> {
+        self.state = State::Idle;
+        self.network = None;
+        self.controller.disconnect()
+    }
+
+    /// Advances the state machine, returns the new state when it changed.
+    pub fn poll(&mut self, now_ms: u64) -> Option<State> {
+        let previous = self.state;
+        self.state = match self.step(now_ms) {
+            Ok(state) => state,
+            Err(err) => {
+                self.last_error = Some(err);
+                self.fail(now_ms, FailReason::Controller)
+            }
+        };
+        (self.state != previous).then_some(self.state)
+    }
+
+    fn step(&mut self, now_ms: u64) -> Result<State, C::Error> {
+        Ok(match self.state {
+            State::Idle => State::Idle,
+            State::Scanning => {
+                self.controller.start()?;
+                self.scan_results = self.controller.scan()?;
+                let found = self.networks.iter().position(|network| {
+                    self.scan_results
+                        .iter()
+                        .any(|access_point| access_point.ssid == network.ssid)
+                });
+                match found {
+                    Some(index) => {
+                        self.network = Some(index);
+                        self.controller.connect(&self.networks[index])?;
+                        self.deadline_ms = now_ms + self.connect_timeout_ms;
+                        State::Connecting
+                    }
+                    None => self.fail(now_ms, FailReason::NotFound),
+                }
+            }
+            State::Connecting => match (self.controller.is_connected()?, self.controller.ip()) {
+                (true, Some(ip)) => {
+                    self.failures = 0;
+                    State::Connected { ip }
+                }
+                _ if now_ms >= self.deadline_ms => {
+                    self.controller.disconnect()?;
+                    self.fail(now_ms, FailReason::Timeout)
+                }
+                _ => State::Connecting,
+            },
+            State::Connected { .. } => {
+                if !self.controller.is_connected()? {
+                    self.fail(now_ms, FailReason::Disconnected)
+                } else {
+                    match self.controller.ip() {
+                        Some(ip) => State::Connected { ip },
+                        // Lease lost, wait for a new one
+                        None => {
+                            self.deadline_ms = now_ms + self.connect_timeout_ms;
+                            State::Connecting
+                        }
+                    }
+                }
+            }
+            State::Failed { .. } if now_ms >= self.deadline_ms => State::Scanning,
+            failed @ State::Failed { .. } => failed,
+        })
+    }
+
+    fn fail(&mut self, now_ms: u64, reason: FailReason) -> State {
+        let exponent = self.failures.min(16);
+        self.failures += 1;
+        self.deadline_ms = now_ms + (Self::BACKOFF_BASE_MS << exponent).min(Self::BACKOFF_MAX_MS);
+        State::Failed { reason }
+    }
+}
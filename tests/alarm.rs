@@ -0,0 +1,247 @@
+use esp_buddy::{
+    alarm::{Alarm, Countdown, Event, Phase, Pomodoro, Scheduler, State, Stopwatch, Weekdays},
+    screens::alarm::{AlarmEditor, Field},
+    time::{DateTime, Weekday},
+};
+
+/// Friday 2024-10-18 07:30:00, local time.
+const FRIDAY_0730: i64 = 1_729_236_600;
+const MINUTE_MS: u64 = 60_000;
+const DAY: i64 = 86_400;
+
+fn local(timestamp: i64) -> DateTime {
+    DateTime::from_unix(timestamp)
+}
+
+#[test]
+fn one_shot_alarm_rings_once() {
+    let mut alarms: Scheduler<4> = Scheduler::new();
+    let index = alarms.add(Alarm::once(7, 30)).unwrap();
+
+    assert_eq!(alarms.update(0, &local(FRIDAY_0730 - 1)), None);
+    assert_eq!(
+        alarms.update(1000, &local(FRIDAY_0730)),
+        Some(Event::Ring(index))
+    );
+    // Only once per minute, and disabled afterwards
+    assert_eq!(alarms.update(2000, &local(FRIDAY_0730 + 1)), None);
+    assert!(!alarms.alarms()[index].enabled);
+
+    assert!(alarms.dismiss());
+    assert_eq!(alarms.update(3000, &local(FRIDAY_0730 + DAY)), None);
+}
+
+#[test]
+fn weekly_alarm_rings_on_selected_days() {
+    let mut alarms: Scheduler<4> = Scheduler::new();
+    alarms
+        .add(Alarm::weekly(7, 30, Weekdays::WORKDAYS))
+        .unwrap();
+
+    let rings: Vec<Weekday> = (0..7)
+        .filter_map(|day| {
+            let time = local(FRIDAY_0730 + day * DAY);
+            let event = alarms.update(day as u64 * 1000, &time);
+            alarms.dismiss();
+            event.map(|_| time.weekday)
+        })
+        .collect();
+
+    use Weekday::*;
+    assert_eq!(rings, [Friday, Monday, Tuesday, Wednesday, Thursday]);
+    assert!(alarms.alarms()[0].enabled);
+}
+
+#[test]
+fn weekdays_set() {
+    let days = Weekdays::NONE.with(Weekday::Saturday).with(Weekday::Sunday);
+
+    assert_eq!(days, Weekdays::WEEKEND);
+    assert!(days.contains(Weekday::Sunday));
+    assert!(!days.contains(Weekday::Monday));
+    assert!(Weekdays::NONE.is_empty());
+}
+
+#[test]
+fn disabled_alarm_stays_silent() {
+    let mut alarms: Scheduler<4> = Scheduler::new();
+    let index = alarms.add(Alarm::once(7, 30)).unwrap();
+    alarms.alarm_mut(index).unwrap().enabled = false;
+
+    assert_eq!(alarms.update(0, &local(FRIDAY_0730)), None);
+    assert_eq!(alarms.state(), State::Idle);
+}
+
+#[test]
+fn snooze_rings_again() {
+    let mut alarms: Scheduler<4> = Scheduler::new().with_snooze_s(5 * 60);
+    alarms.add(Alarm::once(7, 30)).unwrap();
+    alarms.update(0, &local(FRIDAY_0730));
+
+    assert!(alarms.snooze(10_000));
+    assert_eq!(
+        alarms.state(),
+        State::Snoozed {
+            alarm: 0,
+            until_ms: 10_000 + 5 * MINUTE_MS
+        }
+    );
+    assert!(!alarms.snooze(11_000));
+    assert_eq!(
+        alarms.update(10_000 + 5 * MINUTE_MS - 1, &local(FRIDAY_0730 + 300)),
+        None
+    );
+    assert_eq!(
+        alarms.update(10_000 + 5 * MINUTE_MS, &local(FRIDAY_0730 + 300)),
+        Some(Event::Ring(0))
+    );
+
+    // Dismissing also cancels a snoozed alarm
+    alarms.snooze(400_000);
+    assert!(alarms.dismiss());
+    assert_eq!(
+        alarms.update(10 * MINUTE_MS, &local(FRIDAY_0730 + 900)),
+        None
+    );
+    assert!(!alarms.dismiss());
+}
+
+#[test]
+fn unanswered_alarm_times_out() {
+    let mut alarms: Scheduler<4> = Scheduler::new().with_ring_s(60);
+    alarms.add(Alarm::once(7, 30)).unwrap();
+    alarms.update(1000, &local(FRIDAY_0730));
+
+    assert_eq!(alarms.update(60_999, &local(FRIDAY_0730 + 59)), None);
+    assert_eq!(
+        alarms.update(61_000, &local(FRIDAY_0730 + 60)),
+        Some(Event::Timeout(0))
+    );
+    assert_eq!(alarms.state(), State::Idle);
+}
+
+#[test]
+fn flashes_while_ringing() {
+    let mut alarms: Scheduler<4> = Scheduler::new();
+    alarms.add(Alarm::once(7, 30)).unwrap();
+    assert!(!alarms.flash(0));
+
+    alarms.update(1000, &local(FRIDAY_0730));
+    let flashes: Vec<bool> = [1000, 1499, 1500, 1999, 2000]
+        .into_iter()
+        .map(|now| alarms.flash(now))
+        .collect();
+    assert_eq!(flashes, [true, true, false, false, true]);
+
+    alarms.snooze(2000);
+    assert!(!alarms.flash(2000));
+}
+
+#[test]
+fn scheduler_capacity_and_removal() {
+    let mut alarms: Scheduler<2> = Scheduler::new();
+    alarms.add(Alarm::once(6, 0)).unwrap();
+    alarms.add(Alarm::once(7, 30)).unwrap();
+    assert_eq!(alarms.add(Alarm::once(8, 0)), Err(Alarm::once(8, 0)));
+
+    alarms.update(0, &local(FRIDAY_0730));
+    assert!(alarms.is_ringing());
+    assert_eq!(alarms.remove(0), Some(Alarm::once(6, 0)));
+    assert_eq!(alarms.state(), State::Idle);
+    assert_eq!(alarms.remove(1), None);
+    assert_eq!(alarms.alarms().len(), 1);
+}
+
+#[test]
+fn countdown_pauses_and_finishes() {
+    let mut countdown = Countdown::new(10_000);
+    assert_eq!(countdown.remaining_ms(5000), 10_000);
+
+    countdown.start(1000);
+    assert_eq!(countdown.remaining_ms(4000), 7000);
+    countdown.pause(4000);
+    assert_eq!(countdown.remaining_ms(100_000), 7000);
+    countdown.toggle(100_000);
+    assert!(countdown.is_running());
+
+    assert!(!countdown.update(106_999));
+    assert!(countdown.update(107_000));
+    assert!(!countdown.update(107_001));
+    assert!(countdown.is_finished());
+    assert_eq!(countdown.remaining_ms(200_000), 0);
+
+    // A finished countdown starts over
+    countdown.start(200_000);
+    assert_eq!(countdown.remaining_ms(200_000), 10_000);
+    countdown.reset();
+    assert!(!countdown.is_running());
+    assert_eq!(countdown.remaining_ms(300_000), 10_000);
+}
+
+#[test]
+fn pomodoro_cycle() {
+    let mut pomodoro = Pomodoro::with_durations_min(25, 5, 15);
+    pomodoro.countdown_mut().start(0);
+
+    let mut now = 0;
+    let mut phases = Vec::new();
+    for _ in 0..8 {
+        now += pomodoro.countdown().remaining_ms(now);
+        phases.push(pomodoro.update(now).unwrap());
+    }
+
+    use Phase::*;
+    assert_eq!(
+        phases,
+        [ShortBreak, Work, ShortBreak, Work, ShortBreak, Work, LongBreak, Work]
+    );
+    assert_eq!(pomodoro.sessions(), 4);
+    // Four sessions of 25 minutes, three short and one long break
+    assert_eq!(now, (4 * 25 + 3 * 5 + 15) * MINUTE_MS);
+    assert!(pomodoro.countdown().is_running());
+}
+
+#[test]
+fn stopwatch_laps() {
+    let mut stopwatch = Stopwatch::new();
+    assert_eq!(stopwatch.elapsed_ms(1000), 0);
+
+    stopwatch.start(1000);
+    assert_eq!(stopwatch.lap(4000), 3000);
+    stopwatch.stop(5000);
+    assert_eq!(stopwatch.elapsed_ms(60_000), 4000);
+
+    stopwatch.toggle(60_000);
+    assert_eq!(stopwatch.elapsed_ms(62_000), 6000);
+    assert_eq!(stopwatch.lap(62_000), 3000);
+
+    stopwatch.reset();
+    assert!(!stopwatch.is_running());
+    assert_eq!(stopwatch.elapsed_ms(70_000), 0);
+}
+
+#[test]
+fn editor_sets_alarm() {
+    let mut editor = AlarmEditor::new(Alarm::weekly(23, 59, Weekdays::EVERY_DAY));
+
+    editor.increment();
+    assert!(!editor.next_field());
+    editor.increment();
+    assert!(!editor.next_field());
+    assert_eq!(editor.field(), Field::Days);
+    editor.increment();
+    assert!(!editor.next_field());
+    editor.increment();
+    assert!(editor.next_field());
+
+    assert_eq!(
+        editor.alarm(),
+        Alarm {
+            hour: 0,
+            minute: 0,
+            days: Weekdays::NONE,
+            enabled: false,
+        }
+    );
+    assert_eq!(editor.field(), Field::Hour);
+}
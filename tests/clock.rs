@@ -0,0 +1,154 @@
+use esp_buddy::{
+    ntp::Sample,
+    time::{Clock, MAX_SLEW_PPM},
+};
+use smoltcp::wire::{IpAddress, Ipv4Address};
+
+const SECOND: u64 = 1_000_000;
+/// 2024-10-18 12:00:00 UTC
+const UNIX_US: i64 = 1_729_252_800_000_000;
+
+/// Sample saying it is `unix_us` at monotonic time `now_us`.
+fn sample(now_us: u64, unix_us: i64) -> Sample {
+    Sample {
+        server: IpAddress::Ipv4(Ipv4Address::new(162, 159, 200, 1)),
+        stratum: 2,
+        offset_us: unix_us - now_us as i64,
+        delay_us: 12_000,
+    }
+}
+
+fn synced_clock() -> Clock {
+    let mut clock = Clock::new();
+    clock.sync(5 * SECOND, &sample(5 * SECOND, UNIX_US));
+    clock
+}
+
+#[test]
+fn unset_until_synced() {
+    let mut clock = Clock::new();
+
+    assert!(!clock.is_synced());
+    assert!(clock.needs_sync(0));
+    assert_eq!(clock.unix_micros(SECOND), None);
+    assert_eq!(clock.tick(SECOND), None);
+    assert_eq!(clock.until_next_second_us(SECOND), SECOND);
+}
+
+#[test]
+fn follows_monotonic_timer() {
+    let clock = synced_clock();
+
+    assert_eq!(clock.unix_micros(5 * SECOND), Some(UNIX_US));
+    assert_eq!(
+        clock.unix_micros(3605 * SECOND + 250),
+        Some(UNIX_US + 3_600_000_250)
+    );
+    assert_eq!(
+        clock.unix_seconds(6 * SECOND - 1),
+        Some(UNIX_US / 1_000_000)
+    );
+    assert_eq!(clock.stats().syncs, 1);
+    assert_eq!(clock.stats().steps, 0);
+    assert_eq!(clock.stats().last_delay_us, 12_000);
+}
+
+#[test]
+fn ticks_once_per_second() {
+    let mut clock = Clock::new();
+    // Sync in the middle of a second
+    clock.sync(0, &sample(0, UNIX_US + 400_000));
+    let first = UNIX_US / 1_000_000;
+
+    assert_eq!(clock.tick(0), Some(first));
+    assert_eq!(clock.tick(100_000), None);
+    assert_eq!(clock.until_next_second_us(100_000), 500_000);
+    assert_eq!(clock.tick(599_999), None);
+    assert_eq!(clock.tick(600_000), Some(first + 1));
+    assert_eq!(clock.tick(600_001), None);
+    // A slow loop skips seconds but never repeats one
+    assert_eq!(clock.tick(3_700_000), Some(first + 4));
+}
+
+#[test]
+fn small_error_is_slewed() {
+    let mut clock = synced_clock();
+    let sync_at = 905 * SECOND;
+
+    // The clock turns out to be 100 ms behind
+    clock.sync(sync_at, &sample(sync_at, UNIX_US + 900_100_000));
+    assert_eq!(clock.stats().last_offset_us, 100_000);
+    assert_eq!(clock.stats().steps, 0);
+    assert_eq!(clock.unix_micros(sync_at), Some(UNIX_US + 900_000_000));
+
+    // The error is partly blamed on the timer rate, the rest is slewed in
+    let frequency_ppm = clock.stats().frequency_ppb / 1000;
+    assert_eq!(frequency_ppm, 100_000 * 1_000_000 / (900 * 1_000_000) / 4);
+
+    // Never faster than MAX_SLEW_PPM, never backwards
+    let mut previous = clock.unix_micros(sync_at).unwrap();
+    for step in 1..=300 {
+        let now = sync_at + step * SECOND;
+        let unix = clock.unix_micros(now).unwrap();
+        let rate = unix - previous - 1_000_000;
+        assert!((frequency_ppm..=frequency_ppm + MAX_SLEW_PPM + 2).contains(&rate));
+        previous = unix;
+    }
+
+    // 100 ms at 500 ppm take 200 s, then only the rate correction remains
+    let now = sync_at + 300 * SECOND;
+    let drift = 300 * clock.stats().frequency_ppb / 1000;
+    assert_eq!(
+        clock.unix_micros(now),
+        Some(UNIX_US + 1_200_100_000 + drift)
+    );
+}
+
+#[test]
+fn large_error_is_stepped() {
+    let mut clock = synced_clock();
+    let sync_at = 905 * SECOND;
+
+    clock.sync(sync_at, &sample(sync_at, UNIX_US + 890_000_000));
+    assert_eq!(clock.unix_micros(sync_at), Some(UNIX_US + 890_000_000));
+
+    let stats = clock.stats();
+    assert_eq!((stats.syncs, stats.steps), (2, 1));
+    assert_eq!(stats.last_offset_us, -10_000_000);
+    assert_eq!(stats.max_offset_us, 10_000_000);
+    assert_eq!(stats.frequency_ppb, 0);
+}
+
+#[test]
+fn resyncs_after_interval() {
+    let mut clock = Clock::new().with_resync_interval_s(60);
+    clock.sync(SECOND, &sample(SECOND, UNIX_US));
+
+    assert!(!clock.needs_sync(SECOND));
+    assert!(!clock.needs_sync(61 * SECOND - 1));
+    assert!(clock.needs_sync(61 * SECOND));
+    assert_eq!(clock.stats().last_sync_us, Some(SECOND));
+}
+
+#[test]
+fn learns_timer_rate_error() {
+    // The monotonic timer runs 100 ppm slow
+    let monotonic = |unix_offset_us: i64| (unix_offset_us - unix_offset_us / 10_000) as u64;
+    let mut clock = Clock::new();
+    clock.sync(0, &sample(0, UNIX_US));
+
+    let interval = 900 * 1_000_000;
+    let mut last_offset = 0;
+    for sync in 1..=20 {
+        let now = monotonic(sync * interval);
+        last_offset = clock.unix_micros(now).unwrap() - (UNIX_US + sync * interval);
+        clock.sync(now, &sample(now, UNIX_US + sync * interval));
+    }
+
+    // Off by 90 ms per sync at first, within a few milliseconds once learned
+    assert!(last_offset.abs() < 2_000, "offset {last_offset} us");
+    let frequency = clock.stats().frequency_ppb;
+    assert!((95_000..=105_000).contains(&frequency), "{frequency} ppb");
+    assert_eq!(clock.stats().steps, 0);
+    assert!(clock.stats().max_offset_us >= 90_000);
+}
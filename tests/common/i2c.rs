@@ -0,0 +1,147 @@
+//! Mock I2C bus of register-based and command-based devices.
+
+use std::collections::BTreeMap;
+
+use esp_buddy::sensors::I2c;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BusError {
+    /// Nothing acknowledged the address.
+    Nack(u8),
+}
+
+/// Device with 256 byte registers. The first byte written selects the
+/// register, further bytes are written from there on.
+///
+/// Made with [`MockDevice::commands`], it answers reads with the data set
+/// for the last write instead, and does not acknowledge other reads.
+pub struct MockDevice {
+    pub registers: [u8; 256],
+    /// Bit of the register address that makes transfers continue with the
+    /// next register, `None` when they always do.
+    pub auto_increment: Option<u8>,
+    /// Answer to every command of a command-based device.
+    pub answers: BTreeMap<Vec<u8>, Vec<u8>>,
+    commands: bool,
+    /// Register or command of the last write.
+    last: Vec<u8>,
+}
+
+impl MockDevice {
+    pub fn new(auto_increment: Option<u8>) -> Self {
+        Self {
+            registers: [0; 256],
+            auto_increment,
+            answers: BTreeMap::new(),
+            commands: false,
+            last: Vec::new(),
+        }
+    }
+
+    pub fn commands() -> Self {
+        Self {
+            commands: true,
+            ..Self::new(None)
+        }
+    }
+
+    /// Sets `values` from `register` on.
+    pub fn set(&mut self, register: u8, values: &[u8]) {
+        let register = register as usize;
+        self.registers[register..register + values.len()].copy_from_slice(values);
+    }
+
+    /// Answers reads after `command` with `data`.
+    pub fn answer(&mut self, command: &[u8], data: &[u8]) {
+        self.answers.insert(command.to_vec(), data.to_vec());
+    }
+
+    /// Register and whether to move on after every byte.
+    fn select(&self, address: u8) -> (u8, bool) {
+        match self.auto_increment {
+            Some(bit) => (address & !bit, address & bit != 0),
+            None => (address, true),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct MockBus {
+    pub devices: BTreeMap<u8, MockDevice>,
+    /// Address and data of every write, also of the write before a read.
+    pub writes: Vec<(u8, Vec<u8>)>,
+}
+
+impl MockBus {
+    pub fn with_device(mut self, address: u8, device: MockDevice) -> Self {
+        self.devices.insert(address, device);
+        self
+    }
+
+    pub fn device(&mut self, address: u8) -> &mut MockDevice {
+        self.devices.get_mut(&address).unwrap()
+    }
+
+    /// Writes since the last call.
+    pub fn take_writes(&mut self) -> Vec<(u8, Vec<u8>)> {
+        std::mem::take(&mut self.writes)
+    }
+}
+
+impl I2c for MockBus {
+    type Error = BusError;
+
+    fn write(&mut self, address: u8, data: &[u8]) -> Result<(), BusError> {
+        let device = self
+            .devices
+            .get_mut(&address)
+            .ok_or(BusError::Nack(address))?;
+        self.writes.push((address, data.to_vec()));
+        device.last = data.to_vec();
+        if device.commands {
+            return Ok(());
+        }
+        if let Some((&first, values)) = data.split_first() {
+            let (mut register, increment) = device.select(first);
+            for &value in values {
+                device.registers[register as usize] = value;
+                register = register.wrapping_add(increment as u8);
+            }
+        }
+        Ok(())
+    }
+
+    fn write_read(&mut self, address: u8, data: &[u8], buf: &mut [u8]) -> Result<(), BusError> {
+        self.write(address, data)?;
+        self.read(address, buf)
+    }
+
+    fn read(&mut self, address: u8, buf: &mut [u8]) -> Result<(), BusError> {
+        let device = self
+            .devices
+            .get_mut(&address)
+            .ok_or(BusError::Nack(address))?;
+        if device.commands {
+            let answer = device
+                .answers
+                .get(&device.last)
+                .ok_or(BusError::Nack(address))?;
+            for (byte, value) in buf
+                .iter_mut()
+                .zip(answer.iter().chain(std::iter::repeat(&0xff)))
+            {
+                *byte = *value;
+            }
+            return Ok(());
+        }
+        let (mut register, increment) = device
+            .last
+            .first()
+            .map_or((0, true), |&first| device.select(first));
+        for byte in buf {
+            *byte = device.registers[register as usize];
+            register = register.wrapping_add(increment as u8);
+        }
+        Ok(())
+    }
+}
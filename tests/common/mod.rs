@@ -0,0 +1,147 @@
+//! Golden image harness shared by the screen tests.
+//!
+//! Screens are rendered into a [`Framebuffer`] and compared with 1-bit PBM
+//! images in `tests/golden`. Run the tests with `BLESS=1` to write the
+//! current renders as the new golden images:
+//!
+//! ```text
+//! BLESS=1 scripts/test-host.sh
+//! ```
+
+#![allow(dead_code)]
+
+pub mod i2c;
+
+use std::{convert::Infallible, fmt::Write, fs, path::PathBuf};
+
+use embedded_graphics::{pixelcolor::BinaryColor, prelude::*};
+use esp_buddy::{
+    framebuffer::Framebuffer,
+    screens::{Scene, DISPLAY_SIZE},
+};
+
+/// Renders a static screen, which must stay inside the display.
+pub fn render(draw: impl FnOnce(&mut Framebuffer) -> Result<(), Infallible>) -> Framebuffer {
+    let mut frame = Framebuffer::new();
+    draw(&mut frame).unwrap();
+    assert_eq!(
+        frame.clipped(),
+        0,
+        "screen draws outside of the display:\n{}",
+        ascii_art(&frame)
+    );
+    frame
+}
+
+/// Renders the current frame of a scene. Animations may leave the display.
+pub fn render_scene<S: Scene>(scene: &S) -> Framebuffer {
+    let mut frame = Framebuffer::new();
+    scene.draw(&mut frame).unwrap();
+    frame
+}
+
+/// Compares `frame` with `tests/golden/<name>.pbm`, or writes it with `BLESS=1`.
+pub fn assert_golden(name: &str, frame: &Framebuffer) {
+    let path = golden_dir().join(format!("{name}.pbm"));
+
+    if std::env::var_os("BLESS").is_some() {
+        fs::create_dir_all(golden_dir()).unwrap();
+        fs::write(&path, encode_pbm(frame)).unwrap();
+        return;
+    }
+
+    let expected = match fs::read(&path) {
+        Ok(bytes) => decode_pbm(&bytes)
+            .unwrap_or_else(|err| panic!("{}: invalid PBM: {err}", path.display())),
+        Err(err) => panic!("{}: {err}, run with BLESS=1 to create it", path.display()),
+    };
+
+    if expected != *frame {
+        let actual_path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("{name}.pbm"));
+        fs::write(&actual_path, encode_pbm(frame)).unwrap();
+        panic!(
+            "{name} differs from {} ('+' only in actual, '-' only in golden), \
+             actual image written to {}, run with BLESS=1 to accept it:\n{}",
+            path.display(),
+            actual_path.display(),
+            diff_art(&expected, frame)
+        );
+    }
+}
+
+fn golden_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+fn encode_pbm(frame: &Framebuffer) -> Vec<u8> {
+    let mut pbm = format!("P4\n{} {}\n", DISPLAY_SIZE.width, DISPLAY_SIZE.height).into_bytes();
+    pbm.extend_from_slice(frame.as_bytes());
+    pbm
+}
+
+fn decode_pbm(bytes: &[u8]) -> Result<Framebuffer, String> {
+    // Header: magic, width and height separated by whitespace or comments,
+    // followed by a single whitespace before the pixel data.
+    let mut fields = Vec::new();
+    let mut position = 0;
+    while fields.len() < 3 {
+        match bytes.get(position) {
+            Some(b'#') => {
+                while bytes.get(position).is_some_and(|&byte| byte != b'\n') {
+                    position += 1;
+                }
+            }
+            Some(byte) if byte.is_ascii_whitespace() => position += 1,
+            Some(_) => {
+                let start = position;
+                while bytes
+                    .get(position)
+                    .is_some_and(|byte| !byte.is_ascii_whitespace())
+                {
+                    position += 1;
+                }
+                fields.push(String::from_utf8_lossy(&bytes[start..position]).into_owned());
+            }
+            None => return Err("truncated header".into()),
+        }
+    }
+
+    let expected_header = [
+        "P4".to_string(),
+        DISPLAY_SIZE.width.to_string(),
+        DISPLAY_SIZE.height.to_string(),
+    ];
+    if fields != expected_header {
+        return Err(format!(
+            "expected header {expected_header:?}, got {fields:?}"
+        ));
+    }
+
+    let data = bytes
+        .get(position + 1..)
+        .and_then(|data| <[u8; Framebuffer::BYTES]>::try_from(data).ok())
+        .ok_or("pixel data does not match the size")?;
+    Ok(Framebuffer::from_bytes(data))
+}
+
+fn ascii_art(frame: &Framebuffer) -> String {
+    diff_art(frame, frame)
+}
+
+fn diff_art(expected: &Framebuffer, actual: &Framebuffer) -> String {
+    let mut art = String::new();
+    for y in 0..DISPLAY_SIZE.height as i32 {
+        for x in 0..DISPLAY_SIZE.width as i32 {
+            let point = Point::new(x, y);
+            let symbol = match (expected.pixel(point), actual.pixel(point)) {
+                (Some(BinaryColor::On), Some(BinaryColor::On)) => '#',
+                (_, Some(BinaryColor::On)) => '+',
+                (Some(BinaryColor::On), _) => '-',
+                _ => '.',
+            };
+            art.push(symbol);
+        }
+        writeln!(art).unwrap();
+    }
+    art
+}
@@ -0,0 +1,261 @@
+mod common;
+
+use embedded_graphics::prelude::*;
+use esp_buddy::{
+    history::{Bucket, History, DAY_MINUTES, MINUTE_MS},
+    screens::{
+        history::{axis_range, draw_sparkline, Graphs, Quantity, Span, PLOT_WIDTH},
+        ButtonState,
+    },
+    settings::schema::Units,
+};
+
+use common::{assert_golden, render};
+
+fn bucket(min: i16, max: i16, avg: i16) -> Option<Bucket> {
+    Some(Bucket { min, max, avg })
+}
+
+#[test]
+fn buckets_collect_an_interval() {
+    let mut history = History::<4>::new(MINUTE_MS);
+    assert!(history.is_empty());
+    assert_eq!(history.latest(), None);
+
+    history.record(5_000, 210);
+    history.record(20_000, 216);
+    history.record(59_999, 213);
+    assert_eq!(history.len(), 1);
+    assert_eq!(history.latest(), bucket(210, 216, 213));
+
+    history.record(60_000, -5);
+    history.record(61_000, -6);
+    assert_eq!(
+        history.iter().collect::<Vec<_>>(),
+        [bucket(210, 216, 213), bucket(-6, -5, -6)]
+    );
+
+    // Time going backwards stays in the open bucket
+    history.record(1_000, 0);
+    assert_eq!(history.latest(), bucket(-6, 0, -4));
+}
+
+#[test]
+fn gaps_and_wrap_around() {
+    let mut history = History::<4>::new(MINUTE_MS);
+    history.record(0, 1);
+    history.record(3 * MINUTE_MS, 4);
+    assert_eq!(
+        history.iter().collect::<Vec<_>>(),
+        [Some(Bucket::single(1)), None, None, Some(Bucket::single(4))]
+    );
+
+    // Advancing without readings, the newest bucket is a gap
+    history.advance(4 * MINUTE_MS + 1);
+    assert_eq!(
+        history.iter().collect::<Vec<_>>(),
+        [None, None, Some(Bucket::single(4)), None]
+    );
+    assert_eq!(history.latest(), Some(Bucket::single(4)));
+
+    history.record(5 * MINUTE_MS, 6);
+    history.record(6 * MINUTE_MS, 7);
+    assert_eq!(
+        history.iter().collect::<Vec<_>>(),
+        [
+            Some(Bucket::single(4)),
+            None,
+            Some(Bucket::single(6)),
+            Some(Bucket::single(7))
+        ]
+    );
+
+    // A day later only the new reading is left
+    history.record(6 * MINUTE_MS + DAY_MINUTES as u64 * MINUTE_MS, 8);
+    assert_eq!(history.len(), 4);
+    assert_eq!(
+        history.iter().collect::<Vec<_>>(),
+        [None, None, None, Some(Bucket::single(8))]
+    );
+}
+
+#[test]
+fn merge() {
+    assert_eq!(Bucket::merge([]), None);
+    assert_eq!(
+        Bucket::merge([
+            Bucket::single(10),
+            Bucket {
+                min: -3,
+                max: 40,
+                avg: 15
+            },
+            Bucket::single(20),
+        ]),
+        bucket(-3, 40, 15)
+    );
+    // Averages round half away from zero
+    assert_eq!(
+        Bucket::merge([Bucket::single(1), Bucket::single(2)])
+            .unwrap()
+            .avg,
+        2
+    );
+    assert_eq!(
+        Bucket::merge([Bucket::single(-1), Bucket::single(-2)])
+            .unwrap()
+            .avg,
+        -2
+    );
+}
+
+#[test]
+fn downsampling() {
+    let mut history = History::<8>::new(1000);
+    for (second, value) in [(0, 1), (1, 2), (2, 3), (3, 4), (5, 6), (6, 7), (7, 8)] {
+        history.record(second * 1000, value);
+    }
+
+    let mut columns = [None; 4];
+    history.downsample(8, &mut columns);
+    assert_eq!(
+        columns,
+        [
+            bucket(1, 2, 2),
+            bucket(3, 4, 4),
+            Some(Bucket::single(6)),
+            bucket(7, 8, 8)
+        ]
+    );
+
+    // Longer span than the history, the graph starts empty
+    history.downsample(16, &mut columns);
+    assert_eq!(columns, [None, None, bucket(1, 4, 3), bucket(6, 8, 7)]);
+
+    // More columns than buckets, each bucket is repeated
+    let mut columns = [None; 6];
+    history.downsample(3, &mut columns);
+    assert_eq!(
+        columns,
+        [
+            Some(Bucket::single(6)),
+            Some(Bucket::single(6)),
+            Some(Bucket::single(7)),
+            Some(Bucket::single(7)),
+            Some(Bucket::single(8)),
+            Some(Bucket::single(8))
+        ]
+    );
+
+    history.downsample(2, &mut columns[..1]);
+    assert_eq!(columns[0], bucket(7, 8, 8));
+}
+
+#[test]
+fn axis_ranges() {
+    assert_eq!(axis_range(&[None, None]), None);
+    assert_eq!(axis_range(&[bucket(213, 238, 220)]), Some((210, 240)));
+    assert_eq!(
+        axis_range(&[None, Some(Bucket::single(-15))]),
+        Some((-20, 0))
+    );
+    // Flat lines get a range of 2 units
+    assert_eq!(axis_range(&[Some(Bucket::single(450))]), Some((440, 460)));
+    assert_eq!(axis_range(&[bucket(451, 455, 453)]), Some((450, 470)));
+}
+
+/// A day of temperatures, cool at night and warm in the afternoon, with a
+/// sensor outage in the morning and the noise of the readings.
+fn day(temperature: &mut History<DAY_MINUTES>, humidity: &mut History<DAY_MINUTES>) {
+    let mut noise = 0x2545_f491u32;
+    for minute in 0..DAY_MINUTES as u64 {
+        if (420..480).contains(&minute) {
+            continue;
+        }
+        for second in [0, 20, 40] {
+            noise ^= noise << 13;
+            noise ^= noise >> 17;
+            noise ^= noise << 5;
+            let jitter = (noise % 5) as i16 - 2;
+            let phase = minute as f32 / DAY_MINUTES as f32 * core::f32::consts::TAU;
+            let now_ms = minute * MINUTE_MS + second * 1000;
+            temperature.record(now_ms, (215.0 - 35.0 * phase.cos()) as i16 + jitter);
+            humidity.record(now_ms, (520.0 + 80.0 * phase.cos()) as i16 + jitter * 3);
+        }
+    }
+}
+
+#[test]
+fn graphs() {
+    let mut temperature = History::<DAY_MINUTES>::new(MINUTE_MS);
+    let mut humidity = History::<DAY_MINUTES>::new(MINUTE_MS);
+    let mut graphs = Graphs::new();
+    assert_golden(
+        "history_empty",
+        &render(|target| graphs.draw(target, &temperature, &humidity)),
+    );
+
+    day(&mut temperature, &mut humidity);
+    assert_golden(
+        "history_temperature",
+        &render(|target| graphs.draw(target, &temperature, &humidity)),
+    );
+
+    let right = ButtonState {
+        left: false,
+        right: true,
+    };
+    assert!(graphs.update(right));
+    assert!(!graphs.update(right));
+    assert!(!graphs.update(ButtonState::default()));
+    assert_eq!(graphs.quantity(), Quantity::Humidity);
+    assert_golden(
+        "history_humidity",
+        &render(|target| graphs.draw(target, &temperature, &humidity)),
+    );
+
+    let left = ButtonState {
+        left: true,
+        right: false,
+    };
+    graphs.update(left);
+    assert_eq!(graphs.span(), Span::Hour);
+    assert_golden(
+        "history_humidity_hour",
+        &render(|target| graphs.draw(target, &temperature, &humidity)),
+    );
+}
+
+#[test]
+fn graphs_in_fahrenheit() {
+    let mut temperature = History::<DAY_MINUTES>::new(MINUTE_MS);
+    let humidity = History::<DAY_MINUTES>::new(MINUTE_MS);
+    temperature.record(0, Units::Fahrenheit.from_dc(215) as i16);
+    let graphs = Graphs::new().with_units(Units::Fahrenheit);
+    assert_golden(
+        "history_fahrenheit",
+        &render(|target| graphs.draw(target, &temperature, &humidity)),
+    );
+}
+
+#[test]
+fn sparkline_below_zero() {
+    let columns: Vec<_> = (0..PLOT_WIDTH as i16)
+        .map(|x| (x % 30 != 29).then(|| Bucket::single(-120 + x * 2)))
+        .collect();
+    let frame = render(|target| draw_sparkline(target, Point::new(0, 10), &columns));
+    assert_golden("history_sparkline_below_zero", &frame);
+}
+
+#[test]
+fn graphs_show_sensor_errors() {
+    let mut temperature = History::<DAY_MINUTES>::new(MINUTE_MS);
+    let mut humidity = History::<DAY_MINUTES>::new(MINUTE_MS);
+    day(&mut temperature, &mut humidity);
+    let mut graphs = Graphs::new();
+    graphs.set_error(Some("no sensor"));
+    assert_golden(
+        "history_sensor_error",
+        &render(|target| graphs.draw(target, &temperature, &humidity)),
+    );
+}
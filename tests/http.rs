@@ -0,0 +1,256 @@
+use core::net::Ipv4Addr;
+use std::fmt::Write;
+
+use esp_buddy::http::{
+    self,
+    api::{self, Command, DeviceStatus, Rejection},
+    Error, Method, Request, Status,
+};
+use esp_buddy::{led::Rgb, screens::ButtonState};
+
+fn request(data: &[u8]) -> Request<'_> {
+    http::parse(data).unwrap().unwrap()
+}
+
+fn response(buf: &[u8], len: usize) -> (&str, &str) {
+    std::str::from_utf8(&buf[..len])
+        .unwrap()
+        .split_once("\r\n\r\n")
+        .unwrap()
+}
+
+#[test]
+fn request_parsing() {
+    let get = b"GET /generate_204?x=1&y HTTP/1.1\r\nHost: connectivitycheck.gstatic.com\r\n\r\n";
+    let parsed = request(get);
+    assert_eq!(parsed.method, Method::Get);
+    assert_eq!(parsed.path, "/generate_204");
+    assert_eq!(parsed.query, Some("x=1&y"));
+    assert_eq!(parsed.header("HOST"), Some("connectivitycheck.gstatic.com"));
+    assert_eq!(parsed.header("Accept"), None);
+    assert_eq!(parsed.body, b"");
+
+    let post = b"POST /save HTTP/1.0\r\ncontent-length: 9\r\n\r\nssid=home";
+    let parsed = request(post);
+    assert_eq!(parsed.method, Method::Post);
+    assert_eq!(parsed.query, None);
+    assert_eq!(parsed.body, b"ssid=home");
+
+    assert_eq!(request(b"PATCH / HTTP/1.1\r\n\r\n").method, Method::Other);
+}
+
+#[test]
+fn incomplete_requests() {
+    let post = b"POST /led HTTP/1.1\r\nContent-Length: 7\r\n\r\n#ff8000";
+    assert_eq!(http::parse(b""), Ok(None));
+    assert_eq!(http::parse(&post[..20]), Ok(None));
+    assert_eq!(http::parse(&post[..post.len() - 1]), Ok(None));
+    // Pipelined bytes after the body are not part of it
+    let mut pipelined = post.to_vec();
+    pipelined.extend_from_slice(b"GET / HTTP/1.1\r\n\r\n");
+    assert_eq!(request(&pipelined).body, b"#ff8000");
+}
+
+#[test]
+fn malformed_requests() {
+    for data in [
+        &b"GET / HTTP/1.1 extra\r\n\r\n"[..],
+        b"GET\r\n\r\n",
+        b"GET index.html HTTP/1.1\r\n\r\n",
+        b"GET / SPDY/3\r\n\r\n",
+        b"GET / HTTP/1.1\r\nno colon\r\n\r\n",
+        b"GET / HTTP/1.1\r\nHost : example\r\n\r\n",
+        b"GET /\xff HTTP/1.1\r\n\r\n",
+    ] {
+        assert_eq!(http::parse(data), Err(Error::Malformed), "{:?}", data);
+    }
+    assert_eq!(
+        http::parse(b"GET / HTTP/2\r\n\r\n"),
+        Err(Error::UnsupportedVersion)
+    );
+    assert_eq!(
+        http::parse(b"POST / HTTP/1.1\r\nContent-Length: -1\r\n\r\n"),
+        Err(Error::InvalidContentLength)
+    );
+    assert_eq!(
+        http::parse(
+            b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nHello\r\n0\r\n\r\n"
+        ),
+        Err(Error::UnsupportedTransferEncoding)
+    );
+}
+
+#[test]
+fn extra_headers_are_dropped() {
+    let mut data = b"GET / HTTP/1.1\r\n".to_vec();
+    for index in 0..http::MAX_HEADERS + 4 {
+        data.extend_from_slice(format!("X-{}: {}\r\n", index, index).as_bytes());
+    }
+    data.extend_from_slice(b"\r\n");
+    let parsed = request(&data);
+    assert_eq!(parsed.headers.len(), http::MAX_HEADERS);
+    assert_eq!(parsed.header("x-0"), Some("0"));
+    assert_eq!(parsed.header("x-16"), None);
+}
+
+#[test]
+fn response_writing() {
+    let mut buf = [0u8; 512];
+    let len = http::write_response(
+        &mut buf,
+        Status::Ok,
+        &[("Cache-Control", "no-store")],
+        "text/plain",
+        |out| write!(out, "Hello {}", 42),
+    );
+    assert_eq!(
+        std::str::from_utf8(&buf[..len]).unwrap(),
+        "HTTP/1.1 200 OK\r\nCache-Control: no-store\r\nContent-Type: text/plain\r\n\
+         Content-Length: 8\r\nConnection: close\r\n\r\nHello 42"
+    );
+
+    let len = http::error_response(&mut buf, Error::TooLarge);
+    assert_eq!(
+        std::str::from_utf8(&buf[..len]).unwrap(),
+        "HTTP/1.1 413 Payload Too Large\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+    );
+
+    // A body that does not fit becomes a server error
+    let len = http::write_response(&mut buf, Status::Ok, &[], "text/plain", |out| {
+        out.write_str(&"x".repeat(512))
+    });
+    assert!(std::str::from_utf8(&buf[..len])
+        .unwrap()
+        .starts_with("HTTP/1.1 500 Internal Server Error\r\n"));
+}
+
+#[test]
+fn api_routing() {
+    assert_eq!(
+        api::route(&request(b"GET /status HTTP/1.1\r\n\r\n")),
+        Ok(Command::Status)
+    );
+    assert_eq!(
+        api::route(&request(
+            b"POST /display HTTP/1.1\r\nContent-Length: 5\r\n\r\nHello"
+        )),
+        Ok(Command::ShowText("Hello"))
+    );
+    assert_eq!(
+        api::route(&request(
+            b"POST /led HTTP/1.1\r\nContent-Length: 8\r\n\r\n#ff8000\n"
+        )),
+        Ok(Command::SetLed(Rgb::new(0xff, 0x80, 0)))
+    );
+
+    assert_eq!(
+        api::route(&request(b"GET /nothing HTTP/1.1\r\n\r\n")),
+        Err(Rejection::NotFound)
+    );
+    assert_eq!(
+        api::route(&request(b"GET /led HTTP/1.1\r\n\r\n")),
+        Err(Rejection::MethodNotAllowed { allow: "POST" })
+    );
+    assert_eq!(
+        api::route(&request(b"DELETE /status HTTP/1.1\r\n\r\n")),
+        Err(Rejection::MethodNotAllowed { allow: "GET" })
+    );
+    assert!(matches!(
+        api::route(&request(
+            b"POST /led HTTP/1.1\r\nContent-Length: 4\r\n\r\nblue"
+        )),
+        Err(Rejection::BadRequest(_))
+    ));
+    assert!(matches!(
+        api::route(&request(
+            b"POST /display HTTP/1.1\r\nContent-Length: 1\r\n\r\n\xff"
+        )),
+        Err(Rejection::BadRequest(_))
+    ));
+
+    let long = format!(
+        "POST /display HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+        api::MAX_TEXT_LEN + 1,
+        "x".repeat(api::MAX_TEXT_LEN + 1)
+    );
+    assert_eq!(
+        api::route(&request(long.as_bytes())),
+        Err(Rejection::PayloadTooLarge)
+    );
+}
+
+#[test]
+fn colors() {
+    assert_eq!(Rgb::parse("#00ff7f"), Some(Rgb::new(0, 0xff, 0x7f)));
+    assert_eq!(Rgb::parse("A0B1C2"), Some(Rgb::new(0xa0, 0xb1, 0xc2)));
+    assert_eq!(Rgb::parse("#fff"), None);
+    assert_eq!(Rgb::parse("#ff80001"), None);
+    assert_eq!(Rgb::parse("#+f8000"), None);
+    assert_eq!(Rgb::parse("#ff800é"), None);
+    assert_eq!(Rgb::new(1, 0xab, 0xff).to_string(), "#01abff");
+}
+
+#[test]
+fn status_json() {
+    let status = DeviceStatus {
+        ip: Some(Ipv4Addr::new(192, 168, 1, 42)),
+        rssi: Some(-61),
+        uptime_ms: 123_456,
+        free_heap: Some(40_000),
+        temperature_dc: Some(-15),
+        humidity_dpct: Some(455),
+        buttons: ButtonState {
+            left: true,
+            right: false,
+        },
+        led: Rgb::new(0xff, 0x80, 0),
+    };
+    let mut json = String::new();
+    status.write_json(&mut json).unwrap();
+    assert_eq!(
+        json,
+        "{\"ip\":\"192.168.1.42\",\"rssi\":-61,\"uptime_ms\":123456,\"free_heap\":40000,\
+         \"temperature_c\":-1.5,\"humidity_pct\":45.5,\
+         \"buttons\":{\"left\":true,\"right\":false},\"led\":\"#ff8000\"}"
+    );
+
+    let mut json = String::new();
+    DeviceStatus::default().write_json(&mut json).unwrap();
+    assert_eq!(
+        json,
+        "{\"ip\":null,\"rssi\":null,\"uptime_ms\":0,\"free_heap\":null,\
+         \"temperature_c\":null,\"humidity_pct\":null,\
+         \"buttons\":{\"left\":false,\"right\":false},\"led\":\"#000000\"}"
+    );
+}
+
+#[test]
+fn api_responses() {
+    let status = DeviceStatus::default();
+    let mut buf = [0u8; 1024];
+
+    let len = api::respond(Ok(Command::Status), &status, &mut buf);
+    let (head, body) = response(&buf, len);
+    assert!(head.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(head.contains("Content-Type: application/json\r\n"));
+    assert!(head.contains(&format!("Content-Length: {}\r\n", body.len())));
+    assert!(body.starts_with("{\"ip\":null,"));
+
+    let len = api::respond(Ok(Command::ShowText("Hi")), &status, &mut buf);
+    assert_eq!(
+        std::str::from_utf8(&buf[..len]).unwrap(),
+        "HTTP/1.1 204 No Content\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+    );
+
+    let rejected = Err(Rejection::MethodNotAllowed { allow: "POST" });
+    let len = api::respond(rejected, &status, &mut buf);
+    let (head, body) = response(&buf, len);
+    assert!(head.starts_with("HTTP/1.1 405 Method Not Allowed\r\nAllow: POST\r\n"));
+    assert_eq!(body, "{\"error\":\"Method Not Allowed\"}");
+
+    let rejected = Err(Rejection::BadRequest("Expected a color like #ff8000"));
+    let len = api::respond(rejected, &status, &mut buf);
+    let (head, body) = response(&buf, len);
+    assert!(head.starts_with("HTTP/1.1 400 Bad Request\r\n"));
+    assert_eq!(body, "{\"error\":\"Expected a color like #ff8000\"}");
+}
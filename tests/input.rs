@@ -0,0 +1,290 @@
+//! Level traces sampled every [`STEP_MS`], `#` for pressed and `.` for
+//! released, with the events they cause and when.
+
+mod common;
+
+use esp_buddy::{
+    input::{Button, Event, Input, Timing, QUEUE_LEN},
+    screens::{ButtonState, Buttons, Scene},
+};
+
+use common::{assert_golden, render_scene};
+
+const STEP_MS: u64 = 10;
+
+use Button::{Left, Right};
+use Event::*;
+
+/// Runs both traces, the shorter one released after its end, and returns
+/// the events with the time they were queued.
+fn run(input: &mut Input, left: &str, right: &str) -> Vec<(u64, Event)> {
+    let level = |trace: &str, step: usize| trace.as_bytes().get(step) == Some(&b'#');
+    let mut events = Vec::new();
+    for step in 0..left.len().max(right.len()) {
+        let now_ms = step as u64 * STEP_MS;
+        let levels = ButtonState {
+            left: level(left, step),
+            right: level(right, step),
+        };
+        input.update(levels, now_ms);
+        while let Some(event) = input.pop() {
+            events.push((now_ms, event));
+        }
+    }
+    events
+}
+
+/// `count` steps of `level`.
+fn hold(level: char, count: usize) -> String {
+    level.to_string().repeat(count)
+}
+
+#[test]
+fn click() {
+    let mut input = Input::new();
+    // Pressed from 20 to 50 ms, the click once no second press can follow
+    let left = format!("..###{}", hold('.', 40));
+    assert_eq!(
+        run(&mut input, &left, ""),
+        [
+            (40, Pressed(Left)),
+            (70, Released(Left)),
+            (350, Click(Left))
+        ]
+    );
+    assert_eq!(input.state(), ButtonState::default());
+}
+
+#[test]
+fn bounces() {
+    // Shorter than the debounce time, nothing happens
+    let mut input = Input::new();
+    assert_eq!(run(&mut input, "..#.#.##.#.......", ""), []);
+
+    // Bouncing into a press and out of it, timed from the last change
+    let mut input = Input::new();
+    let left = format!("..#.#.#####.#.{}", hold('.', 40));
+    assert_eq!(
+        run(&mut input, &left, ""),
+        [
+            (80, Pressed(Left)),
+            (150, Released(Left)),
+            (430, Click(Left))
+        ]
+    );
+}
+
+#[test]
+fn double_click() {
+    let mut input = Input::new();
+    let left = format!("..###...###{}", hold('.', 40));
+    assert_eq!(
+        run(&mut input, &left, ""),
+        [
+            (40, Pressed(Left)),
+            (70, Released(Left)),
+            (100, Pressed(Left)),
+            (130, Released(Left)),
+            (130, DoubleClick(Left)),
+        ]
+    );
+
+    // The second press too late, two clicks
+    let mut input = Input::new();
+    let right = format!("..###{}###{}", hold('.', 32), hold('.', 40));
+    let events = run(&mut input, "", &right);
+    let clicks: Vec<_> = events
+        .iter()
+        .filter(|(_, event)| matches!(event, Click(_) | DoubleClick(_)))
+        .collect();
+    assert_eq!(clicks, [&(350, Click(Right)), &(700, Click(Right))]);
+}
+
+#[test]
+fn long_press_and_repeat() {
+    let mut input = Input::new();
+    // Held 1.3 s from the start
+    let left = format!("{}{}", hold('#', 130), hold('.', 40));
+    assert_eq!(
+        run(&mut input, &left, ""),
+        [
+            (20, Pressed(Left)),
+            (800, LongPress(Left)),
+            (1000, Repeat(Left)),
+            (1200, Repeat(Left)),
+            (1320, Released(Left)),
+        ]
+    );
+
+    // A double click held the second time is a click and a long press
+    let mut input = Input::new();
+    let right = format!("###...{}{}", hold('#', 90), hold('.', 40));
+    let events = run(&mut input, "", &right);
+    let gestures: Vec<_> = events
+        .into_iter()
+        .filter(|(_, event)| !matches!(event, Pressed(_) | Released(_)))
+        .collect();
+    assert_eq!(gestures, [(860, Click(Right)), (860, LongPress(Right))]);
+}
+
+#[test]
+fn chord() {
+    let mut input = Input::new();
+    // Both held for over a second, right pressed a bit later
+    let left = format!("{}{}", hold('#', 120), hold('.', 40));
+    let right = format!(".....{}{}", hold('#', 110), hold('.', 40));
+    assert_eq!(
+        run(&mut input, &left, &right),
+        [
+            (20, Pressed(Left)),
+            (70, Pressed(Right)),
+            (70, Chord),
+            (1170, Released(Right)),
+            (1220, Released(Left)),
+        ]
+    );
+
+    // No chord once the first button is long pressed
+    let mut input = Input::new();
+    let left = format!("{}{}", hold('#', 100), hold('.', 40));
+    let right = format!("{}###{}", hold('.', 85), hold('.', 40));
+    let events = run(&mut input, &left, &right);
+    assert!(events.contains(&(800, LongPress(Left))));
+    assert!(events.contains(&(870, Pressed(Right))));
+    assert!(!events.iter().any(|(_, event)| *event == Chord));
+    assert!(events.contains(&(1180, Click(Right))));
+}
+
+#[test]
+fn timing() {
+    let timing = Timing {
+        debounce_ms: 0,
+        double_click_ms: 0,
+        long_press_ms: 100,
+        repeat_ms: 50,
+    };
+    let mut input = Input::new().with_timing(timing);
+    assert_eq!(input.timing(), &timing);
+    // Clicks right away without double clicks
+    let left = ".##..##..#############....";
+    assert_eq!(
+        run(&mut input, left, ""),
+        [
+            (10, Pressed(Left)),
+            (30, Released(Left)),
+            (30, Click(Left)),
+            (50, Pressed(Left)),
+            (70, Released(Left)),
+            (70, Click(Left)),
+            (90, Pressed(Left)),
+            (190, LongPress(Left)),
+            (220, Released(Left)),
+        ]
+    );
+}
+
+#[test]
+fn deadlines() {
+    let mut input = Input::new();
+    assert_eq!(input.next_deadline_ms(), None);
+    let pressed = ButtonState {
+        left: true,
+        right: false,
+    };
+    // Debounce, long press, first repeat
+    input.update(pressed, 1000);
+    assert_eq!(input.next_deadline_ms(), Some(1020));
+    input.update(pressed, 1020);
+    assert_eq!(input.next_deadline_ms(), Some(1800));
+    input.update(pressed, 1800);
+    assert_eq!(input.next_deadline_ms(), Some(2000));
+
+    // Click after a short press
+    let mut input = Input::new();
+    input.update(pressed, 0);
+    input.update(pressed, 20);
+    input.update(ButtonState::default(), 100);
+    assert_eq!(input.next_deadline_ms(), Some(120));
+    input.update(ButtonState::default(), 120);
+    assert_eq!(input.next_deadline_ms(), Some(400));
+    // Updated only at the deadlines, the events are the same
+    input.update(ButtonState::default(), 400);
+    let events: Vec<_> = std::iter::from_fn(|| input.pop()).collect();
+    assert_eq!(events, [Pressed(Left), Released(Left), Click(Left)]);
+    assert_eq!(input.next_deadline_ms(), None);
+}
+
+#[test]
+fn queue() {
+    let mut input = Input::new().with_timing(Timing {
+        debounce_ms: 0,
+        double_click_ms: 0,
+        ..Timing::default()
+    });
+    // Three events per click, the newest dropped once full
+    let left = "#.".repeat(QUEUE_LEN);
+    for (step, level) in left.bytes().enumerate() {
+        let levels = ButtonState {
+            left: level == b'#',
+            right: false,
+        };
+        input.update(levels, step as u64 * STEP_MS);
+    }
+    let events: Vec<_> = std::iter::from_fn(|| input.pop()).collect();
+    assert_eq!(events.len(), QUEUE_LEN);
+    assert_eq!(events[..3], [Pressed(Left), Released(Left), Click(Left)]);
+    assert_eq!(input.pop(), None);
+}
+
+#[test]
+fn names() {
+    let names: Vec<String> = [
+        Pressed(Left),
+        Released(Right),
+        Click(Left),
+        DoubleClick(Right),
+        LongPress(Left),
+        Repeat(Right),
+        Chord,
+    ]
+    .iter()
+    .map(ToString::to_string)
+    .collect();
+    assert_eq!(
+        names,
+        [
+            "pressed left",
+            "released right",
+            "click left",
+            "double click right",
+            "long press left",
+            "repeat right",
+            "chord"
+        ]
+    );
+}
+
+#[test]
+fn buttons_scene() {
+    let mut scene = Buttons::new();
+    let released = ButtonState::default();
+    let left = ButtonState {
+        left: true,
+        right: false,
+    };
+    // Frames of 30 ms, clicked twice
+    for buttons in [
+        left, left, released, released, left, left, released, released,
+    ] {
+        scene.update(buttons);
+    }
+    assert_eq!(scene.last(), Some(DoubleClick(Left)));
+    assert_golden("buttons_double_click", &render_scene(&scene));
+
+    let mut scene = Buttons::new();
+    assert!(scene.update_at(left, 0));
+    assert!(!scene.update_at(left, 20));
+    assert!(scene.update_at(left, 800));
+    assert_eq!(scene.last(), Some(LongPress(Left)));
+    assert_eq!(scene.input().state(), left);
+}
@@ -0,0 +1,258 @@
+mod common;
+
+use esp_buddy::{
+    input::{Button, Event, Input, Timing},
+    launcher::{
+        apps::{self, Gpio, Temperature, GPIO_PINS},
+        App, Launcher, Menu, SceneApp,
+    },
+    ntp::Sample,
+    screens::{
+        self,
+        clock::{ClockTime, DateWeekday, Face, LargeDigits},
+        Animation, ButtonState, Car, Clock, Scene, Timers,
+    },
+    sensors::Hts221Reader,
+    settings::schema::Config,
+    time::TimeZone,
+};
+use smoltcp::wire::{IpAddress, Ipv4Address};
+
+use common::{assert_golden, i2c::MockBus, render, render_scene};
+
+use Button::{Left, Right};
+use Event::*;
+
+type Apps = (
+    SceneApp<Animation>,
+    SceneApp<Car>,
+    SceneApp<Clock>,
+    Gpio<fn() -> u64>,
+    Temperature<MockBus>,
+);
+
+/// GPIO4 reads low.
+fn gpio4_low() -> u64 {
+    1 << 4
+}
+
+fn launcher() -> Launcher<Apps> {
+    Launcher::new((
+        SceneApp::new("Animation", Animation::new()),
+        SceneApp::new("Car", Car::new()),
+        SceneApp::new("Clock", Clock::new(0)),
+        Gpio::new(gpio4_low),
+        Temperature::new(MockBus::default(), Hts221Reader::new()),
+    ))
+}
+
+#[test]
+fn menu() {
+    let mut menu = Menu::new(5);
+    assert_eq!((menu.selected(), menu.top()), (0, 0));
+    menu.next();
+    menu.next();
+    assert_eq!((menu.selected(), menu.top()), (2, 0));
+    // Scrolled one item at a time once past the last row
+    menu.next();
+    assert_eq!((menu.selected(), menu.top()), (3, 1));
+    menu.next();
+    assert_eq!((menu.selected(), menu.top()), (4, 2));
+    // Back to the top after the last item
+    menu.next();
+    assert_eq!((menu.selected(), menu.top()), (0, 0));
+
+    menu.select(9);
+    assert_eq!((menu.selected(), menu.top()), (4, 2));
+    menu.select(1);
+    assert_eq!((menu.selected(), menu.top()), (1, 1));
+
+    let mut empty = Menu::new(0);
+    empty.next();
+    assert!(empty.is_empty());
+    assert_eq!(empty.selected(), 0);
+}
+
+#[test]
+fn navigation() {
+    let mut launcher = launcher();
+    assert_eq!(launcher.opened(), None);
+    assert_golden("launcher_menu", &render(|frame| launcher.draw(frame)));
+
+    // Presses alone do nothing, clicks on left select the next app
+    assert!(!launcher.handle_event(Pressed(Left), 0));
+    for _ in 0..3 {
+        assert!(launcher.handle_event(Click(Left), 0));
+    }
+    assert!(launcher.handle_event(Click(Right), 0));
+    assert_eq!(launcher.opened(), Some(3));
+    assert_eq!(launcher.menu().selected(), 3);
+
+    // Long press goes back, its repeats do not scroll the menu
+    assert!(launcher.handle_event(LongPress(Right), 100));
+    assert_eq!(launcher.opened(), None);
+    assert!(!launcher.handle_event(Repeat(Right), 300));
+    assert!(!launcher.handle_event(Released(Right), 400));
+    assert_eq!(launcher.menu().selected(), 3);
+    assert_golden(
+        "launcher_menu_scrolled",
+        &render(|frame| launcher.draw(frame)),
+    );
+
+    // Holding left scrolls once the long press is over
+    assert!(!launcher.handle_event(LongPress(Left), 500));
+    assert!(launcher.handle_event(Repeat(Left), 700));
+    assert_eq!(launcher.menu().selected(), 4);
+    assert!(launcher.handle_event(Repeat(Left), 900));
+    assert_eq!(launcher.menu().selected(), 0);
+    assert_eq!(launcher.menu().top(), 0);
+}
+
+#[test]
+fn apps() {
+    let mut launcher = launcher();
+    launcher.open(1, 1000);
+    assert_eq!(launcher.opened(), Some(1));
+    // Only every frame of the scene
+    assert!(!launcher.update(1000));
+    assert!(launcher.update(1000 + u64::from(Car::FRAME_MS)));
+    assert!(!launcher.update(1000 + u64::from(Car::FRAME_MS)));
+    let mut car = Car::new();
+    car.update(ButtonState::default());
+    assert_eq!(
+        render_scene(launcher.apps().1.scene()),
+        render_scene(&car),
+        "one frame"
+    );
+    assert_eq!(
+        render(|target| launcher.draw(target)),
+        render_scene(&car),
+        "the open app is drawn"
+    );
+
+    launcher.close();
+    launcher.open(3, 0);
+    launcher.update(0);
+    let expected = render(|frame| {
+        let states = GPIO_PINS.map(|(position, pin)| (position, pin == 4));
+        screens::gpio::draw(frame, &states)
+    });
+    assert_eq!(render(|frame| launcher.draw(frame)), expected);
+
+    // Read when opened, then every 5 s
+    launcher.close();
+    launcher.open(4, 0);
+    assert!(launcher.update(0));
+    assert!(!launcher.update(4999));
+    assert!(launcher.update(5000));
+    assert_eq!(launcher.apps().4.reading(), Some(Err("no sensor")));
+
+    // Out of range, stays open
+    launcher.open(9, 0);
+    assert_eq!(launcher.opened(), Some(4));
+}
+
+#[test]
+fn clock() {
+    const PRAGUE: &str = "CET-1CEST,M3.5.0,M10.5.0/3";
+    /// 2024-10-18 12:00:00 UTC
+    const TIMESTAMP: i64 = 1_729_252_800;
+    let config = Config {
+        time_zone: PRAGUE.try_into().unwrap(),
+        ..Config::default()
+    };
+    let mut app = apps::Clock::new().with_config(&config);
+    app.init(0);
+
+    // Seconds since boot until synced
+    assert!(!app.update(999));
+    assert!(app.update(1000));
+    assert_eq!(app.timestamp(), 1);
+    let sample = Sample {
+        server: IpAddress::Ipv4(Ipv4Address::new(162, 159, 200, 1)),
+        stratum: 2,
+        offset_us: TIMESTAMP * 1_000_000 - 2_000_000,
+        delay_us: 12_000,
+    };
+    app.wall_clock_mut().sync(2_000_000, &sample);
+    assert!(app.update(2000));
+    assert_eq!(app.timestamp(), TIMESTAMP);
+    assert!(!app.update(2500));
+
+    // Local time of the zone from the settings, a left click switches faces
+    let time = ClockTime::new(TIMESTAMP, &TimeZone::parse(PRAGUE).unwrap());
+    let large_digits = render(|target| LargeDigits.draw(target, &time));
+    assert_eq!(render(|target| app.draw(target)), large_digits);
+    assert!(!app.handle_event(Pressed(Left), 2500));
+    assert!(app.handle_event(Click(Left), 2500));
+    let date = render(|target| DateWeekday.draw(target, &time));
+    assert_eq!(render(|target| app.draw(target)), date);
+
+    // Changed in the settings, UTC when it does not parse
+    app.set_config(&Config {
+        time_zone: "X".try_into().unwrap(),
+        ..Config::default()
+    });
+    let utc = ClockTime::new(TIMESTAMP, &TimeZone::UTC);
+    let date = render(|target| DateWeekday.draw(target, &utc));
+    assert_eq!(render(|target| app.draw(target)), date);
+}
+
+#[test]
+fn scene_buttons() {
+    // A click shorter than a frame still reaches the scene
+    let mut app = SceneApp::new("Timer", Timers::new());
+    app.init(0);
+    app.handle_event(Pressed(Left), 10);
+    app.handle_event(Released(Left), 20);
+    assert!(app.update(u64::from(Timers::FRAME_MS)));
+
+    let mut timers = Timers::new();
+    timers.update(ButtonState {
+        left: true,
+        right: false,
+    });
+    assert_eq!(render_scene(app.scene()), render_scene(&timers));
+    assert_eq!(app.name(), "Timer");
+}
+
+#[test]
+fn buttons() {
+    // Level traces through the input, sampled every 10 ms
+    let mut launcher = launcher();
+    let mut input = Input::new().with_timing(Timing {
+        double_click_ms: 0,
+        ..Timing::default()
+    });
+    let mut press = |launcher: &mut Launcher<Apps>, levels: ButtonState, steps: u64, start: u64| {
+        for step in 0..steps {
+            let now_ms = (start + step) * 10;
+            input.update(levels, now_ms);
+            launcher.update(now_ms);
+            while let Some(event) = input.pop() {
+                launcher.handle_event(event, now_ms);
+            }
+        }
+        start + steps
+    };
+    let released = ButtonState::default();
+    let left = ButtonState {
+        left: true,
+        right: false,
+    };
+    let right = ButtonState {
+        left: false,
+        right: true,
+    };
+
+    // Next, open, then held down back to the menu
+    let mut at = press(&mut launcher, left, 5, 0);
+    at = press(&mut launcher, released, 5, at);
+    at = press(&mut launcher, right, 5, at);
+    at = press(&mut launcher, released, 5, at);
+    assert_eq!(launcher.opened(), Some(1));
+    at = press(&mut launcher, left, 120, at);
+    assert_eq!(launcher.opened(), None);
+    press(&mut launcher, released, 5, at);
+    assert_eq!(launcher.menu().selected(), 1);
+}
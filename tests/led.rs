@@ -0,0 +1,314 @@
+use esp_buddy::led::{
+    self, bit_code, buffer_len,
+    effects::{
+        self, gamma, hsv, scale, Blink, Breathe, Comet, Effect, Output, Rainbow, Solid, Status,
+        BLINK_DOUBLE, BLUE, GREEN, OFF, RED,
+    },
+    encode, encode_chain, latch_code, pulse_code, Polarity, Rgb, BITS_PER_LED,
+};
+
+/// Levels and lengths of a pulse code.
+fn decode(code: u32) -> (bool, u16, bool, u16) {
+    (
+        code & 1 << 15 != 0,
+        (code & 0x7fff) as u16,
+        code & 1 << 31 != 0,
+        (code >> 16 & 0x7fff) as u16,
+    )
+}
+
+/// The bits sent by pulse codes of a normal line, most significant first.
+fn bits(codes: &[u32]) -> u32 {
+    codes.iter().fold(0, |bits, &code| {
+        let (high, high_ticks, low, low_ticks) = decode(code);
+        assert!(high && !low, "{code:08x}");
+        let one = (high_ticks, low_ticks) == (led::T1H_TICKS, led::T1L_TICKS);
+        let zero = (high_ticks, low_ticks) == (led::T0H_TICKS, led::T0L_TICKS);
+        assert!(one || zero, "{code:08x}");
+        bits << 1 | u32::from(one)
+    })
+}
+
+#[test]
+fn pulse_codes() {
+    assert_eq!(pulse_code(false, 0, false, 0), 0);
+    assert_eq!(pulse_code(true, 1, false, 0), 0x0000_8001);
+    assert_eq!(pulse_code(false, 0, true, 1), 0x8001_0000);
+    assert_eq!(pulse_code(true, 0x7fff, true, 0x7fff), 0xffff_ffff);
+    // Lengths are 15 bits
+    assert_eq!(pulse_code(false, 0xffff, false, 0), 0x0000_7fff);
+    assert_eq!(
+        decode(pulse_code(true, 32, false, 68)),
+        (true, 32, false, 68)
+    );
+}
+
+#[test]
+fn timing() {
+    let ns = |ticks: u16| u32::from(ticks) * 1000 / led::CLOCK_MHZ;
+    // WS2812B datasheet, 150 ns either way
+    for (ticks, expected) in [
+        (led::T0H_TICKS, 400),
+        (led::T0L_TICKS, 850),
+        (led::T1H_TICKS, 800),
+        (led::T1L_TICKS, 450),
+    ] {
+        assert!(ns(ticks).abs_diff(expected) <= 150, "{ticks}");
+    }
+    // A bit takes 1.25 us
+    assert_eq!(ns(led::T0H_TICKS + led::T0L_TICKS), 1250);
+    assert_eq!(ns(led::T1H_TICKS + led::T1L_TICKS), 1250);
+    assert!(ns(led::LATCH_TICKS) >= 280_000);
+}
+
+#[test]
+fn bits_and_polarity() {
+    assert_eq!(
+        decode(bit_code(true, Polarity::Normal)),
+        (true, led::T1H_TICKS, false, led::T1L_TICKS)
+    );
+    assert_eq!(
+        decode(bit_code(false, Polarity::Normal)),
+        (true, led::T0H_TICKS, false, led::T0L_TICKS)
+    );
+    // Same lengths, levels swapped
+    assert_eq!(
+        decode(bit_code(true, Polarity::Inverted)),
+        (false, led::T1H_TICKS, true, led::T1L_TICKS)
+    );
+    assert_eq!(
+        decode(bit_code(false, Polarity::Inverted)),
+        (false, led::T0H_TICKS, true, led::T0L_TICKS)
+    );
+    assert_eq!(led::BOARD_POLARITY, Polarity::Inverted);
+
+    // The line stays low, the zero length ends the transmission
+    assert_eq!(
+        decode(latch_code(Polarity::Normal)),
+        (false, led::LATCH_TICKS, false, 0)
+    );
+    assert_eq!(
+        decode(latch_code(Polarity::Inverted)),
+        (true, led::LATCH_TICKS, true, 0)
+    );
+}
+
+#[test]
+fn green_red_blue() {
+    let codes = encode(Rgb::new(0x12, 0x34, 0x56), Polarity::Normal);
+    assert_eq!(bits(&codes), 0x34_12_56);
+    assert_eq!(
+        bits(&encode(Rgb::new(0xff, 0, 0), Polarity::Normal)),
+        0x00_ff_00
+    );
+    assert_eq!(
+        bits(&encode(Rgb::new(0, 0, 1), Polarity::Normal)),
+        0x00_00_01
+    );
+    assert_eq!(bits(&encode(Rgb::default(), Polarity::Normal)), 0);
+
+    // Inverted codes are the normal ones with both levels flipped
+    let inverted = encode(Rgb::new(0x12, 0x34, 0x56), Polarity::Inverted);
+    for (normal, inverted) in codes.iter().zip(inverted) {
+        assert_eq!(normal ^ 0x8000_8000, inverted);
+    }
+}
+
+#[test]
+fn chains() {
+    let mut codes = [0; buffer_len(2)];
+    assert_eq!(codes.len(), 2 * BITS_PER_LED + 1);
+    let colors = [Rgb::new(1, 2, 3), Rgb::new(4, 5, 6)];
+    let len = encode_chain(colors, Polarity::Normal, &mut codes);
+    assert_eq!(len, Some(49));
+    assert_eq!(bits(&codes[..24]), 0x02_01_03);
+    assert_eq!(bits(&codes[24..48]), 0x05_04_06);
+    assert_eq!(codes[48], latch_code(Polarity::Normal));
+
+    // Fewer LEDs than room for
+    let len = encode_chain([Rgb::new(7, 8, 9)], Polarity::Inverted, &mut codes);
+    assert_eq!(len, Some(25));
+    assert_eq!(codes[24], latch_code(Polarity::Inverted));
+    assert_eq!(encode_chain([], Polarity::Normal, &mut codes), Some(1));
+
+    // No room for the latch, or for the LEDs
+    assert_eq!(
+        encode_chain(colors, Polarity::Normal, &mut codes[..48]),
+        None
+    );
+    let three = [Rgb::default(); 3];
+    assert_eq!(encode_chain(three, Polarity::Normal, &mut codes), None);
+    assert_eq!(encode_chain([], Polarity::Normal, &mut []), None);
+}
+
+/// Colors of a single LED at each time.
+fn frames<E: Effect>(effect: &E, times_ms: impl IntoIterator<Item = u64>) -> Vec<Rgb> {
+    times_ms
+        .into_iter()
+        .map(|now_ms| effect.color(now_ms, 0, 1))
+        .collect()
+}
+
+/// Red channel of every LED of a chain of `len`.
+fn reds<E: Effect>(effect: &E, now_ms: u64, len: usize) -> Vec<u8> {
+    (0..len)
+        .map(|index| effect.color(now_ms, index, len).r)
+        .collect()
+}
+
+#[test]
+fn colors() {
+    assert_eq!(hsv(0, 255, 255), RED);
+    assert_eq!(hsv(0, 0, 255), Rgb::new(255, 255, 255));
+    assert_eq!(hsv(0, 255, 0), OFF);
+    assert_eq!(hsv(43, 255, 255), Rgb::new(253, 255, 0));
+    assert_eq!(hsv(64, 255, 255), Rgb::new(127, 255, 0));
+    assert_eq!(hsv(85, 255, 255), Rgb::new(1, 255, 0));
+    assert_eq!(hsv(128, 255, 255), Rgb::new(0, 255, 255));
+    assert_eq!(hsv(171, 255, 255), Rgb::new(1, 0, 255));
+    assert_eq!(hsv(192, 255, 255), Rgb::new(127, 0, 255));
+    assert_eq!(hsv(128, 128, 200), Rgb::new(99, 200, 200));
+    // Every hue is fully saturated, without a jump around the wheel
+    for hue in 0..=255u8 {
+        let color = hsv(hue, 255, 255);
+        let channels = [color.r, color.g, color.b];
+        assert!(channels.contains(&255) && channels.contains(&0), "{hue}");
+        let next = hsv(hue.wrapping_add(1), 255, 255);
+        for (a, b) in channels.into_iter().zip([next.r, next.g, next.b]) {
+            assert!(a.abs_diff(b) <= 6, "{hue}");
+        }
+    }
+
+    assert_eq!(
+        (gamma(0), gamma(1), gamma(128), gamma(255)),
+        (0, 0, 45, 255)
+    );
+    assert!((1..=255u8).all(|value| gamma(value) >= gamma(value - 1)));
+    assert_eq!(scale(Rgb::new(255, 128, 1), 255), Rgb::new(255, 128, 1));
+    assert_eq!(scale(Rgb::new(255, 128, 1), 0), OFF);
+    assert_eq!(scale(Rgb::new(255, 128, 1), 127), Rgb::new(127, 64, 0));
+}
+
+#[test]
+fn solid_and_breathe() {
+    let color = Rgb::new(200, 100, 0);
+    assert_eq!(frames(&Solid(color), [0, 1, 1_000_000]), [color; 3]);
+
+    let breathe = Breathe::new(Solid(RED), 1000);
+    let levels: Vec<u8> = frames(&breathe, (0..=1000).step_by(250))
+        .iter()
+        .map(|color| color.r)
+        .collect();
+    assert_eq!(levels, [0, 127, 255, 127, 0]);
+    // Same time, same color, whenever it is asked
+    assert_eq!(breathe.color(123_456, 0, 1), breathe.color(123_456, 0, 1));
+    assert_eq!(breathe.color(456, 0, 1), breathe.color(10_456, 0, 1));
+    // Rising, then falling
+    let rising = frames(&breathe, 0..500);
+    assert!(rising.windows(2).all(|pair| pair[0].r <= pair[1].r));
+    let falling = frames(&breathe, 500..1000);
+    assert!(falling.windows(2).all(|pair| pair[0].r >= pair[1].r));
+}
+
+#[test]
+fn blink_patterns() {
+    let blink = Blink::new(Solid(GREEN), BLINK_DOUBLE);
+    let on: Vec<bool> = (0..1000).step_by(100).map(|ms| blink.is_on(ms)).collect();
+    assert_eq!(
+        on,
+        [true, false, true, false, false, false, false, false, false, false]
+    );
+    assert_eq!(
+        frames(&blink, [0, 99, 100, 250, 1050]),
+        [GREEN, GREEN, OFF, GREEN, GREEN]
+    );
+    // Without durations, always on
+    assert!(Blink::new(Solid(GREEN), &[]).is_on(12));
+    assert!(Blink::new(Solid(GREEN), &[0, 0]).is_on(12));
+    assert_eq!(
+        frames(
+            &Blink::new(Solid(RED), effects::BLINK_SLOW),
+            [0, 499, 500, 999, 1000]
+        ),
+        [RED, RED, OFF, OFF, RED]
+    );
+}
+
+#[test]
+fn rainbow() {
+    let rainbow = Rainbow::new(2560);
+    assert_eq!(rainbow.color(0, 0, 1), hsv(0, 255, 255));
+    assert_eq!(rainbow.color(10, 0, 1), hsv(1, 255, 255));
+    assert_eq!(rainbow.color(1280, 0, 1), hsv(128, 255, 255));
+    assert_eq!(rainbow.color(2560, 0, 1), hsv(0, 255, 255));
+    // Spread over the chain, a quarter of the wheel apart
+    let chain: Vec<Rgb> = (0..4).map(|index| rainbow.color(0, index, 4)).collect();
+    assert_eq!(
+        chain,
+        [
+            hsv(0, 255, 255),
+            hsv(64, 255, 255),
+            hsv(128, 255, 255),
+            hsv(192, 255, 255)
+        ]
+    );
+    assert_eq!(rainbow.color(1280, 3, 4), hsv(64, 255, 255));
+}
+
+#[test]
+fn comet() {
+    let comet = Comet::new(Solid(RED), 800).with_tail(3);
+    // A chain of 8, one LED further every 100 ms
+    assert_eq!(reds(&comet, 0, 8), [255, 0, 0, 0, 0, 0, 85, 170]);
+    assert_eq!(reds(&comet, 100, 8), [170, 255, 0, 0, 0, 0, 0, 85]);
+    assert_eq!(reds(&comet, 300, 8), [0, 85, 170, 255, 0, 0, 0, 0]);
+    assert_eq!(reds(&comet, 850, 8), reds(&comet, 50, 8));
+    assert_eq!(reds(&comet, 50, 8), [212, 0, 0, 0, 0, 0, 42, 127]);
+    // A single LED fades out once a period
+    let single = Comet::new(Solid(RED), 1000).with_tail(1);
+    let fading = frames(&single, (0..1000).step_by(250));
+    let levels: Vec<u8> = fading.iter().map(|color| color.r).collect();
+    assert_eq!(levels, [255, 191, 127, 63]);
+    // Composed with another effect
+    let rainbow = Comet::new(Rainbow::new(1000), 1000);
+    assert_eq!(rainbow.color(0, 0, 1), hsv(0, 255, 255));
+}
+
+#[test]
+fn status() {
+    assert_eq!(frames(&Status::Off, [0, 500]), [OFF, OFF]);
+    let connecting = frames(&Status::Connecting, [0, 500, 1000, 2000]);
+    assert_eq!(
+        connecting,
+        [OFF, Rgb::new(0, 0, 127), BLUE, OFF],
+        "breathes blue"
+    );
+    let connected = frames(&Status::Connected, [0, 99, 100, 2999, 3000]);
+    assert_eq!(connected, [GREEN, GREEN, OFF, OFF, GREEN]);
+    let error = frames(&Status::Error, [0, 150, 250, 500, 1000]);
+    assert_eq!(error, [RED, OFF, RED, OFF, RED]);
+}
+
+#[test]
+fn output() {
+    let output = Output::new();
+    assert_eq!(output.brightness(), effects::DEFAULT_BRIGHTNESS);
+    assert_eq!(output.correct(RED), Rgb::new(32, 0, 0));
+    // Gamma first, half the value is much less than half the light
+    assert_eq!(output.correct(Rgb::new(128, 0, 0)), Rgb::new(5, 0, 0));
+    let linear = output.with_gamma(false);
+    assert_eq!(linear.correct(Rgb::new(128, 0, 0)), Rgb::new(16, 0, 0));
+    let full = Output::new().with_brightness(255).with_gamma(false);
+    assert_eq!(full.correct(Rgb::new(1, 128, 255)), Rgb::new(1, 128, 255));
+
+    let mut leds = [Rgb::default(); 4];
+    let rainbow = Rainbow::new(1000);
+    full.render(&rainbow, 0, &mut leds);
+    assert_eq!(leds[2], hsv(128, 255, 255));
+    // Any effect, also behind a reference
+    let effect: &dyn Effect = &Status::Error;
+    output.render(effect, 0, &mut leds);
+    assert_eq!(leds, [Rgb::new(32, 0, 0); 4]);
+    output.render(&effect, 150, &mut leds);
+    assert_eq!(leds, [OFF; 4]);
+}
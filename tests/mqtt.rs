@@ -0,0 +1,565 @@
+use std::collections::VecDeque;
+
+use esp_buddy::{
+    led::Rgb,
+    mqtt::{
+        discovery::{self, Component, Device, MAX_CONFIG_LEN},
+        format_rgb, format_tenths, Client, Command, Connect, Error, Event, Packet, PacketError,
+        Publish, QoS, Topics, Transport, Will,
+    },
+    sensors::{registry::Field, Quantity},
+};
+
+fn encode(packet: &Packet) -> Vec<u8> {
+    let mut buf = [0u8; 512];
+    let len = packet.encode(&mut buf).unwrap();
+    buf[..len].to_vec()
+}
+
+fn round_trip(packet: Packet) -> Vec<u8> {
+    let bytes = encode(&packet);
+    assert_eq!(Packet::decode(&bytes), Ok(Some((packet, bytes.len()))));
+    bytes
+}
+
+#[test]
+fn connect_layout() {
+    let connect = Connect {
+        client_id: "a",
+        keep_alive_s: 60,
+        clean_session: true,
+        will: None,
+        username: None,
+        password: None,
+    };
+    assert_eq!(
+        round_trip(Packet::Connect(connect)),
+        b"\x10\x0d\x00\x04MQTT\x04\x02\x00\x3c\x00\x01a"
+    );
+
+    let bytes = round_trip(Packet::Connect(Connect {
+        will: Some(Will {
+            topic: "s",
+            payload: b"off",
+            retain: true,
+        }),
+        username: Some("u"),
+        password: Some(b"pw"),
+        ..connect
+    }));
+    // User name, password, will retain, will QoS 0, will, clean session
+    assert_eq!(bytes[9], 0b1110_0110);
+    assert_eq!(&bytes[15..], b"\x00\x01s\x00\x03off\x00\x01u\x00\x02pw");
+}
+
+#[test]
+fn packets_round_trip() {
+    assert_eq!(
+        round_trip(Packet::ConnAck {
+            session_present: true,
+            code: 0
+        }),
+        [0x20, 2, 1, 0]
+    );
+    assert_eq!(
+        round_trip(Packet::Publish(Publish {
+            topic: "a/b",
+            payload: b"hi",
+            qos: QoS::AtMostOnce,
+            retain: false,
+            dup: false,
+            packet_id: 0,
+        })),
+        b"\x30\x07\x00\x03a/bhi"
+    );
+    assert_eq!(
+        round_trip(Packet::Publish(Publish {
+            topic: "a/b",
+            payload: b"",
+            qos: QoS::AtLeastOnce,
+            retain: true,
+            dup: true,
+            packet_id: 0x1234,
+        })),
+        b"\x3b\x07\x00\x03a/b\x12\x34"
+    );
+    assert_eq!(round_trip(Packet::PubAck { packet_id: 7 }), [0x40, 2, 0, 7]);
+    assert_eq!(
+        round_trip(Packet::Subscribe {
+            packet_id: 1,
+            topic: "a/#",
+            qos: QoS::AtLeastOnce
+        }),
+        b"\x82\x08\x00\x01\x00\x03a/#\x01"
+    );
+    assert_eq!(
+        round_trip(Packet::SubAck {
+            packet_id: 1,
+            code: 0x80
+        }),
+        [0x90, 3, 0, 1, 0x80]
+    );
+    assert_eq!(round_trip(Packet::PingReq), [0xc0, 0]);
+    assert_eq!(round_trip(Packet::PingResp), [0xd0, 0]);
+    assert_eq!(round_trip(Packet::Disconnect), [0xe0, 0]);
+}
+
+#[test]
+fn remaining_length() {
+    let payload = vec![b'x'; 16_384];
+    for (payload_len, header) in [
+        (122, &[0x30, 127][..]),
+        (123, &[0x30, 0x80, 1]),
+        (16_378, &[0x30, 0xff, 0x7f]),
+        (16_379, &[0x30, 0x80, 0x80, 1]),
+    ] {
+        let publish = Packet::Publish(Publish {
+            topic: "abc",
+            payload: &payload[..payload_len],
+            qos: QoS::AtMostOnce,
+            retain: false,
+            dup: false,
+            packet_id: 0,
+        });
+        let mut buf = vec![0u8; 20_000];
+        let len = publish.encode(&mut buf).unwrap();
+        assert_eq!(&buf[..header.len()], header);
+        assert_eq!(Packet::decode(&buf[..len]), Ok(Some((publish, len))));
+    }
+}
+
+#[test]
+fn incomplete_and_invalid_packets() {
+    let publish = b"\x30\x07\x00\x03a/bhi";
+    for len in 0..publish.len() {
+        assert_eq!(Packet::decode(&publish[..len]), Ok(None));
+    }
+    // Followed by the next packet
+    let mut two = publish.to_vec();
+    two.extend_from_slice(&[0xd0, 0]);
+    assert_eq!(Packet::decode(&two).unwrap().unwrap().1, publish.len());
+
+    assert_eq!(
+        Packet::decode(&[0x30, 0xff, 0xff, 0xff, 0xff]),
+        Err(PacketError::Malformed)
+    );
+    assert_eq!(Packet::decode(&[0xd1, 0]), Err(PacketError::Malformed));
+    assert_eq!(Packet::decode(&[0x20, 1, 0]), Err(PacketError::Malformed));
+    assert_eq!(
+        Packet::decode(b"\x34\x05\x00\x01a\x00\x01"),
+        Err(PacketError::Unsupported(2))
+    );
+    assert_eq!(
+        Packet::decode(&[0x50, 2, 0, 1]),
+        Err(PacketError::Unsupported(5))
+    );
+    assert_eq!(
+        Packet::decode(b"\x30\x03\x00\x01\xff"),
+        Err(PacketError::InvalidUtf8)
+    );
+    assert_eq!(
+        Packet::PingReq.encode(&mut [0u8; 1]),
+        Err(PacketError::BufferTooSmall)
+    );
+}
+
+#[derive(Default)]
+struct MockTransport {
+    sent: Vec<Vec<u8>>,
+    incoming: VecDeque<Vec<u8>>,
+}
+
+impl MockTransport {
+    fn reply(&mut self, packet: Packet) {
+        self.incoming.push_back(encode(&packet));
+    }
+
+    fn take_sent(&mut self) -> Vec<Vec<u8>> {
+        std::mem::take(&mut self.sent)
+    }
+}
+
+impl Transport for MockTransport {
+    type Error = ();
+
+    fn send(&mut self, data: &[u8]) -> Result<(), ()> {
+        self.sent.push(data.to_vec());
+        Ok(())
+    }
+
+    fn receive(&mut self, data: &mut [u8]) -> Result<usize, ()> {
+        let Some(mut chunk) = self.incoming.pop_front() else {
+            return Ok(0);
+        };
+        let len = chunk.len().min(data.len());
+        data[..len].copy_from_slice(&chunk[..len]);
+        if len < chunk.len() {
+            self.incoming.push_front(chunk.split_off(len));
+        }
+        Ok(len)
+    }
+}
+
+fn connected<'a>(client: &mut Client<'a>, transport: &mut MockTransport) {
+    client.connect(transport, 0).unwrap();
+    transport.reply(Packet::ConnAck {
+        session_present: false,
+        code: 0,
+    });
+    assert_eq!(
+        client.poll(transport, 10),
+        Ok(Some(Event::Connected {
+            session_present: false
+        }))
+    );
+    transport.take_sent();
+}
+
+#[test]
+fn client_session() {
+    let (mut rx, mut tx) = ([0u8; 256], [0u8; 256]);
+    let mut client = Client::new("buddy", &mut rx, &mut tx)
+        .with_keep_alive_s(30)
+        .with_credentials("user", "secret")
+        .with_will("esp-buddy/1/status", "offline");
+    let mut transport = MockTransport::default();
+
+    assert_eq!(
+        client.publish(&mut transport, "t", b"1", QoS::AtMostOnce, false),
+        Err(Error::NotConnected)
+    );
+    client.connect(&mut transport, 0).unwrap();
+    let connect = transport.take_sent().remove(0);
+    let Ok(Some((Packet::Connect(connect), _))) = Packet::decode(&connect) else {
+        panic!("not a CONNECT");
+    };
+    assert_eq!(connect.client_id, "buddy");
+    assert_eq!(connect.keep_alive_s, 30);
+    assert_eq!(connect.username, Some("user"));
+    assert_eq!(connect.will.unwrap().payload, b"offline");
+
+    assert_eq!(client.poll(&mut transport, 100), Ok(None));
+    transport.reply(Packet::ConnAck {
+        session_present: false,
+        code: 0,
+    });
+    assert!(client.poll(&mut transport, 200).unwrap().is_some());
+    assert!(client.is_connected());
+
+    assert_eq!(
+        client.subscribe(&mut transport, "esp-buddy/1/set/#", QoS::AtLeastOnce),
+        Ok(1)
+    );
+    assert_eq!(
+        client.publish(&mut transport, "t", b"1", QoS::AtLeastOnce, true),
+        Ok(2)
+    );
+    assert_eq!(
+        transport.take_sent(),
+        [
+            b"\x82\x16\x00\x01\x00\x11esp-buddy/1/set/#\x01".to_vec(),
+            b"\x33\x06\x00\x01t\x00\x021".to_vec()
+        ]
+    );
+
+    transport.reply(Packet::SubAck {
+        packet_id: 1,
+        code: 1,
+    });
+    transport.reply(Packet::PubAck { packet_id: 2 });
+    assert_eq!(
+        client.poll(&mut transport, 300),
+        Ok(Some(Event::Subscribed {
+            packet_id: 1,
+            qos: Some(QoS::AtLeastOnce)
+        }))
+    );
+    assert_eq!(
+        client.poll(&mut transport, 300),
+        Ok(Some(Event::Published { packet_id: 2 }))
+    );
+
+    client.disconnect(&mut transport).unwrap();
+    assert_eq!(transport.take_sent(), [vec![0xe0, 0]]);
+    assert!(!client.is_connected());
+}
+
+#[test]
+fn client_receives_messages_in_pieces() {
+    let (mut rx, mut tx) = ([0u8; 64], [0u8; 64]);
+    let mut client = Client::new("buddy", &mut rx, &mut tx);
+    let mut transport = MockTransport::default();
+    connected(&mut client, &mut transport);
+
+    let qos_1 = encode(&Packet::Publish(Publish {
+        topic: "esp-buddy/1/set/display",
+        payload: b"Hello",
+        qos: QoS::AtLeastOnce,
+        retain: false,
+        dup: false,
+        packet_id: 9,
+    }));
+    let mut stream = qos_1.clone();
+    stream.extend_from_slice(b"\x30\x07\x00\x03a/bhi");
+    // One byte at a time, then the rest at once
+    transport
+        .incoming
+        .extend(qos_1[..5].iter().map(|&byte| vec![byte]));
+    transport.incoming.push_back(stream[5..].to_vec());
+
+    assert_eq!(
+        client.poll(&mut transport, 20),
+        Ok(Some(Event::Message {
+            topic: "esp-buddy/1/set/display",
+            payload: b"Hello"
+        }))
+    );
+    assert_eq!(transport.take_sent(), [vec![0x40, 2, 0, 9]]);
+    assert_eq!(
+        client.poll(&mut transport, 20),
+        Ok(Some(Event::Message {
+            topic: "a/b",
+            payload: b"hi"
+        }))
+    );
+    assert_eq!(client.poll(&mut transport, 20), Ok(None));
+}
+
+#[test]
+fn client_keep_alive() {
+    let (mut rx, mut tx) = ([0u8; 64], [0u8; 64]);
+    let mut client = Client::new("buddy", &mut rx, &mut tx)
+        .with_keep_alive_s(10)
+        .with_timeout_ms(2000);
+    let mut transport = MockTransport::default();
+    connected(&mut client, &mut transport);
+
+    assert_eq!(client.poll(&mut transport, 9_999), Ok(None));
+    assert!(transport.sent.is_empty());
+    assert_eq!(client.poll(&mut transport, 10_010), Ok(None));
+    assert_eq!(transport.take_sent(), [vec![0xc0, 0]]);
+
+    transport.reply(Packet::PingResp);
+    assert_eq!(client.poll(&mut transport, 10_100), Ok(None));
+    assert_eq!(client.poll(&mut transport, 20_010), Ok(None));
+    assert_eq!(transport.take_sent(), [vec![0xc0, 0]]);
+
+    // No answer to the second ping
+    assert_eq!(client.poll(&mut transport, 22_000), Ok(None));
+    assert_eq!(client.poll(&mut transport, 22_010), Err(Error::Timeout));
+    assert!(!client.is_connected());
+    assert_eq!(
+        client.poll(&mut transport, 22_020),
+        Err(Error::NotConnected)
+    );
+}
+
+#[test]
+fn client_connect_failures() {
+    let (mut rx, mut tx) = ([0u8; 16], [0u8; 64]);
+    let mut client = Client::new("buddy", &mut rx, &mut tx);
+    let mut transport = MockTransport::default();
+
+    client.connect(&mut transport, 0).unwrap();
+    assert_eq!(
+        client.poll(&mut transport, Client::DEFAULT_TIMEOUT_MS),
+        Err(Error::Timeout)
+    );
+
+    client.connect(&mut transport, 0).unwrap();
+    transport.reply(Packet::ConnAck {
+        session_present: false,
+        code: 5,
+    });
+    assert_eq!(client.poll(&mut transport, 10), Err(Error::Refused(5)));
+
+    // Anything but CONNACK first
+    client.connect(&mut transport, 0).unwrap();
+    transport.reply(Packet::PingResp);
+    assert_eq!(
+        client.poll(&mut transport, 10),
+        Err(Error::Packet(PacketError::Malformed))
+    );
+
+    // Longer than the receive buffer
+    connected(&mut client, &mut transport);
+    transport
+        .incoming
+        .push_back(b"\x30\x20\x00\x03a/b".to_vec());
+    transport.incoming.push_back(vec![b'x'; 29]);
+    assert_eq!(client.poll(&mut transport, 20), Err(Error::TooLarge));
+}
+
+#[test]
+fn topics_and_commands() {
+    let topics = Topics::new("a0b1c2d3e4f5");
+    assert_eq!(
+        topics.state(Topics::TEMPERATURE),
+        "esp-buddy/a0b1c2d3e4f5/temperature"
+    );
+    assert_eq!(
+        topics.set(Topics::LED_RGB),
+        "esp-buddy/a0b1c2d3e4f5/set/led/rgb"
+    );
+    assert_eq!(topics.availability(), "esp-buddy/a0b1c2d3e4f5/status");
+    assert_eq!(topics.commands(), "esp-buddy/a0b1c2d3e4f5/set/#");
+
+    let command =
+        |name: &str, payload: &'static str| topics.command(&topics.set(name), payload.as_bytes());
+    assert_eq!(
+        command(Topics::DISPLAY, "Hi there"),
+        Some(Command::ShowText("Hi there"))
+    );
+    assert_eq!(command(Topics::LED, "ON"), Some(Command::Led(true)));
+    assert_eq!(command(Topics::LED, "OFF"), Some(Command::Led(false)));
+    assert_eq!(command(Topics::LED, "on"), None);
+    assert_eq!(
+        command(Topics::LED_RGB, "255, 128,0"),
+        Some(Command::LedColor(Rgb::new(255, 128, 0)))
+    );
+    assert_eq!(
+        command(Topics::LED_RGB, "#0080ff"),
+        Some(Command::LedColor(Rgb::new(0, 128, 255)))
+    );
+    assert_eq!(command(Topics::LED_RGB, "1,2"), None);
+    assert_eq!(command(Topics::LED_RGB, "1,2,3,4"), None);
+    assert_eq!(command(Topics::LED_RGB, "1,2,300"), None);
+    let long = "x".repeat(65);
+    assert_eq!(
+        topics.command(&topics.set(Topics::DISPLAY), long.as_bytes()),
+        None
+    );
+    assert_eq!(command(Topics::TEMPERATURE, "20"), None);
+    assert_eq!(
+        topics.command("esp-buddy/a0b1c2d3e4f6/set/led", b"ON"),
+        None
+    );
+    assert_eq!(topics.command("esp-buddy/a0b1c2d3e4f5/led", b"ON"), None);
+
+    assert_eq!(format_tenths(215), "21.5");
+    assert_eq!(format_tenths(-5), "-0.5");
+    assert_eq!(format_rgb(Rgb::new(255, 128, 0)), "255,128,0");
+}
+
+fn discovery(component: Component) -> (String, String) {
+    let topics = Topics::new("a0b1c2d3e4f5");
+    let device = Device {
+        sw_version: "0.3.0",
+        ..Device::default()
+    };
+    let mut config = heapless::String::<MAX_CONFIG_LEN>::new();
+    component.config(&topics, &device, &mut config).unwrap();
+    (
+        component.config_topic(&topics).to_string(),
+        config.to_string(),
+    )
+}
+
+const DEVICE: &str = "\"device\":{\"identifiers\":[\"esp-buddy-a0b1c2d3e4f5\"],\
+                      \"name\":\"ESP-Buddy\",\"model\":\"ESP-Buddy\",\
+                      \"manufacturer\":\"Espressif\",\"sw_version\":\"0.3.0\"}}";
+
+#[test]
+fn discovery_sensor() {
+    let (topic, config) = discovery(Component::Temperature);
+    assert_eq!(
+        topic,
+        "homeassistant/sensor/a0b1c2d3e4f5/temperature/config"
+    );
+    assert_eq!(
+        config,
+        format!(
+            "{{\"name\":\"Temperature\",\"unique_id\":\"a0b1c2d3e4f5_temperature\",\
+             \"availability_topic\":\"esp-buddy/a0b1c2d3e4f5/status\",\
+             \"payload_available\":\"online\",\"payload_not_available\":\"offline\",\
+             \"state_topic\":\"esp-buddy/a0b1c2d3e4f5/temperature\",\
+             \"device_class\":\"temperature\",\"unit_of_measurement\":\"°C\",\
+             \"state_class\":\"measurement\",{DEVICE}"
+        )
+    );
+
+    let (_, config) = discovery(Component::Rssi);
+    assert!(config.contains("\"device_class\":\"signal_strength\""));
+    assert!(config.contains("\"entity_category\":\"diagnostic\""));
+}
+
+#[test]
+fn discovery_light_text_and_triggers() {
+    let (topic, config) = discovery(Component::Led);
+    assert_eq!(topic, "homeassistant/light/a0b1c2d3e4f5/led/config");
+    assert!(config.contains(
+        "\"command_topic\":\"esp-buddy/a0b1c2d3e4f5/set/led\",\
+         \"rgb_state_topic\":\"esp-buddy/a0b1c2d3e4f5/led/rgb\",\
+         \"rgb_command_topic\":\"esp-buddy/a0b1c2d3e4f5/set/led/rgb\""
+    ));
+
+    let (topic, config) = discovery(Component::Display);
+    assert_eq!(topic, "homeassistant/text/a0b1c2d3e4f5/display/config");
+    assert!(config.contains("\"max\":64"));
+
+    let (topic, config) = discovery(Component::RightButton);
+    assert_eq!(
+        topic,
+        "homeassistant/device_automation/a0b1c2d3e4f5/right/config"
+    );
+    assert_eq!(
+        config,
+        format!(
+            "{{\"automation_type\":\"trigger\",\"type\":\"button_short_press\",\
+             \"subtype\":\"button_2\",\"topic\":\"esp-buddy/a0b1c2d3e4f5/button\",\
+             \"payload\":\"right\",{DEVICE}"
+        )
+    );
+
+    let name = Device {
+        name: "Desk \"buddy\"",
+        ..Device::default()
+    };
+    let mut config = String::new();
+    Component::Humidity
+        .config(&Topics::new("1"), &name, &mut config)
+        .unwrap();
+    assert!(config.contains("\"name\":\"Desk \\\"buddy\\\"\""));
+
+    // Every configuration fits into the buffer of the example
+    for component in Component::ALL {
+        let mut config = heapless::String::<MAX_CONFIG_LEN>::new();
+        let topics = Topics::new("a0b1c2d3e4f5");
+        component
+            .config(&topics, &Device::default(), &mut config)
+            .unwrap();
+    }
+}
+
+#[test]
+fn discovery_fields() {
+    let topics = Topics::new("a0b1c2d3e4f5");
+    let device = Device {
+        sw_version: "0.3.0",
+        ..Device::default()
+    };
+    let field = Field {
+        sensor: "BME280",
+        address: 0x76,
+        key: "bme280_76_pressure".try_into().unwrap(),
+        quantity: Quantity::Pressure,
+        value: Some(10065),
+    };
+    assert_eq!(
+        discovery::field_config_topic(&topics, &field),
+        "homeassistant/sensor/a0b1c2d3e4f5/bme280_76_pressure/config"
+    );
+    let mut config = heapless::String::<MAX_CONFIG_LEN>::new();
+    discovery::field_config(&topics, &device, &field, &mut config).unwrap();
+    assert_eq!(
+        config.as_str(),
+        format!(
+            "{{\"name\":\"BME280 76 Pressure\",\"unique_id\":\"a0b1c2d3e4f5_bme280_76_pressure\",\
+             \"availability_topic\":\"esp-buddy/a0b1c2d3e4f5/status\",\
+             \"payload_available\":\"online\",\"payload_not_available\":\"offline\",\
+             \"state_topic\":\"esp-buddy/a0b1c2d3e4f5/sensors/bme280_76_pressure\",\
+             \"device_class\":\"pressure\",\"unit_of_measurement\":\"hPa\",\
+             \"state_class\":\"measurement\",{DEVICE}"
+        )
+    );
+}
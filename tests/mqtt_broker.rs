@@ -0,0 +1,240 @@
+//! Runs the client against a real broker, e.g. a local mosquitto:
+//!
+//! ```text
+//! mosquitto -p 1883 &
+//! MQTT_BROKER=127.0.0.1:1883 cargo +stable test --no-default-features \
+//!     --target "$(rustc +stable -vV | sed -n 's/^host: //p')" --test mqtt_broker -- --ignored
+//! ```
+
+use std::{
+    io::{ErrorKind, Read, Write},
+    net::TcpStream,
+    time::{Duration, Instant},
+};
+
+use esp_buddy::{
+    led::Rgb,
+    mqtt::{
+        discovery::{Component, Device, MAX_CONFIG_LEN},
+        Client, Command, Event, QoS, Topics, Transport, OFFLINE, ONLINE,
+    },
+};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+struct Tcp(TcpStream);
+
+impl Tcp {
+    fn connect() -> Self {
+        let address = std::env::var("MQTT_BROKER").expect("MQTT_BROKER=host:port not set");
+        let stream = TcpStream::connect(address).unwrap();
+        stream.set_nonblocking(true).unwrap();
+        Tcp(stream)
+    }
+}
+
+impl Transport for Tcp {
+    type Error = std::io::Error;
+
+    fn send(&mut self, data: &[u8]) -> std::io::Result<()> {
+        self.0.set_nonblocking(false)?;
+        let written = self.0.write_all(data);
+        self.0.set_nonblocking(true)?;
+        written
+    }
+
+    fn receive(&mut self, data: &mut [u8]) -> std::io::Result<usize> {
+        match self.0.read(data) {
+            Ok(0) => Err(ErrorKind::UnexpectedEof.into()),
+            Err(err) if err.kind() == ErrorKind::WouldBlock => Ok(0),
+            result => result,
+        }
+    }
+}
+
+/// Polls until `wanted` returns something for an event.
+fn wait_for<T>(
+    client: &mut Client,
+    transport: &mut Tcp,
+    start: Instant,
+    mut wanted: impl FnMut(Event) -> Option<T>,
+) -> T {
+    let deadline = Instant::now() + TIMEOUT;
+    while Instant::now() < deadline {
+        let now_ms = start.elapsed().as_millis() as u64;
+        if let Some(found) = client
+            .poll(transport, now_ms)
+            .unwrap()
+            .and_then(&mut wanted)
+        {
+            return found;
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    panic!("nothing arrived in time");
+}
+
+#[test]
+#[ignore = "needs a broker, set MQTT_BROKER=host:port"]
+fn device_and_controller() {
+    let start = Instant::now();
+    let device_id = format!("test{}", std::process::id());
+    let topics = Topics::new(&device_id);
+    let availability = topics.availability();
+
+    // The board: will, discovery and state, listening to commands
+    let client_id = format!("esp-buddy-{device_id}");
+    let (mut rx, mut tx) = ([0u8; 256], [0u8; 1200]);
+    let mut device = Client::new(&client_id, &mut rx, &mut tx).with_will(&availability, OFFLINE);
+    let mut device_tcp = Tcp::connect();
+    device.connect(&mut device_tcp, 0).unwrap();
+    wait_for(&mut device, &mut device_tcp, start, |event| {
+        matches!(event, Event::Connected { .. }).then_some(())
+    });
+    let subscription = device
+        .subscribe(&mut device_tcp, &topics.commands(), QoS::AtLeastOnce)
+        .unwrap();
+    let granted = wait_for(&mut device, &mut device_tcp, start, |event| match event {
+        Event::Subscribed { packet_id, qos } if packet_id == subscription => Some(qos),
+        _ => None,
+    });
+    assert_eq!(granted, Some(QoS::AtLeastOnce));
+
+    let component = Component::Temperature;
+    let mut config = heapless::String::<MAX_CONFIG_LEN>::new();
+    component
+        .config(&topics, &Device::default(), &mut config)
+        .unwrap();
+    let config_topic = component.config_topic(&topics);
+    device
+        .publish(
+            &mut device_tcp,
+            &config_topic,
+            config.as_bytes(),
+            QoS::AtMostOnce,
+            true,
+        )
+        .unwrap();
+    device
+        .publish(
+            &mut device_tcp,
+            &availability,
+            ONLINE.as_bytes(),
+            QoS::AtMostOnce,
+            true,
+        )
+        .unwrap();
+    let temperature = topics.state(Topics::TEMPERATURE);
+    let published = device
+        .publish(
+            &mut device_tcp,
+            &temperature,
+            b"21.5",
+            QoS::AtLeastOnce,
+            true,
+        )
+        .unwrap();
+    wait_for(&mut device, &mut device_tcp, start, |event| {
+        (event
+            == Event::Published {
+                packet_id: published,
+            })
+        .then_some(())
+    });
+
+    // Home Assistant: sees the retained messages and sends commands
+    let controller_id = format!("controller-{device_id}");
+    let (mut rx, mut tx) = ([0u8; 1200], [0u8; 256]);
+    let mut controller = Client::new(&controller_id, &mut rx, &mut tx);
+    let mut controller_tcp = Tcp::connect();
+    controller.connect(&mut controller_tcp, 0).unwrap();
+    wait_for(&mut controller, &mut controller_tcp, start, |event| {
+        matches!(event, Event::Connected { .. }).then_some(())
+    });
+    let mut retained = Vec::new();
+    for filter in [config_topic.as_str(), &availability, &temperature] {
+        controller
+            .subscribe(&mut controller_tcp, filter, QoS::AtMostOnce)
+            .unwrap();
+        retained.push(wait_for(
+            &mut controller,
+            &mut controller_tcp,
+            start,
+            |event| match event {
+                Event::Message { topic, payload } => Some((
+                    topic.to_string(),
+                    String::from_utf8(payload.to_vec()).unwrap(),
+                )),
+                _ => None,
+            },
+        ));
+    }
+    assert_eq!(
+        retained,
+        [
+            (config_topic.to_string(), config.to_string()),
+            (availability.to_string(), ONLINE.to_string()),
+            (temperature.to_string(), "21.5".to_string()),
+        ]
+    );
+
+    for (name, payload) in [(Topics::DISPLAY, "Hello"), (Topics::LED_RGB, "255,128,0")] {
+        controller
+            .publish(
+                &mut controller_tcp,
+                &topics.set(name),
+                payload.as_bytes(),
+                QoS::AtLeastOnce,
+                false,
+            )
+            .unwrap();
+    }
+    let mut commands = Vec::new();
+    while commands.len() < 2 {
+        commands.push(wait_for(
+            &mut device,
+            &mut device_tcp,
+            start,
+            |event| match event {
+                Event::Message { topic, payload } => {
+                    topics.command(topic, payload).map(|command| match command {
+                        Command::ShowText(text) => text.to_string(),
+                        command => format!("{command:?}"),
+                    })
+                }
+                _ => None,
+            },
+        ));
+    }
+    assert_eq!(
+        commands,
+        [
+            "Hello".to_string(),
+            format!("{:?}", Command::LedColor(Rgb::new(255, 128, 0)))
+        ]
+    );
+
+    // Losing the connection publishes the will
+    drop(device_tcp);
+    let (topic, payload) = wait_for(
+        &mut controller,
+        &mut controller_tcp,
+        start,
+        |event| match event {
+            Event::Message { topic, payload } => Some((topic.to_string(), payload.to_vec())),
+            _ => None,
+        },
+    );
+    assert_eq!(
+        (topic.as_str(), payload.as_slice()),
+        (availability.as_str(), OFFLINE.as_bytes())
+    );
+
+    // Clean up the retained messages
+    for topic in [config_topic.as_str(), &availability, &temperature] {
+        controller
+            .publish(&mut controller_tcp, topic, b"", QoS::AtMostOnce, true)
+            .unwrap();
+    }
+    controller.disconnect(&mut controller_tcp).unwrap();
+}
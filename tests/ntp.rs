@@ -0,0 +1,258 @@
+use std::collections::VecDeque;
+
+use esp_buddy::ntp::{
+    decode, decode_response, encode_request, offset_and_delay, Client, Error, PacketError,
+    Timestamp, Transport, PACKET_SIZE,
+};
+use smoltcp::wire::{IpAddress, Ipv4Address};
+
+/// Reply of a stratum 2 server to a request sent 5 s after boot, receive and
+/// transmit timestamps 2024-10-18 12:00:00.250001 and .250120 UTC.
+const STRATUM_2_REPLY: &str = "240200e700000c2a00000345d8ef2304eabccc368a3d70a4\
+                               83aa7e8500000000eabccc4040001234eabccc404007de70";
+
+/// Rate limiting kiss-o'-death reply to the same request.
+const KISS_RATE_REPLY: &str = "240000e80000000000000000524154450000000000000000\
+                               83aa7e85000000000000000000000000eabccc404007de70";
+
+const SERVER: IpAddress = IpAddress::Ipv4(Ipv4Address::new(216, 239, 35, 4));
+const OTHER_SERVER: IpAddress = IpAddress::Ipv4(Ipv4Address::new(162, 159, 200, 1));
+
+fn hex(text: &str) -> Vec<u8> {
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn originate() -> Timestamp {
+    Timestamp::from_unix_micros(5_000_000)
+}
+
+#[test]
+fn request_layout() {
+    let request = encode_request(Timestamp::new(0xeabc_cc40, 0x8000_0000));
+
+    assert_eq!(request.len(), PACKET_SIZE);
+    // LI 0, version 4, mode 3 (client)
+    assert_eq!(request[0], 0b00_100_011);
+    assert!(request[1..40].iter().all(|&byte| byte == 0));
+    assert_eq!(request[40..48], [0xea, 0xbc, 0xcc, 0x40, 0x80, 0, 0, 0]);
+}
+
+#[test]
+fn decode_server_reply() {
+    let packet = decode_response(&hex(STRATUM_2_REPLY), originate()).unwrap();
+
+    assert_eq!(packet.leap, 0);
+    assert_eq!(packet.version, 4);
+    assert_eq!(packet.mode, 4);
+    assert_eq!(packet.stratum, 2);
+    assert_eq!(packet.precision, -25);
+    assert_eq!(packet.root_delay, 0x0c2a);
+    assert_eq!(packet.root_dispersion, 0x0345);
+    assert_eq!(packet.reference_id, [216, 239, 35, 4]);
+    assert_eq!(packet.originate, originate());
+    assert_eq!(packet.receive.to_unix_micros(), 1_729_252_800_250_001);
+    assert_eq!(packet.transmit.to_unix_micros(), 1_729_252_800_250_120);
+}
+
+#[test]
+fn reject_invalid_replies() {
+    let reply = hex(STRATUM_2_REPLY);
+
+    assert_eq!(
+        decode_response(&reply[..47], originate()),
+        Err(PacketError::TooShort)
+    );
+    assert_eq!(
+        decode_response(&reply, Timestamp::from_unix_micros(6_000_000)),
+        Err(PacketError::OriginateMismatch)
+    );
+    assert_eq!(
+        decode_response(&hex(KISS_RATE_REPLY), originate()),
+        Err(PacketError::KissOfDeath(*b"RATE"))
+    );
+
+    let mut unsynchronized = reply.clone();
+    unsynchronized[0] |= 0b11 << 6;
+    assert_eq!(
+        decode_response(&unsynchronized, originate()),
+        Err(PacketError::Unsynchronized)
+    );
+
+    let mut broadcast = reply.clone();
+    broadcast[0] = (broadcast[0] & !0b111) | 5;
+    assert_eq!(
+        decode_response(&broadcast, originate()),
+        Err(PacketError::UnexpectedMode(5))
+    );
+
+    let mut version_2 = reply.clone();
+    version_2[0] = (version_2[0] & !0b111_000) | 2 << 3;
+    assert_eq!(
+        decode_response(&version_2, originate()),
+        Err(PacketError::UnsupportedVersion(2))
+    );
+
+    let mut no_transmit = reply;
+    no_transmit[40..48].fill(0);
+    assert_eq!(
+        decode_response(&no_transmit, originate()),
+        Err(PacketError::ZeroTransmit)
+    );
+}
+
+#[test]
+fn decode_ignores_extension_fields() {
+    let mut reply = hex(STRATUM_2_REPLY);
+    reply.extend_from_slice(&[0xaa; 20]);
+    assert_eq!(decode(&reply).unwrap().stratum, 2);
+}
+
+#[test]
+fn timestamp_fraction() {
+    let timestamp = Timestamp::from_unix_micros(1_729_252_800_500_000);
+    assert_eq!(timestamp, Timestamp::new(0xeabc_cc40, 0x8000_0000));
+    assert_eq!(timestamp.to_unix_micros(), 1_729_252_800_500_000);
+
+    // Truncating the fraction never rounds up into the next microsecond
+    assert_eq!(
+        Timestamp::new(0xeabc_cc40, 0xffff_ffff).to_unix_micros(),
+        1_729_252_800_999_999
+    );
+}
+
+#[test]
+fn timestamp_eras() {
+    // 1900-01-01 is before the covered range, so seconds 0 is 2036-02-07
+    assert_eq!(Timestamp::ZERO.to_unix_micros(), 2_085_978_496_000_000);
+    assert_eq!(Timestamp::new(0, 0).to_be_bytes(), [0; 8]);
+
+    let after_rollover = 2_085_978_496_000_000 + 3_600_000_000;
+    let timestamp = Timestamp::from_unix_micros(after_rollover);
+    assert_eq!(timestamp.seconds, 3600);
+    assert_eq!(timestamp.to_unix_micros(), after_rollover);
+
+    let unix_epoch = Timestamp::from_unix_micros(0);
+    assert_eq!(unix_epoch.seconds, 2_208_988_800);
+    assert_eq!(unix_epoch.to_unix_micros(), 0);
+}
+
+#[test]
+fn offset_and_delay_symmetric_path() {
+    // Server is 10 s ahead, 20 ms each way, 1 ms processing
+    let (offset, delay) = offset_and_delay(1_000_000, 11_020_000, 11_021_000, 1_041_000);
+    assert_eq!(offset, 10_000_000);
+    assert_eq!(delay, 40_000);
+}
+
+#[test]
+fn offset_and_delay_server_behind() {
+    let (offset, delay) = offset_and_delay(50_000_000, 40_005_000, 40_005_500, 50_010_500);
+    assert_eq!(offset, -10_000_000);
+    assert_eq!(delay, 10_000);
+}
+
+#[derive(Default)]
+struct MockTransport {
+    sent: Vec<(IpAddress, u16, Vec<u8>)>,
+    incoming: VecDeque<(Vec<u8>, IpAddress)>,
+}
+
+impl Transport for MockTransport {
+    type Error = ();
+
+    fn send(&mut self, server: IpAddress, port: u16, data: &[u8]) -> Result<(), ()> {
+        self.sent.push((server, port, data.to_vec()));
+        Ok(())
+    }
+
+    fn receive(&mut self, data: &mut [u8]) -> Result<Option<(usize, IpAddress)>, ()> {
+        Ok(self.incoming.pop_front().map(|(datagram, from)| {
+            data[..datagram.len()].copy_from_slice(&datagram);
+            (datagram.len(), from)
+        }))
+    }
+}
+
+#[test]
+fn client_exchange() {
+    let servers = [SERVER];
+    let mut client = Client::new(&servers);
+    let mut transport = MockTransport::default();
+
+    assert_eq!(client.poll(&mut transport, 5_000_000), Ok(None));
+    assert_eq!(transport.sent.len(), 1);
+    let (server, port, request) = &transport.sent[0];
+    assert_eq!((*server, *port), (SERVER, 123));
+    assert_eq!(request[40..48], originate().to_be_bytes());
+
+    assert_eq!(client.poll(&mut transport, 5_010_000), Ok(None));
+
+    transport.incoming.push_back((hex(STRATUM_2_REPLY), SERVER));
+    let sample = client.poll(&mut transport, 5_030_000).unwrap().unwrap();
+    assert_eq!(sample.server, SERVER);
+    assert_eq!(sample.stratum, 2);
+    assert_eq!(sample.offset_us, 1_729_252_795_235_060);
+    assert_eq!(sample.delay_us, 29_881);
+    assert_eq!(sample.unix_micros(5_030_000), 1_729_252_800_265_060);
+}
+
+#[test]
+fn client_ignores_other_senders() {
+    let servers = [SERVER];
+    let mut client = Client::new(&servers);
+    let mut transport = MockTransport::default();
+
+    client.poll(&mut transport, 5_000_000).unwrap();
+    transport
+        .incoming
+        .push_back((hex(STRATUM_2_REPLY), OTHER_SERVER));
+    assert_eq!(client.poll(&mut transport, 5_030_000), Ok(None));
+}
+
+#[test]
+fn client_rotates_servers() {
+    let servers = [SERVER, OTHER_SERVER];
+    let mut client = Client::new(&servers).with_timeout_ms(1000);
+    let mut transport = MockTransport::default();
+
+    client.poll(&mut transport, 5_000_000).unwrap();
+    assert_eq!(client.poll(&mut transport, 5_999_999), Ok(None));
+    assert_eq!(
+        client.poll(&mut transport, 6_000_000),
+        Err(Error::Timeout(SERVER))
+    );
+
+    client.poll(&mut transport, 6_000_000).unwrap();
+    assert_eq!(transport.sent[1].0, OTHER_SERVER);
+
+    // Kiss-o'-death moves on without waiting for the timeout
+    let mut kiss = hex(KISS_RATE_REPLY);
+    kiss[24..32].copy_from_slice(&Timestamp::from_unix_micros(6_000_000).to_be_bytes());
+    transport.incoming.push_back((kiss, OTHER_SERVER));
+    assert_eq!(
+        client.poll(&mut transport, 6_001_000),
+        Err(Error::Packet(
+            OTHER_SERVER,
+            PacketError::KissOfDeath(*b"RATE")
+        ))
+    );
+    assert_eq!(client.server(), SERVER);
+}
+
+#[test]
+fn client_skips_late_replies() {
+    let servers = [SERVER];
+    let mut client = Client::new(&servers).with_timeout_ms(1000);
+    let mut transport = MockTransport::default();
+
+    client.poll(&mut transport, 5_000_000).unwrap();
+    client.poll(&mut transport, 6_000_000).unwrap_err();
+    client.poll(&mut transport, 6_000_000).unwrap();
+
+    // Reply to the first, timed out request
+    transport.incoming.push_back((hex(STRATUM_2_REPLY), SERVER));
+    assert_eq!(client.poll(&mut transport, 6_100_000), Ok(None));
+}
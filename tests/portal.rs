@@ -0,0 +1,378 @@
+use core::net::Ipv4Addr;
+
+use esp_buddy::http;
+use esp_buddy::portal::{
+    self, credentials,
+    dhcp::{self, MessageType},
+    dns::{self, DnsError},
+    form::{self, FormError},
+    respond, AP_IP, URL,
+};
+use esp_buddy::settings::StoredNetwork;
+use esp_buddy::wifi::AccessPoint;
+
+const MAC: [u8; 6] = [0x02, 0x11, 0x22, 0x33, 0x44, 0x55];
+
+fn access_point(ssid: &str, rssi: i8, open: bool) -> AccessPoint {
+    AccessPoint {
+        ssid: ssid.try_into().unwrap(),
+        rssi,
+        channel: 6,
+        open,
+    }
+}
+
+/// Standard query with recursion desired for one question.
+fn dns_query(id: u16, name: &str, qtype: u16) -> Vec<u8> {
+    let mut query = id.to_be_bytes().to_vec();
+    query.extend_from_slice(&[0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0]);
+    for label in name.split('.') {
+        query.push(label.len() as u8);
+        query.extend_from_slice(label.as_bytes());
+    }
+    query.push(0);
+    query.extend_from_slice(&qtype.to_be_bytes());
+    query.extend_from_slice(&1u16.to_be_bytes());
+    query
+}
+
+fn dhcp_message(message_type: MessageType, options: &[&[u8]]) -> Vec<u8> {
+    let mut message = vec![0u8; 240];
+    message[..4].copy_from_slice(&[1, 1, 6, 0]);
+    message[4..8].copy_from_slice(&0x1234_5678u32.to_be_bytes());
+    message[10] = 0x80;
+    message[28..34].copy_from_slice(&MAC);
+    message[236..240].copy_from_slice(&[99, 130, 83, 99]);
+    message.extend_from_slice(&[53, 1, message_type as u8]);
+    for option in options {
+        message.extend_from_slice(option);
+    }
+    message.push(255);
+    message
+}
+
+/// Options of a reply as (code, value) pairs.
+fn dhcp_options(reply: &[u8]) -> Vec<(u8, Vec<u8>)> {
+    let mut options = Vec::new();
+    let mut rest = &reply[240..];
+    while let [code, tail @ ..] = rest {
+        match code {
+            0 => rest = tail,
+            255 => break,
+            _ => {
+                let len = tail[0] as usize;
+                options.push((*code, tail[1..1 + len].to_vec()));
+                rest = &tail[1 + len..];
+            }
+        }
+    }
+    options
+}
+
+#[test]
+fn form_pairs() {
+    let pairs: Vec<_> = form::pairs("ssid=home&password=&flag&&x=a%3Db").collect();
+    assert_eq!(
+        pairs,
+        [
+            ("ssid", "home"),
+            ("password", ""),
+            ("flag", ""),
+            ("x", "a%3Db")
+        ]
+    );
+}
+
+#[test]
+fn form_decoding() {
+    assert_eq!(
+        form::decode::<32>("My+Home%20Wi-Fi%21").unwrap(),
+        "My Home Wi-Fi!"
+    );
+    assert_eq!(form::decode::<8>("%2b%2B").unwrap(), "++");
+    assert_eq!(form::decode::<8>("caf%C3%A9").unwrap(), "café");
+    assert_eq!(form::decode::<8>("%4"), Err(FormError::InvalidEncoding));
+    assert_eq!(form::decode::<8>("%zz"), Err(FormError::InvalidEncoding));
+    assert_eq!(form::decode::<8>("%C3"), Err(FormError::InvalidEncoding));
+    assert_eq!(form::decode::<4>("%41BCDE"), Err(FormError::TooLong));
+
+    assert_eq!(
+        form::value::<16>("a=1&b=two+words&b=3", "b").unwrap(),
+        Some("two words".try_into().unwrap())
+    );
+    assert_eq!(form::value::<16>("a=1", "b"), Ok(None));
+}
+
+#[test]
+fn submitted_credentials() {
+    assert_eq!(
+        credentials(b"ssid=home&other=&password=correct+horse"),
+        Ok(StoredNetwork::new("home", "correct horse", portal::PRIORITY).unwrap())
+    );
+    // Hidden network typed in
+    assert_eq!(
+        credentials(b"ssid=&other=hidden%26net&password="),
+        Ok(StoredNetwork::new("hidden&net", "", portal::PRIORITY).unwrap())
+    );
+    assert_eq!(
+        credentials(b"ssid=&other=&password=secret123"),
+        Err("Choose a network")
+    );
+    assert_eq!(
+        credentials(b"ssid=home&password=short"),
+        Err("Password too short")
+    );
+    let long_ssid = format!("ssid={}&password=", "s".repeat(33));
+    assert_eq!(credentials(long_ssid.as_bytes()), Err("SSID too long"));
+    let long_password = format!("ssid=home&password={}", "p".repeat(65));
+    assert_eq!(
+        credentials(long_password.as_bytes()),
+        Err("Password too long")
+    );
+    assert_eq!(
+        credentials(b"ssid=home%zz&password=secret123"),
+        Err("Invalid form")
+    );
+    assert_eq!(
+        credentials(b"ssid=home&password=%C3secret"),
+        Err("Invalid form")
+    );
+}
+
+#[test]
+fn portal_page_lists_networks() {
+    let access_points = [
+        access_point("home", -40, false),
+        access_point("<script>", -70, true),
+    ];
+    let request = http::parse(b"GET / HTTP/1.1\r\n\r\n").unwrap().unwrap();
+    let mut buf = [0u8; 4096];
+    let (len, saved) = respond(&request, &access_points, &mut buf);
+    let response = std::str::from_utf8(&buf[..len]).unwrap();
+
+    assert_eq!(saved, None);
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    assert!(head.contains(&format!("Content-Length: {}\r\n", body.len())));
+    assert!(body.contains("value=\"home\" checked> home <small>-40 dBm &#128274;"));
+    assert!(body.contains("value=\"&lt;script&gt;\"> &lt;script&gt; <small>-70 dBm</small>"));
+    assert!(!body.contains("<script>"));
+}
+
+#[test]
+fn portal_saves_network() {
+    let request =
+        b"POST /save HTTP/1.1\r\nContent-Length: 34\r\n\r\nssid=home&other=&password=12345678";
+    let request = http::parse(request).unwrap().unwrap();
+    let mut buf = [0u8; 4096];
+    let (len, saved) = respond(&request, &[], &mut buf);
+
+    assert_eq!(
+        saved,
+        StoredNetwork::new("home", "12345678", portal::PRIORITY)
+    );
+    let response = std::str::from_utf8(&buf[..len]).unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(response.contains("Saved <b>home</b>"));
+
+    let request = http::parse(b"POST /save HTTP/1.1\r\nContent-Length: 5\r\n\r\nssid=")
+        .unwrap()
+        .unwrap();
+    let (len, saved) = respond(&request, &[], &mut buf);
+    assert_eq!(saved, None);
+    let response = std::str::from_utf8(&buf[..len]).unwrap();
+    assert!(response.starts_with("HTTP/1.1 400 Bad Request\r\n"));
+    assert!(response.contains("<b>Choose a network</b>"));
+}
+
+#[test]
+fn other_paths_redirect_to_portal() {
+    let request = http::parse(b"GET /hotspot-detect.html HTTP/1.1\r\n\r\n")
+        .unwrap()
+        .unwrap();
+    let mut buf = [0u8; 256];
+    let (len, _) = respond(&request, &[], &mut buf);
+    assert_eq!(
+        std::str::from_utf8(&buf[..len]).unwrap(),
+        format!(
+            "HTTP/1.1 302 Found\r\nLocation: {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            URL
+        )
+    );
+}
+
+#[test]
+fn response_too_large_for_buffer() {
+    let access_points = [access_point("home", -40, false)];
+    let request = http::parse(b"GET / HTTP/1.1\r\n\r\n").unwrap().unwrap();
+    let mut buf = [0u8; 512];
+    let (len, _) = respond(&request, &access_points, &mut buf);
+    assert!(std::str::from_utf8(&buf[..len])
+        .unwrap()
+        .starts_with("HTTP/1.1 500 Internal Server Error\r\n"));
+}
+
+#[test]
+fn dns_answers_with_portal_address() {
+    let query = dns_query(0xbeef, "connectivitycheck.gstatic.com", 1);
+    let mut buf = [0u8; 512];
+    let len = dns::reply(&query, AP_IP, &mut buf).unwrap();
+    let reply = &buf[..len];
+
+    // Same id, response with authoritative answer and recursion desired
+    assert_eq!(&reply[..4], &[0xbe, 0xef, 0x85, 0x00]);
+    assert_eq!(&reply[4..12], &[0, 1, 0, 1, 0, 0, 0, 0]);
+    assert_eq!(&reply[12..query.len()], &query[12..]);
+    assert_eq!(
+        &reply[query.len()..],
+        &[0xc0, 12, 0, 1, 0, 1, 0, 0, 0, 10, 0, 4, 192, 168, 4, 1]
+    );
+}
+
+#[test]
+fn dns_other_types_have_no_answer() {
+    let query = dns_query(7, "example.com", 28);
+    let mut buf = [0u8; 512];
+    let len = dns::reply(&query, AP_IP, &mut buf).unwrap();
+    assert_eq!(len, query.len());
+    assert_eq!(&buf[2..8], &[0x85, 0x00, 0, 1, 0, 0]);
+
+    // Inverse query, not implemented
+    let mut query = dns_query(7, "example.com", 1);
+    query[2] |= 0x08;
+    let len = dns::reply(&query, AP_IP, &mut buf).unwrap();
+    assert_eq!(&buf[2..8], &[0x8d, 0x04, 0, 1, 0, 0]);
+    assert_eq!(len, query.len());
+}
+
+#[test]
+fn dns_rejects_malformed_queries() {
+    let mut buf = [0u8; 512];
+    let query = dns_query(1, "example.com", 1);
+    assert_eq!(
+        dns::reply(&query[..10], AP_IP, &mut buf),
+        Err(DnsError::Truncated)
+    );
+    assert_eq!(
+        dns::reply(&query[..query.len() - 1], AP_IP, &mut buf),
+        Err(DnsError::Truncated)
+    );
+
+    let mut response = query.clone();
+    response[2] |= 0x80;
+    assert_eq!(
+        dns::reply(&response, AP_IP, &mut buf),
+        Err(DnsError::NotAQuery)
+    );
+
+    let mut compressed = query.clone();
+    compressed[12] = 0xc0;
+    assert_eq!(
+        dns::reply(&compressed, AP_IP, &mut buf),
+        Err(DnsError::InvalidName)
+    );
+
+    assert_eq!(
+        dns::reply(&query, AP_IP, &mut buf[..20]),
+        Err(DnsError::BufferTooSmall)
+    );
+}
+
+#[test]
+fn dhcp_discover_request_release() {
+    let mut server = dhcp::Server::<2>::new(AP_IP).with_captive_portal(URL);
+    let mut buf = [0u8; 576];
+
+    let len = server
+        .handle(&dhcp_message(MessageType::Discover, &[]), &mut buf)
+        .unwrap();
+    let offer = &buf[..len];
+    assert_eq!(len, dhcp::MIN_MESSAGE_SIZE);
+    assert_eq!(&offer[..4], &[2, 1, 6, 0]);
+    assert_eq!(&offer[4..8], &0x1234_5678u32.to_be_bytes());
+    // Broadcast flag kept, offered address and server address
+    assert_eq!(offer[10], 0x80);
+    assert_eq!(&offer[16..20], &[192, 168, 4, 2]);
+    assert_eq!(&offer[20..24], &[192, 168, 4, 1]);
+    assert_eq!(&offer[28..34], &MAC);
+    assert_eq!(
+        dhcp_options(offer),
+        [
+            (53, vec![2]),
+            (54, vec![192, 168, 4, 1]),
+            (51, 3600u32.to_be_bytes().to_vec()),
+            (1, vec![255, 255, 255, 0]),
+            (3, vec![192, 168, 4, 1]),
+            (6, vec![192, 168, 4, 1]),
+            (114, URL.as_bytes().to_vec()),
+        ]
+    );
+
+    let request = dhcp_message(
+        MessageType::Request,
+        &[&[50, 4, 192, 168, 4, 2], &[54, 4, 192, 168, 4, 1]],
+    );
+    let len = server.handle(&request, &mut buf).unwrap();
+    assert_eq!(dhcp_options(&buf[..len])[0], (53, vec![5]));
+    assert_eq!(&buf[16..20], &[192, 168, 4, 2]);
+    assert_eq!(server.lease(&MAC), Some(Ipv4Addr::new(192, 168, 4, 2)));
+
+    assert_eq!(
+        server.handle(&dhcp_message(MessageType::Release, &[]), &mut buf),
+        None
+    );
+    assert_eq!(server.lease(&MAC), None);
+}
+
+#[test]
+fn dhcp_naks_unknown_address() {
+    let mut server = dhcp::Server::<2>::new(AP_IP);
+    let mut buf = [0u8; 576];
+    let request = dhcp_message(MessageType::Request, &[&[50, 4, 10, 0, 0, 7]]);
+    let len = server.handle(&request, &mut buf).unwrap();
+    assert_eq!(
+        dhcp_options(&buf[..len]),
+        [(53, vec![6]), (54, vec![192, 168, 4, 1])]
+    );
+    assert_eq!(&buf[16..20], &[0, 0, 0, 0]);
+
+    // Request to another server is ignored
+    let request = dhcp_message(MessageType::Request, &[&[54, 4, 10, 0, 0, 1]]);
+    assert_eq!(server.handle(&request, &mut buf), None);
+}
+
+#[test]
+fn dhcp_reuses_leases_when_full() {
+    let mut server = dhcp::Server::<2>::new(AP_IP);
+    let mut buf = [0u8; 576];
+    let mut offered = Vec::new();
+    for last in 0..3u8 {
+        let mut discover = dhcp_message(MessageType::Discover, &[]);
+        discover[33] = last;
+        server.handle(&discover, &mut buf).unwrap();
+        offered.push(buf[19]);
+    }
+    assert_eq!(offered, [2, 3, 2]);
+
+    let mut mac = MAC;
+    mac[5] = 1;
+    assert_eq!(server.lease(&mac), Some(Ipv4Addr::new(192, 168, 4, 3)));
+    mac[5] = 0;
+    assert_eq!(server.lease(&mac), None);
+}
+
+#[test]
+fn dhcp_ignores_other_messages() {
+    let mut server = dhcp::Server::<2>::new(AP_IP);
+    let mut buf = [0u8; 576];
+    let mut reply = dhcp_message(MessageType::Discover, &[]);
+    reply[0] = 2;
+    assert_eq!(server.handle(&reply, &mut buf), None);
+    assert_eq!(server.handle(&[1, 1, 6, 0], &mut buf), None);
+    assert_eq!(
+        dhcp::parse(&dhcp_message(MessageType::Inform, &[]))
+            .unwrap()
+            .message_type,
+        MessageType::Inform
+    );
+}
@@ -0,0 +1,143 @@
+//! Checked against published tables, within their rounding.
+
+mod common;
+
+use esp_buddy::{
+    psychrometrics::{absolute_humidity_dg, dew_point_dc, heat_index_dc, Climate, Comfort},
+    screens::comfort,
+    sensors::hts221::Reading,
+};
+
+use common::{assert_golden, render};
+
+fn fahrenheit_to_tenths(fahrenheit: i32) -> i32 {
+    ((fahrenheit - 32) * 100 + 9) / 18
+}
+
+#[test]
+fn dew_point() {
+    // Temperature and relative humidity, dew point
+    for (temperature_dc, humidity_dpct, expected) in [
+        (200, 500, 93),
+        (250, 500, 139),
+        (250, 600, 167),
+        (200, 800, 164),
+        (300, 700, 239),
+        (300, 800, 262),
+        (350, 200, 87),
+        (100, 400, -30),
+        (-100, 500, -185),
+        (0, 1000, 0),
+        (226, 1000, 226),
+    ] {
+        let dew_point = dew_point_dc(temperature_dc, humidity_dpct).unwrap();
+        assert!(
+            (dew_point - expected).abs() <= 1,
+            "{temperature_dc} {humidity_dpct}: {dew_point} instead of {expected}"
+        );
+    }
+    assert_eq!(dew_point_dc(200, 0), None);
+}
+
+#[test]
+fn saturated_vapour_density() {
+    // Temperature, grams per cubic meter of saturated air
+    for (temperature_dc, expected) in [
+        (-100, 2.36),
+        (0, 4.85),
+        (100, 9.40),
+        (200, 17.30),
+        (250, 23.05),
+        (300, 30.38),
+        (400, 51.19),
+    ] {
+        let density = absolute_humidity_dg(temperature_dc, 1000) as f32 / 10.0;
+        assert!(
+            (density - expected).abs() <= expected * 0.01 + 0.05,
+            "{temperature_dc}: {density} instead of {expected}"
+        );
+    }
+    assert_eq!(absolute_humidity_dg(200, 500), 86);
+    assert_eq!(absolute_humidity_dg(200, 0), 0);
+}
+
+#[test]
+fn heat_index() {
+    // NWS heat index chart, degrees Fahrenheit
+    for (temperature, humidity, expected) in [
+        (80, 40, 80),
+        (84, 85, 96),
+        (86, 90, 105),
+        (90, 50, 95),
+        (96, 65, 121),
+        (100, 40, 109),
+        (104, 55, 137),
+        (110, 10, 104),
+    ] {
+        let heat_index = heat_index_dc(fahrenheit_to_tenths(temperature), humidity * 10);
+        let expected = fahrenheit_to_tenths(expected);
+        // A degree Fahrenheit of the chart, plus rounding to tenths
+        assert!(
+            (heat_index - expected).abs() <= 6,
+            "{temperature} F {humidity}%: {heat_index} instead of {expected}"
+        );
+    }
+    // Only defined from 80 F
+    assert_eq!(heat_index_dc(215, 455), 215);
+    assert_eq!(heat_index_dc(-123, 1000), -123);
+    assert_eq!(heat_index_dc(266, 900), 266);
+}
+
+#[test]
+fn comfort_zone() {
+    for (temperature_dc, humidity_dpct, expected) in [
+        (215, 455, Comfort::Comfortable),
+        (200, 300, Comfort::Comfortable),
+        (260, 600, Comfort::Humid),
+        (199, 455, Comfort::Cold),
+        (261, 455, Comfort::Hot),
+        (215, 601, Comfort::Humid),
+        (215, 299, Comfort::Dry),
+        (215, 0, Comfort::Dry),
+        // Dew point of 16.6 C at 58 %
+        (255, 580, Comfort::Humid),
+        // Cold wins over humid
+        (150, 900, Comfort::Cold),
+    ] {
+        assert_eq!(
+            Comfort::classify(temperature_dc, humidity_dpct),
+            expected,
+            "{temperature_dc} {humidity_dpct}"
+        );
+    }
+}
+
+#[test]
+fn climate_of_a_reading() {
+    let climate = Climate::from(Reading {
+        temperature_dc: 215,
+        humidity_dpct: 455,
+    });
+    assert_eq!(climate, Climate::new(215, 455));
+    assert_eq!(climate.temperature_dc, 215);
+    assert_eq!(climate.humidity_dpct, 455);
+    assert_eq!(climate.dew_point_dc, Some(92));
+    assert_eq!(climate.absolute_humidity_dg, 86);
+    assert_eq!(climate.comfort, Comfort::Comfortable);
+}
+
+#[test]
+fn screen() {
+    let frame = render(|target| comfort::draw(target, &Climate::new(215, 455)));
+    assert_golden("comfort", &frame);
+
+    // Widest values
+    let frame = render(|target| comfort::draw(target, &Climate::new(-123, 1000)));
+    assert_golden("comfort_cold", &frame);
+
+    let frame = render(|target| comfort::draw(target, &Climate::new(325, 0)));
+    assert_golden("comfort_dry", &frame);
+
+    let frame = render(|target| comfort::draw_error(target, "no sensor"));
+    assert_golden("comfort_error", &frame);
+}
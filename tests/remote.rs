@@ -0,0 +1,163 @@
+mod common;
+
+use embedded_graphics::{pixelcolor::BinaryColor, prelude::*};
+use esp_buddy::{
+    framebuffer::Framebuffer,
+    remote::{
+        protocol::{self, Error, Frame, Packet, FRAME_BYTES, LENGTH_PREFIX_LEN, MAX_PACKET_LEN},
+        Panel, StreamReader,
+    },
+};
+
+use common::{assert_golden, render};
+
+/// Checkerboard of 8x8 squares.
+fn checkerboard() -> Frame {
+    core::array::from_fn(|index| {
+        if (index % 16 + index / 128) % 2 == 0 {
+            0xff
+        } else {
+            0
+        }
+    })
+}
+
+fn packet(packet: Packet) -> Vec<u8> {
+    let mut buf = [0u8; MAX_PACKET_LEN];
+    let len = packet.encode(&mut buf).unwrap();
+    buf[..len].to_vec()
+}
+
+fn stream(packets: &[Packet]) -> Vec<u8> {
+    let mut stream = Vec::new();
+    for packet in packets {
+        let mut buf = [0u8; LENGTH_PREFIX_LEN + MAX_PACKET_LEN];
+        let len = protocol::encode_stream(packet, &mut buf).unwrap();
+        stream.extend_from_slice(&buf[..len]);
+    }
+    stream
+}
+
+/// Hands `data` to `reader` in chunks as large as its spare space, like
+/// reads from a socket.
+fn feed(reader: &mut StreamReader, panel: &mut Panel, mut data: &[u8]) -> Result<bool, Error> {
+    let mut shown = false;
+    while !data.is_empty() {
+        let spare = reader.spare();
+        let len = spare.len().min(data.len());
+        spare[..len].copy_from_slice(&data[..len]);
+        shown |= reader.advance(len, panel)?;
+        data = &data[len..];
+    }
+    Ok(shown)
+}
+
+#[test]
+fn raw_and_compressed_frames() {
+    let frame = checkerboard();
+    let mut panel = Panel::new();
+    panel.handle(&packet(Packet::Raw(&frame))).unwrap();
+    assert_eq!(panel.framebuffer(), &Framebuffer::from_bytes(frame));
+    assert_eq!(
+        panel.framebuffer().pixel(Point::new(0, 0)),
+        Some(BinaryColor::On)
+    );
+    assert_eq!(
+        panel.framebuffer().pixel(Point::new(8, 0)),
+        Some(BinaryColor::Off)
+    );
+    assert_eq!(
+        panel.framebuffer().pixel(Point::new(8, 8)),
+        Some(BinaryColor::On)
+    );
+
+    let mut buf = [0u8; MAX_PACKET_LEN];
+    let len = protocol::encode_frame(&[0xf0; FRAME_BYTES], &mut buf).unwrap();
+    assert!(len < 16);
+    panel.handle(&buf[..len]).unwrap();
+    assert_eq!(
+        panel.framebuffer(),
+        &Framebuffer::from_bytes([0xf0; FRAME_BYTES])
+    );
+}
+
+#[test]
+fn invalid_packets_keep_the_panel() {
+    let frame = checkerboard();
+    let mut panel = Panel::new();
+    panel.handle(&packet(Packet::Raw(&frame))).unwrap();
+
+    assert_eq!(
+        panel.handle(&packet(Packet::Rle(&[0x81, 0]))),
+        Err(Error::InvalidRle)
+    );
+    assert_eq!(
+        panel.handle(b"EB\x07\x01"),
+        Err(Error::UnsupportedVersion(7))
+    );
+    assert_eq!(panel.framebuffer(), &Framebuffer::from_bytes(frame));
+}
+
+#[test]
+fn text() {
+    let mut panel = Panel::new();
+    panel
+        .handle(&packet(Packet::Raw(&[0xff; FRAME_BYTES])))
+        .unwrap();
+    panel
+        .handle(&packet(Packet::Text("Build #42\nmain: passed\n3m 12s")))
+        .unwrap();
+    assert_golden("remote_text", panel.framebuffer());
+}
+
+#[test]
+fn panel_draws_its_frame() {
+    let mut panel = Panel::new();
+    panel.handle(&packet(Packet::Raw(&checkerboard()))).unwrap();
+    let frame = render(|target| panel.draw(target));
+    assert_eq!(&frame, panel.framebuffer());
+}
+
+#[test]
+fn stream_reassembly() {
+    let frame = checkerboard();
+    let data = stream(&[Packet::Text("one"), Packet::Raw(&frame)]);
+
+    // Byte by byte, the frame is only shown once complete
+    let mut panel = Panel::new();
+    let mut reader = StreamReader::new();
+    let shown: Vec<usize> = (0..data.len())
+        .filter(|&index| {
+            reader.spare()[0] = data[index];
+            reader.advance(1, &mut panel).unwrap()
+        })
+        .collect();
+    let first_len = data.len() - LENGTH_PREFIX_LEN - protocol::HEADER_LEN - FRAME_BYTES;
+    assert_eq!(shown, [first_len - 1, data.len() - 1]);
+    assert_eq!(panel.framebuffer(), &Framebuffer::from_bytes(frame));
+
+    // As fast as the buffer takes it, then garbage
+    let mut panel = Panel::new();
+    let mut reader = StreamReader::new();
+    assert_eq!(feed(&mut reader, &mut panel, &data), Ok(true));
+    assert_eq!(panel.framebuffer(), &Framebuffer::from_bytes(frame));
+    assert_eq!(
+        feed(&mut reader, &mut panel, &[0xff, 0xff]),
+        Err(Error::InvalidLength)
+    );
+
+    reader.reset();
+    let data = stream(&[Packet::Text("two")]);
+    assert_eq!(feed(&mut reader, &mut panel, &data), Ok(true));
+}
+
+#[test]
+fn invalid_stream_packets_are_skipped() {
+    let mut panel = Panel::new();
+    let mut reader = StreamReader::new();
+    let mut data = vec![0, 4];
+    data.extend_from_slice(b"XX\x01\x03");
+    data.extend_from_slice(&stream(&[Packet::Text("ok")]));
+    assert_eq!(feed(&mut reader, &mut panel, &data), Ok(true));
+    assert_ne!(panel.framebuffer(), &Framebuffer::new());
+}
@@ -0,0 +1,233 @@
+mod common;
+
+use esp_buddy::{
+    scanner::{self, Diagnostics, View, CHECK_INTERVAL_MS, FIRST_ADDRESS, HOLD_MS, LAST_ADDRESS},
+    screens::{self, ButtonState},
+    sensors::hts221,
+};
+
+use common::{
+    assert_golden,
+    i2c::{MockBus, MockDevice},
+    render,
+};
+
+/// The display, the HTS221, an unknown chip and devices at both reserved
+/// ends of the address space.
+fn bus() -> MockBus {
+    let mut hts221 = MockDevice::new(Some(0x80));
+    hts221.set(hts221::WHO_AM_I, &[hts221::DEVICE_ID]);
+    hts221.set(hts221::CTRL_REG1, &[0x85]);
+    MockBus::default()
+        .with_device(0x3c, MockDevice::new(None))
+        .with_device(hts221::ADDRESS, hts221)
+        .with_device(0x2a, MockDevice::new(None))
+        .with_device(FIRST_ADDRESS - 1, MockDevice::new(None))
+        .with_device(LAST_ADDRESS + 1, MockDevice::new(None))
+}
+
+const RELEASED: ButtonState = ButtonState {
+    left: false,
+    right: false,
+};
+const LEFT: ButtonState = ButtonState {
+    left: true,
+    right: false,
+};
+const RIGHT: ButtonState = ButtonState {
+    left: false,
+    right: true,
+};
+
+/// Presses and releases `buttons` at `now_ms`.
+fn click(diagnostics: &mut Diagnostics, bus: &mut MockBus, buttons: ButtonState, now_ms: u64) {
+    diagnostics.update(bus, buttons, now_ms);
+    diagnostics.update(bus, RELEASED, now_ms + 50);
+}
+
+/// Holds `buttons` from `now_ms` long enough to count as held once.
+fn hold(diagnostics: &mut Diagnostics, bus: &mut MockBus, buttons: ButtonState, now_ms: u64) {
+    diagnostics.update(bus, buttons, now_ms);
+    diagnostics.update(bus, buttons, now_ms + HOLD_MS);
+    diagnostics.update(bus, RELEASED, now_ms + HOLD_MS + 50);
+}
+
+#[test]
+fn scan_finds_devices_in_range() {
+    let mut bus = bus();
+    assert_eq!(scanner::scan(&mut bus), [0x2a, 0x3c, 0x5f]);
+    // One write without data per address
+    let writes = bus.take_writes();
+    assert_eq!(writes.len(), 3);
+    assert!(writes.iter().all(|(_, data)| data.is_empty()));
+
+    assert!(scanner::scan(&mut MockBus::default()).is_empty());
+}
+
+#[test]
+fn known_names() {
+    assert_eq!(scanner::name(0x3c), Some("SSD1306"));
+    assert_eq!(scanner::name(0x5f), Some("HTS221"));
+    assert_eq!(scanner::name(0x76), Some("BME280"));
+    assert_eq!(scanner::name(0x5c), Some("LPS22/BH1750"));
+    assert_eq!(scanner::name(0x2a), None);
+}
+
+#[test]
+fn error_rates() {
+    let mut bus = bus();
+    let mut diagnostics = Diagnostics::new();
+    diagnostics.scan(&mut bus);
+    assert_eq!(diagnostics.scans(), 1);
+    let names: Vec<_> = diagnostics
+        .devices()
+        .iter()
+        .map(|device| (device.address, device.name))
+        .collect();
+    assert_eq!(
+        names,
+        [
+            (0x2a, None),
+            (0x3c, Some("SSD1306")),
+            (0x5f, Some("HTS221"))
+        ]
+    );
+
+    // Checked every interval while the list shows
+    for step in 0..9 {
+        diagnostics.update(&mut bus, RELEASED, step * CHECK_INTERVAL_MS);
+    }
+    let unknown = diagnostics.devices()[0];
+    assert_eq!((unknown.transfers, unknown.errors), (10, 0));
+
+    // Gone for half the checks
+    let device = bus.devices.remove(&0x2a).unwrap();
+    for step in 9..19 {
+        diagnostics.update(&mut bus, RELEASED, step * CHECK_INTERVAL_MS);
+    }
+    let unknown = diagnostics.devices()[0];
+    assert_eq!((unknown.transfers, unknown.errors), (20, 10));
+    assert_eq!(unknown.error_rate_dpct(), 500);
+    assert_eq!(diagnostics.devices()[1].error_rate_dpct(), 0);
+
+    // A scan keeps the device and its counts, and adds new ones
+    bus.devices.insert(0x2a, device);
+    bus.devices.insert(0x10, MockDevice::new(None));
+    diagnostics.scan(&mut bus);
+    let counts: Vec<_> = diagnostics
+        .devices()
+        .iter()
+        .map(|device| (device.address, device.transfers, device.errors))
+        .collect();
+    assert_eq!(
+        counts,
+        [(0x10, 1, 0), (0x2a, 21, 10), (0x3c, 21, 0), (0x5f, 21, 0)]
+    );
+}
+
+#[test]
+fn register_dump_and_write() {
+    let mut bus = bus();
+    let mut diagnostics = Diagnostics::new();
+    diagnostics.scan(&mut bus);
+
+    // Third device, the HTS221
+    click(&mut diagnostics, &mut bus, LEFT, 1000);
+    click(&mut diagnostics, &mut bus, LEFT, 1100);
+    assert_eq!(diagnostics.selected().unwrap().address, 0x5f);
+    click(&mut diagnostics, &mut bus, RIGHT, 1200);
+    assert_eq!(diagnostics.view(), View::Registers);
+    assert_eq!(diagnostics.value(hts221::WHO_AM_I), Some(hts221::DEVICE_ID));
+    assert_eq!(diagnostics.value(hts221::CTRL_REG1), Some(0x85));
+
+    // Next register, then a row at a time while held
+    click(&mut diagnostics, &mut bus, LEFT, 2000);
+    assert_eq!(diagnostics.register(), 0x01);
+    diagnostics.update(&mut bus, LEFT, 3000);
+    diagnostics.update(&mut bus, LEFT, 3000 + HOLD_MS);
+    assert_eq!(diagnostics.register(), 0x08);
+    diagnostics.update(&mut bus, LEFT, 3000 + HOLD_MS + 200);
+    diagnostics.update(&mut bus, LEFT, 3000 + HOLD_MS + 400);
+    assert_eq!(diagnostics.register(), 0x18);
+    // No click when released after holding
+    diagnostics.update(&mut bus, RELEASED, 5000);
+    assert_eq!(diagnostics.register(), 0x18);
+    for step in 0..8 {
+        click(&mut diagnostics, &mut bus, LEFT, 6000 + step * 100);
+    }
+    assert_eq!(diagnostics.register(), hts221::CTRL_REG1);
+
+    // Edit from the current value
+    click(&mut diagnostics, &mut bus, RIGHT, 7000);
+    assert_eq!(diagnostics.view(), View::Edit(0x85));
+    click(&mut diagnostics, &mut bus, LEFT, 7100);
+    hold(&mut diagnostics, &mut bus, LEFT, 7200);
+    assert_eq!(diagnostics.view(), View::Edit(0x96));
+    bus.take_writes();
+    click(&mut diagnostics, &mut bus, RIGHT, 9000);
+    assert_eq!(diagnostics.view(), View::Registers);
+    assert_eq!(
+        bus.take_writes(),
+        [
+            (0x5f, vec![hts221::CTRL_REG1, 0x96]),
+            (0x5f, vec![hts221::CTRL_REG1]),
+        ]
+    );
+    assert_eq!(diagnostics.value(hts221::CTRL_REG1), Some(0x96));
+
+    // Holding right cancels the edit, then goes back to the list
+    click(&mut diagnostics, &mut bus, RIGHT, 10000);
+    hold(&mut diagnostics, &mut bus, RIGHT, 10100);
+    assert_eq!(diagnostics.view(), View::Registers);
+    assert!(bus.take_writes().is_empty());
+    hold(&mut diagnostics, &mut bus, RIGHT, 12000);
+    assert_eq!(diagnostics.view(), View::List);
+    assert_eq!(diagnostics.scans(), 1);
+
+    // and scans again
+    hold(&mut diagnostics, &mut bus, RIGHT, 14000);
+    assert_eq!(diagnostics.scans(), 2);
+    assert_eq!(diagnostics.selected().unwrap().address, 0x5f);
+}
+
+#[test]
+fn unreadable_registers() {
+    // Command-based, reads after a register address are not acknowledged
+    let mut bus = MockBus::default().with_device(0x44, MockDevice::commands());
+    let mut diagnostics = Diagnostics::new();
+    diagnostics.scan(&mut bus);
+    click(&mut diagnostics, &mut bus, RIGHT, 1000);
+    assert_eq!(diagnostics.value(0x00), None);
+    let device = diagnostics.devices()[0];
+    assert_eq!((device.transfers, device.errors), (258, 256));
+}
+
+#[test]
+fn diagnostics_screens() {
+    let mut bus = bus();
+    let mut diagnostics = Diagnostics::new();
+    let frame = render(|target| screens::scanner::draw(target, &diagnostics));
+    assert_golden("scanner_empty", &frame);
+
+    diagnostics.scan(&mut bus);
+    bus.devices.remove(&0x2a);
+    for step in 0..4 {
+        diagnostics.update(&mut bus, RELEASED, step * CHECK_INTERVAL_MS);
+    }
+    click(&mut diagnostics, &mut bus, LEFT, 1000);
+    let frame = render(|target| screens::scanner::draw(target, &diagnostics));
+    assert_golden("scanner_list", &frame);
+
+    click(&mut diagnostics, &mut bus, LEFT, 1100);
+    click(&mut diagnostics, &mut bus, RIGHT, 1200);
+    for step in 0..0x0f {
+        click(&mut diagnostics, &mut bus, LEFT, 2000 + step * 100);
+    }
+    let frame = render(|target| screens::scanner::draw(target, &diagnostics));
+    assert_golden("scanner_registers", &frame);
+
+    click(&mut diagnostics, &mut bus, RIGHT, 4000);
+    click(&mut diagnostics, &mut bus, LEFT, 4100);
+    let frame = render(|target| screens::scanner::draw(target, &diagnostics));
+    assert_golden("scanner_edit", &frame);
+}
@@ -0,0 +1,236 @@
+mod common;
+
+use core::net::Ipv4Addr;
+
+use common::{assert_golden, render, render_scene};
+use esp_buddy::{
+    alarm::{Alarm, Weekdays},
+    framebuffer::Framebuffer,
+    portal,
+    screens::{
+        alarm::{self, AlarmEditor},
+        clock::{
+            self, Analog, Binary, ClockTime, DateWeekday, DualZone, Face, FaceCycle, Faces,
+            LargeDigits,
+        },
+        gpio, temperature, wifi, Animation, ButtonState, Buttons, Car, Scene, Timers,
+    },
+    time::TimeZone,
+    wifi::{FailReason, State},
+};
+
+#[test]
+fn gpio_grid() {
+    let states = [
+        (0, true),
+        (2, false),
+        (4, true),
+        (5, false),
+        (12, true),
+        (15, false),
+        (16, false),
+        (19, true),
+        (27, true),
+        (28, false),
+    ];
+    let frame = render(|target| gpio::draw(target, &states));
+    assert_golden("gpio", &frame);
+}
+
+#[test]
+fn temperature() {
+    let frame = render(|target| temperature::draw(target, 23, 41));
+    assert_golden("temperature", &frame);
+}
+
+#[test]
+fn temperature_below_zero() {
+    let frame = render(|target| temperature::draw(target, -12, 100));
+    assert_golden("temperature_below_zero", &frame);
+}
+
+#[test]
+fn clock_face() {
+    let frame = render(|target| clock::draw_time(target, 23, 59, 7));
+    assert_golden("clock", &frame);
+}
+
+/// 2024-10-18 12:05:09 UTC, a Friday.
+const TIMESTAMP: i64 = 1_729_253_109;
+
+fn render_face(face: &impl Face) -> Framebuffer {
+    let prague = TimeZone::parse("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+    render(|target| face.draw(target, &ClockTime::new(TIMESTAMP, &prague)))
+}
+
+#[test]
+fn clock_face_large_digits() {
+    assert_golden("clock_large_digits", &render_face(&LargeDigits));
+}
+
+#[test]
+fn clock_face_date() {
+    assert_golden("clock_date", &render_face(&DateWeekday));
+}
+
+#[test]
+fn clock_face_analog() {
+    assert_golden("clock_analog", &render_face(&Analog));
+}
+
+#[test]
+fn clock_face_binary() {
+    assert_golden("clock_binary", &render_face(&Binary));
+}
+
+#[test]
+fn clock_face_dual_zone() {
+    assert_golden("clock_dual_zone", &render_face(&DualZone::default()));
+}
+
+#[test]
+fn clock_face_dual_zone_cuts_long_abbreviations() {
+    let other = TimeZone::parse("ABCDEFGHIJKLMN-1").unwrap();
+    assert_golden("clock_dual_zone_long", &render_face(&DualZone::new(other)));
+}
+
+#[test]
+fn clock_faces_cycle_on_left_press() {
+    let press = |left| ButtonState { left, right: false };
+    let mut faces = FaceCycle::new(clock::DefaultFaces::default());
+    assert_eq!(clock::DefaultFaces::COUNT, 5);
+
+    assert!(faces.update(press(true)));
+    assert_eq!(faces.current(), 1);
+    // Holding the button or pressing the right one does not switch
+    assert!(!faces.update(press(true)));
+    assert!(!faces.update(ButtonState {
+        left: false,
+        right: true,
+    }));
+    assert_eq!(faces.current(), 1);
+
+    for _ in 0..4 {
+        faces.update(press(false));
+        faces.update(press(true));
+    }
+    assert_eq!(faces.current(), 0);
+
+    let faces = FaceCycle::new((LargeDigits, Analog)).with_current(3);
+    assert_eq!(faces.current(), 1);
+}
+
+#[test]
+fn alarm_ringing() {
+    let frame = render(|target| alarm::draw_ringing(target, &Alarm::once(7, 30)));
+    assert_golden("alarm_ringing", &frame);
+}
+
+#[test]
+fn alarm_editor_days() {
+    let mut editor = AlarmEditor::new(Alarm::weekly(6, 45, Weekdays::WORKDAYS));
+    editor.next_field();
+    editor.next_field();
+    assert_golden("alarm_editor", &render(|target| editor.draw(target)));
+}
+
+#[test]
+fn durations() {
+    render(|target| alarm::draw_duration(target, "Stopwatch", 3_599_999));
+    render(|target| alarm::draw_duration(target, "Stopwatch", 99 * 3_600_000));
+}
+
+#[test]
+fn timers_pomodoro_running() {
+    let press = |right| ButtonState { left: false, right };
+    let mut timers = Timers::new();
+    timers.update(press(true));
+    for _ in 0..=1200 {
+        timers.update(press(false));
+    }
+    assert_golden("timers_pomodoro", &render_scene(&timers));
+
+    // Longest label
+    let mut timers = Timers::new();
+    for _ in 0..100 {
+        timers.update(ButtonState::default());
+    }
+    render_scene(&timers);
+}
+
+#[test]
+fn wifi_connecting() {
+    let frame = render(wifi::draw_connecting);
+    assert_golden("wifi_connecting", &frame);
+}
+
+#[test]
+fn wifi_connected() {
+    let frame = render(|target| wifi::draw_connected(target, Ipv4Addr::new(192, 168, 1, 42)));
+    assert_golden("wifi_connected", &frame);
+}
+
+#[test]
+fn wifi_failed() {
+    let state = State::Failed {
+        reason: FailReason::NotFound,
+    };
+    assert_golden(
+        "wifi_failed",
+        &render(|target| wifi::draw_state(target, &state)),
+    );
+}
+
+#[test]
+fn wifi_portal() {
+    let frame = render(|target| wifi::draw_portal(target, portal::AP_SSID, portal::URL));
+    assert_golden("wifi_portal", &frame);
+}
+
+#[test]
+fn wifi_states() {
+    for state in [
+        State::Idle,
+        State::Scanning,
+        State::Failed {
+            reason: FailReason::Timeout,
+        },
+        State::Failed {
+            reason: FailReason::Disconnected,
+        },
+        State::Failed {
+            reason: FailReason::Controller,
+        },
+    ] {
+        render(|target| wifi::draw_state(target, &state));
+    }
+}
+
+#[test]
+fn wifi_connected_longest_address() {
+    render(|target| wifi::draw_connected(target, Ipv4Addr::new(255, 255, 255, 255)));
+}
+
+#[test]
+fn buttons_pressed() {
+    let mut scene = Buttons::new();
+    scene.update(ButtonState {
+        left: true,
+        right: true,
+    });
+    assert_golden("buttons_pressed", &render_scene(&scene));
+}
+
+#[test]
+fn animation_first_frame() {
+    assert_golden("animation", &render_scene(&Animation::new()));
+}
+
+#[test]
+fn car_in_the_middle() {
+    let mut car = Car::new();
+    for _ in 0..100 {
+        car.update(ButtonState::default());
+    }
+    assert_golden("car", &render_scene(&car));
+}
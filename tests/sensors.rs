@@ -0,0 +1,601 @@
+mod common;
+
+use esp_buddy::{
+    screens::{sensors::Pages, ButtonState},
+    sensors::{
+        bh1750,
+        bme280::{self, Coefficients},
+        hts221::{
+            Averaging, Calibration, DataRate, Error, Reading, ADDRESS, AV_CONF, CALIBRATION,
+            CTRL_REG1, CTRL_REG2, DEVICE_ID, STATUS, WHO_AM_I,
+        },
+        lps22,
+        registry::{self, Device},
+        sht3x::{self, crc8},
+        Bh1750, Bme280, Correction, Hts221Reader, Lps22, Quantity, Registry, Sensor, Sht3x,
+    },
+};
+
+use common::{
+    assert_golden,
+    i2c::{BusError, MockBus, MockDevice},
+    render,
+};
+
+const HUMIDITY_OUT: u8 = 0x28;
+const TEMPERATURE_OUT: u8 = 0x2a;
+const READY: u8 = 0x03;
+
+/// An HTS221 calibrated at 10.0 and 35.0 C, outputs 0 and 1000, and at 20 and
+/// 80 %, outputs -6000 and 6000: 40 outputs a degree, 200 a percent.
+fn hts221() -> MockDevice {
+    let mut device = MockDevice::new(Some(0x80));
+    device.set(WHO_AM_I, &[DEVICE_ID]);
+    let mut calibration = [0u8; 16];
+    calibration[0] = 40; // H0_rH_x2
+    calibration[1] = 160; // H1_rH_x2
+    calibration[2] = 80; // T0_degC_x8
+    calibration[3] = (280u16 & 0xff) as u8; // T1_degC_x8
+    calibration[5] = ((280u16 >> 8) << 2) as u8; // T1 and T0 bits 8 and 9
+    calibration[6..8].copy_from_slice(&(-6000i16).to_le_bytes()); // H0_T0_OUT
+    calibration[10..12].copy_from_slice(&6000i16.to_le_bytes()); // H1_T0_OUT
+    calibration[12..14].copy_from_slice(&0i16.to_le_bytes()); // T0_OUT
+    calibration[14..16].copy_from_slice(&1000i16.to_le_bytes()); // T1_OUT
+    device.set(CALIBRATION, &calibration);
+    device
+}
+
+fn bus() -> MockBus {
+    MockBus::default().with_device(ADDRESS, hts221())
+}
+
+/// Sets the outputs and marks both as ready.
+fn convert(bus: &mut MockBus, temperature: i16, humidity: i16) {
+    let device = bus.device(ADDRESS);
+    device.set(STATUS, &[READY]);
+    device.set(HUMIDITY_OUT, &humidity.to_le_bytes());
+    device.set(TEMPERATURE_OUT, &temperature.to_le_bytes());
+}
+
+fn reading(temperature_dc: i32, humidity_dpct: i32) -> Reading {
+    Reading {
+        temperature_dc,
+        humidity_dpct,
+    }
+}
+
+#[test]
+fn start_configures_the_chip() {
+    let mut bus = bus();
+    let mut reader = Hts221Reader::new();
+    assert!(!reader.is_started());
+    reader.start(&mut bus).unwrap();
+    assert!(reader.is_started());
+    assert_eq!(
+        bus.take_writes(),
+        [
+            (ADDRESS, vec![WHO_AM_I]),
+            (ADDRESS, vec![CALIBRATION | 0x80]),
+            // 16 and 32 samples
+            (ADDRESS, vec![AV_CONF, 0x1b]),
+            // Powered on, block data update, 1 Hz
+            (ADDRESS, vec![CTRL_REG1, 0x85]),
+            (ADDRESS, vec![CTRL_REG2, 0x00]),
+        ]
+    );
+
+    let mut reader = Hts221Reader::new()
+        .with_data_rate(DataRate::Hz12_5)
+        .with_averaging(Averaging::new(2, 512).unwrap());
+    reader.start(&mut bus).unwrap();
+    let registers = &bus.device(ADDRESS).registers;
+    assert_eq!(registers[AV_CONF as usize], 0x07);
+    assert_eq!(registers[CTRL_REG1 as usize], 0x87);
+}
+
+#[test]
+fn averaging() {
+    let averaging = Averaging::new(256, 4).unwrap();
+    assert_eq!(averaging.temperature_samples(), 256);
+    assert_eq!(averaging.humidity_samples(), 4);
+    assert_eq!(Averaging::default().temperature_samples(), 16);
+    assert_eq!(Averaging::default().humidity_samples(), 32);
+    for (temperature, humidity) in [(1, 4), (512, 4), (3, 4), (2, 2), (2, 1024), (2, 12), (0, 4)] {
+        assert_eq!(Averaging::new(temperature, humidity), None);
+    }
+}
+
+#[test]
+fn conversion_keeps_tenths() {
+    let mut bus = bus();
+    let mut reader = Hts221Reader::new();
+    for (temperature, humidity, expected) in [
+        (460, -900, reading(215, 455)),
+        // 21.55 C and 45.55 %, rounded up
+        (462, -890, reading(216, 456)),
+        (0, -6000, reading(100, 200)),
+        (1000, 6000, reading(350, 800)),
+        // -0.825 C, rounded towards -0.8
+        (-433, 0, reading(-8, 500)),
+        (-1000, 0, reading(-150, 500)),
+        // Saturates at 0 and 100 %
+        (0, 20_000, reading(100, 1000)),
+        (0, -10_000, reading(100, 0)),
+    ] {
+        convert(&mut bus, temperature, humidity);
+        assert_eq!(
+            reader.read(&mut bus),
+            Ok(expected),
+            "{temperature} {humidity}"
+        );
+    }
+}
+
+#[test]
+fn readings_wait_for_a_conversion() {
+    let mut bus = bus();
+    let mut reader = Hts221Reader::new();
+    assert_eq!(reader.read(&mut bus), Err(Error::NotReady));
+    assert_eq!(Error::<BusError>::NotReady.message(), "waiting for sensor");
+
+    // Both outputs have to be updated
+    bus.device(ADDRESS).set(STATUS, &[0x01]);
+    assert_eq!(reader.read(&mut bus), Err(Error::NotReady));
+
+    convert(&mut bus, 460, -900);
+    assert_eq!(reader.read(&mut bus), Ok(reading(215, 455)));
+    // The previous reading until the next conversion
+    bus.device(ADDRESS).set(STATUS, &[0x00]);
+    bus.device(ADDRESS)
+        .set(TEMPERATURE_OUT, &0i16.to_le_bytes());
+    assert_eq!(reader.read(&mut bus), Ok(reading(215, 455)));
+}
+
+#[test]
+fn one_shot() {
+    let mut bus = bus();
+    let mut reader = Hts221Reader::new().with_data_rate(DataRate::OneShot);
+    assert_eq!(reader.read(&mut bus), Err(Error::NotReady));
+    let writes = bus.take_writes();
+    assert_eq!(writes[3], (ADDRESS, vec![CTRL_REG1, 0x84]));
+    assert_eq!(writes.last(), Some(&(ADDRESS, vec![CTRL_REG2, 0x01])));
+
+    // Not started again while converting
+    assert_eq!(reader.read(&mut bus), Err(Error::NotReady));
+    assert_eq!(bus.take_writes(), [(ADDRESS, vec![STATUS | 0x80])]);
+
+    convert(&mut bus, 460, -900);
+    assert_eq!(reader.read(&mut bus), Ok(reading(215, 455)));
+    assert_eq!(
+        bus.take_writes(),
+        [
+            (ADDRESS, vec![STATUS | 0x80]),
+            (ADDRESS, vec![CTRL_REG2, 0x01])
+        ]
+    );
+}
+
+#[test]
+fn user_calibration() {
+    let mut bus = bus();
+    let calibration = Calibration {
+        // The board warms the sensor by 1.5 C
+        temperature: Correction {
+            offset: -15,
+            gain_permille: 1000,
+        },
+        humidity: Correction {
+            offset: 20,
+            gain_permille: 1010,
+        },
+    };
+    let mut reader = Hts221Reader::new().with_calibration(calibration);
+    assert_eq!(reader.calibration(), calibration);
+    convert(&mut bus, 460, -900);
+    // 45.5 % * 1.01 + 2 % = 47.955 %
+    assert_eq!(reader.read(&mut bus), Ok(reading(200, 480)));
+
+    // Corrected humidity still saturates
+    convert(&mut bus, 460, 6000);
+    assert_eq!(reader.read(&mut bus), Ok(reading(200, 828)));
+    reader.set_calibration(Calibration {
+        humidity: Correction {
+            offset: 300,
+            gain_permille: 1000,
+        },
+        ..Calibration::default()
+    });
+    assert_eq!(reader.read(&mut bus), Ok(reading(215, 1000)));
+}
+
+#[test]
+fn corrections_round() {
+    assert_eq!(Correction::NONE.apply(-123), -123);
+    let gain = |gain_permille| Correction {
+        offset: 0,
+        gain_permille,
+    };
+    assert_eq!(gain(1005).apply(100), 101);
+    assert_eq!(gain(1005).apply(-100), -101);
+    assert_eq!(gain(995).apply(100), 100);
+    assert_eq!(gain(0).apply(100), 0);
+}
+
+#[test]
+fn errors_instead_of_panics() {
+    let mut bus = MockBus::default();
+    let mut reader = Hts221Reader::new();
+    let err = reader.read(&mut bus).unwrap_err();
+    assert_eq!(err, Error::I2c(BusError::Nack(ADDRESS)));
+    assert_eq!(err.message(), "no sensor");
+
+    let mut other = MockDevice::new(Some(0x80));
+    other.set(WHO_AM_I, &[0x33]);
+    let mut bus = bus.with_device(ADDRESS, other);
+    let err = reader.read(&mut bus).unwrap_err();
+    assert_eq!(err, Error::WrongDevice(0x33));
+    assert_eq!(err.message(), "unknown sensor");
+
+    let mut uncalibrated = MockDevice::new(Some(0x80));
+    uncalibrated.set(WHO_AM_I, &[DEVICE_ID]);
+    let mut bus = bus.with_device(ADDRESS, uncalibrated);
+    let err = reader.read(&mut bus).unwrap_err();
+    assert_eq!(err, Error::BadCoefficients);
+    assert_eq!(err.message(), "bad calibration");
+    assert!(!reader.is_started());
+}
+
+#[test]
+fn restarts_after_bus_errors() {
+    let mut bus = bus();
+    let mut reader = Hts221Reader::new();
+    convert(&mut bus, 460, -900);
+    assert_eq!(reader.read(&mut bus), Ok(reading(215, 455)));
+
+    // Unplugged
+    let device = bus.devices.remove(&ADDRESS).unwrap();
+    assert_eq!(
+        reader.read(&mut bus),
+        Err(Error::I2c(BusError::Nack(ADDRESS)))
+    );
+    assert!(!reader.is_started());
+
+    // Plugged in again, after a reset
+    let mut bus = MockBus::default().with_device(ADDRESS, device);
+    bus.device(ADDRESS).set(CTRL_REG1, &[0x00]);
+    convert(&mut bus, 500, -900);
+    assert_eq!(reader.read(&mut bus), Ok(reading(225, 455)));
+    assert_eq!(bus.device(ADDRESS).registers[CTRL_REG1 as usize], 0x85);
+}
+
+#[test]
+fn heater() {
+    let mut bus = bus();
+    let mut reader = Hts221Reader::new();
+    // Applied when the reader starts
+    reader.set_heater(&mut bus, true).unwrap();
+    assert!(bus.take_writes().is_empty());
+    reader.start(&mut bus).unwrap();
+    assert_eq!(bus.device(ADDRESS).registers[CTRL_REG2 as usize], 0x02);
+
+    reader.set_heater(&mut bus, false).unwrap();
+    assert!(!reader.heater());
+    assert_eq!(
+        bus.take_writes().last(),
+        Some(&(ADDRESS, vec![CTRL_REG2, 0x00]))
+    );
+}
+
+#[test]
+fn reading_display() {
+    assert_eq!(reading(215, 455).to_string(), "21.5 C 45.5%");
+    assert_eq!(reading(-8, 1000).to_string(), "-0.8 C 100.0%");
+}
+
+/// An SHT3x with an empty status word and a measurement of 25.0 C and 50 %.
+fn sht3x() -> MockDevice {
+    let mut device = MockDevice::commands();
+    device.answer(&sht3x::READ_STATUS, &[0x00, 0x00, 0x81]);
+    device.answer(&sht3x::PERIODIC_1MPS_HIGH, &[]);
+    device.answer(&sht3x::FETCH_DATA, &[0x66, 0x66, 0x93, 0x80, 0x00, 0xa2]);
+    device
+}
+
+#[test]
+fn sht3x_checks_crcs() {
+    // Example of the datasheet
+    assert_eq!(crc8(&[0xbe, 0xef]), 0x92);
+    assert_eq!(crc8(&[0x66, 0x66]), 0x93);
+    assert_eq!(crc8(&[0x80, 0x00]), 0xa2);
+
+    let mut bus = MockBus::default().with_device(sht3x::ADDRESS_ALT, sht3x());
+    let mut sensor = Sht3x::probe(&mut bus, sht3x::ADDRESS_ALT).unwrap();
+    assert_eq!(
+        bus.take_writes(),
+        [
+            (sht3x::ADDRESS_ALT, sht3x::READ_STATUS.to_vec()),
+            (sht3x::ADDRESS_ALT, sht3x::PERIODIC_1MPS_HIGH.to_vec()),
+        ]
+    );
+    assert_eq!(sensor.read(&mut bus), Ok((250, 500)));
+
+    bus.device(sht3x::ADDRESS_ALT)
+        .answer(&sht3x::FETCH_DATA, &[0x66, 0x66, 0x93, 0x80, 0x00, 0x00]);
+    assert_eq!(sensor.read(&mut bus), Err(Error::Checksum));
+    assert_eq!(Error::<BusError>::Checksum.message(), "bad checksum");
+
+    // Anything with registers fails the status CRC
+    let mut bus = MockBus::default().with_device(sht3x::ADDRESS, MockDevice::new(None));
+    assert!(Sht3x::probe(&mut bus, sht3x::ADDRESS).is_none());
+}
+
+/// Trimming values of the example in the Bosch datasheets, and typical ones
+/// for humidity.
+fn bme280() -> MockDevice {
+    let mut device = MockDevice::new(None);
+    device.set(bme280::CHIP_ID, &[bme280::DEVICE_ID]);
+    let mut registers = Vec::new();
+    registers.extend(27504u16.to_le_bytes());
+    for value in [26435i16, -1000] {
+        registers.extend(value.to_le_bytes());
+    }
+    registers.extend(36477u16.to_le_bytes());
+    for value in [-10685i16, 3024, 2855, 140, -7, 15500, -14600, 6000] {
+        registers.extend(value.to_le_bytes());
+    }
+    registers.extend([0, 75]);
+    device.set(bme280::CALIBRATION, &registers);
+    // H2 362, H3 0, H4 324 and H5 50 sharing 0xe5, H6 30
+    device.set(
+        bme280::CALIBRATION_HUMIDITY,
+        &[0x6a, 0x01, 0, 20, 0x24, 3, 30],
+    );
+    // Pressure 415148, temperature 519888, humidity 30000
+    device.set(
+        bme280::DATA,
+        &[0x65, 0x5a, 0xc0, 0x7e, 0xed, 0x00, 0x75, 0x30],
+    );
+    device
+}
+
+#[test]
+fn bme280_compensation() {
+    let mut bus = MockBus::default().with_device(bme280::ADDRESS, bme280());
+    let mut registers = [0u8; 26];
+    let mut humidity = [0u8; 7];
+    let device = bus.device(bme280::ADDRESS);
+    registers.copy_from_slice(&device.registers[0x88..0xa2]);
+    humidity.copy_from_slice(&device.registers[0xe1..0xe8]);
+    let coefficients = Coefficients::decode(&registers, &humidity).unwrap();
+    assert_eq!((coefficients.h4, coefficients.h5), (324, 50));
+    assert_eq!(coefficients.temperature(519888), (2508, 128422));
+    assert_eq!(coefficients.pressure(415148, 128422), 25767233);
+    assert_eq!(coefficients.humidity(30000, 128422), 52306);
+
+    let mut sensor = Bme280::probe(&mut bus, bme280::ADDRESS).unwrap();
+    assert_eq!(
+        bus.take_writes()[3..],
+        [
+            (bme280::ADDRESS, vec![bme280::CTRL_HUM, 0x01]),
+            (bme280::ADDRESS, vec![bme280::CONFIG, 0xa0]),
+            (bme280::ADDRESS, vec![bme280::CTRL_MEAS, 0x27]),
+        ]
+    );
+    // 25.08 C, 51.08 % and 1006.53 hPa
+    assert_eq!(sensor.read(&mut bus), Ok([251, 511, 10065]));
+
+    // Reset values before the first measurement
+    bus.device(bme280::ADDRESS)
+        .set(bme280::DATA, &[0x80, 0, 0, 0x80, 0, 0, 0x80, 0]);
+    assert_eq!(sensor.read(&mut bus), Err(Error::NotReady));
+
+    // A BMP280 has no humidity
+    bus.device(bme280::ADDRESS).set(bme280::CHIP_ID, &[0x58]);
+    assert!(Bme280::probe(&mut bus, bme280::ADDRESS).is_none());
+}
+
+fn lps22(id: u8) -> MockDevice {
+    let mut device = MockDevice::new(None);
+    device.set(lps22::WHO_AM_I, &[id]);
+    device
+}
+
+#[test]
+fn lps22_pressure() {
+    let mut bus = MockBus::default().with_device(lps22::ADDRESS_ALT, lps22(0xb3));
+    let mut sensor = Lps22::probe(&mut bus, lps22::ADDRESS_ALT).unwrap();
+    let device = bus.device(lps22::ADDRESS_ALT);
+    assert_eq!(device.registers[lps22::CTRL_REG1 as usize], 0x12);
+    assert_eq!(sensor.read(&mut bus), Err(Error::NotReady));
+
+    // 1013.25 hPa and 23.45 C
+    let mut data = vec![0x03];
+    data.extend(&(101_325 * 4096 / 100i32).to_le_bytes()[..3]);
+    data.extend(2345i16.to_le_bytes());
+    bus.device(lps22::ADDRESS_ALT).set(lps22::STATUS, &data);
+    assert_eq!(sensor.read(&mut bus), Ok((10133, 235)));
+
+    // Kept until the next conversion
+    bus.device(lps22::ADDRESS_ALT).set(lps22::STATUS, &[0x00]);
+    assert_eq!(sensor.read(&mut bus), Ok((10133, 235)));
+
+    let mut bus = MockBus::default().with_device(lps22::ADDRESS, lps22(0xbd));
+    assert!(Lps22::probe(&mut bus, lps22::ADDRESS).is_none());
+}
+
+/// A BH1750 measuring 300 counts, 250 lx.
+fn bh1750() -> MockDevice {
+    let mut device = MockDevice::commands();
+    device.answer(&[bh1750::POWER_ON], &[]);
+    device.answer(&[bh1750::CONTINUOUS_HIGH_RES], &[0x01, 0x2c]);
+    device
+}
+
+#[test]
+fn bh1750_illuminance() {
+    let mut bus = MockBus::default().with_device(bh1750::ADDRESS, bh1750());
+    let mut sensor = Bh1750::probe(&mut bus, bh1750::ADDRESS).unwrap();
+    assert_eq!(
+        bus.take_writes(),
+        [
+            (bh1750::ADDRESS, vec![bh1750::POWER_ON]),
+            (bh1750::ADDRESS, vec![bh1750::CONTINUOUS_HIGH_RES]),
+        ]
+    );
+    assert_eq!(sensor.read(&mut bus), Ok(2500));
+    assert!(Bh1750::probe(&mut bus, bh1750::ADDRESS_ALT).is_none());
+}
+
+/// Every known chip, the display and a chip with a wrong id at `0x77`.
+fn crowded_bus() -> MockBus {
+    let mut bus = MockBus::default()
+        .with_device(0x3c, MockDevice::new(None))
+        .with_device(bh1750::ADDRESS, bh1750())
+        .with_device(sht3x::ADDRESS, sht3x())
+        .with_device(lps22::ADDRESS, lps22(0xb1))
+        .with_device(ADDRESS, hts221())
+        .with_device(bme280::ADDRESS, bme280())
+        .with_device(bme280::ADDRESS_ALT, MockDevice::new(None));
+    convert(&mut bus, 460, -900);
+    bus
+}
+
+#[test]
+fn registry_scans_known_addresses() {
+    let mut sorted = registry::ADDRESSES.to_vec();
+    sorted.sort();
+    sorted.dedup();
+    assert_eq!(sorted, registry::ADDRESSES);
+    for addresses in [
+        Hts221Reader::ADDRESSES,
+        Sht3x::ADDRESSES,
+        Bme280::ADDRESSES,
+        Bh1750::ADDRESSES,
+        Lps22::ADDRESSES,
+    ] {
+        assert!(addresses
+            .iter()
+            .all(|address| registry::ADDRESSES.contains(address)));
+    }
+
+    let mut bus = crowded_bus();
+    let registry = Registry::scan(&mut bus);
+    let found: Vec<_> = registry
+        .entries()
+        .iter()
+        .map(|entry| (entry.device.name(), entry.device.address()))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("BH1750", 0x23),
+            ("SHT3x", 0x44),
+            ("LPS22", 0x5c),
+            ("HTS221", 0x5f),
+            ("BME280", 0x76),
+        ]
+    );
+    assert!(bus.writes.iter().all(|(address, _)| *address != 0x3c));
+
+    // A BH1750 strapped to the address of the LPS22
+    let mut bus = MockBus::default().with_device(bh1750::ADDRESS_ALT, bh1750());
+    assert!(matches!(
+        Device::probe(&mut bus, bh1750::ADDRESS_ALT),
+        Some(Device::Bh1750(_))
+    ));
+    assert!(Registry::scan(&mut MockBus::default()).is_empty());
+}
+
+#[test]
+fn registry_fields() {
+    let mut bus = crowded_bus();
+    let mut registry = Registry::scan(&mut bus);
+    assert!(registry.fields().all(|field| field.value.is_none()));
+
+    registry.poll(&mut bus);
+    let fields: Vec<_> = registry
+        .fields()
+        .map(|field| (field.key.to_string(), field.quantity, field.value))
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            (
+                "bh1750_23_illuminance".into(),
+                Quantity::Illuminance,
+                Some(2500)
+            ),
+            (
+                "sht3x_44_temperature".into(),
+                Quantity::Temperature,
+                Some(250)
+            ),
+            ("sht3x_44_humidity".into(), Quantity::Humidity, Some(500)),
+            ("lps22_5c_pressure".into(), Quantity::Pressure, None),
+            ("lps22_5c_temperature".into(), Quantity::Temperature, None),
+            (
+                "hts221_5f_temperature".into(),
+                Quantity::Temperature,
+                Some(215)
+            ),
+            ("hts221_5f_humidity".into(), Quantity::Humidity, Some(455)),
+            (
+                "bme280_76_temperature".into(),
+                Quantity::Temperature,
+                Some(251)
+            ),
+            ("bme280_76_humidity".into(), Quantity::Humidity, Some(511)),
+            ("bme280_76_pressure".into(), Quantity::Pressure, Some(10065)),
+        ]
+    );
+    assert_eq!(registry.entries()[2].error, Some("waiting for sensor"));
+
+    // Unplugged: the values stay, with the error
+    bus.devices.remove(&bme280::ADDRESS);
+    registry.poll(&mut bus);
+    let entry = &registry.entries()[4];
+    assert_eq!(entry.error, Some("no sensor"));
+    assert_eq!(entry.values.as_deref(), Some(&[251, 511, 10065][..]));
+}
+
+#[test]
+fn sensor_pages() {
+    let mut bus = crowded_bus();
+    let mut registry = Registry::scan(&mut bus);
+    let mut pages = Pages::new();
+    let frame = render(|target| pages.draw(target, &registry));
+    assert_golden("sensors_waiting", &frame);
+
+    registry.poll(&mut bus);
+    let right = ButtonState {
+        left: false,
+        right: true,
+    };
+    let left = ButtonState {
+        left: true,
+        right: false,
+    };
+    assert!(!pages.update(ButtonState::default(), 5));
+    assert!(pages.update(left, 5));
+    assert_eq!(pages.page(), 4);
+    let frame = render(|target| pages.draw(target, &registry));
+    assert_golden("sensors_bme280", &frame);
+
+    // Held down, no repeat
+    assert!(!pages.update(left, 5));
+    pages.update(ButtonState::default(), 5);
+    assert!(pages.update(right, 5));
+    assert_eq!(pages.page(), 0);
+    let frame = render(|target| pages.draw(target, &registry));
+    assert_golden("sensors_bh1750", &frame);
+
+    for _ in 0..2 {
+        pages.update(ButtonState::default(), 5);
+        pages.update(right, 5);
+    }
+    let frame = render(|target| pages.draw(target, &registry));
+    assert_golden("sensors_error", &frame);
+
+    let frame = render(|target| Pages::new().draw(target, &Registry::default()));
+    assert_golden("sensors_none", &frame);
+}
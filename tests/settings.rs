@@ -0,0 +1,726 @@
+mod common;
+
+use embedded_storage::nor_flash::{
+    ErrorType, NorFlash, NorFlashError, NorFlashErrorKind, ReadNorFlash,
+};
+use esp_buddy::input::{Button, Event};
+use esp_buddy::launcher::{apps::Settings, App};
+use esp_buddy::screens;
+use esp_buddy::sensors::{hts221::Calibration, Correction};
+use esp_buddy::settings::schema::{
+    Config, Kind, Rotation, Setting, Units, Value, CONTRAST, LED_BRIGHTNESS, ROTATION,
+    SAMPLE_INTERVAL, SCHEMA, TIME_ZONE, UNITS,
+};
+use esp_buddy::settings::{
+    crc32, known_networks, Editor, Error, Store, StoredNetwork, MAX_NETWORKS, MAX_VALUE_LEN,
+};
+use esp_buddy::wifi::Network;
+
+use common::{assert_golden, render};
+use Button::{Left, Right};
+
+const SECTOR: usize = 4096;
+const SIZE: u32 = 2 * SECTOR as u32;
+
+#[derive(Debug, PartialEq, Eq)]
+enum FlashError {
+    OutOfBounds,
+    NotAligned,
+    /// Writes only clear bits, setting one needs an erase.
+    NotErased,
+    PowerLoss,
+}
+
+impl NorFlashError for FlashError {
+    fn kind(&self) -> NorFlashErrorKind {
+        match self {
+            FlashError::OutOfBounds => NorFlashErrorKind::OutOfBounds,
+            FlashError::NotAligned => NorFlashErrorKind::NotAligned,
+            _ => NorFlashErrorKind::Other,
+        }
+    }
+}
+
+/// RAM flash with NOR semantics, erase counters and a byte budget after which
+/// writes are cut off like at a power loss.
+struct RamFlash {
+    data: Vec<u8>,
+    erases: Vec<u32>,
+    bytes_until_power_loss: Option<usize>,
+}
+
+impl RamFlash {
+    fn new(sectors: usize) -> Self {
+        Self {
+            data: vec![0xff; sectors * SECTOR],
+            erases: vec![0; sectors],
+            bytes_until_power_loss: None,
+        }
+    }
+}
+
+impl ErrorType for RamFlash {
+    type Error = FlashError;
+}
+
+impl ReadNorFlash for RamFlash {
+    const READ_SIZE: usize = 4;
+
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)]
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), FlashError> {
+        let offset = offset as usize;
+        if offset % Self::READ_SIZE != 0 || bytes.len() % Self::READ_SIZE != 0 {
+            return Err(FlashError::NotAligned);
+        }
+        let data = self
+            .data
+            .get(offset..offset + bytes.len())
+            .ok_or(FlashError::OutOfBounds)?;
+        bytes.copy_from_slice(data);
+        Ok(())
+    }
+
+    fn capacity(&self) -> usize {
+        self.data.len()
+    }
+}
+
+impl NorFlash for RamFlash {
+    const WRITE_SIZE: usize = 4;
+    const ERASE_SIZE: usize = SECTOR;
+
+    fn erase(&mut self, from: u32, to: u32) -> Result<(), FlashError> {
+        let (from, to) = (from as usize, to as usize);
+        if from % SECTOR != 0 || to % SECTOR != 0 {
+            return Err(FlashError::NotAligned);
+        }
+        if to > self.data.len() {
+            return Err(FlashError::OutOfBounds);
+        }
+        self.data[from..to].fill(0xff);
+        for sector in from / SECTOR..to / SECTOR {
+            self.erases[sector] += 1;
+        }
+        Ok(())
+    }
+
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)]
+    fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), FlashError> {
+        let offset = offset as usize;
+        if offset % Self::WRITE_SIZE != 0 || bytes.len() % Self::WRITE_SIZE != 0 {
+            return Err(FlashError::NotAligned);
+        }
+        let data = self
+            .data
+            .get_mut(offset..offset + bytes.len())
+            .ok_or(FlashError::OutOfBounds)?;
+        if data.iter().zip(bytes).any(|(&old, &new)| new & !old != 0) {
+            return Err(FlashError::NotErased);
+        }
+
+        let len = match &mut self.bytes_until_power_loss {
+            Some(budget) => {
+                let len = bytes.len().min(*budget);
+                *budget -= len;
+                len
+            }
+            None => bytes.len(),
+        };
+        data[..len].copy_from_slice(&bytes[..len]);
+        if len < bytes.len() {
+            return Err(FlashError::PowerLoss);
+        }
+        Ok(())
+    }
+}
+
+fn mount(flash: RamFlash) -> Store<RamFlash> {
+    Store::new(flash, 0, SIZE).unwrap()
+}
+
+fn get(store: &mut Store<RamFlash>, key: &str) -> Option<Vec<u8>> {
+    let mut buf = [0u8; MAX_VALUE_LEN];
+    store.get(key, &mut buf).unwrap().map(<[u8]>::to_vec)
+}
+
+#[test]
+fn crc32_check_value() {
+    assert_eq!(crc32(&[b"123456789"]), 0xcbf4_3926);
+    assert_eq!(crc32(&[b"1234", b"56789"]), 0xcbf4_3926);
+}
+
+#[test]
+fn set_get_remove() {
+    let mut store = mount(RamFlash::new(2));
+    assert_eq!(get(&mut store, "tz"), None);
+
+    store.set("tz", b"CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+    store.set("brightness", &[7]).unwrap();
+    store.set("tz", b"UTC0").unwrap();
+    assert_eq!(get(&mut store, "tz").as_deref(), Some(&b"UTC0"[..]));
+    assert_eq!(get(&mut store, "brightness"), Some(vec![7]));
+    assert!(store.contains("brightness").unwrap());
+
+    store.remove("tz").unwrap();
+    assert_eq!(get(&mut store, "tz"), None);
+    assert!(!store.contains("tz").unwrap());
+
+    // Empty values are values
+    store.set("tz", b"").unwrap();
+    assert_eq!(get(&mut store, "tz"), Some(vec![]));
+}
+
+#[test]
+fn values_survive_remount() {
+    let mut store = mount(RamFlash::new(2));
+    store.set("a", b"first").unwrap();
+    store.set("b", b"second").unwrap();
+    store.remove("a").unwrap();
+
+    let mut store = mount(store.into_inner());
+    assert_eq!(get(&mut store, "a"), None);
+    assert_eq!(get(&mut store, "b").as_deref(), Some(&b"second"[..]));
+}
+
+#[test]
+fn invalid_arguments() {
+    let mut store = mount(RamFlash::new(2));
+    assert_eq!(store.set("", b"x"), Err(Error::EmptyKey));
+    assert_eq!(store.set(&"k".repeat(33), b"x"), Err(Error::KeyTooLong));
+    assert_eq!(
+        store.set("k", &[0; MAX_VALUE_LEN + 1]),
+        Err(Error::ValueTooLong)
+    );
+
+    store.set("k", b"longer than four").unwrap();
+    let mut buf = [0u8; 4];
+    assert_eq!(store.get("k", &mut buf), Err(Error::BufferTooSmall));
+}
+
+#[test]
+fn compaction_spreads_erases() {
+    let mut store = mount(RamFlash::new(2));
+    store.set("keep", b"kept across compactions").unwrap();
+    for i in 0..2000u32 {
+        store.set("counter", &i.to_le_bytes()).unwrap();
+    }
+
+    assert_eq!(
+        get(&mut store, "counter"),
+        Some(1999u32.to_le_bytes().to_vec())
+    );
+    assert_eq!(
+        get(&mut store, "keep").as_deref(),
+        Some(&b"kept across compactions"[..])
+    );
+
+    // 24 bytes per record, about 170 records per bank and one erase each
+    let flash = store.into_inner();
+    assert!(flash.erases.iter().all(|&erases| (4..=7).contains(&erases)));
+    assert!(flash.erases[0].abs_diff(flash.erases[1]) <= 1);
+}
+
+#[test]
+fn full_when_latest_values_do_not_fit() {
+    let mut store = mount(RamFlash::new(2));
+    let value = [0x55; MAX_VALUE_LEN];
+    let mut stored = 0;
+    let err = loop {
+        match store.set(&format!("key{}", stored), &value) {
+            Ok(()) => stored += 1,
+            Err(err) => break err,
+        }
+    };
+    assert_eq!(err, Error::Full);
+    assert_eq!(stored, 15);
+
+    // Nothing was lost and removing makes room again
+    let mut store = mount(store.into_inner());
+    for i in 0..stored {
+        assert!(store.contains(&format!("key{}", i)).unwrap());
+    }
+    store.remove("key0").unwrap();
+    store.set("key15", &value).unwrap();
+}
+
+#[test]
+fn torn_write_keeps_previous_value() {
+    let mut store = mount(RamFlash::new(2));
+    store.set("ssid", b"old network").unwrap();
+
+    let mut flash = store.into_inner();
+    flash.bytes_until_power_loss = Some(10);
+    let mut store = mount(flash);
+    assert_eq!(
+        store.set("ssid", b"new network"),
+        Err(Error::Flash(FlashError::PowerLoss))
+    );
+
+    let mut flash = store.into_inner();
+    flash.bytes_until_power_loss = None;
+    let mut store = mount(flash);
+    assert_eq!(
+        get(&mut store, "ssid").as_deref(),
+        Some(&b"old network"[..])
+    );
+    assert_eq!(store.free(), 0);
+
+    // The next write moves past the torn record
+    store.set("ssid", b"new network").unwrap();
+    let mut store = mount(store.into_inner());
+    assert_eq!(
+        get(&mut store, "ssid").as_deref(),
+        Some(&b"new network"[..])
+    );
+}
+
+#[test]
+fn interrupted_compaction_keeps_previous_bank() {
+    let mut store = mount(RamFlash::new(2));
+    store.set("a", b"alpha").unwrap();
+    store.set("b", b"beta").unwrap();
+
+    let mut flash = store.into_inner();
+    flash.bytes_until_power_loss = Some(16);
+    let mut store = mount(flash);
+    assert_eq!(store.compact(), Err(Error::Flash(FlashError::PowerLoss)));
+
+    let mut flash = store.into_inner();
+    flash.bytes_until_power_loss = None;
+    let mut store = mount(flash);
+    assert_eq!(get(&mut store, "a").as_deref(), Some(&b"alpha"[..]));
+    assert_eq!(get(&mut store, "b").as_deref(), Some(&b"beta"[..]));
+
+    store.compact().unwrap();
+    let mut store = mount(store.into_inner());
+    assert_eq!(get(&mut store, "a").as_deref(), Some(&b"alpha"[..]));
+}
+
+#[test]
+fn corrupt_record_ends_the_log() {
+    let mut store = mount(RamFlash::new(2));
+    store.set("a", b"alpha").unwrap();
+    store.set("b", b"beta").unwrap();
+
+    // Flip a bit in the value of the first record after the bank header
+    let mut flash = store.into_inner();
+    flash.data[8 + 8 + 1] ^= 0x01;
+    let mut store = mount(flash);
+    assert_eq!(get(&mut store, "a"), None);
+    assert_eq!(get(&mut store, "b"), None);
+}
+
+#[test]
+fn region_offset() {
+    let mut store = Store::new(RamFlash::new(6), 2 * SECTOR as u32, 4 * SECTOR as u32).unwrap();
+    store.set("k", b"v").unwrap();
+    let flash = store.into_inner();
+    assert!(flash.data[..2 * SECTOR].iter().all(|&byte| byte == 0xff));
+    assert_eq!(&flash.data[2 * SECTOR..2 * SECTOR + 4], b"ESB1");
+}
+
+#[test]
+fn networks_by_priority() {
+    let mut store = mount(RamFlash::new(2));
+    let home = StoredNetwork::new("home", "secret", 10).unwrap();
+    let phone = StoredNetwork::new("phone", "hotspot", 20).unwrap();
+    let office = StoredNetwork::new("office", "", 10).unwrap();
+    for network in [&home, &phone, &office] {
+        store.save_network(network).unwrap();
+    }
+    assert_eq!(
+        store.networks().unwrap(),
+        [phone.clone(), home.clone(), office.clone()]
+    );
+
+    // Same SSID replaces
+    let home = StoredNetwork::new("home", "changed", 30).unwrap();
+    store.save_network(&home).unwrap();
+    let mut store = mount(store.into_inner());
+    assert_eq!(
+        store.networks().unwrap(),
+        [home.clone(), phone.clone(), office.clone()]
+    );
+
+    assert!(store.remove_network("phone").unwrap());
+    assert!(!store.remove_network("phone").unwrap());
+    assert_eq!(store.networks().unwrap(), [home, office]);
+}
+
+#[test]
+fn network_slots_are_limited() {
+    let mut store = mount(RamFlash::new(2));
+    for i in 0..MAX_NETWORKS {
+        let network = StoredNetwork::new(&format!("net{}", i), "", 0).unwrap();
+        store.save_network(&network).unwrap();
+    }
+    let extra = StoredNetwork::new("extra", "", 0).unwrap();
+    assert_eq!(store.save_network(&extra), Err(Error::Full));
+
+    store.remove_network("net1").unwrap();
+    store.save_network(&extra).unwrap();
+    assert_eq!(store.networks().unwrap()[1], extra);
+}
+
+#[test]
+fn stored_network_limits() {
+    assert!(StoredNetwork::new("", "password", 0).is_none());
+    assert!(StoredNetwork::new(&"s".repeat(33), "", 0).is_none());
+    assert!(StoredNetwork::new("ssid", &"p".repeat(65), 0).is_none());
+    assert!(StoredNetwork::new(&"s".repeat(32), &"p".repeat(64), 0).is_some());
+}
+
+#[test]
+fn built_in_network_comes_last() {
+    let stored = [
+        StoredNetwork::new("home", "secret", 10).unwrap(),
+        StoredNetwork::new("office", "", 5).unwrap(),
+    ];
+    let built_in = Network::new("lab", "compile time");
+
+    assert_eq!(
+        known_networks(&stored, Some(built_in)),
+        [
+            Network::new("home", "secret"),
+            Network::new("office", ""),
+            built_in,
+        ]
+    );
+    // Stored credentials win over the built-in ones
+    assert_eq!(
+        known_networks(&stored, Some(Network::new("home", "old"))).len(),
+        2
+    );
+    assert_eq!(known_networks(&[], Some(built_in)), [built_in]);
+    assert!(known_networks(&[], None).is_empty());
+}
+
+#[test]
+fn hts221_calibration() {
+    let mut store = mount(RamFlash::new(2));
+    assert_eq!(store.hts221_calibration(), Ok(Calibration::default()));
+
+    let calibration = Calibration {
+        temperature: Correction {
+            offset: -15,
+            gain_permille: 1000,
+        },
+        humidity: Correction {
+            offset: 20,
+            gain_permille: 1010,
+        },
+    };
+    store.save_hts221_calibration(&calibration).unwrap();
+    assert_eq!(
+        get(&mut store, "hts221.cal"),
+        Some(vec![0xf1, 0xff, 0xe8, 0x03, 0x14, 0x00, 0xf2, 0x03])
+    );
+    let mut store = mount(store.into_inner());
+    assert_eq!(store.hts221_calibration(), Ok(calibration));
+
+    // Values that do not decode are ignored, saving no correction removes it
+    store.set("hts221.cal", b"bad").unwrap();
+    assert_eq!(store.hts221_calibration(), Ok(Calibration::default()));
+    store
+        .save_hts221_calibration(&Calibration::default())
+        .unwrap();
+    assert!(!store.contains("hts221.cal").unwrap());
+}
+
+fn text(text: &str) -> Value {
+    Value::Text(text.try_into().unwrap())
+}
+
+const NAME: Setting = Setting {
+    key: "name",
+    label: "Name",
+    kind: Kind::Text {
+        default: "ab",
+        max_len: 3,
+    },
+};
+
+const SOUND: Setting = Setting {
+    key: "sound",
+    label: "Sound",
+    kind: Kind::Bool { default: true },
+};
+
+#[test]
+fn setting_values_round_trip() {
+    for setting in SCHEMA.iter().chain([&NAME, &SOUND]) {
+        let default = setting.default_value();
+        assert!(setting.is_valid(&default), "{}", setting.key);
+        let next = setting.next(&default);
+        assert!(setting.is_valid(&next), "{}", setting.key);
+        assert_eq!(setting.decode(&setting.encode(&next)), Some(next));
+    }
+    assert_eq!(SOUND.encode(&Value::Bool(false)), [0]);
+    assert_eq!(CONTRAST.encode(&Value::Int(70)), [70, 0, 0, 0]);
+    assert_eq!(UNITS.encode(&Value::Enum(1)), [1]);
+    assert_eq!(NAME.encode(&text("Ab")), *b"Ab");
+
+    // Bytes that do not fit the setting
+    assert_eq!(SOUND.decode(&[2]), None);
+    assert_eq!(ROTATION.decode(&[2]), None);
+    assert_eq!(CONTRAST.decode(&[70, 0]), None);
+    assert_eq!(CONTRAST.decode(&55i32.to_le_bytes()), None);
+    assert_eq!(CONTRAST.decode(&110i32.to_le_bytes()), None);
+    assert_eq!(NAME.decode(b"abcd"), None);
+    assert_eq!(NAME.decode(b"a;"), None);
+    assert_eq!(NAME.decode(&[0xff]), None);
+}
+
+#[test]
+fn setting_next_goes_around() {
+    assert_eq!(SOUND.next(&Value::Bool(true)), Value::Bool(false));
+    assert_eq!(ROTATION.next(&Value::Enum(1)), Value::Enum(0));
+    assert_eq!(CONTRAST.next(&Value::Int(90)), Value::Int(100));
+    assert_eq!(CONTRAST.next(&Value::Int(100)), Value::Int(0));
+    assert_eq!(SAMPLE_INTERVAL.next(&Value::Int(60)), Value::Int(1));
+    // Texts change in the editor
+    assert_eq!(NAME.next(&text("ab")), text("ab"));
+}
+
+#[test]
+fn setting_without_step() {
+    const ANY: Setting = Setting {
+        key: "any",
+        label: "Any",
+        kind: Kind::Int {
+            min: -2,
+            max: 3,
+            step: 0,
+            default: 0,
+            unit: "",
+        },
+    };
+    assert!(ANY.is_valid(&Value::Int(-2)));
+    assert!(ANY.is_valid(&Value::Int(1)));
+    assert!(!ANY.is_valid(&Value::Int(4)));
+    assert_eq!(ANY.next(&Value::Int(1)), Value::Int(2));
+    assert_eq!(ANY.next(&Value::Int(3)), Value::Int(-2));
+}
+
+#[test]
+fn setting_display() {
+    let shown = |setting: &Setting, value: Value| setting.display(&value).to_string();
+    assert_eq!(shown(&SOUND, Value::Bool(true)), "on");
+    assert_eq!(shown(&ROTATION, Value::Enum(1)), "flipped");
+    assert_eq!(shown(&CONTRAST, Value::Int(40)), "40%");
+    assert_eq!(shown(&TIME_ZONE, text("UTC0")), "UTC0");
+}
+
+#[test]
+fn settings_store_only_changes() {
+    let mut store = mount(RamFlash::new(2));
+    assert_eq!(store.config(), Ok(Config::default()));
+
+    store.save_setting(&CONTRAST, &Value::Int(70)).unwrap();
+    store.save_setting(&UNITS, &Value::Enum(1)).unwrap();
+    store.save_setting(&TIME_ZONE, &text("UTC0")).unwrap();
+    assert_eq!(get(&mut store, "display.contrast"), Some(vec![70, 0, 0, 0]));
+    assert_eq!(get(&mut store, "units.temperature"), Some(vec![1]));
+
+    let mut store = mount(store.into_inner());
+    let config = store.config().unwrap();
+    assert_eq!(config.contrast_pct, 70);
+    assert_eq!(config.units, Units::Fahrenheit);
+    assert_eq!(config.time_zone, "UTC0");
+    assert_eq!(config.rotation, Rotation::Normal);
+
+    // Saving the default removes the key
+    store.save_setting(&UNITS, &Value::Enum(0)).unwrap();
+    assert!(!store.contains("units.temperature").unwrap());
+    assert_eq!(store.setting(&UNITS), Ok(Value::Enum(0)));
+}
+
+#[test]
+fn invalid_stored_settings_read_as_default() {
+    let mut store = mount(RamFlash::new(2));
+    store.set("display.rotation", &[7]).unwrap();
+    store.set("display.contrast", &[1, 2]).unwrap();
+    store.set("time.tz", &[b'a'; 64]).unwrap();
+    store.set("sensors.interval", &0i32.to_le_bytes()).unwrap();
+    assert_eq!(store.config(), Ok(Config::default()));
+}
+
+#[test]
+fn config_defaults() {
+    let config = Config::default();
+    assert_eq!(config.rotation, Rotation::Normal);
+    assert_eq!(config.contrast_pct, 40);
+    assert_eq!(config.led_brightness, 32);
+    assert_eq!(config.time_zone, "CET-1CEST,M3.5.0,M10.5.0/3");
+    assert_eq!(config.units, Units::Celsius);
+    assert_eq!(config.sample_interval_ms, 5000);
+
+    // Settings missing from a schema keep their default
+    let config = Config::new(&[SAMPLE_INTERVAL], &[Value::Int(30)]);
+    assert_eq!(config.sample_interval_ms, 30_000);
+    assert_eq!(config.contrast_pct, 40);
+
+    assert_eq!(Units::Fahrenheit.from_dc(215), 707);
+    assert_eq!(Units::Fahrenheit.from_dc(-400), -400);
+    assert_eq!(Rotation::Flipped.flipped(), Rotation::Normal);
+}
+
+#[test]
+fn editor_selects_and_saves() {
+    let mut editor = Editor::new(&SCHEMA, &[]);
+    assert_eq!(editor.values()[1], Value::Int(40));
+    assert_eq!(editor.edit(), None);
+
+    // Left moves down the list, right edits
+    assert!(editor.handle_event(Event::Click(Left)));
+    assert_eq!(editor.selected(), &CONTRAST);
+    assert!(editor.handle_event(Event::Click(Right)));
+    assert_eq!(editor.edit().unwrap().value, Value::Int(40));
+
+    // Left changes the value, only saved with right
+    assert!(editor.handle_event(Event::Click(Left)));
+    assert!(editor.handle_event(Event::Click(Left)));
+    assert!(!editor.handle_event(Event::Pressed(Left)));
+    assert_eq!(editor.edit().unwrap().value, Value::Int(60));
+    assert_eq!(editor.values()[1], Value::Int(40));
+    assert_eq!(editor.take_saved(), None);
+    assert!(editor.handle_event(Event::Click(Right)));
+    assert_eq!(editor.edit(), None);
+    assert_eq!(editor.values()[1], Value::Int(60));
+    assert_eq!(editor.take_saved(), Some(1));
+    assert_eq!(editor.take_saved(), None);
+
+    // Holding does not scroll, the launcher leaves the app instead
+    assert!(!editor.handle_event(Event::Repeat(Left)));
+    assert_eq!(editor.selected(), &CONTRAST);
+    editor.handle_event(Event::Click(Right));
+    assert!(!editor.handle_event(Event::Repeat(Left)));
+    assert_eq!(editor.edit().unwrap().value, Value::Int(60));
+    editor.cancel();
+
+    // Cancelled edits are dropped
+    editor.handle_event(Event::Click(Right));
+    editor.handle_event(Event::Click(Left));
+    editor.cancel();
+    assert_eq!(editor.edit(), None);
+    assert_eq!(editor.values()[1], Value::Int(60));
+    assert_eq!(editor.take_saved(), None);
+}
+
+#[test]
+fn editor_values_start_valid() {
+    let editor = Editor::new(
+        &SCHEMA,
+        &[Value::Enum(1), Value::Int(55), Value::Bool(true)],
+    );
+    assert_eq!(editor.values().len(), SCHEMA.len());
+    assert_eq!(editor.values()[0], Value::Enum(1));
+    assert_eq!(editor.values()[1], Value::Int(40));
+    assert_eq!(editor.values()[2], LED_BRIGHTNESS.default_value());
+
+    let mut empty = Editor::new(&[], &[]);
+    assert!(!empty.handle_event(Event::Click(Right)));
+}
+
+#[test]
+fn editor_edits_text() {
+    let schema = [SOUND, NAME];
+    let mut editor = Editor::new(&schema, &[]);
+    editor.handle_event(Event::Click(Left));
+    editor.handle_event(Event::Click(Right));
+    let edit = |editor: &Editor| {
+        let edit = editor.edit().unwrap();
+        (edit.value.as_text().unwrap().to_string(), edit.cursor)
+    };
+    assert_eq!(edit(&editor), ("ab".to_string(), 0));
+
+    // Left changes the character at the cursor, right moves on
+    editor.handle_event(Event::Click(Left));
+    assert_eq!(edit(&editor), ("bb".to_string(), 0));
+    editor.handle_event(Event::Click(Right));
+    editor.handle_event(Event::Click(Left));
+    assert_eq!(edit(&editor), ("bc".to_string(), 1));
+
+    // At the end left appends, up to the longest text
+    editor.handle_event(Event::Click(Right));
+    editor.handle_event(Event::Click(Left));
+    assert_eq!(edit(&editor), ("bcA".to_string(), 2));
+    editor.handle_event(Event::Click(Right));
+    editor.handle_event(Event::Click(Left));
+    assert_eq!(edit(&editor), ("bcA".to_string(), 3));
+
+    // Right at the end saves
+    editor.handle_event(Event::Click(Right));
+    assert_eq!(editor.edit(), None);
+    assert_eq!(editor.values()[1], text("bcA"));
+    assert_eq!(editor.take_saved(), Some(1));
+}
+
+#[test]
+fn editor_text_ends_after_last_character() {
+    let schema = [NAME];
+    let mut editor = Editor::new(&schema, &[text("a_Z")]);
+    editor.handle_event(Event::Click(Right));
+    editor.handle_event(Event::Click(Right));
+    // '_' is followed by ' ', then the text ends at the cursor
+    editor.handle_event(Event::Click(Left));
+    assert_eq!(editor.edit().unwrap().value, text("a Z"));
+    editor.handle_event(Event::Click(Left));
+    assert_eq!(editor.edit().unwrap().value, text("a"));
+    assert_eq!(editor.edit().unwrap().cursor, 1);
+    editor.handle_event(Event::Click(Right));
+    assert_eq!(editor.values()[0], text("a"));
+}
+
+#[test]
+fn settings_app_saves_to_store() {
+    let mut app = Settings::new(mount(RamFlash::new(2)), &SCHEMA);
+    app.init(0);
+    // Units: fourth below the rotation
+    for _ in 0..4 {
+        app.handle_event(Event::Click(Left), 0);
+    }
+    app.handle_event(Event::Click(Right), 0);
+    app.handle_event(Event::Click(Left), 0);
+    assert!(!app.take_changed());
+    app.handle_event(Event::Click(Right), 0);
+    assert!(app.take_changed());
+    assert!(!app.take_changed());
+    assert_eq!(app.config().units, Units::Fahrenheit);
+    assert_eq!(get(app.store_mut(), "units.temperature"), Some(vec![1]));
+
+    // Loads what was saved
+    let mut store = mount(RamFlash::new(2));
+    store
+        .save_setting(&SAMPLE_INTERVAL, &Value::Int(10))
+        .unwrap();
+    let app = Settings::new(store, &SCHEMA);
+    assert_eq!(app.editor().values()[5], Value::Int(10));
+    assert_eq!(app.config().sample_interval_ms, 10_000);
+}
+
+#[test]
+fn settings_screens() {
+    let mut store = mount(RamFlash::new(2));
+    store.save_setting(&ROTATION, &Value::Enum(1)).unwrap();
+    let mut editor = Editor::new(&SCHEMA, &store.settings(&SCHEMA).unwrap());
+    editor.handle_event(Event::Click(Left));
+    let frame = render(|target| screens::settings::draw(target, &editor));
+    assert_golden("settings_list", &frame);
+
+    editor.handle_event(Event::Click(Right));
+    editor.handle_event(Event::Click(Left));
+    let frame = render(|target| screens::settings::draw(target, &editor));
+    assert_golden("settings_edit_int", &frame);
+
+    // The time zone, scrolled with the cursor
+    editor.cancel();
+    editor.handle_event(Event::Click(Left));
+    editor.handle_event(Event::Click(Left));
+    editor.handle_event(Event::Click(Right));
+    for _ in 0..24 {
+        editor.handle_event(Event::Click(Right));
+    }
+    let frame = render(|target| screens::settings::draw(target, &editor));
+    assert_golden("settings_edit_text", &frame);
+}
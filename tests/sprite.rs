@@ -0,0 +1,229 @@
+mod common;
+
+use embedded_graphics::{pixelcolor::BinaryColor, prelude::*, primitives::Rectangle};
+use esp_buddy::{
+    ascii_bitmap,
+    framebuffer::Framebuffer,
+    gfx::sprite::{Bitmap, ParseError, Sprite, Stage, Wrap},
+    xbm_bitmap,
+};
+
+use common::{assert_golden, render};
+
+const ARROW: Bitmap = ascii_bitmap!(
+    "
+    ..#..
+    .###.
+    #####
+    ..#
+    "
+);
+
+const ARROW_XBM: &str = "#define arrow_width 5
+#define arrow_height 4
+static unsigned char arrow_bits[] = {
+   0x04, 0x0e, 0x1f, 0x04 };
+";
+
+const BLOCK: Bitmap = ascii_bitmap!(
+    "
+    ####
+    #..#
+    ####
+    "
+);
+
+/// Lit pixels of `frame` as ASCII art rows, from `top_left` on.
+fn rows(frame: &Framebuffer, top_left: Point, size: Size) -> Vec<String> {
+    (0..size.height as i32)
+        .map(|y| {
+            (0..size.width as i32)
+                .map(|x| match frame.pixel(top_left + Point::new(x, y)) {
+                    Some(BinaryColor::On) => '#',
+                    _ => '.',
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn ascii_art() {
+    assert_eq!(ARROW.size(), Size::new(5, 4));
+    assert_eq!(ARROW.data(), [0x20, 0x70, 0xf8, 0x20]);
+    assert!(ARROW.pixel(2, 0));
+    assert!(!ARROW.pixel(3, 3));
+    assert!(!ARROW.pixel(5, 0));
+
+    let mut art = String::new();
+    ARROW.write_ascii(&mut art).unwrap();
+    assert_eq!(art, "..#..\n.###.\n#####\n..#..\n");
+}
+
+#[test]
+fn xbm() {
+    const FROM_XBM: Bitmap = xbm_bitmap!(ARROW_XBM);
+    assert_eq!(FROM_XBM, ARROW);
+
+    let mut source = String::new();
+    ARROW.write_xbm(&mut source, "arrow").unwrap();
+    assert_eq!(
+        source,
+        "#define arrow_width 5\n#define arrow_height 4\n\
+         static unsigned char arrow_bits[] = {\n   0x04, 0x0e, 0x1f, 0x04 };\n"
+    );
+}
+
+#[test]
+fn pbm() {
+    let truck = Bitmap::from_pbm(include_bytes!("../src/screens/car.pbm"));
+    assert_eq!(truck.size(), Size::new(121, 31));
+
+    let bitmap = Bitmap::parse_pbm(b"P4\n# arrow\n5 4\n\x20\x70\xf8\x20").unwrap();
+    assert_eq!(bitmap, ARROW);
+    assert_eq!(Bitmap::parse_pbm(b"P1\n5 4\n"), Err(ParseError::NotPbm));
+    assert_eq!(
+        Bitmap::parse_pbm(b"P4\n5\n"),
+        Err(ParseError::InvalidHeader)
+    );
+    assert_eq!(Bitmap::parse_pbm(b"P4 5 4"), Err(ParseError::InvalidHeader));
+    assert_eq!(
+        Bitmap::parse_pbm(b"P4 5 4\n\x20\x70"),
+        Err(ParseError::TooShort)
+    );
+}
+
+#[test]
+fn sprite_is_clipped() {
+    // Partly off every edge, `render` fails on pixels outside of the display
+    for position in [
+        Point::new(-2, 10),
+        Point::new(125, 10),
+        Point::new(60, -2),
+        Point::new(60, 30),
+    ] {
+        let sprite = Sprite::new(ARROW).with_position(position);
+        let frame = render(|target| sprite.draw(target));
+        let shown = sprite.bounding_box().intersection(&frame.bounding_box());
+        let lit = shown.points().filter(|&point| {
+            let offset = point - position;
+            ARROW.pixel(offset.x as u32, offset.y as u32)
+        });
+        assert_eq!(
+            lit.count(),
+            frame
+                .bounding_box()
+                .points()
+                .filter(|&point| frame.pixel(point) == Some(BinaryColor::On))
+                .count(),
+            "{position}"
+        );
+    }
+
+    let mut hidden = Sprite::new(ARROW);
+    hidden.set_visible(false);
+    assert_eq!(render(|target| hidden.draw(target)), Framebuffer::new());
+}
+
+#[test]
+fn layers() {
+    let mut stage: Stage<3> = Stage::new();
+    let arrow = stage
+        .add(
+            Sprite::new(ARROW)
+                .with_position(Point::new(1, 0))
+                .with_layer(1),
+        )
+        .unwrap();
+    // Added later, but below the arrow
+    let block = stage.add(Sprite::new(BLOCK).with_opaque(true)).unwrap();
+    let frame = render(|target| stage.draw(target));
+    assert_eq!(
+        rows(&frame, Point::zero(), Size::new(6, 4)),
+        ["####..", "#.###.", "######", "...#.."]
+    );
+
+    // Over the arrow, the dark pixels hide it
+    *stage.sprite_mut(block).unwrap() = Sprite::new(BLOCK).with_opaque(true).with_layer(2);
+    let frame = render(|target| stage.draw(target));
+    assert_eq!(
+        rows(&frame, Point::zero(), Size::new(6, 4)),
+        ["####..", "#..##.", "######", "...#.."]
+    );
+
+    assert_eq!(stage.sprite(arrow).unwrap().layer(), 1);
+    assert_eq!(stage.add(Sprite::new(ARROW)), Ok(2));
+    assert_eq!(stage.add(Sprite::new(BLOCK)), Err(Sprite::new(BLOCK)));
+}
+
+#[test]
+fn wrap() {
+    let mut stage: Stage<1> = Stage::new().with_wrap(Wrap::HORIZONTAL);
+    let arrow = stage
+        .add(Sprite::new(ARROW).with_position(Point::new(-2, 0)))
+        .unwrap();
+    let frame = render(|target| stage.draw(target));
+    assert_eq!(
+        rows(&frame, Point::new(125, 0), Size::new(3, 4)),
+        ["...", "..#", ".##", "..."]
+    );
+    assert_eq!(
+        rows(&frame, Point::zero(), Size::new(3, 4)),
+        ["#..", "##.", "###", "#.."]
+    );
+
+    // Positions any number of turns away wrap the same way
+    stage
+        .sprite_mut(arrow)
+        .unwrap()
+        .move_by(Point::new(3 * 128, 0));
+    assert_eq!(render(|target| stage.draw(target)), frame);
+
+    // Wrapped vertically only, nothing shows on the other side
+    let stage = stage.with_wrap(Wrap::VERTICAL);
+    let frame = render(|target| stage.draw(target));
+    assert_eq!(
+        rows(&frame, Point::new(125, 0), Size::new(3, 4)),
+        ["...", "...", "...", "..."]
+    );
+
+    // Wraps around the area, not the display
+    let mut stage: Stage<1> = Stage::new()
+        .with_area(Rectangle::new(Point::new(10, 10), Size::new(8, 8)))
+        .with_wrap(Wrap::BOTH);
+    stage
+        .add(Sprite::new(ARROW).with_position(Point::new(15, 16)))
+        .unwrap();
+    let frame = render(|target| stage.draw(target));
+    assert_eq!(
+        rows(&frame, Point::new(10, 10), Size::new(8, 8)),
+        [
+            "##...###", ".......#", "........", "........", "........", "........", ".......#",
+            "#.....##",
+        ]
+    );
+    // Clipped below the area
+    assert_eq!(frame.pixel(Point::new(16, 18)), Some(BinaryColor::Off));
+}
+
+#[test]
+fn stage() {
+    let mut stage: Stage<4> = Stage::new().with_wrap(Wrap::HORIZONTAL);
+    stage
+        .add(
+            Sprite::new(Bitmap::from_pbm(include_bytes!("../src/screens/car.pbm")))
+                .with_position(Point::new(60, 1)),
+        )
+        .unwrap();
+    for x in [4, 20, 36] {
+        stage
+            .add(
+                Sprite::new(BLOCK)
+                    .with_position(Point::new(x, 12))
+                    .with_layer(1)
+                    .with_opaque(true),
+            )
+            .unwrap();
+    }
+    assert_golden("sprite_stage", &render(|target| stage.draw(target)));
+}
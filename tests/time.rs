@@ -0,0 +1,249 @@
+use esp_buddy::time::{civil_from_days, days_from_civil, DateTime, ParseError, TimeZone, Weekday};
+
+/// Expected transitions below come from the IANA tz database of the host.
+const PRAGUE: &str = "CET-1CEST,M3.5.0,M10.5.0/3";
+const NEW_YORK: &str = "EST5EDT,M3.2.0,M11.1.0";
+const SYDNEY: &str = "AEST-10AEDT,M10.1.0,M4.1.0/3";
+const AUCKLAND: &str = "NZST-12NZDT,M9.5.0,M4.1.0/3";
+const NUUK: &str = "<-02>2<-01>,M3.5.0/-1,M10.5.0/0";
+const KOLKATA: &str = "IST-5:30";
+const TEHRAN: &str = "<+0330>-3:30";
+
+fn date_time(timestamp: i64) -> (i32, u8, u8, u8, u8, u8, Weekday) {
+    let t = DateTime::from_unix(timestamp);
+    (
+        t.year, t.month, t.day, t.hour, t.minute, t.second, t.weekday,
+    )
+}
+
+/// Local hour and abbreviation just before and at `timestamp`.
+fn around(tz: &str, timestamp: i64) -> [(u8, &str); 2] {
+    let zone = TimeZone::parse(tz).unwrap();
+    [timestamp - 1, timestamp].map(|timestamp| {
+        let local = zone.local(timestamp);
+        (local.date_time.hour, local.abbreviation)
+    })
+}
+
+#[test]
+fn civil_dates() {
+    use Weekday::*;
+
+    assert_eq!(date_time(0), (1970, 1, 1, 0, 0, 0, Thursday));
+    assert_eq!(date_time(-1), (1969, 12, 31, 23, 59, 59, Wednesday));
+    assert_eq!(date_time(951_782_400), (2000, 2, 29, 0, 0, 0, Tuesday));
+    assert_eq!(date_time(1_719_835_200), (2024, 7, 1, 12, 0, 0, Monday));
+    assert_eq!(
+        date_time(4_102_444_799),
+        (2099, 12, 31, 23, 59, 59, Thursday)
+    );
+
+    assert_eq!(DateTime::from_unix(951_782_400).day_of_year(), 60);
+    assert_eq!(DateTime::from_unix(4_102_444_799).day_of_year(), 365);
+}
+
+#[test]
+fn civil_round_trip() {
+    for days in -800_000..800_000 {
+        let (year, month, day) = civil_from_days(days);
+        assert_eq!(days_from_civil(year, month, day), days);
+    }
+
+    for timestamp in (-5_000_000_000..5_000_000_000).step_by(86_399 * 7) {
+        assert_eq!(DateTime::from_unix(timestamp).to_unix(), timestamp);
+    }
+}
+
+#[test]
+fn parse_rules() {
+    let zone = TimeZone::parse(PRAGUE).unwrap();
+    assert_eq!(zone.std_offset(), 3600);
+    assert_eq!(zone.dst_offset(), Some(7200));
+
+    let zone = TimeZone::parse("<+0545>-5:45").unwrap();
+    assert_eq!(zone.std_offset(), 5 * 3600 + 45 * 60);
+    assert_eq!(zone.dst_offset(), None);
+
+    // DST is one hour ahead and follows the US rule unless specified
+    let zone = TimeZone::parse("EST5EDT").unwrap();
+    assert_eq!(zone.std_offset(), -5 * 3600);
+    assert_eq!(zone.dst_offset(), Some(-4 * 3600));
+    assert_eq!(
+        zone.transitions(2024),
+        TimeZone::parse(NEW_YORK).unwrap().transitions(2024)
+    );
+}
+
+#[test]
+fn parse_errors() {
+    assert_eq!(TimeZone::parse(""), Err(ParseError::InvalidName));
+    assert_eq!(TimeZone::parse("CE-1"), Err(ParseError::InvalidName));
+    assert_eq!(TimeZone::parse("<+01-1"), Err(ParseError::InvalidName));
+    assert_eq!(TimeZone::parse("CET"), Err(ParseError::InvalidOffset));
+    assert_eq!(TimeZone::parse("CET-25"), Err(ParseError::InvalidOffset));
+    assert_eq!(TimeZone::parse("CET-1:60"), Err(ParseError::InvalidOffset));
+    assert_eq!(
+        TimeZone::parse("CET-1CEST,M3.5.0"),
+        Err(ParseError::InvalidRule)
+    );
+    assert_eq!(
+        TimeZone::parse("CET-1CEST,M13.5.0,M10.5.0"),
+        Err(ParseError::InvalidRule)
+    );
+    assert_eq!(
+        TimeZone::parse("CET-1CEST,M3.6.0,M10.5.0"),
+        Err(ParseError::InvalidRule)
+    );
+    assert_eq!(
+        TimeZone::parse("CET-1CEST,J0,J300"),
+        Err(ParseError::InvalidRule)
+    );
+    assert_eq!(
+        TimeZone::parse("CET-1CEST,M3.5.0/168,M10.5.0"),
+        Err(ParseError::InvalidRule)
+    );
+    assert_eq!(
+        TimeZone::parse("CET-1CEST,M3.5.0,M10.5.0/3 "),
+        Err(ParseError::TrailingCharacters)
+    );
+    assert_eq!(
+        TimeZone::parse(":Europe/Prague"),
+        Err(ParseError::Unsupported)
+    );
+}
+
+#[test]
+fn extended_transition_times() {
+    // Negative times and days longer than 24 hours, as allowed by RFC 8536
+    let zone = TimeZone::parse("AAA3BBB,J60/-1:30,100/26").unwrap();
+    let (start, end) = zone.transitions(2023).unwrap();
+    assert_eq!(
+        start,
+        days_from_civil(2023, 3, 1) * 86_400 - 5_400 + 3 * 3600
+    );
+    assert_eq!(
+        end,
+        days_from_civil(2023, 4, 11) * 86_400 + 26 * 3600 + 2 * 3600
+    );
+}
+
+#[test]
+fn prague() {
+    let (start, end) = TimeZone::parse(PRAGUE).unwrap().transitions(2024).unwrap();
+    assert_eq!((start, end), (1_711_846_800, 1_729_990_800));
+
+    assert_eq!(around(PRAGUE, start), [(1, "CET"), (3, "CEST")]);
+    assert_eq!(around(PRAGUE, end), [(2, "CEST"), (2, "CET")]);
+
+    let local = TimeZone::parse(PRAGUE).unwrap().local(1_719_835_200);
+    assert_eq!(local.offset, 7200);
+    assert!(local.is_dst);
+}
+
+#[test]
+fn new_york() {
+    let (start, end) = TimeZone::parse(NEW_YORK)
+        .unwrap()
+        .transitions(2024)
+        .unwrap();
+    assert_eq!((start, end), (1_710_054_000, 1_730_613_600));
+
+    assert_eq!(around(NEW_YORK, start), [(1, "EST"), (3, "EDT")]);
+    assert_eq!(around(NEW_YORK, end), [(1, "EDT"), (1, "EST")]);
+
+    // New Year's Eve in UTC is still the previous day in New York
+    let local = TimeZone::parse(NEW_YORK).unwrap().local(1_704_067_200);
+    assert_eq!(
+        (
+            local.date_time.year,
+            local.date_time.day,
+            local.date_time.hour
+        ),
+        (2023, 31, 19)
+    );
+}
+
+#[test]
+fn sydney() {
+    let zone = TimeZone::parse(SYDNEY).unwrap();
+    let (start, end) = zone.transitions(2024).unwrap();
+    // DST ends in April and starts again in October
+    assert_eq!((start, end), (1_728_144_000, 1_712_419_200));
+
+    assert_eq!(around(SYDNEY, end), [(2, "AEDT"), (2, "AEST")]);
+    assert_eq!(around(SYDNEY, start), [(1, "AEST"), (3, "AEDT")]);
+
+    assert!(zone.is_dst(1_704_067_200)); // 2024-01-01 UTC
+    assert!(!zone.is_dst(1_719_835_200)); // 2024-07-01 UTC
+    assert!(zone.is_dst(1_735_603_200)); // 2024-12-31 UTC
+}
+
+#[test]
+fn auckland() {
+    let (start, end) = TimeZone::parse(AUCKLAND)
+        .unwrap()
+        .transitions(2025)
+        .unwrap();
+    assert_eq!((start, end), (1_758_981_600, 1_743_861_600));
+
+    assert_eq!(around(AUCKLAND, end), [(2, "NZDT"), (2, "NZST")]);
+    assert_eq!(around(AUCKLAND, start), [(1, "NZST"), (3, "NZDT")]);
+
+    // New Year in UTC is already afternoon in Auckland
+    let local = TimeZone::parse(AUCKLAND).unwrap().local(1_735_689_600);
+    assert_eq!((local.date_time.day, local.date_time.hour), (1, 13));
+    assert_eq!(local.offset, 13 * 3600);
+}
+
+#[test]
+fn nuuk_negative_transition_times() {
+    let (start, end) = TimeZone::parse(NUUK).unwrap().transitions(2025).unwrap();
+    // -1:00 on the last Sunday of March is 23:00 on Saturday
+    assert_eq!((start, end), (1_743_296_400, 1_761_440_400));
+
+    assert_eq!(around(NUUK, start), [(22, "-02"), (0, "-01")]);
+    assert_eq!(around(NUUK, end), [(23, "-01"), (23, "-02")]);
+}
+
+#[test]
+fn fixed_offsets() {
+    let local = TimeZone::parse(KOLKATA).unwrap().local(1_719_835_200);
+    assert_eq!((local.date_time.hour, local.date_time.minute), (17, 30));
+    assert_eq!(
+        (local.offset, local.is_dst, local.abbreviation),
+        (19_800, false, "IST")
+    );
+
+    let local = TimeZone::parse(TEHRAN).unwrap().local(1_719_835_200);
+    assert_eq!((local.date_time.hour, local.date_time.minute), (15, 30));
+    assert_eq!(local.abbreviation, "+0330");
+
+    let local = TimeZone::UTC.local(1_719_835_200);
+    assert_eq!((local.date_time.hour, local.abbreviation), (12, "UTC"));
+}
+
+#[test]
+fn julian_days() {
+    // J60 is always March 1, day 59 counted from zero is February 29 in leap years
+    let julian = TimeZone::parse("AAA0BBB,J60/0,J300/0").unwrap();
+    let zero_based = TimeZone::parse("AAA0BBB,59/0,299/0").unwrap();
+
+    let march_1_2024 = days_from_civil(2024, 3, 1) * 86_400;
+    let march_1_2023 = days_from_civil(2023, 3, 1) * 86_400;
+    assert_eq!(julian.transitions(2024).unwrap().0, march_1_2024);
+    assert_eq!(julian.transitions(2023).unwrap().0, march_1_2023);
+    assert_eq!(
+        zero_based.transitions(2024).unwrap().0,
+        march_1_2024 - 86_400
+    );
+    assert_eq!(zero_based.transitions(2023).unwrap().0, march_1_2023);
+}
+
+#[test]
+fn daylight_saving_all_year() {
+    // Common encoding of permanent DST
+    let zone = TimeZone::parse("EST5EDT,0/0,J365/25").unwrap();
+    for timestamp in (1_704_067_200..1_735_689_600).step_by(86_400 * 13) {
+        assert!(zone.is_dst(timestamp));
+    }
+}
@@ -0,0 +1,289 @@
+use core::net::Ipv4Addr;
+
+use esp_buddy::launcher::{apps::Wifi, App};
+use esp_buddy::wifi::{
+    AccessPoint, Controller, FailReason, Network, ScanResults, State, WifiManager,
+};
+
+const HOME: Network = Network::new("home", "secret");
+const OFFICE: Network = Network::new("office", "hunter22");
+const IP: Ipv4Addr = Ipv4Addr::new(192, 168, 1, 42);
+
+#[derive(Debug, PartialEq)]
+struct MockError;
+
+#[derive(Default)]
+struct MockController {
+    visible: Vec<&'static str>,
+    link: bool,
+    ip: Option<Ipv4Addr>,
+    rssi: Option<i8>,
+    /// Fails the next call to `connect`.
+    fail_connect: bool,
+    /// SSIDs of all `connect` calls.
+    connects: Vec<String>,
+    disconnects: u32,
+    scans: u32,
+}
+
+impl Controller for MockController {
+    type Error = MockError;
+
+    fn start(&mut self) -> Result<(), MockError> {
+        Ok(())
+    }
+
+    fn scan(&mut self) -> Result<ScanResults, MockError> {
+        self.scans += 1;
+        Ok(self
+            .visible
+            .iter()
+            .map(|ssid| AccessPoint {
+                ssid: (*ssid).try_into().unwrap(),
+                rssi: -60,
+                channel: 6,
+                open: false,
+            })
+            .collect())
+    }
+
+    fn connect(&mut self, network: &Network) -> Result<(), MockError> {
+        if std::mem::take(&mut self.fail_connect) {
+            return Err(MockError);
+        }
+        self.connects.push(network.ssid.into());
+        Ok(())
+    }
+
+    fn disconnect(&mut self) -> Result<(), MockError> {
+        self.disconnects += 1;
+        self.link = false;
+        Ok(())
+    }
+
+    fn is_connected(&mut self) -> Result<bool, MockError> {
+        Ok(self.link)
+    }
+
+    fn ip(&mut self) -> Option<Ipv4Addr> {
+        self.ip
+    }
+
+    fn rssi(&mut self) -> Option<i8> {
+        self.rssi
+    }
+}
+
+fn mock(visible: &[&'static str]) -> MockController {
+    MockController {
+        visible: visible.to_vec(),
+        ..Default::default()
+    }
+}
+
+/// Manager connected to `home` at 1000 ms.
+fn connected<'a>(networks: &'a [Network<'a>]) -> WifiManager<'a, MockController> {
+    let mut wifi = WifiManager::new(mock(&["home"]), networks);
+    wifi.start();
+    wifi.poll(0);
+    let controller = wifi.controller_mut();
+    controller.link = true;
+    controller.ip = Some(IP);
+    assert_eq!(wifi.poll(1000), Some(State::Connected { ip: IP }));
+    wifi
+}
+
+#[test]
+fn idle_until_started() {
+    let networks = [HOME];
+    let mut wifi = WifiManager::new(mock(&["home"]), &networks);
+
+    assert_eq!(wifi.poll(0), None);
+    assert_eq!(wifi.state(), State::Idle);
+    assert_eq!(wifi.controller_mut().scans, 0);
+}
+
+#[test]
+fn connects_to_first_known_network_in_range() {
+    let networks = [OFFICE, HOME];
+    let mut wifi = WifiManager::new(mock(&["neighbour", "home"]), &networks);
+    wifi.start();
+    assert_eq!(wifi.state(), State::Scanning);
+
+    assert_eq!(wifi.poll(0), Some(State::Connecting));
+    assert_eq!(wifi.network(), Some(&HOME));
+    assert_eq!(wifi.scan_results().len(), 2);
+    assert_eq!(wifi.controller_mut().connects, ["home"]);
+
+    // Link up, but no DHCP lease yet
+    wifi.controller_mut().link = true;
+    assert_eq!(wifi.poll(500), None);
+    wifi.controller_mut().ip = Some(IP);
+    assert_eq!(wifi.poll(900), Some(State::Connected { ip: IP }));
+    assert_eq!(wifi.poll(1000), None);
+}
+
+#[test]
+fn prefers_networks_by_priority() {
+    let networks = [OFFICE, HOME];
+    let mut wifi = WifiManager::new(mock(&["home", "office"]), &networks);
+    wifi.start();
+    wifi.poll(0);
+
+    assert_eq!(wifi.network(), Some(&OFFICE));
+}
+
+#[test]
+fn backs_off_exponentially() {
+    let networks = [HOME];
+    let mut wifi = WifiManager::new(mock(&[]), &networks);
+    wifi.start();
+
+    let mut now = 0;
+    let mut waits = Vec::new();
+    for _ in 0..8 {
+        let state = wifi.poll(now).unwrap();
+        assert_eq!(
+            state,
+            State::Failed {
+                reason: FailReason::NotFound
+            }
+        );
+        let wait = wifi.retry_in_ms(now).unwrap();
+        waits.push(wait);
+
+        // Nothing happens before the wait is over
+        assert_eq!(wifi.poll(now + wait - 1), None);
+        now += wait;
+        assert_eq!(wifi.poll(now), Some(State::Scanning));
+    }
+
+    assert_eq!(
+        waits,
+        [1000, 2000, 4000, 8000, 16_000, 32_000, 60_000, 60_000]
+    );
+    assert_eq!(wifi.failures(), 8);
+    assert_eq!(wifi.controller_mut().scans, 8);
+}
+
+#[test]
+fn connect_timeout() {
+    let networks = [HOME];
+    let mut wifi = WifiManager::new(mock(&["home"]), &networks).with_connect_timeout_ms(10_000);
+    wifi.start();
+    wifi.poll(0);
+
+    wifi.controller_mut().link = true;
+    assert_eq!(wifi.poll(9999), None);
+    assert_eq!(
+        wifi.poll(10_000),
+        Some(State::Failed {
+            reason: FailReason::Timeout
+        })
+    );
+    assert_eq!(wifi.controller_mut().disconnects, 1);
+    assert_eq!(wifi.retry_in_ms(10_000), Some(1000));
+}
+
+#[test]
+fn reconnects_after_drop() {
+    let networks = [HOME];
+    let mut wifi = connected(&networks);
+
+    wifi.controller_mut().link = false;
+    assert_eq!(
+        wifi.poll(5000),
+        Some(State::Failed {
+            reason: FailReason::Disconnected
+        })
+    );
+    assert_eq!(wifi.poll(6000), Some(State::Scanning));
+    assert_eq!(wifi.poll(6000), Some(State::Connecting));
+
+    wifi.controller_mut().link = true;
+    assert_eq!(wifi.poll(7000), Some(State::Connected { ip: IP }));
+    assert_eq!(wifi.failures(), 0);
+    assert_eq!(wifi.controller_mut().connects, ["home", "home"]);
+}
+
+#[test]
+fn waits_for_new_lease() {
+    let networks = [HOME];
+    let mut wifi = connected(&networks);
+
+    wifi.controller_mut().ip = None;
+    assert_eq!(wifi.poll(2000), Some(State::Connecting));
+
+    let renewed = Ipv4Addr::new(192, 168, 1, 77);
+    wifi.controller_mut().ip = Some(renewed);
+    assert_eq!(wifi.poll(3000), Some(State::Connected { ip: renewed }));
+
+    // The address may also change without losing the lease first
+    wifi.controller_mut().ip = Some(IP);
+    assert_eq!(wifi.poll(4000), Some(State::Connected { ip: IP }));
+}
+
+#[test]
+fn controller_error() {
+    let networks = [HOME];
+    let mut wifi = WifiManager::new(mock(&["home"]), &networks);
+    wifi.controller_mut().fail_connect = true;
+    wifi.start();
+
+    assert_eq!(
+        wifi.poll(0),
+        Some(State::Failed {
+            reason: FailReason::Controller
+        })
+    );
+    assert_eq!(wifi.last_error(), Some(&MockError));
+
+    assert_eq!(wifi.poll(1000), Some(State::Scanning));
+    assert_eq!(wifi.poll(1000), Some(State::Connecting));
+}
+
+#[test]
+fn live_rssi_only_while_connected() {
+    let networks = [HOME];
+    let mut wifi = WifiManager::new(mock(&["home"]), &networks);
+    wifi.controller_mut().rssi = Some(-48);
+    wifi.start();
+    wifi.poll(0);
+    assert_eq!(wifi.state(), State::Connecting);
+    assert_eq!(wifi.rssi(), None);
+
+    let mut wifi = connected(&networks);
+    assert_eq!(wifi.rssi(), None);
+    wifi.controller_mut().rssi = Some(-48);
+    assert_eq!(wifi.rssi(), Some(-48));
+    // Not the -60 dBm seen by the scan before connecting
+    assert_eq!(wifi.scan_results()[0].rssi, -60);
+}
+
+#[test]
+fn stop_disconnects() {
+    let networks = [HOME];
+    let mut wifi = connected(&networks);
+
+    wifi.stop().unwrap();
+    assert_eq!(wifi.state(), State::Idle);
+    assert_eq!(wifi.network(), None);
+    assert_eq!(wifi.controller_mut().disconnects, 1);
+    assert_eq!(wifi.poll(10_000), None);
+}
+
+#[test]
+fn app_shows_state_polled_by_owner() {
+    let networks = [HOME];
+    let mut app = Wifi::new(WifiManager::new(mock(&["home"]), &networks));
+    app.manager_mut().start();
+    app.init(0);
+
+    // The app neither starts nor polls the manager
+    assert!(!app.update(0));
+    assert_eq!(app.manager_mut().controller_mut().scans, 0);
+
+    app.manager_mut().poll(0);
+    assert!(app.update(0));
+    assert!(!app.update(5));
+}